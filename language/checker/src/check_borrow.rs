@@ -0,0 +1,111 @@
+use data::tokens::Span;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{EffectType, Effects};
+use syntax::program::function::CodeBody;
+
+/// A mutable borrow that's still live
+struct LiveBorrow {
+    /// The borrowed variable
+    borrowed: String,
+    /// The last line of the block the borrow is used on
+    until: usize,
+}
+
+/// Errors at the second of two mutable borrows of the same variable that are live at the same time in the block.
+/// A borrow stored in a variable is live until the last line of the block using that variable,
+/// any other borrow only lasts for its own line. Borrows in nested blocks are checked with those blocks.
+pub fn check_borrows(code: &CodeBody) -> Result<(), ParsingError> {
+    let mut live: Vec<LiveBorrow> = Vec::default();
+    for (line, expression) in code.expressions.iter().enumerate() {
+        live.retain(|borrow| borrow.until >= line);
+
+        let holder = match &expression.effect.types {
+            EffectType::CreateVariable(name, value, _) if matches!(value.types, EffectType::MutableBorrow(_)) => Some(name),
+            _ => None,
+        };
+        let mut borrows = Vec::default();
+        find_borrows(&expression.effect, &mut borrows);
+        for (borrowed, span) in borrows {
+            if live.iter().any(|borrow| borrow.borrowed == borrowed) {
+                return Err(span.make_error(ParsingMessage::MutableBorrowConflict(borrowed)));
+            }
+            let until = match holder {
+                Some(holder) => code.expressions[line + 1..]
+                    .iter()
+                    .rposition(|later| uses_variable(&later.effect, holder))
+                    .map_or(line, |last| line + 1 + last),
+                None => line,
+            };
+            live.push(LiveBorrow { borrowed, until });
+        }
+    }
+    return Ok(());
+}
+
+/// Adds the variable and span of every mutable borrow in the effect, in the order they're written,
+/// skipping the ones in nested blocks
+fn find_borrows(effect: &Effects, borrows: &mut Vec<(String, Span)>) {
+    if let EffectType::MutableBorrow(name) = &effect.types {
+        borrows.push((name.clone(), effect.span.clone()));
+    }
+    for inner in inner_effects(effect) {
+        find_borrows(inner, borrows);
+    }
+}
+
+/// Checks if the effect loads or borrows the variable anywhere, including in nested blocks
+fn uses_variable(effect: &Effects, name: &String) -> bool {
+    if let EffectType::LoadVariable(found) | EffectType::MutableBorrow(found) = &effect.types {
+        if found == name {
+            return true;
+        }
+    }
+    return inner_effects(effect).into_iter().any(|inner| uses_variable(inner, name))
+        || inner_bodies(effect)
+            .into_iter()
+            .any(|body| body.expressions.iter().any(|expression| uses_variable(&expression.effect, name)));
+}
+
+/// The effects directly inside of the effect, not counting the ones in nested blocks
+fn inner_effects(effect: &Effects) -> Vec<&Effects> {
+    let mut inner = Vec::default();
+    match &effect.types {
+        EffectType::Paren(value)
+        | EffectType::CreateVariable(_, value, _)
+        | EffectType::CompareJump(value, _, _)
+        | EffectType::Break(_, Some(value))
+        | EffectType::Load(value, _)
+        | EffectType::NamedArgument(_, value)
+        | EffectType::StaticAssert(value)
+        | EffectType::Cast(value, _)
+        | EffectType::Destructure(_, value)
+        | EffectType::Switch(value, _, _) => inner.push(value.as_ref()),
+        EffectType::Set(base, value) => {
+            inner.push(base.as_ref());
+            inner.push(value.as_ref());
+        }
+        EffectType::ImplementationCall(calling, _, _, arguments, _, _) => {
+            inner.push(calling.as_ref());
+            inner.extend(arguments);
+        }
+        EffectType::MethodCall(calling, _, arguments, _, _) => {
+            inner.extend(calling.as_deref());
+            inner.extend(arguments);
+        }
+        EffectType::Operation(_, arguments) | EffectType::CreateArray(arguments) | EffectType::Interpolation(arguments) => {
+            inner.extend(arguments)
+        }
+        EffectType::CreateStruct(_, fields) => inner.extend(fields.iter().map(|(_, field)| field)),
+        _ => {}
+    }
+    return inner;
+}
+
+/// The blocks directly inside of the effect
+fn inner_bodies(effect: &Effects) -> Vec<&CodeBody> {
+    return match &effect.types {
+        EffectType::CodeBody(body) | EffectType::Loop(body, _, _, _) | EffectType::Closure(_, _, body) => vec![body],
+        EffectType::Switch(_, arms, _) => arms.iter().map(|arm| &arm.body).collect(),
+        _ => vec![],
+    };
+}
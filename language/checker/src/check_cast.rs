@@ -0,0 +1,89 @@
+use async_recursion::async_recursion;
+use data::tokens::Span;
+
+use syntax::async_util::UnparsedType;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::r#struct::get_integer;
+use syntax::program::syntax::Syntax;
+use syntax::program::types::FinalizedTypes;
+use syntax::top_element_manager::ImplWaiter;
+use syntax::SimpleVariableManager;
+
+use crate::check_code::{store, verify_effect};
+use crate::{get_checked_return, CodeVerifier};
+
+/// The trait other types implement to be cast, like impl Cast<u64> for Meters
+const CAST_TRAIT: &str = "numbers::Cast";
+/// The floats built-in numbers can be cast to and from, on top of every integer
+const CASTABLE_FLOATS: [&str; 2] = ["f64", "f32"];
+
+/// Checks a cast like value as f64. Built-in numbers convert directly between each other,
+/// any other type has to implement Cast for the type it's cast to.
+#[async_recursion]
+pub async fn verify_cast(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    value: Effects,
+    target: UnparsedType,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let found = verify_effect(code_verifier, variables, value.clone()).await?;
+    if matches!(found.types, FinalizedEffectType::Poison(_)) {
+        return Ok(found);
+    }
+    let from = get_checked_return(&found.types, variables, &code_verifier.syntax, &value.span).await?;
+    let to = Syntax::parse_type(
+        code_verifier.syntax.clone(),
+        span.clone(),
+        code_verifier.resolver.boxed_clone(),
+        target.clone(),
+        vec![],
+    )
+    .await?
+    .finalize(code_verifier.syntax.clone())
+    .await;
+
+    if is_castable_number(&from) && is_castable_number(&to) {
+        return Ok(FinalizedEffects::new(span.clone(), store(FinalizedEffectType::Cast(Box::new(found), from, to))));
+    }
+
+    let cast = Syntax::get_struct(
+        code_verifier.syntax.clone(),
+        span.clone(),
+        CAST_TRAIT.to_string(),
+        code_verifier.resolver.boxed_clone(),
+        vec![],
+    )
+    .await?
+    .finalize(code_verifier.syntax.clone())
+    .await;
+    // Waits for every impl to be parsed before deciding there isn't one
+    ImplWaiter {
+        syntax: code_verifier.syntax.clone(),
+        base_type: from.clone(),
+        trait_type: FinalizedTypes::GenericType(Box::new(cast), vec![to.clone()]),
+        error: span.make_error(ParsingMessage::InvalidCast(from, to)),
+    }
+    .await?;
+
+    let calling = Effects::new(
+        span.clone(),
+        EffectType::ImplementationCall(
+            Box::new(value),
+            CAST_TRAIT.to_string(),
+            "cast".to_string(),
+            vec![],
+            Some(target),
+            vec![],
+        ),
+    );
+    return verify_effect(code_verifier, variables, calling).await;
+}
+
+/// Checks if the type is a built-in number, which can be cast to any other built-in number without an impl
+fn is_castable_number(types: &FinalizedTypes) -> bool {
+    return types.inner_struct_safe().is_some_and(|inner| {
+        get_integer(&inner.data.name).is_some() || CASTABLE_FLOATS.contains(&inner.data.name.as_str())
+    });
+}
@@ -0,0 +1,178 @@
+use async_recursion::async_recursion;
+use data::tokens::Span;
+use syntax::async_util::UnparsedType;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{
+    EffectType, Effects, ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression, FinalizedField,
+};
+use syntax::program::function::{CodeBody, FinalizedCodeBody};
+use syntax::program::r#struct::VOID;
+use syntax::program::syntax::Syntax;
+use syntax::program::types::FinalizedTypes;
+use syntax::program::walk::walk_effects;
+use syntax::SimpleVariableManager;
+
+use crate::check_code::{coerce_literal, verify_code, verify_effect};
+use crate::{get_return, CodeVerifier};
+
+/// Verifies a closure, capturing every variable from the enclosing scope that its code uses
+#[async_recursion]
+pub async fn verify_closure(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+    arguments: Vec<(String, UnparsedType)>,
+    returning: Option<UnparsedType>,
+    code: CodeBody,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let mut finalized_arguments = Vec::default();
+    for (name, types) in arguments {
+        finalized_arguments.push(FinalizedField { name, field_type: finalize_type(code_verifier, types, span).await? });
+    }
+    let returning = match returning {
+        Some(found) => finalize_type(code_verifier, found, span).await?,
+        None => FinalizedTypes::Struct(VOID.clone()),
+    };
+
+    // The closure can see the enclosing variables, but doesn't own any of them
+    let mut inner_variables =
+        SimpleVariableManager { variables: variables.variables.clone(), ..SimpleVariableManager::default() };
+    for argument in &finalized_arguments {
+        inner_variables
+            .variables
+            .insert(argument.name.clone(), FinalizedTypes::Reference(Box::new(argument.field_type.clone())));
+    }
+
+    let mut inner_verifier = CodeVerifier {
+        process_manager: code_verifier.process_manager,
+        resolver: code_verifier.resolver.boxed_clone(),
+        return_type: if returning.is_void() { None } else { Some(returning.clone()) },
+        syntax: code_verifier.syntax.clone(),
+        debug_impls: code_verifier.debug_impls,
+        checked_math: code_verifier.checked_math,
+        depth: code_verifier.depth,
+        // Closures can't break out of the loop they're made in
+        loops: Vec::default(),
+        constants: code_verifier.constants.clone(),
+        literal_types: code_verifier.literal_types.clone(),
+    };
+    let mut code = verify_code(&mut inner_verifier, &mut inner_variables, code, true).await?;
+
+    if !code.returns {
+        if !returning.is_void() {
+            return Err(span.make_error(ParsingMessage::MissingReturn(returning.display_with_generics())));
+        }
+        code.expressions.push(FinalizedExpression::new(
+            ExpressionType::Return(span.clone()),
+            FinalizedEffects::new(span.clone(), FinalizedEffectType::NOP),
+        ));
+    }
+
+    let mut used = Vec::default();
+    find_variables(&code, &mut used);
+    let captures = used
+        .into_iter()
+        .filter(|name| !finalized_arguments.iter().any(|argument| &argument.name == name))
+        .filter_map(|name| {
+            variables.variables.get(&name).map(|field_type| FinalizedField { name, field_type: field_type.clone() })
+        })
+        .collect::<Vec<_>>();
+
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        FinalizedEffectType::Closure(captures, finalized_arguments, returning, code),
+    ));
+}
+
+/// Checks a call to a closure stored in a variable, or returns None if the variable isn't a closure
+pub async fn check_closure_call(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: &Effects,
+) -> Option<Result<FinalizedEffects, ParsingError>> {
+    let (name, arguments) = match &effect.types {
+        EffectType::MethodCall(None, name, arguments, _, _) => (name, arguments),
+        _ => return None,
+    };
+    let expected = match variables.variables.get(name).and_then(|types| types.as_function()) {
+        Some((arguments, _)) => arguments.clone(),
+        None => return None,
+    };
+
+    return Some(
+        check_closure_arguments(code_verifier, variables, name.clone(), arguments.clone(), expected, &effect.span).await,
+    );
+}
+
+/// Checks the arguments given to a closure match the closure's argument types
+async fn check_closure_arguments(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    name: String,
+    arguments: Vec<Effects>,
+    expected: Vec<FinalizedTypes>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    if arguments.len() != expected.len() {
+        return Err(span.make_error(ParsingMessage::MissingArgument()));
+    }
+
+    let mut finalized = Vec::default();
+    for (argument, expected) in arguments.into_iter().zip(&expected) {
+        let error = argument.span.clone();
+        let mut argument = verify_effect(code_verifier, variables, argument).await?;
+        coerce_literal(&mut argument, expected, &error)?;
+        let found = get_return(&argument.types, variables, &code_verifier.syntax).await.unwrap();
+        if !found.of_type(expected, code_verifier.syntax.clone()).await {
+            return Err(error.make_error(ParsingMessage::MismatchedTypes(found, expected.clone())));
+        }
+        finalized.push(argument);
+    }
+
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        FinalizedEffectType::ClosureCall(
+            Box::new(FinalizedEffects::new(span.clone(), FinalizedEffectType::LoadVariable(name))),
+            finalized,
+        ),
+    ));
+}
+
+/// Parses and finalizes a type written in a closure's signature
+async fn finalize_type(
+    code_verifier: &CodeVerifier<'_>,
+    types: UnparsedType,
+    span: &Span,
+) -> Result<FinalizedTypes, ParsingError> {
+    return Ok(Syntax::parse_type(
+        code_verifier.syntax.clone(),
+        span.clone(),
+        code_verifier.resolver.boxed_clone(),
+        types,
+        vec![],
+    )
+    .await?
+    .finalize(code_verifier.syntax.clone())
+    .await);
+}
+
+/// Adds the name of every variable loaded in the code, in order of first use.
+/// Inner closures aren't walked, they already captured the variables they use.
+fn find_variables(code: &FinalizedCodeBody, found: &mut Vec<String>) {
+    let mut add = |name: &String| {
+        if !found.contains(name) {
+            found.push(name.clone());
+        }
+    };
+    walk_effects(code, &mut |effect, _| {
+        match &effect.types {
+            FinalizedEffectType::LoadVariable(name) => add(name),
+            FinalizedEffectType::Closure(captures, _, _, _) => {
+                captures.iter().for_each(|capture| add(&capture.name));
+                return false;
+            }
+            _ => {}
+        }
+        return true;
+    });
+}
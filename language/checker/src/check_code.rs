@@ -1,3 +1,5 @@
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 
 use async_recursion::async_recursion;
@@ -5,41 +7,74 @@ use data::tokens::Span;
 use syntax::async_util::UnparsedType;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{
-    EffectType, Effects, ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression,
+    EffectType, Effects, Expression, ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression,
 };
+use syntax::program::constant::UnfinalizedConstant;
 use syntax::program::function::{CodeBody, FinalizedCodeBody};
+use syntax::program::r#struct::{get_integer, integer_max, literal_integers, RC, U64};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
-use syntax::SimpleVariableManager;
+use syntax::top_element_manager::FinishedImplsWaiter;
+use syntax::{is_modifier, Modifier, SimpleVariableManager};
 
+use crate::check_borrow::check_borrows;
+use crate::check_cast::verify_cast;
+use crate::check_closure::verify_closure;
+use crate::check_constant::verify_constant;
+use crate::check_destructure::verify_destructure;
+use crate::check_enum::{check_variant, verify_switch};
 use crate::check_impl_call::check_impl_call;
+use crate::check_interpolation::verify_interpolation;
+use crate::check_loop::{find_loop, verify_break, verify_continue, verify_loop};
 use crate::check_method_call::check_method_call;
 use crate::check_operator::check_operator;
+use crate::check_static_assert::verify_static_assert;
+use crate::check_string::verify_string;
 use crate::degeneric::degeneric_type_fields;
-use crate::{get_return, CodeVerifier};
+use crate::{check_poison, closest_name, get_checked_return, get_return, returns_first_argument, CodeVerifier};
+
+/// The trait called when an owned variable goes out of scope
+const DROP_TRAIT: &str = "mem::Drop";
+/// The marker trait for structs that are copied instead of moved when passed by value
+const COPY_TRAIT: &str = "mem::Copy";
+/// Holds the returned value while the owned variables are dropped
+const RETURNING: &str = "$returning";
 
 /// Verifies a block of code, linking all method calls and types, and making sure the code is ready to compile.
+#[async_recursion]
 pub async fn verify_code(
     code_verifier: &mut CodeVerifier<'_>,
     variables: &mut SimpleVariableManager,
     code: CodeBody,
     top: bool,
 ) -> Result<FinalizedCodeBody, ParsingError> {
+    check_borrows(&code)?;
     let mut body = Vec::default();
     let mut found_end = false;
-    for line in code.expressions {
-        match &line.effect.types {
-            EffectType::CompareJump(_, _, _) => found_end = true,
-            EffectType::Jump(_) => found_end = true,
-            _ => {}
+    // Variables owned before this block belong to the outer scope
+    let scope = variables.owned.len();
+    // Set once the block returns or breaks, so only the first statement after it is warned about
+    let mut returned = false;
+    let mut lines = code.expressions.into_iter();
+    while let Some(line) = lines.next() {
+        if returned {
+            returned = !warn_unreachable(code_verifier, [&line].into_iter());
         }
 
-        body.push(FinalizedExpression::new(
-            line.expression_type.clone(),
-            verify_effect(code_verifier, variables, line.effect).await?,
-        ));
+        let line = match verify_statement(code_verifier, variables, line, &mut body).await? {
+            Some(line) => line,
+            None => continue,
+        };
+        found_end |= drop_before_jump(code_verifier, variables, &line.effect.types, &mut body, scope).await?;
+
+        let effect = verify_effect(code_verifier, variables, line.effect).await?;
+        returned |= push_line(variables, &mut body, &line.expression_type, effect);
 
         if check_return_type(line.expression_type, code_verifier, &mut body, variables, &code_verifier.syntax).await? {
+            // The rest of the block is never checked or compiled
+            let rest = lines.collect::<Vec<_>>();
+            warn_unreachable(code_verifier, rest.iter());
+            drop_returning(code_verifier, variables, &mut body).await?;
             return Ok(FinalizedCodeBody::new(body.clone(), code.label.clone(), true));
         }
     }
@@ -48,10 +83,259 @@ pub async fn verify_code(
         panic!("Code body with label {} doesn't return or jump!", code.label)
     }
 
-    return Ok(FinalizedCodeBody::new(body, code.label.clone(), false));
+    if top {
+        drop_owned(code_verifier, variables, &mut body, 0).await?;
+    }
+
+    let returns = branches_return(&body);
+    return Ok(FinalizedCodeBody::new(body, code.label.clone(), returns));
+}
+
+/// Checks the lines that are more than an effect, static asserts and destructuring, adding them to the body.
+/// Returns the line if it's a single effect that still needs to be checked.
+/// It's separate from verify_code to keep its stack frame small, since it's on the stack for every nested block.
+#[async_recursion]
+async fn verify_statement(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    line: Expression,
+    body: &mut Vec<FinalizedExpression>,
+) -> Result<Option<Expression>, ParsingError> {
+    // Static asserts are checked now and removed, unless they need the generics to be known
+    if let EffectType::StaticAssert(condition) = line.effect.types {
+        if let Some(assert) = verify_static_assert(code_verifier, *condition, &line.effect.span).await? {
+            body.push(FinalizedExpression::new(line.expression_type, assert));
+        }
+        return Ok(None);
+    }
+
+    // Destructuring is checked into a line creating each variable in the pattern
+    if let EffectType::Destructure(pattern, value) = line.effect.types {
+        for effect in verify_destructure(code_verifier, variables, pattern, *value).await? {
+            body.push(FinalizedExpression::new(ExpressionType::Line, effect));
+        }
+        return Ok(None);
+    }
+    return Ok(Some(line));
+}
+
+/// Drops the variables owned in the scope being jumped out of, returning if the effect is a jump ending the block
+#[async_recursion]
+async fn drop_before_jump(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: &EffectType,
+    body: &mut Vec<FinalizedExpression>,
+    scope: usize,
+) -> Result<bool, ParsingError> {
+    match effect {
+        EffectType::CompareJump(_, _, _) => {}
+        EffectType::Jump(_) => drop_owned(code_verifier, variables, body, scope).await?,
+        // Jumping out of the loop drops everything owned inside of it, not just in this block
+        EffectType::Break(label, _) | EffectType::Continue(label) => {
+            if let Some(scope) = find_loop(&code_verifier.loops, label).map(|found| found.scope) {
+                drop_owned(code_verifier, variables, body, scope).await?;
+            }
+        }
+        _ => return Ok(false),
+    }
+    return Ok(true);
+}
+
+/// Adds the checked line to the body, returning if it returns or breaks
+fn push_line(
+    variables: &mut SimpleVariableManager,
+    body: &mut Vec<FinalizedExpression>,
+    expression_type: &ExpressionType,
+    effect: FinalizedEffects,
+) -> bool {
+    if let (ExpressionType::Return(_), FinalizedEffectType::LoadVariable(name)) = (expression_type, &effect.types) {
+        variables.owned.retain(|owned| owned != name);
+    }
+    if matches!(expression_type, ExpressionType::Break) {
+        // Breaks and continues are checked into jumps, which are compiled like any other line
+        body.push(FinalizedExpression::new(ExpressionType::Line, effect));
+    } else {
+        body.push(FinalizedExpression::new(expression_type.clone(), effect));
+    }
+    return matches!(expression_type, ExpressionType::Return(_) | ExpressionType::Break);
+}
+
+/// Checks if the block is an if where every branch returns, which is when both blocks its condition jumps to return.
+/// Else ifs are nested ifs in the else block, and loops jump to the end of the block, so they never return.
+fn branches_return(body: &[FinalizedExpression]) -> bool {
+    let returning = |label: &String| {
+        body.iter().any(|line| match &line.effect.types {
+            FinalizedEffectType::CodeBody(inner) => inner.label == *label && inner.returns,
+            _ => false,
+        })
+    };
+    return body.iter().any(|line| match &line.effect.types {
+        FinalizedEffectType::CompareJump(_, first, second) => returning(first) && returning(second),
+        _ => false,
+    });
+}
+
+/// Warns about the first statement written in the code, if any, since it comes after a return or break.
+/// Returns if a warning was made, the jumps added by control statements aren't written so they're skipped.
+fn warn_unreachable<'a>(code_verifier: &CodeVerifier<'_>, mut dead: impl Iterator<Item = &'a Expression>) -> bool {
+    return match dead.find_map(|line| written_span(&line.effect)) {
+        Some(span) => {
+            code_verifier.syntax.lock().unwrap().warn(span.make_error(ParsingMessage::UnreachableCode()));
+            true
+        }
+        None => false,
+    };
+}
+
+/// Finds the span of the first part of the effect written in the code, like the condition of an if statement
+fn written_span(effect: &Effects) -> Option<Span> {
+    if !effect.span.is_default() {
+        return Some(effect.span.clone());
+    }
+    return match &effect.types {
+        EffectType::CodeBody(body) | EffectType::Loop(body, _, _, _) => {
+            body.expressions.iter().find_map(|line| written_span(&line.effect))
+        }
+        EffectType::CompareJump(condition, _, _)
+        | EffectType::CreateVariable(_, condition, _)
+        | EffectType::Destructure(_, condition) => written_span(condition),
+        _ => None,
+    };
+}
+
+/// Calls Drop::drop on every variable owned since the start of the scope, in reverse declaration order
+#[async_recursion]
+async fn drop_owned(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    body: &mut Vec<FinalizedExpression>,
+    scope: usize,
+) -> Result<(), ParsingError> {
+    // Moved values are dropped by whatever they were moved into
+    let dropping =
+        variables.owned[scope..].iter().rev().filter(|name| !variables.moved.contains(*name)).cloned().collect::<Vec<_>>();
+    for name in dropping {
+        let effect = Effects::new(
+            Span::default(),
+            EffectType::ImplementationCall(
+                Box::new(Effects::new(Span::default(), EffectType::LoadVariable(name))),
+                DROP_TRAIT.to_string(),
+                "drop".to_string(),
+                vec![],
+                None,
+                vec![],
+            ),
+        );
+        body.push(FinalizedExpression::new(ExpressionType::Line, verify_effect(code_verifier, variables, effect).await?));
+    }
+    return Ok(());
+}
+
+/// Drops every owned variable before the function returns, storing the returned value first so it can still use them
+#[async_recursion]
+async fn drop_returning(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    body: &mut Vec<FinalizedExpression>,
+) -> Result<(), ParsingError> {
+    if variables.owned.is_empty() {
+        return Ok(());
+    }
+
+    let returning = body.pop().unwrap();
+    // If/for/while blocks drop the variables in each of their branches
+    if matches!(returning.effect.types, FinalizedEffectType::CodeBody(_)) {
+        body.push(returning);
+        return Ok(());
+    }
+
+    let types = get_return(&returning.effect.types, variables, &code_verifier.syntax).await.unwrap();
+    let span = returning.effect.span.clone();
+    variables.declare(RETURNING.to_string(), types.clone());
+    body.push(FinalizedExpression::new(
+        ExpressionType::Line,
+        FinalizedEffects::new(
+            span.clone(),
+            FinalizedEffectType::CreateVariable(RETURNING.to_string(), Box::new(returning.effect), types),
+        ),
+    ));
+    drop_owned(code_verifier, variables, body, 0).await?;
+    body.push(FinalizedExpression::new(
+        returning.expression_type,
+        FinalizedEffects::new(span, FinalizedEffectType::LoadVariable(RETURNING.to_string())),
+    ));
+    return Ok(());
+}
+
+/// Checks if the type is a reference to a struct, which values like numbers are copied instead of
+fn is_borrowable(types: &FinalizedTypes) -> bool {
+    return match types {
+        FinalizedTypes::Reference(inner) => {
+            matches!(**inner, FinalizedTypes::Struct(_) | FinalizedTypes::GenericType(_, _))
+                && !is_modifier(inner.inner_struct().data.modifiers, Modifier::Internal)
+        }
+        _ => false,
+    };
+}
+
+/// Checks if a new variable owns its value, which is only true for newly created structs implementing Drop
+/// and Rcs made by Rc::new or clone, since those are the only calls that count them.
+/// Other calls returning an Rc, like a getter returning one from a field, don't own it.
+async fn owns_value(code_verifier: &CodeVerifier<'_>, effect: &FinalizedEffects, types: &FinalizedTypes) -> bool {
+    let owned = match &effect.types {
+        FinalizedEffectType::CreateStruct(_, _, _) => true,
+        FinalizedEffectType::MethodCall(_, function, _, _) => {
+            // Rc's methods are named like mem::Rc<T>::clone, with the generics after a $ once they're degenericed
+            let name = function.data.name.split('$').next().unwrap();
+            types.is_rc() && name.starts_with(RC) && (name.ends_with("::new") || name.ends_with("::clone"))
+        }
+        _ => false,
+    };
+    if !owned {
+        return false;
+    }
+
+    let drop_trait = match Syntax::get_struct(
+        code_verifier.syntax.clone(),
+        Span::default(),
+        DROP_TRAIT.to_string(),
+        code_verifier.resolver.boxed_clone(),
+        vec![],
+    )
+    .await
+    {
+        Ok(found) => found.finalize(code_verifier.syntax.clone()).await,
+        Err(_) => return false,
+    };
+    return types.of_type(&drop_trait, code_verifier.syntax.clone()).await;
+}
+
+/// Checks if passing a variable of the type by value moves it, which is true for structs that don't implement Copy.
+/// Numbers and other internal types are always copied.
+pub async fn moves_value(code_verifier: &CodeVerifier<'_>, types: &FinalizedTypes) -> bool {
+    match types.inner_struct_safe() {
+        Some(found) if !types.is_trait() && !is_modifier(found.data.modifiers, Modifier::Internal) => {}
+        _ => return false,
+    }
+
+    let copy_trait = match Syntax::get_struct(
+        code_verifier.syntax.clone(),
+        Span::default(),
+        COPY_TRAIT.to_string(),
+        code_verifier.resolver.boxed_clone(),
+        vec![],
+    )
+    .await
+    {
+        Ok(found) => found.finalize(code_verifier.syntax.clone()).await,
+        Err(_) => return false,
+    };
+    return !types.of_type(&copy_trait, code_verifier.syntax.clone()).await;
 }
 
 /// Checks to make sure the return type matches in the code block.
+#[async_recursion]
 async fn check_return_type(
     line: ExpressionType,
     code_verifier: &CodeVerifier<'_>,
@@ -69,17 +353,21 @@ async fn check_return_type(
         None => return Ok(false),
     };
 
-    let last_effect = body.pop().unwrap();
+    let mut last_effect = body.pop().unwrap();
+    coerce_literal(&mut last_effect.effect, return_type, &span)?;
+    expect_return(&mut last_effect.effect, return_type);
     let last_effect_type;
     if let Some(found) = get_return(&last_effect.effect.types, variables, syntax).await {
         last_effect_type = found;
     } else {
-        // This is an if/for/while block, skip it
+        // This is an if or switch whose branches all return, they checked their own return types
+        body.push(last_effect);
         return Ok(true);
     }
 
-    // Only downcast types that don't match and aren't generic
-    if last_effect_type == *return_type || !last_effect_type.name_safe().is_some() {
+    // Only downcast types that don't match and aren't generic or poisoned
+    if last_effect_type == *return_type || !last_effect_type.name_safe().is_some() || !last_effect_type.poisoned().is_empty()
+    {
         body.push(last_effect);
         return Ok(true);
     }
@@ -94,88 +382,334 @@ async fn check_return_type(
         ));
         Ok(true)
     } else {
+        retype_literal(variables, &last_effect.effect, &last_effect_type, return_type)?;
         Err(span.make_error(ParsingMessage::UnexpectedReturnType(last_effect_type, return_type.clone())))
     };
 }
 
-/// Verifies a single effect
+/// Verifies a single effect, erroring if it's nested too deep inside other effects
 #[async_recursion]
-// skipcq: RS-R1000 Match statements have complexity calculated incorrectly
 pub async fn verify_effect(
     code_verifier: &mut CodeVerifier<'_>,
     variables: &mut SimpleVariableManager,
     effect: Effects,
 ) -> Result<FinalizedEffects, ParsingError> {
+    // Every nested effect is checked recursively, so without a limit deeply nested code overflows the stack
+    if code_verifier.depth >= code_verifier.process_manager.max_nesting {
+        return Err(effect.span.make_error(ParsingMessage::NestingTooDeep(code_verifier.process_manager.max_nesting)));
+    }
+    code_verifier.depth += 1;
+    let output = verify_nested_effect(code_verifier, variables, effect).await;
+    code_verifier.depth -= 1;
+    return output;
+}
+
+/// Finds the constant with the name, waiting for every file to be parsed if it isn't declared before its use
+pub async fn find_constant(code_verifier: &CodeVerifier<'_>, name: &String) -> Option<Arc<UnfinalizedConstant>> {
+    if let Some(constant) = code_verifier.resolver.constant(name) {
+        return Some(constant);
+    }
+    FinishedImplsWaiter { syntax: code_verifier.syntax.clone() }.await;
+    let full_name = format!("{}::{}", code_verifier.resolver.module(), name);
+    return code_verifier.syntax.lock().unwrap().constants.get(&full_name).cloned();
+}
+
+/// Verifies a single effect, see verify_effect.
+/// Effects with more than a call to check are verified in their own functions, which keeps this function's stack
+/// frame small since it's on the stack once for each level of nesting.
+// skipcq: RS-R1000 Match statements have complexity calculated incorrectly
+async fn verify_nested_effect(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: Effects,
+) -> Result<FinalizedEffects, ParsingError> {
+    if let EffectType::LoadVariable(_) = &effect.types {
+        if let Some(found) = verify_variable(code_verifier, variables, &effect).await {
+            return found;
+        }
+    }
+
+    // Some domains forbid NaN and Inf, so writing them is an error there
+    if let EffectType::Float(value) = &effect.types {
+        if code_verifier.process_manager.reject_special_floats && !value.is_finite() {
+            let literal = if value.is_nan() { "NaN" } else { "Inf" };
+            return Err(effect.span.make_error(ParsingMessage::SpecialFloatLiteral(literal.to_string())));
+        }
+    }
+
     // Some basic effects are handled in finalize_basic
     if let Some(found) = finalize_basic(&effect).await {
         return Ok(found);
     }
 
-    let output = match effect.types {
-        EffectType::Paren(inner) => verify_effect(code_verifier, variables, *inner).await?,
-        EffectType::CodeBody(body) => FinalizedEffects::new(
-            effect.span.clone(),
-            FinalizedEffectType::CodeBody(verify_code(code_verifier, &mut variables.clone(), body, false).await?),
-        ),
-        EffectType::Set(first, second) => FinalizedEffects::new(
-            effect.span.clone(),
-            FinalizedEffectType::Set(
-                Box::new(verify_effect(code_verifier, variables, *first).await?),
-                Box::new(verify_effect(code_verifier, variables, *second).await?),
-            ),
-        ),
-        EffectType::Operation(_, _) => check_operator(code_verifier, variables, effect).await?,
-        EffectType::ImplementationCall(_, _, _, _, _) => check_impl_call(code_verifier, variables, effect).await?,
-        EffectType::MethodCall(_, _, _, _) => check_method_call(code_verifier, variables, effect).await?,
-        EffectType::CompareJump(effect, first, second) => FinalizedEffects::new(
-            effect.span.clone(),
-            FinalizedEffectType::CompareJump(
-                Box::new(verify_effect(code_verifier, variables, *effect).await?),
-                first,
-                second,
-            ),
-        ),
-        EffectType::CreateStruct(target, effects) => verify_create_struct(code_verifier, target, effects, variables).await?,
-        EffectType::Load(inner_effect, target) => {
-            let output = verify_effect(code_verifier, variables, *inner_effect).await?;
-            let types = get_return(&output.types, variables, &code_verifier.syntax).await.unwrap();
-
-            FinalizedEffects::new(effect.span.clone(), FinalizedEffectType::Load(Box::new(output), target.clone(), types))
-        }
-        EffectType::CreateVariable(name, inner_effect) => {
-            let effect = verify_effect(code_verifier, variables, *inner_effect).await?;
-            let found;
-            if let Some(temp_found) = get_return(&effect.types, variables, &code_verifier.syntax).await {
-                found = temp_found;
-            } else {
-                return Err(effect.span.make_error(ParsingMessage::UnexpectedVoid()));
-            };
+    // Each effect is verified by a boxed future, so this function's frame only holds one of them at a time
+    let verifying: Pin<Box<dyn Future<Output = Result<FinalizedEffects, ParsingError>> + Send + Sync + '_>> = match effect
+        .types
+    {
+        EffectType::Paren(inner) => verify_effect(code_verifier, variables, *inner),
+        EffectType::CodeBody(body) => verify_body(code_verifier, variables, body, &effect.span),
+        EffectType::Set(first, second) => verify_set(code_verifier, variables, *first, *second, &effect.span),
+        EffectType::Operation(_, _) => check_operator(code_verifier, variables, effect),
+        EffectType::ImplementationCall(_, _, _, _, _, _) => check_impl_call(code_verifier, variables, effect),
+        EffectType::MethodCall(_, _, _, _, _) => check_method_call(code_verifier, variables, effect),
+        EffectType::CompareJump(effect, first, second) => {
+            verify_compare_jump(code_verifier, variables, *effect, first, second)
+        }
+        EffectType::CreateStruct(target, effects) => {
+            verify_create_struct(code_verifier, target, effects, variables, &effect.span)
+        }
+        EffectType::Load(inner_effect, target) => verify_load(code_verifier, variables, *inner_effect, target, &effect.span),
+        EffectType::CreateVariable(name, inner_effect, types) => {
+            verify_create_variable(code_verifier, variables, name, *inner_effect, types)
+        }
+        EffectType::CreateArray(effects) => verify_create_array(code_verifier, variables, effects, &effect.span),
+        EffectType::Closure(arguments, returning, body) => {
+            verify_closure(code_verifier, variables, arguments, returning, body, &effect.span)
+        }
+        EffectType::Switch(value, arms, label) => verify_switch(code_verifier, variables, *value, arms, label, &effect.span),
+        EffectType::Loop(body, continuing, breaking, label) => {
+            verify_loop(code_verifier, variables, body, continuing, breaking, label, &effect.span)
+        }
+        EffectType::Break(label, value) => return verify_break(code_verifier, label, value, &effect.span),
+        EffectType::Continue(label) => return verify_continue(code_verifier, label, &effect.span),
+        EffectType::StringLiteral(literal) => return verify_string(&literal, &effect.span),
+        EffectType::Interpolation(parts) => verify_interpolation(code_verifier, variables, parts, &effect.span),
+        EffectType::Cast(value, target) => verify_cast(code_verifier, variables, *value, target, &effect.span),
+        EffectType::MutableBorrow(name) => verify_borrow(code_verifier, variables, name, &effect.span),
+        // Method calls unwrap their named arguments, so any left over were passed to something else
+        EffectType::NamedArgument(name, _) => {
+            return Err(effect.span.make_error(ParsingMessage::UnexpectedNamedArgument(name)))
+        }
+        _ => unreachable!(),
+    };
 
-            variables.variables.insert(name.clone(), found.clone());
-            FinalizedEffects::new(
+    return verifying.await;
+}
+
+/// Verifies a block of code in its own scope
+#[async_recursion]
+async fn verify_body(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    body: CodeBody,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    variables.push_scope();
+    let body = verify_code(code_verifier, variables, body, false).await;
+    variables.pop_scope();
+    return Ok(FinalizedEffects::new(span.clone(), FinalizedEffectType::CodeBody(body?)));
+}
+
+/// Verifies the condition of a jump to the first label if it's true, or the second if it isn't
+#[async_recursion]
+async fn verify_compare_jump(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: Effects,
+    first: String,
+    second: String,
+) -> Result<FinalizedEffects, ParsingError> {
+    let span = effect.span.clone();
+    let condition = verify_effect(code_verifier, variables, effect).await?;
+    return Ok(FinalizedEffects::new(span, FinalizedEffectType::CompareJump(Box::new(condition), first, second)));
+}
+
+/// Verifies loading a variable, or returns None if it's a variable that's in scope
+#[async_recursion]
+async fn verify_variable(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: &Effects,
+) -> Option<Result<FinalizedEffects, ParsingError>> {
+    // Enum variants without fields, like Shape::Empty, look like variables
+    if let Some(found) = check_variant(code_verifier, variables, effect).await {
+        return Some(found);
+    }
+
+    let name = match &effect.types {
+        EffectType::LoadVariable(name) => name,
+        _ => unreachable!(),
+    };
+    // Constants are replaced by their checked value
+    if !variables.variables.contains_key(name) && !variables.ended.contains(name) {
+        if let Some(constant) = find_constant(code_verifier, name).await {
+            let resolver = code_verifier.resolver.boxed_clone();
+            let process_manager = code_verifier.process_manager;
+            let syntax = code_verifier.syntax.clone();
+            let using = code_verifier.constants.clone();
+            return Some(
+                verify_constant(process_manager, &constant, resolver, &syntax, code_verifier.depth, using)
+                    .await
+                    .map(|constant| constant.value),
+            );
+        }
+    }
+
+    // Variables are removed when their scope ends, so using one afterwards is an error
+    if !variables.variables.contains_key(name) && variables.ended.contains(name) {
+        return Some(Err(effect.span.make_error(ParsingMessage::VariableOutOfScope(name.clone()))));
+    }
+    if variables.moved.contains(name) {
+        return Some(Err(effect.span.make_error(ParsingMessage::UseAfterMove(name.clone()))));
+    }
+    return None;
+}
+
+/// Verifies setting the first effect, like a variable or field, to the second
+#[async_recursion]
+async fn verify_set(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    first: Effects,
+    second: Effects,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    // Setting a moved variable gives it a new value to use, even if the value moved it
+    let setting = match &first.types {
+        EffectType::LoadVariable(name) => Some(name.clone()),
+        _ => None,
+    };
+    // The value runs before it's stored, so anything it moves is moved before the variable is set
+    let value = verify_effect(code_verifier, variables, second).await?;
+    if let Some(name) = &setting {
+        variables.moved.remove(name);
+    }
+    let set = FinalizedEffectType::Set(Box::new(verify_effect(code_verifier, variables, first).await?), Box::new(value));
+    return Ok(FinalizedEffects::new(span.clone(), set));
+}
+
+/// Verifies loading the target field of the effect
+#[async_recursion]
+async fn verify_load(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    inner_effect: Effects,
+    target: String,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let output = verify_effect(code_verifier, variables, inner_effect).await?;
+    let types = get_checked_return(&output.types, variables, &code_verifier.syntax, span).await?;
+    check_field(&types, &target, span)?;
+
+    return Ok(FinalizedEffects::new(span.clone(), FinalizedEffectType::Load(Box::new(output), target, types)));
+}
+
+/// Verifies declaring a variable with the value, and the type if it was given one
+#[async_recursion]
+async fn verify_create_variable(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    name: String,
+    inner_effect: Effects,
+    types: Option<UnparsedType>,
+) -> Result<FinalizedEffects, ParsingError> {
+    let mut effect = verify_effect(code_verifier, variables, inner_effect).await?;
+    let declared = match types {
+        Some(types) => {
+            let types = Syntax::parse_type(
+                code_verifier.syntax.clone(),
                 effect.span.clone(),
-                FinalizedEffectType::CreateVariable(name.clone(), Box::new(effect), found),
+                code_verifier.resolver.boxed_clone(),
+                types,
+                vec![],
             )
+            .await?
+            .finalize(code_verifier.syntax.clone())
+            .await;
+            let span = effect.span.clone();
+            coerce_literal(&mut effect, &types, &span)?;
+            expect_return(&mut effect, &types);
+            Some(types)
         }
-        EffectType::CreateArray(effects) => {
-            let mut output = Vec::default();
-            for effect in effects {
-                output.push(verify_effect(code_verifier, variables, effect).await?);
+        None => {
+            if let Some((_, retyped)) = code_verifier.literal_types.iter().find(|(span, _)| same_span(span, &effect.span)) {
+                let span = effect.span.clone();
+                coerce_literal(&mut effect, retyped, &span)?;
             }
+            None
+        }
+    };
+    // Only a variable without a type can take the type it's used as
+    if declared.is_none() && is_untyped_literal(&effect) && !effect.span.is_default() {
+        variables.literals.insert(name.clone(), effect.span.clone());
+    } else {
+        variables.literals.remove(&name);
+    }
+    let found;
+    if let Some(temp_found) = get_return(&effect.types, variables, &code_verifier.syntax).await {
+        found = temp_found;
+    } else {
+        return Err(effect.span.make_error(ParsingMessage::UnexpectedVoid()));
+    };
+    if let Some(declared) = declared {
+        if !found.of_type(&declared, code_verifier.syntax.clone()).await {
+            retype_literal(variables, &effect, &found, &declared)?;
+            return Err(effect.span.make_error(ParsingMessage::MismatchedTypes(found, declared)));
+        }
+    }
 
-            let types = match output.first() {
-                Some(found) => get_return(&found.types, variables, &code_verifier.syntax).await,
-                None => None,
-            };
+    if variables.declare(name.clone(), found.clone()) {
+        let warning = effect.span.make_error(ParsingMessage::ShadowedVariable(name.clone()));
+        code_verifier.syntax.lock().unwrap().warn(warning);
+    }
+    variables.owned.retain(|owned| *owned != name);
+    if owns_value(code_verifier, &effect, &found).await {
+        variables.owned.push(name.clone());
+    }
+    return Ok(FinalizedEffects::new(
+        effect.span.clone(),
+        FinalizedEffectType::CreateVariable(name, Box::new(effect), found),
+    ));
+}
 
-            check_type(&types, &output, variables, code_verifier, &effect.span).await?;
+/// Verifies creating an array of the effects
+#[async_recursion]
+async fn verify_create_array(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effects: Vec<Effects>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let mut output = Vec::default();
+    for effect in effects {
+        output.push(verify_effect(code_verifier, variables, effect).await?);
+    }
 
-            FinalizedEffects::new(effect.span.clone(), store(FinalizedEffectType::CreateArray(types, output)))
-        }
-        _ => unreachable!(),
+    // Untyped literals take the type of the array's other elements, like the 2 in [1u64, 2]
+    let typed = output.iter().find(|value| !is_int_literal(value));
+    let types = match typed.or(output.first()) {
+        Some(found) => get_return(&found.types, variables, &code_verifier.syntax).await,
+        None => None,
     };
+    let mixed = typed.is_some();
+    if let Some(element) = types.as_ref().filter(|element| mixed && element.is_integer()) {
+        for value in output.iter_mut().filter(|value| is_int_literal(value)) {
+            coerce_literal(value, element, span)?;
+        }
+    }
+
+    check_type(&types, &output, variables, code_verifier, span).await?;
 
-    return Ok(output);
+    return Ok(FinalizedEffects::new(span.clone(), store(FinalizedEffectType::CreateArray(types, output))));
+}
+
+/// Verifies a mutable borrow of the variable.
+/// Struct variables already hold a reference to their struct, so a borrow loads that reference without
+/// moving it. check_borrows makes sure borrows don't overlap
+#[async_recursion]
+async fn verify_borrow(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    name: String,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let borrowed =
+        verify_effect(code_verifier, variables, Effects::new(span.clone(), EffectType::LoadVariable(name.clone()))).await?;
+    let types = get_return(&borrowed.types, variables, &code_verifier.syntax).await.unwrap();
+    if !is_borrowable(&types) {
+        return Err(span.make_error(ParsingMessage::CopiedBorrow(name, types)));
+    }
+    return Ok(borrowed);
 }
 
 /// Separately handles a few basic effects to declutter the main function
@@ -187,22 +721,52 @@ async fn finalize_basic(effects: &Effects) -> Option<FinalizedEffects> {
             EffectType::Jump(jumping) => FinalizedEffectType::Jump(jumping.clone()),
             EffectType::LoadVariable(variable) => FinalizedEffectType::LoadVariable(variable.clone()),
             EffectType::Float(float) => store(FinalizedEffectType::Float(*float)),
-            EffectType::Int(int) => store(FinalizedEffectType::UInt(*int as u64)),
-            EffectType::UInt(uint) => store(FinalizedEffectType::UInt(*uint)),
+            EffectType::Int(int) => store(FinalizedEffectType::Int(*int)),
+            EffectType::UInt(uint) => store(FinalizedEffectType::TypedInt(*uint, FinalizedTypes::Struct(U64.clone()))),
+            EffectType::TypedInt(int, suffix) => {
+                store(FinalizedEffectType::TypedInt(*int, FinalizedTypes::Struct(get_integer(suffix).unwrap())))
+            }
             EffectType::Bool(bool) => store(FinalizedEffectType::Bool(*bool)),
-            EffectType::String(string) => store(FinalizedEffectType::String(string.clone())),
             EffectType::Char(char) => store(FinalizedEffectType::Char(*char)),
             _ => return None,
         },
     ));
 }
 
+/// Errors if the type doesn't have the field, or if the field is private and accessed from another file
+fn check_field(types: &FinalizedTypes, name: &String, span: &Span) -> Result<(), ParsingError> {
+    // Generics don't have fields until they're degenericed
+    let structure = match types.inner_struct_safe() {
+        Some(structure) => structure,
+        None => return Ok(()),
+    };
+    return match structure.fields.iter().find(|field| &field.field.name == name) {
+        Some(field) => {
+            let visible =
+                is_modifier(field.modifiers, Modifier::Public) || is_modifier(field.modifiers, Modifier::Protected);
+            if !visible && structure.data.span.file != span.file {
+                // Instances of generic structs are shown as the struct they were degenericed from
+                let structure = structure.data.name.split('$').next().unwrap().to_string();
+                Err(span.make_error(ParsingMessage::PrivateField(name.clone(), structure)))
+            } else {
+                Ok(())
+            }
+        }
+        None => {
+            let similar = closest_name(name, structure.fields.iter().map(|field| &field.field.name));
+            Err(span.make_error(ParsingMessage::UnknownField(name.clone(), similar)))
+        }
+    };
+}
+
 /// Verifies a CreateStruct call
+#[async_recursion]
 async fn verify_create_struct(
     code_verifier: &mut CodeVerifier<'_>,
     target: UnparsedType,
     effects: Vec<(String, Effects)>,
     variables: &mut SimpleVariableManager,
+    span: &Span,
 ) -> Result<FinalizedEffects, ParsingError> {
     let mut target = Syntax::parse_type(
         code_verifier.syntax.clone(),
@@ -215,6 +779,14 @@ async fn verify_create_struct(
     .finalize(code_verifier.syntax.clone())
     .await;
 
+    if target.is_trait() {
+        return Err(span.make_error(ParsingMessage::InstantiateTrait(target)));
+    }
+    if target.is_enum() {
+        return Err(span.make_error(ParsingMessage::InstantiateEnum(target)));
+    }
+    check_poison(&target, span)?;
+
     let mut generics = code_verifier.process_manager.generics.clone();
     let mut final_effects = vec![];
     let fields = target.get_fields();
@@ -228,11 +800,18 @@ async fn verify_create_struct(
         }
 
         if i == fields.len() {
-            return Err(effect.span.make_error(ParsingMessage::UnknownField(field_name)));
+            let similar = closest_name(&field_name, fields.iter().map(|field| &field.field.name));
+            return Err(effect.span.make_error(ParsingMessage::UnknownField(field_name, similar)));
+        }
+
+        // Storing a variable in a struct moves it, so it's no longer dropped with the scope
+        if let EffectType::LoadVariable(name) = &effect.types {
+            variables.owned.retain(|owned| owned != name);
         }
 
         let error = effect.span.clone();
-        let final_effect = verify_effect(code_verifier, variables, effect).await?;
+        let mut final_effect = verify_effect(code_verifier, variables, effect).await?;
+        coerce_literal(&mut final_effect, &fields[i].field.field_type, &error)?;
         get_return(&final_effect.types, variables, &code_verifier.syntax)
             .await
             .unwrap()
@@ -242,6 +821,17 @@ async fn verify_create_struct(
     }
 
     degeneric_type_fields(&mut target, &mut generics, &code_verifier.syntax).await;
+    // Checked once the generics are filled in, so a field like [u64; N] has the struct's length
+    let fields = target.get_fields();
+    for (i, effect) in &mut final_effects {
+        let span = effect.span.clone();
+        coerce_literal(effect, &fields[*i].field.field_type, &span)?;
+        let returning = get_return(&effect.types, variables, &code_verifier.syntax).await.unwrap();
+        if !returning.of_type(&fields[*i].field.field_type, code_verifier.syntax.clone()).await {
+            let field_type = fields[*i].field.field_type.clone();
+            return Err(span.make_error(ParsingMessage::MismatchedTypes(returning, field_type)));
+        }
+    }
     return Ok(FinalizedEffects::new(
         Span::default(),
         FinalizedEffectType::CreateStruct(
@@ -271,7 +861,137 @@ async fn check_type(
     return Ok(());
 }
 
+/// Gives integer literals the integer type their context expects, erroring if a float is used instead
+pub fn coerce_literal(effect: &mut FinalizedEffects, expected: &FinalizedTypes, span: &Span) -> Result<(), ParsingError> {
+    let mut expected = expected;
+    while let FinalizedTypes::Reference(inner) = expected {
+        expected = inner;
+    }
+
+    match &mut effect.types {
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => {
+            return coerce_literal(inner, expected, span);
+        }
+        FinalizedEffectType::Int(int) if expected.is_integer() => {
+            // Literals are never negative, but constants folded from them can be
+            let name = &expected.inner_struct().data.name;
+            if *int >= 0 && integer_max(name).is_some_and(|max| *int as u64 > max) {
+                return Err(span.make_error(ParsingMessage::IntegerOutOfRange(int.to_string(), name.clone())));
+            }
+            effect.types = FinalizedEffectType::TypedInt(*int as u64, expected.clone());
+        }
+        FinalizedEffectType::Float(_) if expected.is_integer() => {
+            return Err(span.make_error(ParsingMessage::FloatAsInteger(expected.clone())));
+        }
+        // Arrays of only literals take the expected element type, like [1, 2] passed as a [u8]
+        FinalizedEffectType::CreateArray(types, values) => {
+            if let FinalizedTypes::Array(element, _) = expected {
+                if element.is_integer() && values.iter().all(is_int_literal) {
+                    for value in values.iter_mut() {
+                        coerce_literal(value, element, span)?;
+                    }
+                    *types = Some(*element.clone());
+                }
+            }
+        }
+        // The array operator returns its argument unchanged, so it's replaced by the argument with the expected type
+        FinalizedEffectType::MethodCall(_, function, arguments, _)
+            if returns_first_argument(function) && !arguments.is_empty() =>
+        {
+            let mut argument = arguments.remove(0);
+            coerce_literal(&mut argument, expected, span)?;
+            *effect = argument;
+        }
+        _ => {}
+    }
+    return Ok(());
+}
+
+/// Gets the type an untyped integer literal is when it's used as the given type, like a trait,
+/// which is the first integer type that's of it, or None if none are
+pub async fn literal_integer(expected: &FinalizedTypes, syntax: &Arc<Mutex<Syntax>>) -> Option<FinalizedTypes> {
+    for integer in literal_integers() {
+        let integer = FinalizedTypes::Struct(integer);
+        if integer.of_type(expected, syntax.clone()).await {
+            return Some(integer);
+        }
+    }
+    return None;
+}
+
+/// Gives a call to a generic function the type its context expects, so generics that only appear in
+/// the function's return type, like the T of fn empty<T>() -> [T], can be inferred
+fn expect_return(effect: &mut FinalizedEffects, expected: &FinalizedTypes) {
+    let mut expected = expected;
+    while let FinalizedTypes::Reference(inner) = expected {
+        expected = inner;
+    }
+
+    match &mut effect.types {
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => expect_return(inner, expected),
+        FinalizedEffectType::MethodCall(_, function, _, returning @ None) if !function.generics.is_empty() => {
+            *returning = Some((expected.clone(), effect.span.clone()));
+        }
+        _ => {}
+    }
+}
+
+/// Checks if the effect is an integer literal without a type suffix
+pub fn is_int_literal(effect: &FinalizedEffects) -> bool {
+    return match &effect.types {
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => is_int_literal(inner),
+        FinalizedEffectType::Int(_) => true,
+        _ => false,
+    };
+}
+
+/// Checks if the effect is an untyped integer literal or an array of only them, which can still become another type
+fn is_untyped_literal(effect: &FinalizedEffects) -> bool {
+    return match &effect.types {
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => is_untyped_literal(inner),
+        FinalizedEffectType::CreateArray(_, values) => !values.is_empty() && values.iter().all(is_int_literal),
+        // The array operator returns the array it's given
+        FinalizedEffectType::MethodCall(_, function, arguments, _) if returns_first_argument(function) => {
+            arguments.first().is_some_and(is_untyped_literal)
+        }
+        _ => is_int_literal(effect),
+    };
+}
+
+/// If the effect loads a variable declared from an untyped literal and the expected type is another integer type
+/// or an array of one, errors with RetypedLiteral so the function is checked again with the variable as that type
+pub fn retype_literal(
+    variables: &SimpleVariableManager,
+    effect: &FinalizedEffects,
+    found: &FinalizedTypes,
+    expected: &FinalizedTypes,
+) -> Result<(), ParsingError> {
+    let name = match &effect.types {
+        FinalizedEffectType::LoadVariable(name) => name,
+        _ => return Ok(()),
+    };
+    let declaration = match variables.literals.get(name) {
+        Some(declaration) => declaration,
+        None => return Ok(()),
+    };
+    let expected = expected.dereferenced();
+    let retyped = match (found.dereferenced(), expected) {
+        (FinalizedTypes::Array(_, _), FinalizedTypes::Array(element, _)) => element.is_integer(),
+        (FinalizedTypes::Array(_, _), _) => false,
+        _ => expected.is_integer(),
+    };
+    if !retyped || found.dereferenced() == expected {
+        return Ok(());
+    }
+    return Err(declaration.make_error(ParsingMessage::RetypedLiteral(name.clone(), expected.clone())));
+}
+
+/// Checks if the spans cover the same tokens of the same file
+fn same_span(first: &Span, second: &Span) -> bool {
+    return (first.file, first.start, first.end) == (second.file, second.start, second.end);
+}
+
 /// Shorthand for storing an effect on the heap
-fn store(effect: FinalizedEffectType) -> FinalizedEffectType {
+pub fn store(effect: FinalizedEffectType) -> FinalizedEffectType {
     return FinalizedEffectType::HeapStore(Box::new(FinalizedEffects::new(Span::default(), effect)));
 }
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use syntax::async_util::NameResolver;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::constant::{FinalizedConstant, UnfinalizedConstant};
+use syntax::program::function::CodelessFinalizedFunction;
+use syntax::program::syntax::Syntax;
+use syntax::program::types::FinalizedTypes;
+use syntax::program::walk::walk_effect;
+use syntax::{is_modifier, Attribute, Modifier, SimpleVariableManager};
+
+use crate::check_code::{coerce_literal, verify_effect};
+use crate::output::TypesChecker;
+use crate::{get_return, CodeVerifier};
+
+/// Checks a constant's value matches its type and doesn't call anything with side effects.
+/// Depth is how deeply the constant's use is nested, which counts against the nesting limit, and using is the
+/// constants whose values use this one.
+pub async fn verify_constant(
+    process_manager: &TypesChecker,
+    constant: &UnfinalizedConstant,
+    resolver: Box<dyn NameResolver>,
+    syntax: &Arc<Mutex<Syntax>>,
+    depth: usize,
+    mut using: Vec<String>,
+) -> Result<FinalizedConstant, ParsingError> {
+    if let Some(start) = using.iter().position(|name| name == &constant.name) {
+        let cycle = using[start..].iter().chain([&constant.name]).cloned().collect::<Vec<_>>();
+        return Err(constant.span.make_error(ParsingMessage::ConstantCycle(constant.name.clone(), cycle.join(" -> "))));
+    }
+    using.push(constant.name.clone());
+
+    let types =
+        Syntax::parse_type(syntax.clone(), constant.span.clone(), resolver.boxed_clone(), constant.types.clone(), vec![])
+            .await?
+            .finalize(syntax.clone())
+            .await;
+    let value =
+        verify_constant_value(process_manager, constant.value.clone(), &types, resolver, syntax, depth, using).await?;
+    return Ok(FinalizedConstant { name: constant.name.clone(), types, value });
+}
+
+/// Checks a value matches the type and doesn't call anything with side effects, like a constant or a default argument
+pub async fn verify_constant_value(
+    process_manager: &TypesChecker,
+    value: Effects,
+    types: &FinalizedTypes,
+    resolver: Box<dyn NameResolver>,
+    syntax: &Arc<Mutex<Syntax>>,
+    depth: usize,
+    constants: Vec<String>,
+) -> Result<FinalizedEffects, ParsingError> {
+    let mut code_verifier = CodeVerifier {
+        process_manager,
+        resolver,
+        return_type: None,
+        syntax: syntax.clone(),
+        debug_impls: false,
+        checked_math: false,
+        depth,
+        loops: Vec::default(),
+        constants,
+        literal_types: Vec::default(),
+    };
+    let mut variables = SimpleVariableManager::default();
+    let error = value.span.clone();
+    let mut value = verify_effect(&mut code_verifier, &mut variables, value).await?;
+    coerce_literal(&mut value, types, &error)?;
+
+    if let Some(name) = find_non_constant(&value) {
+        return Err(error.make_error(ParsingMessage::NonConstantCall(name)));
+    }
+
+    let found = get_return(&value.types, &variables, syntax).await.unwrap();
+    if !found.of_type(types, syntax.clone()).await {
+        return Err(error.make_error(ParsingMessage::MismatchedTypes(found, types.clone())));
+    }
+    return Ok(value);
+}
+
+/// Finds the name of the first function called by the effect that isn't a built-in operator
+fn find_non_constant(effect: &FinalizedEffects) -> Option<String> {
+    let mut found = None;
+    walk_effect(effect, None, &mut |effect, _| {
+        if found.is_none() {
+            found = match &effect.types {
+                FinalizedEffectType::MethodCall(_, function, _, _)
+                | FinalizedEffectType::GenericMethodCall(function, _, _)
+                    if !is_constant(function) =>
+                {
+                    Some(function.data.name.clone())
+                }
+                FinalizedEffectType::VirtualCall(_, function, _, _)
+                | FinalizedEffectType::GenericVirtualCall(_, _, function, _, _) => Some(function.data.name.clone()),
+                FinalizedEffectType::Closure(_, _, _, _) | FinalizedEffectType::ClosureCall(_, _) => {
+                    Some("a closure".to_string())
+                }
+                _ => None,
+            };
+        }
+        return found.is_none();
+    });
+    return found;
+}
+
+/// Only internal functions implemented by the compiler, like math operators, are safe to call from a constant
+fn is_constant(function: &CodelessFinalizedFunction) -> bool {
+    return is_modifier(function.data.modifiers, Modifier::Internal)
+        && Attribute::find_attribute("llvm_intrinsic", &function.data.attributes).is_none();
+}
@@ -0,0 +1,125 @@
+use async_recursion::async_recursion;
+use data::tokens::Span;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{EffectType, Effects, FinalizedEffects, Pattern};
+use syntax::program::syntax::Syntax;
+use syntax::program::types::{FinalizedTypes, Types};
+use syntax::SimpleVariableManager;
+
+use crate::check_code::verify_effect;
+use crate::{check_poison, CodeVerifier};
+
+/// The operation indexing an array, which loads each element of an array pattern
+const INDEX_OPERATION: &str = "{}[{}]";
+
+/// Checks a let destructuring its value, like let Point { x, y } = point;, into the lines creating each variable.
+/// The value is stored in a variable first, then each part of the pattern is loaded out of it.
+#[async_recursion]
+pub async fn verify_destructure(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    pattern: Pattern,
+    value: Effects,
+) -> Result<Vec<FinalizedEffects>, ParsingError> {
+    let mut lines = Vec::default();
+    bind_pattern(code_verifier, variables, pattern, value, &mut lines).await?;
+    return Ok(lines);
+}
+
+/// Binds the value to the pattern, recursing into the parts of arrays and structs
+#[async_recursion]
+async fn bind_pattern(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    pattern: Pattern,
+    value: Effects,
+    lines: &mut Vec<FinalizedEffects>,
+) -> Result<(), ParsingError> {
+    let written = pattern.to_string();
+    match pattern {
+        Pattern::Variable(name, span) => {
+            let creating = Effects::new(span, EffectType::CreateVariable(name, Box::new(value), None));
+            lines.push(verify_effect(code_verifier, variables, creating).await?);
+        }
+        Pattern::Array(elements, span) => {
+            let (holder, types) = hold_value(code_verifier, variables, value, &span, lines).await?;
+            let length = match strip_references(&types) {
+                FinalizedTypes::Array(_, length) => length.as_deref(),
+                _ => return Err(span.make_error(ParsingMessage::PatternMismatch(types, written))),
+            };
+            // Arrays without a known length can't be checked against the pattern
+            if !matches!(length, Some(FinalizedTypes::Const(length)) if *length == elements.len() as u64) {
+                return Err(span.make_error(ParsingMessage::PatternLength(types, elements.len())));
+            }
+
+            for (index, element) in elements.into_iter().enumerate() {
+                let span = element.span().clone();
+                let loading = Effects::new(
+                    span.clone(),
+                    EffectType::Operation(
+                        INDEX_OPERATION.to_string(),
+                        vec![
+                            Effects::new(span.clone(), EffectType::LoadVariable(holder.clone())),
+                            Effects::new(span, EffectType::Int(index as i64)),
+                        ],
+                    ),
+                );
+                bind_pattern(code_verifier, variables, element, loading, lines).await?;
+            }
+        }
+        Pattern::Struct(name, fields, span) => {
+            let (holder, types) = hold_value(code_verifier, variables, value, &span, lines).await?;
+            let expected = Syntax::get_struct(
+                code_verifier.syntax.clone(),
+                span.clone(),
+                name,
+                code_verifier.resolver.boxed_clone(),
+                vec![],
+            )
+            .await?;
+            // Instances of generic structs are named after the struct they were degenericed from
+            let matching = match (types.inner_struct_safe(), &expected) {
+                (Some(found), Types::Struct(expected)) => found.data.name.split('$').next().unwrap() == expected.name,
+                _ => false,
+            };
+            if !matching {
+                return Err(span.make_error(ParsingMessage::PatternMismatch(types, written)));
+            }
+
+            // Loading each field checks that it exists and can be accessed
+            for (field, pattern) in fields {
+                let span = pattern.span().clone();
+                let loading = Effects::new(
+                    span.clone(),
+                    EffectType::Load(Box::new(Effects::new(span, EffectType::LoadVariable(holder.clone()))), field),
+                );
+                bind_pattern(code_verifier, variables, pattern, loading, lines).await?;
+            }
+        }
+    }
+    return Ok(());
+}
+
+/// Stores the value being destructured in a variable named after the pattern, returning the variable and its type
+async fn hold_value(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    value: Effects,
+    span: &Span,
+    lines: &mut Vec<FinalizedEffects>,
+) -> Result<(String, FinalizedTypes), ParsingError> {
+    let holder = format!("$destructure{}", span.start);
+    let creating = Effects::new(value.span.clone(), EffectType::CreateVariable(holder.clone(), Box::new(value), None));
+    lines.push(verify_effect(code_verifier, variables, creating).await?);
+    let types = variables.variables.get(&holder).unwrap().clone();
+    check_poison(&types, span)?;
+    return Ok((holder, types));
+}
+
+/// Gets the type behind any references, like the array a literal is stored in
+fn strip_references(types: &FinalizedTypes) -> &FinalizedTypes {
+    return match types {
+        FinalizedTypes::Reference(inner) => strip_references(inner),
+        _ => types,
+    };
+}
@@ -0,0 +1,365 @@
+use async_recursion::async_recursion;
+use data::tokens::Span;
+use syntax::async_util::UnparsedType;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{
+    EffectType, Effects, ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression, SwitchArm,
+};
+use syntax::program::function::FinalizedCodeBody;
+use syntax::program::r#struct::{FinalizedStruct, SOME_VARIANT, U64, VARIANT_FIELD};
+use syntax::program::syntax::Syntax;
+use syntax::program::types::{FinalizedTypes, Types};
+use syntax::{is_modifier, Modifier, SimpleVariableManager};
+
+use crate::check_code::{coerce_literal, store, verify_code, verify_effect};
+use crate::check_method_call::finalize_explicit_generics;
+use crate::degeneric::degeneric_type_fields;
+use crate::{check_poison, get_checked_return, get_return, CodeVerifier};
+
+/// The name of the arm matching every variant not matched by another arm
+const WILDCARD: &str = "_";
+
+/// Checks the creation of an enum variant, like Shape::Circle(5) or Shape::Empty,
+/// or returns None if the effect isn't creating a variant
+pub async fn check_variant(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    effect: &Effects,
+) -> Option<Result<FinalizedEffects, ParsingError>> {
+    let (name, arguments, explicit_generics) = match &effect.types {
+        EffectType::MethodCall(None, name, arguments, _, generics) => (name, arguments.clone(), generics.clone()),
+        EffectType::LoadVariable(name) if !variables.variables.contains_key(name) => (name, Vec::default(), Vec::default()),
+        _ => return None,
+    };
+    let possible = name.split("::").collect::<Vec<_>>();
+    if possible.len() < 2 {
+        return None;
+    }
+
+    let types = match Syntax::get_struct(
+        code_verifier.syntax.clone(),
+        Span::default(),
+        possible[possible.len() - 2].to_string(),
+        code_verifier.resolver.boxed_clone(),
+        vec![],
+    )
+    .await
+    {
+        Ok(Types::Struct(found)) if is_modifier(found.modifiers, Modifier::Enum) => {
+            Types::Struct(found).finalize(code_verifier.syntax.clone()).await
+        }
+        _ => return None,
+    };
+
+    return Some(
+        create_variant(
+            code_verifier,
+            variables,
+            types,
+            possible[possible.len() - 1],
+            arguments,
+            explicit_generics,
+            &effect.span,
+        )
+        .await,
+    );
+}
+
+/// Creates the variant of the enum, checking that the arguments match the variant's fields.
+/// The enum's generics are taken from the explicit generics, like Option::None<u64>(), or inferred from the fields.
+async fn create_variant(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    mut types: FinalizedTypes,
+    name: &str,
+    arguments: Vec<Effects>,
+    explicit_generics: Vec<UnparsedType>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    check_poison(&types, span)?;
+    let structure = types.inner_struct().clone();
+    let (index, variant) = match structure.data.get_variant(name) {
+        Some(found) => found,
+        None => return Err(span.make_error(ParsingMessage::UnknownVariant(types, name.to_string()))),
+    };
+    if arguments.len() != variant.fields.len() {
+        return Err(span.make_error(ParsingMessage::VariantFieldCount(
+            name.to_string(),
+            variant.fields.len(),
+            arguments.len(),
+        )));
+    }
+
+    let mut generics = code_verifier.process_manager.generics.clone();
+    let explicit_generics = finalize_explicit_generics(code_verifier, explicit_generics, span).await?;
+    if !explicit_generics.is_empty() {
+        if explicit_generics.len() != structure.generics.len() {
+            return Err(
+                span.make_error(ParsingMessage::IncorrectGenericsLength(structure.generics.len(), explicit_generics.len()))
+            );
+        }
+        for (generic, found) in structure.generics.keys().zip(explicit_generics) {
+            generics.insert(generic.clone(), found);
+        }
+    }
+
+    let fields = types.get_fields().clone();
+    let mut final_effects = vec![(
+        0,
+        FinalizedEffects::new(
+            Span::default(),
+            store(FinalizedEffectType::TypedInt(structure.discriminants[index], FinalizedTypes::Struct(U64.clone()))),
+        ),
+    )];
+    for (field, argument) in variant.fields.iter().zip(arguments) {
+        let field_name = variant.field_name(field);
+        let i = fields.iter().position(|found| found.field.name == field_name).unwrap();
+
+        // Storing a variable in a variant moves it, so it's no longer dropped with the scope
+        if let EffectType::LoadVariable(name) = &argument.types {
+            variables.owned.retain(|owned| owned != name);
+        }
+
+        let error = argument.span.clone();
+        let mut expected = fields[i].field.field_type.clone();
+        let mut argument = verify_effect(code_verifier, variables, argument).await?;
+        coerce_literal(&mut argument, &expected, &error)?;
+        let found = get_return(&argument.types, variables, &code_verifier.syntax).await.unwrap();
+        expected.resolve_generic(&found, &code_verifier.syntax, &mut generics, error.clone()).await?;
+        degeneric_type_fields(&mut expected, &generics, &code_verifier.syntax).await;
+        if !found.of_type(&expected, code_verifier.syntax.clone()).await {
+            return Err(error.make_error(ParsingMessage::MismatchedTypes(found, expected)));
+        }
+        final_effects.push((i, argument));
+    }
+
+    if structure.generics.keys().any(|generic| !generics.contains_key(generic)) {
+        let base = structure.data.name.split("::").last().unwrap();
+        return Err(span.make_error(ParsingMessage::UninferredGenerics(format!("{}::{}", base, name))));
+    }
+    degeneric_type_fields(&mut types, &generics, &code_verifier.syntax).await;
+
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        FinalizedEffectType::CreateStruct(
+            Some(Box::new(FinalizedEffects::new(Span::default(), FinalizedEffectType::HeapAllocate(types.clone())))),
+            types,
+            final_effects,
+        ),
+    ));
+}
+
+/// Checks a method called on an Option. Only unwrap can be called, which loads the value of Some and traps if it's None,
+/// so other methods have to be called on the unwrapped value instead.
+pub fn check_option_call(
+    calling: FinalizedEffects,
+    types: FinalizedTypes,
+    method: String,
+    arguments: &[FinalizedEffects],
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    if method != "unwrap" || !arguments.is_empty() {
+        return Err(span.make_error(ParsingMessage::UnwrappedOption(method, types)));
+    }
+    let structure = types.inner_struct().clone();
+    let (index, variant) = structure.data.get_variant(SOME_VARIANT).unwrap();
+    let loading = FinalizedEffects::new(
+        span.clone(),
+        FinalizedEffectType::Load(Box::new(calling), variant.field_name(&variant.fields[0]), types),
+    );
+    // Unwrapping None traps instead of loading the field only Some sets
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        FinalizedEffectType::VariantCheck(Box::new(loading), structure.discriminants[index]),
+    ));
+}
+
+/// Verifies a switch, making sure its arms cover every variant of the enum exactly once.
+/// The switch becomes a block storing the enum, jumping to the arm of its variant, then each arm in order.
+#[async_recursion]
+pub async fn verify_switch(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    value: Effects,
+    arms: Vec<SwitchArm>,
+    label: String,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let value = verify_effect(code_verifier, variables, value).await?;
+    let types = get_checked_return(&value.types, variables, &code_verifier.syntax, span).await?;
+    if !types.is_enum() {
+        return Err(span.make_error(ParsingMessage::SwitchOnNonEnum(types)));
+    }
+
+    let structure = types.inner_struct().clone();
+    let targets = find_targets(&structure, &types, &arms)?;
+    let missing = structure
+        .data
+        .variants
+        .iter()
+        .zip(&targets)
+        .filter(|(_, target)| target.is_none())
+        .map(|(variant, _)| variant.name.clone())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(span.make_error(ParsingMessage::NonExhaustiveSwitch(missing)));
+    }
+
+    // The enum is stored in a variable so every arm can load its fields
+    let storing = format!("$switch{}", label);
+    variables.push_scope();
+    variables.declare(storing.clone(), types.clone());
+    let bodies = verify_arms(code_verifier, variables, &structure, &types, arms, &storing).await;
+    variables.pop_scope();
+    let bodies = bodies?;
+
+    let loading = FinalizedEffects::new(Span::default(), FinalizedEffectType::LoadVariable(storing.clone()));
+    let labels = structure
+        .discriminants
+        .iter()
+        .zip(&targets)
+        .map(|(discriminant, target)| (*discriminant, bodies[target.unwrap()].label.clone()))
+        .collect::<Vec<_>>();
+    let mut expressions = vec![
+        FinalizedExpression::new(
+            ExpressionType::Line,
+            FinalizedEffects::new(
+                span.clone(),
+                FinalizedEffectType::CreateVariable(storing, Box::new(value), types.clone()),
+            ),
+        ),
+        FinalizedExpression::new(
+            ExpressionType::Line,
+            FinalizedEffects::new(
+                span.clone(),
+                FinalizedEffectType::Switch(
+                    Box::new(FinalizedEffects::new(
+                        Span::default(),
+                        FinalizedEffectType::Load(Box::new(loading), VARIANT_FIELD.to_string(), types),
+                    )),
+                    labels,
+                ),
+            ),
+        ),
+    ];
+
+    let returns = bodies.iter().all(|body| body.returns);
+    for body in bodies {
+        expressions.push(FinalizedExpression::new(
+            ExpressionType::Line,
+            FinalizedEffects::new(Span::default(), FinalizedEffectType::CodeBody(body)),
+        ));
+    }
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        FinalizedEffectType::CodeBody(FinalizedCodeBody::new(expressions, label, returns)),
+    ));
+}
+
+/// Finds the index of the arm each variant jumps to, erroring on any arm that can't be reached
+fn find_targets(
+    structure: &FinalizedStruct,
+    types: &FinalizedTypes,
+    arms: &[SwitchArm],
+) -> Result<Vec<Option<usize>>, ParsingError> {
+    let mut targets = vec![None; structure.data.variants.len()];
+    for (i, arm) in arms.iter().enumerate() {
+        if arm.variant == WILDCARD {
+            if !arm.bindings.is_empty() {
+                return Err(arm.span.make_error(ParsingMessage::VariantFieldCount(
+                    arm.variant.clone(),
+                    0,
+                    arm.bindings.len(),
+                )));
+            }
+            if targets.iter().all(Option::is_some) {
+                return Err(arm.span.make_error(ParsingMessage::RedundantSwitchArm(arm.variant.clone())));
+            }
+            for target in &mut targets {
+                target.get_or_insert(i);
+            }
+            continue;
+        }
+
+        let (index, variant) = match structure.data.get_variant(&arm.variant) {
+            Some(found) => found,
+            None => return Err(arm.span.make_error(ParsingMessage::UnknownVariant(types.clone(), arm.variant.clone()))),
+        };
+        if targets[index].is_some() {
+            return Err(arm.span.make_error(ParsingMessage::RedundantSwitchArm(arm.variant.clone())));
+        }
+        if arm.bindings.len() != variant.fields.len() {
+            return Err(arm.span.make_error(ParsingMessage::VariantFieldCount(
+                arm.variant.clone(),
+                variant.fields.len(),
+                arm.bindings.len(),
+            )));
+        }
+        targets[index] = Some(i);
+    }
+    return Ok(targets);
+}
+
+/// Verifies the code of every arm, each in its own scope starting with its bound fields
+async fn verify_arms(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    structure: &FinalizedStruct,
+    types: &FinalizedTypes,
+    arms: Vec<SwitchArm>,
+    storing: &str,
+) -> Result<Vec<FinalizedCodeBody>, ParsingError> {
+    let mut bodies = Vec::default();
+    for arm in arms {
+        variables.push_scope();
+        let body = verify_arm(code_verifier, variables, structure, types, arm, storing).await;
+        variables.pop_scope();
+        bodies.push(body?);
+    }
+    return Ok(bodies);
+}
+
+/// Verifies a single arm, loading each bound field into its variable before the arm's code
+async fn verify_arm(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    structure: &FinalizedStruct,
+    types: &FinalizedTypes,
+    arm: SwitchArm,
+    storing: &str,
+) -> Result<FinalizedCodeBody, ParsingError> {
+    let mut expressions = Vec::default();
+    if let Some((_, variant)) = structure.data.get_variant(&arm.variant) {
+        for (binding, field) in arm.bindings.iter().zip(&variant.fields) {
+            if binding == WILDCARD {
+                continue;
+            }
+
+            let loading = FinalizedEffects::new(
+                arm.span.clone(),
+                FinalizedEffectType::Load(
+                    Box::new(FinalizedEffects::new(Span::default(), FinalizedEffectType::LoadVariable(storing.to_string()))),
+                    variant.field_name(field),
+                    types.clone(),
+                ),
+            );
+            let field_type = get_return(&loading.types, variables, &code_verifier.syntax).await.unwrap();
+            if variables.declare(binding.clone(), field_type.clone()) {
+                let warning = arm.span.make_error(ParsingMessage::ShadowedVariable(binding.clone()));
+                code_verifier.syntax.lock().unwrap().warn(warning);
+            }
+            expressions.push(FinalizedExpression::new(
+                ExpressionType::Line,
+                FinalizedEffects::new(
+                    arm.span.clone(),
+                    FinalizedEffectType::CreateVariable(binding.clone(), Box::new(loading), field_type),
+                ),
+            ));
+        }
+    }
+
+    let mut body = verify_code(code_verifier, variables, arm.body, false).await?;
+    expressions.append(&mut body.expressions);
+    body.expressions = expressions;
+    return Ok(body);
+}
@@ -1,6 +1,7 @@
 use crate::check_code::verify_code;
-use crate::output::TypesChecker;
-use crate::{finalize_generics, CodeVerifier};
+use crate::check_constant::verify_constant_value;
+use crate::output::{CodeScheduling, TypesChecker};
+use crate::{finalize_generics, warn_unused_generics, CodeVerifier};
 use data::tokens::Span;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -10,15 +11,18 @@ use syntax::program::code::{
     ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedExpression, FinalizedField, FinalizedMemberField,
 };
 use syntax::program::function::{
-    CodeBody, CodelessFinalizedFunction, FinalizedCodeBody, FinalizedFunction, UnfinalizedFunction,
+    CodeBody, CodelessFinalizedFunction, FinalizedCodeBody, FinalizedFunction, Inline, UnfinalizedFunction,
 };
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
-use syntax::{is_modifier, Modifier, ProcessManager, SimpleVariableManager};
+use syntax::program::walk::walk_effects;
+use syntax::{is_modifier, Attribute, Modifier, ProcessManager, SimpleVariableManager};
 
 /// Verifies a function and returns its code, which is verified seperate to prevent deadlocks
 pub async fn verify_function(
+    process_manager: &TypesChecker,
     mut function: UnfinalizedFunction,
+    resolver: Box<dyn NameResolver>,
     syntax: &Arc<Mutex<Syntax>>,
     include_refs: bool,
 ) -> Result<(CodelessFinalizedFunction, CodeBody), ParsingError> {
@@ -26,13 +30,20 @@ pub async fn verify_function(
     // Verify arguments
     for argument in &mut function.fields {
         let field = argument.await?;
+        let field_type = field.field.field_type.finalize(syntax.clone()).await;
+        // Defaults are filled in at the call, so they can't depend on anything else in the function
+        let default = match field.default {
+            Some(default) => Some(
+                verify_constant_value(process_manager, default, &field_type, resolver.boxed_clone(), syntax, 0, vec![])
+                    .await?,
+            ),
+            None => None,
+        };
         let mut field = FinalizedMemberField {
             modifiers: field.modifiers,
             attributes: field.attributes,
-            field: FinalizedField {
-                field_type: field.field.field_type.finalize(syntax.clone()).await,
-                name: field.field.name,
-            },
+            field: FinalizedField { field_type, name: field.field.name },
+            default,
         };
         if include_refs {
             field.field.field_type = FinalizedTypes::Reference(Box::new(field.field.field_type));
@@ -41,6 +52,19 @@ pub async fn verify_function(
         fields.push(field);
     }
 
+    // Extern functions with #[ptr_len] pass each slice as a pointer and a length, which must match the C signature.
+    // Other functions aren't called from C, so their slices are passed as usual
+    if is_modifier(function.data.modifiers, Modifier::Extern) {
+        let ptr_len =
+            Attribute::find_attribute("ptr_len", &function.data.attributes).and_then(|inner| inner.as_int_attribute());
+        if let Some(expected) = ptr_len {
+            let found = fields.iter().map(|field| if field.field.field_type.is_array() { 2 } else { 1 }).sum();
+            if expected != found {
+                return Err(function.data.span.make_error(ParsingMessage::PtrLenArguments(expected, found)));
+            }
+        }
+    }
+
     // Verify return type
     let return_type = if let Some(return_type) = function.return_type.as_mut() {
         Some(return_type.await?.finalize(syntax.clone()).await)
@@ -48,11 +72,21 @@ pub async fn verify_function(
         None
     };
 
+    let mut self_bounds = Vec::default();
+    for bound in function.self_bounds {
+        self_bounds.push(bound.await?.finalize(syntax.clone()).await);
+    }
+
+    let generics = finalize_generics(syntax, function.generics).await?;
+    let types = fields.iter().map(|field| &field.field.field_type).chain(&return_type).chain(&self_bounds);
+    warn_unused_generics(syntax, &function.generic_spans, &generics, types.collect());
+
     // Return the codeless finalized function
     let codeless = CodelessFinalizedFunction {
-        generics: finalize_generics(syntax, function.generics).await?,
+        generics,
         arguments: fields,
         return_type,
+        self_bounds,
         data: function.data.clone(),
         parent: match function.parent {
             Some(found) => Some(found.await?.finalize(syntax.clone()).await),
@@ -78,10 +112,21 @@ pub async fn verify_function_code(
 
     //Internal/external/trait functions verify everything but the code.
     if is_modifier(codeless.data.modifiers, Modifier::Internal) || is_modifier(codeless.data.modifiers, Modifier::Extern) {
+        // Their code comes from the compiler or the linked binary, so a body here would be silently ignored
+        if !code.expressions.is_empty() {
+            return Err(codeless.data.span.make_error(ParsingMessage::ExternalWithBody(codeless.data.name.clone())));
+        }
         return Ok(codeless.clone().add_code(FinalizedCodeBody::new(Vec::default(), String::default(), true)));
     }
 
-    let mut variable_manager = SimpleVariableManager::for_function(&codeless);
+    // Code only waits on the signatures of the functions it calls, which are added before this, so no function
+    // holding the lock waits on one waiting for it
+    let _sequential = match process_manager.scheduling {
+        CodeScheduling::Sequential => Some(process_manager.sequential.lock().await),
+        CodeScheduling::Concurrent => None,
+    };
+
+    let variable_manager = SimpleVariableManager::for_function(&codeless);
     let mut process_manager = process_manager.clone();
     for (name, bounds) in resolver.generics() {
         let mut output = vec![];
@@ -101,21 +146,68 @@ pub async fn verify_function_code(
         resolver,
         return_type: codeless.return_type.clone(),
         syntax: syntax.clone(),
+        debug_impls: Attribute::find_attribute("debug_impls", &codeless.data.attributes).is_some(),
+        checked_math: Attribute::find_attribute("checked_math", &codeless.data.attributes).is_some(),
+        depth: 0,
+        loops: Vec::default(),
+        constants: Vec::default(),
+        literal_types: Vec::default(),
     };
 
-    let mut code = verify_code(&mut code_verifier, &mut variable_manager, code, true).await?;
+    // Each variable declared from an untyped literal and used as another integer type is declared as that type
+    // and the function is checked again, which happens at most once per variable
+    let mut code = loop {
+        let mut variables = variable_manager.clone();
+        let error = match verify_code(&mut code_verifier, &mut variables, code.clone(), true).await {
+            Ok(code) => break code,
+            Err(error) => error,
+        };
+        match error.message {
+            ParsingMessage::RetypedLiteral(_, types) => code_verifier.literal_types.push((error.span, types)),
+            _ => return Err(error),
+        }
+    };
+
+    if codeless.data.inline == Inline::Always {
+        if let Some(span) = recursive_call(&code, &codeless.data.name) {
+            let warning = span.make_error(ParsingMessage::InlineRecursive(codeless.data.name.clone()));
+            syntax.lock().unwrap().warn(warning);
+        }
+    }
 
     // Checks the return type exists
     if !code.returns {
         if codeless.return_type.is_none() {
+            // Points at the function, so anything about the implicit return can say where it came from
             code.expressions.push(FinalizedExpression::new(
-                ExpressionType::Return(Span::default()),
-                FinalizedEffects::new(Span::default(), FinalizedEffectType::NOP),
+                ExpressionType::Return(codeless.data.span.clone()),
+                FinalizedEffects::new(codeless.data.span.clone(), FinalizedEffectType::NOP),
             ));
         } else if !is_modifier(codeless.data.modifiers, Modifier::Trait) {
-            return Err(codeless.data.span.make_error(ParsingMessage::UnexpectedVoid()));
+            let expected = codeless.return_type.as_ref().unwrap().display_with_generics();
+            return Err(codeless.data.span.make_error(ParsingMessage::MissingReturn(expected)));
         }
     }
 
     return Ok(codeless.clone().add_code(code));
 }
+
+/// Finds the first call the code makes to the function with the given name
+fn recursive_call(code: &FinalizedCodeBody, name: &String) -> Option<Span> {
+    let mut found = None;
+    walk_effects(code, &mut |effect, _| {
+        if found.is_some() {
+            return false;
+        }
+        match &effect.types {
+            FinalizedEffectType::MethodCall(_, function, _, _) | FinalizedEffectType::GenericMethodCall(function, _, _)
+                if function.data.name == *name =>
+            {
+                found = Some(effect.span.clone())
+            }
+            _ => {}
+        }
+        return true;
+    });
+    return found;
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use indexmap::IndexMap;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::function::CodelessFinalizedFunction;
+use syntax::program::syntax::Syntax;
+use syntax::program::types::FinalizedTypes;
+use syntax::FinishedTraitImplementor;
+
+use crate::degeneric::degeneric_type_no_generic_types;
+use crate::output::TypesChecker;
+
+/// Verifies an impl block against its trait without checking any of its code: every method of the trait has to be
+/// implemented with the same signature, and the impl can't have methods the trait doesn't declare.
+/// Associated constants have to be defined too, but their types are only checked once their values are.
+/// Every problem is returned instead of only the first, so a language server can check one impl at a time.
+pub async fn verify_impl(
+    process_manager: &TypesChecker,
+    implementor: &FinishedTraitImplementor,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> Vec<ParsingError> {
+    let traits = implementor.target.inner_struct().clone();
+    let trait_name = implementor.target.to_string();
+
+    let mut errors = Vec::default();
+    for declared in &traits.data.functions {
+        let method = short_name(&declared.name);
+        if !implementor.functions.iter().any(|function| short_name(&function.name) == method) {
+            errors.push(
+                implementor.span.make_error(ParsingMessage::MissingTraitMethod(method.to_string(), trait_name.clone())),
+            );
+        }
+    }
+    errors.extend(verify_signatures(process_manager, implementor, syntax).await);
+
+    for function in &implementor.functions {
+        let method = short_name(&function.name);
+        if !traits.data.functions.iter().any(|declared| short_name(&declared.name) == method) {
+            errors.push(function.span.make_error(ParsingMessage::NotInTrait(method.to_string(), trait_name.clone())));
+        }
+    }
+
+    for constant in traits.constants.keys() {
+        if !implementor.constants.iter().any(|found| short_name(&found.name) == constant) {
+            errors.push(
+                implementor.span.make_error(ParsingMessage::MissingAssociatedConstant(constant.clone(), trait_name.clone())),
+            );
+        }
+    }
+    for constant in &implementor.constants {
+        let name = short_name(&constant.name);
+        if !traits.constants.contains_key(name) {
+            errors.push(constant.span.make_error(ParsingMessage::NotTraitConstant(name.to_string(), trait_name.clone())));
+        }
+    }
+    return errors;
+}
+
+/// Checks each method the impl overrides takes the same arguments and returns the same type as the trait's declaration.
+/// Methods missing from the impl are left to verify_impl, this only compares the ones both have.
+pub async fn verify_signatures(
+    process_manager: &TypesChecker,
+    implementor: &FinishedTraitImplementor,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> Vec<ParsingError> {
+    let traits = implementor.target.inner_struct().clone();
+
+    // The trait's generics are whatever the impl passed for them, like E in Add<E> being u64 in Add<u64>
+    let mut generics = IndexMap::default();
+    if let Some((_, arguments)) = implementor.target.inner_generic_type() {
+        for (generic, argument) in traits.generics.keys().zip(arguments) {
+            generics.insert(generic.clone(), argument.clone());
+        }
+    }
+
+    let mut errors = Vec::default();
+    for declared in &traits.data.functions {
+        let method = short_name(&declared.name);
+        let found = match implementor.functions.iter().find(|function| short_name(&function.name) == method) {
+            Some(found) => found,
+            None => continue,
+        };
+
+        let declared = process_manager.functions.get(syntax, declared).await;
+        let found = process_manager.functions.get(syntax, found).await;
+        let expected = signature(&declared, implementor, &generics, syntax).await;
+        if !signatures_match(&expected, &found) {
+            let error = found.data.span.make_error(ParsingMessage::TraitSignatureMismatch(
+                method.to_string(),
+                display_signature(&expected.0, &expected.1),
+                display_signature(&argument_types(&found), &found.return_type),
+            ));
+            errors.push(error.with_note(declared.data.span.make_error(ParsingMessage::DeclaredInTrait())));
+        }
+    }
+    return errors;
+}
+
+/// Gets the method's name without the trait or impl it's in
+fn short_name(name: &str) -> &str {
+    return name.split("::").last().unwrap();
+}
+
+/// Gets the argument and return types the trait's method should have in the impl,
+/// with Self as the implementing type and the trait's generics as the impl's arguments for them
+async fn signature(
+    declared: &CodelessFinalizedFunction,
+    implementor: &FinishedTraitImplementor,
+    generics: &IndexMap<String, FinalizedTypes>,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> (Vec<FinalizedTypes>, Option<FinalizedTypes>) {
+    let mut arguments = Vec::default();
+    for argument in argument_types(declared) {
+        arguments.push(in_impl(argument, implementor, generics, syntax).await);
+    }
+    let returning = match &declared.return_type {
+        Some(returning) => Some(in_impl(returning.clone(), implementor, generics, syntax).await),
+        None => None,
+    };
+    return (arguments, returning);
+}
+
+/// Converts a type from the trait's declaration into the type the impl should use instead
+async fn in_impl(
+    mut types: FinalizedTypes,
+    implementor: &FinishedTraitImplementor,
+    generics: &IndexMap<String, FinalizedTypes>,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> FinalizedTypes {
+    types = types.dereferenced().clone();
+    // Self, including the type of self, is the trait in its own declaration
+    if types.inner_struct_safe().is_some_and(|inner| inner.data == implementor.target.inner_struct().data) {
+        return implementor.base.clone();
+    }
+    degeneric_type_no_generic_types(&mut types, generics, syntax).await;
+    return types;
+}
+
+/// Gets the types of the function's arguments, without the references wrapping them
+fn argument_types(function: &CodelessFinalizedFunction) -> Vec<FinalizedTypes> {
+    return function.arguments.iter().map(|argument| argument.field.field_type.dereferenced().clone()).collect();
+}
+
+/// Checks the impl's method takes and returns the types the trait expects
+fn signatures_match(expected: &(Vec<FinalizedTypes>, Option<FinalizedTypes>), found: &CodelessFinalizedFunction) -> bool {
+    let (arguments, returning) = expected;
+    let found_arguments = argument_types(found);
+    if arguments.len() != found_arguments.len() {
+        return false;
+    }
+    if !arguments.iter().zip(&found_arguments).all(|(expected, found)| found.of_type_sync(expected, None).0) {
+        return false;
+    }
+    return match (returning, &found.return_type) {
+        (Some(expected), Some(found)) => found.of_type_sync(expected, None).0,
+        (None, None) => true,
+        _ => false,
+    };
+}
+
+/// Displays the signature like fn(u64, str) -> bool
+fn display_signature(arguments: &[FinalizedTypes], returning: &Option<FinalizedTypes>) -> String {
+    let arguments = arguments.iter().map(|argument| argument.to_string()).collect::<Vec<_>>().join(", ");
+    return match returning {
+        Some(returning) => format!("fn({}) -> {}", arguments, returning.dereferenced()),
+        None => format!("fn({})", arguments),
+    };
+}
@@ -1,21 +1,25 @@
 use std::mem;
+use std::sync::{Arc, Mutex};
 
+use async_recursion::async_recursion;
 use data::tokens::Span;
-use syntax::async_util::{AsyncDataGetter, UnparsedType};
+use syntax::async_util::UnparsedType;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
-use syntax::program::r#struct::VOID;
+use syntax::program::function::FunctionData;
+use syntax::program::r#struct::{literal_integers, U64, VOID};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
-use syntax::top_element_manager::ImplWaiter;
-use syntax::SimpleVariableManager;
+use syntax::top_element_manager::{ImplWaiter, NextImplWaiter, PendingImpl};
+use syntax::{is_modifier, Modifier, ProcessManager, SimpleVariableManager};
 
-use crate::check_code::verify_effect;
-use crate::check_method_call::check_method;
-use crate::degeneric::degeneric_header;
-use crate::{get_return, CodeVerifier};
+use crate::check_code::{coerce_literal, is_int_literal, retype_literal, verify_effect};
+use crate::check_method_call::{check_method, check_self_bounds, finalize_explicit_generics};
+use crate::degeneric::{bind_explicit_generics, degeneric_header};
+use crate::{get_checked_return, get_return, CodeVerifier};
 
 /// Checks an implementation call generated by control_parser or an operator to get the correct method
+#[async_recursion]
 pub async fn check_impl_call(
     code_verifier: &mut CodeVerifier<'_>,
     variables: &mut SimpleVariableManager,
@@ -23,76 +27,272 @@ pub async fn check_impl_call(
 ) -> Result<FinalizedEffects, ParsingError> {
     // Get all the ImplementationCall variables
     let mut finalized_effects = Vec::default();
-    let calling;
+    let mut calling = None;
     let traits;
     let method;
+    let returning;
     let explicit_generics;
-    if let EffectType::ImplementationCall(new_calling, new_traits, new_method, effects, new_returning) = effect.types {
+    if let EffectType::ImplementationCall(new_calling, new_traits, new_method, effects, new_returning, new_generics) =
+        effect.types
+    {
+        // The value it's called on runs before the arguments, so it's checked first to move variables in that order
+        if !matches!(new_calling.types, EffectType::NOP) {
+            let calling_effect = verify_effect(code_verifier, variables, *new_calling).await?;
+            if is_poisoned(&calling_effect) {
+                return Ok(calling_effect);
+            }
+            calling = Some(calling_effect);
+        }
         for effect in effects {
             finalized_effects.push(verify_effect(code_verifier, variables, effect).await?)
         }
-        calling = new_calling;
+        // The argument's error was already reported, so don't report another for this call
+        if let Some(poisoned) = finalized_effects.iter().find(|effect| is_poisoned(effect)) {
+            return Ok(poisoned.clone());
+        }
         traits = new_traits;
         method = new_method;
-        explicit_generics = new_returning;
+        returning = new_returning;
+        explicit_generics = new_generics;
     } else {
         unreachable!()
     }
 
+    return check_impl(
+        code_verifier,
+        variables,
+        calling,
+        finalized_effects,
+        traits,
+        method,
+        returning,
+        explicit_generics,
+        &effect.span,
+    )
+    .await;
+}
+
+/// Checks the implementation call with the checked receiver and arguments.
+/// It's separate from check_impl_call so only check_impl_call's stack frame is on the stack while its arguments are
+/// checked, which happens once for every level of nesting
+#[async_recursion]
+async fn check_impl(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    calling: Option<FinalizedEffects>,
+    mut finalized_effects: Vec<FinalizedEffects>,
+    traits: String,
+    method: String,
+    returning: Option<UnparsedType>,
+    explicit_generics: Vec<UnparsedType>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let explicit_generics = finalize_explicit_generics(code_verifier, explicit_generics, span).await?;
+
     // Get the return type, or VOID if there is none
     let calling_type;
-    if matches!(calling.types, EffectType::NOP) {
-        calling_type = FinalizedTypes::Struct(VOID.clone());
-    } else {
-        let calling_effect = verify_effect(code_verifier, variables, *calling.clone()).await?;
-        calling_type = get_return(&calling_effect.types, variables, &code_verifier.syntax).await.unwrap();
-        finalized_effects.insert(0, calling_effect);
+    let mut literals = None;
+    match calling {
+        None => {
+            // Only operators without any values are called on nothing, a named method needs a value to be called on
+            if !method.is_empty() {
+                return Err(span.make_error(ParsingMessage::MissingReceiver(method)));
+            }
+            calling_type = FinalizedTypes::Struct(VOID.clone());
+        }
+        Some(mut calling_effect) => {
+            if is_int_literal(&calling_effect) {
+                literals = Some((calling_effect.clone(), finalized_effects.clone()));
+            }
+            // Literals take the type of the other side of the operation, like the 1 in 1 + value
+            if let Some(other) = finalized_effects.first().filter(|_| is_int_literal(&calling_effect)) {
+                if let Some(other) = get_return(&other.types, variables, &code_verifier.syntax).await {
+                    coerce_literal(&mut calling_effect, &other, span)?;
+                }
+            }
+            calling_type = get_checked_return(&calling_effect.types, variables, &code_verifier.syntax, span).await?;
+            if calling_type.is_integer() {
+                for argument in &mut finalized_effects {
+                    coerce_literal(argument, &calling_type, span)?;
+                }
+            }
+            finalized_effects.insert(0, calling_effect);
+        }
     }
 
     // Get the trait
-    if let Ok(trait_type) = Syntax::get_struct(
+    let trait_type = Syntax::get_struct(
         code_verifier.syntax.clone(),
         Span::default(),
         traits.clone(),
         code_verifier.resolver.boxed_clone(),
         vec![],
     )
-    .await
-    {
-        let trait_type = trait_type.finalize(code_verifier.syntax.clone()).await;
-
-        // Simple container for all the data that needs to be stored
-        let mut impl_checker = ImplCheckerData {
-            code_verifier,
-            trait_type: &trait_type,
-            explicit_generics: &explicit_generics,
-            method: &method,
-            calling_type: &calling_type,
-            finalized_effects: &mut finalized_effects,
-            variables,
-        };
+    .await;
+    match trait_type {
+        Ok(trait_type) => {
+            let trait_type = trait_type.finalize(code_verifier.syntax.clone()).await;
 
-        // Check if the trait_type matches the calling_type. If so, it's a virtual call (a method call on a trait)
-        if let Some(found) = check_virtual_type(&mut impl_checker, &effect.span).await? {
-            return Ok(found);
-        }
+            // Simple container for all the data that needs to be stored
+            let mut impl_checker = ImplCheckerData {
+                code_verifier,
+                trait_type: &trait_type,
+                returning: &returning,
+                explicit_generics: &explicit_generics,
+                method: &method,
+                calling_type: &calling_type,
+                finalized_effects: &mut finalized_effects,
+                variables,
+            };
+
+            // Check if the trait_type matches the calling_type. If so, it's a virtual call (a method call on a trait)
+            if let Some(found) = check_virtual_type(&mut impl_checker, span).await? {
+                return Ok(found);
+            }
 
-        // If not, wait for an impl to be parsed that fits the criteria
-        let mut output = None;
-        while output.is_none() {
-            // TODO switch this to some kind of pipeline instead of rechecking them all every single time
-            output = try_get_impl(&impl_checker, &effect.span).await?;
+            // Calls on a concrete type can usually be resolved from its own impl without searching every impl
+            let mut output = try_direct_impl(&impl_checker, span).await?;
+            let mut log = Vec::default();
+            if output.is_some() {
+                log.push(format!(
+                    "Resolved impl of {} for {} directly",
+                    trait_type.display_with_generics(),
+                    calling_type.display_with_generics()
+                ));
+            }
+
+            // If not, wait for an impl to be parsed that fits the criteria
+            let mut unimplemented = None;
+            while output.is_none() {
+                log.clear();
+                log.push(format!(
+                    "Resolving impl of {} for {}",
+                    trait_type.display_with_generics(),
+                    calling_type.display_with_generics()
+                ));
+                // TODO switch this to some kind of pipeline instead of rechecking them all every single time
+                output = match try_get_impl(&impl_checker, span, &mut log).await {
+                    // Untyped literals could still be another integer type implementing it
+                    Err(error) if literals.is_some() => {
+                        unimplemented = Some(error);
+                        break;
+                    }
+                    found => found?,
+                };
+                if output.is_some() {
+                    break;
+                }
+                // Every impl has been parsed and all candidates were rejected, so none will ever be found
+                if impl_checker.code_verifier.syntax.lock().unwrap().finished_impls() {
+                    break;
+                }
+                // Otherwise, an impl that hasn't finished parsing could still match
+                NextImplWaiter {
+                    syntax: impl_checker.code_verifier.syntax.clone(),
+                    pending: PendingImpl {
+                        trait_name: Some(trait_type.display_with_generics()),
+                        types: calling_type.display_with_generics(),
+                        method: Some(method.clone()).filter(|method| !method.is_empty()),
+                    },
+                    waiting: false,
+                }
+                .await;
+            }
+
+            if impl_checker.code_verifier.debug_impls {
+                println!("{}", log.join("\n"));
+            } else if let Some((calling, arguments)) = literals.filter(|_| output.is_none()) {
+                output = try_literal_impl(&impl_checker, calling, arguments, span).await?;
+            }
+            if let Some(error) = unimplemented.filter(|_| output.is_none()) {
+                return Err(error);
+            }
+
+            // Failed to find an impl, the rest of the function is still checked for more errors
+            return match output {
+                Some(found) => Ok(check_overflow(impl_checker.code_verifier, found)),
+                None if impl_checker.code_verifier.debug_impls => Ok(poison(
+                    impl_checker.code_verifier,
+                    expression_span(span, impl_checker.finalized_effects)
+                        .make_error(ParsingMessage::UnresolvedImpl(log.join("\n"))),
+                )),
+                None => {
+                    // Operators pass their right side as the only argument, so name it to show which impl is missing
+                    let argument = match impl_checker.finalized_effects.get(1) {
+                        Some(argument) if impl_checker.finalized_effects.len() == 2 => {
+                            get_return(&argument.types, variables, &code_verifier.syntax).await
+                        }
+                        _ => None,
+                    };
+                    // A variable declared from a literal takes the type of the other side, like the sum in sum += value
+                    if let Some(argument) = &argument {
+                        retype_literal(variables, &impl_checker.finalized_effects[0], &calling_type, argument)?;
+                        retype_literal(variables, &impl_checker.finalized_effects[1], argument, &calling_type)?;
+                    }
+                    let error = match argument {
+                        // Every array takes a u64 index, so a wrong index is named instead of the missing impl
+                        Some(argument) if is_bad_index(&trait_type, &argument) => {
+                            ParsingMessage::NonUnsignedIndex(argument.dereferenced().clone())
+                        }
+                        Some(argument) => ParsingMessage::NoTraitImplTaking(
+                            calling_type.display_with_generics(),
+                            trait_type.display_with_generics(),
+                            argument.display_with_generics(),
+                        ),
+                        None => no_trait_impl(&code_verifier.syntax, &calling_type, &trait_type),
+                    };
+                    let span = expression_span(span, impl_checker.finalized_effects);
+                    Ok(poison(impl_checker.code_verifier, span.make_error(error)))
+                }
+            };
         }
+        Err(error) => Ok(poison(code_verifier, error)),
+    }
+}
+
+/// The error for a type without an impl of the trait, explaining why it doesn't implement it if there's a simple reason
+fn no_trait_impl(syntax: &Arc<Mutex<Syntax>>, types: &FinalizedTypes, traits: &FinalizedTypes) -> ParsingMessage {
+    let reason = types.explain_mismatch(traits, &syntax.lock().unwrap());
+    return ParsingMessage::NoTraitImpl(types.display_with_generics(), traits.display_with_generics(), reason);
+}
+
+/// Checks if the trait is the array index and the index isn't a u64
+fn is_bad_index(trait_type: &FinalizedTypes, index: &FinalizedTypes) -> bool {
+    return trait_type.inner_struct().data.name == "array::Index"
+        && *index.dereferenced() != FinalizedTypes::Struct(U64.clone());
+}
+
+/// The span of the whole call, from the receiver through the last argument, so errors cover the entire expression
+fn expression_span(span: &Span, arguments: &[FinalizedEffects]) -> Span {
+    return arguments.iter().fold(span.clone(), |span, argument| span.merge(&argument.span));
+}
 
-        // Failed to find an impl
-        verify_effect(code_verifier, variables, *calling).await?.types;
-        if output.is_none() {
-            panic!("Failed for {} and {}", calling_type, trait_type);
+/// Wraps calls to the internal integer add, subtract and multiply in an overflow check if #[checked_math] is set
+fn check_overflow(code_verifier: &CodeVerifier<'_>, effect: FinalizedEffects) -> FinalizedEffects {
+    let checked = match &effect.types {
+        FinalizedEffectType::MethodCall(_, function, _, _) => {
+            is_modifier(function.data.modifiers, Modifier::Internal)
+                && ["math::Add", "math::Subtract", "math::Multiply"]
+                    .iter()
+                    .any(|operation| function.data.name.starts_with(operation))
         }
-        return Ok(output.unwrap());
-    } else {
-        panic!("Screwed up trait! {} for {:?}", traits, code_verifier.resolver.imports());
+        _ => false,
+    };
+    if !code_verifier.checked_math || !checked {
+        return effect;
     }
+    return FinalizedEffects::new(effect.span.clone(), FinalizedEffectType::OverflowCheck(Box::new(effect)));
+}
+
+/// Records the error and returns an effect poisoned with it, so verifying the function can continue
+fn poison(code_verifier: &CodeVerifier<'_>, error: ParsingError) -> FinalizedEffects {
+    code_verifier.syntax.lock().unwrap().errors.push(error.clone());
+    return FinalizedEffects::new(error.span.clone(), FinalizedEffectType::Poison(error));
+}
+
+/// Checks if the effect failed to verify and its error was already reported
+fn is_poisoned(effect: &FinalizedEffects) -> bool {
+    return matches!(effect.types, FinalizedEffectType::Poison(_));
 }
 
 /// All the data used by implementation checkers
@@ -102,7 +302,9 @@ pub struct ImplCheckerData<'a> {
     /// Trait being checked
     trait_type: &'a FinalizedTypes,
     /// The generic return type
-    explicit_generics: &'a Option<UnparsedType>,
+    returning: &'a Option<UnparsedType>,
+    /// The generics explicitly passed to the method
+    explicit_generics: &'a Vec<FinalizedTypes>,
     /// The name of the method, can be empty to just return the first found method
     method: &'a String,
     /// The trait to find
@@ -113,6 +315,43 @@ pub struct ImplCheckerData<'a> {
     variables: &'a SimpleVariableManager,
 }
 
+/// Literals are i64 unless only another integer type has the impl, like 0..5 using the u64 Range, so once every impl
+/// is finished this tries the call with the untyped literals as each other integer type
+async fn try_literal_impl(
+    data: &ImplCheckerData<'_>,
+    calling: FinalizedEffects,
+    arguments: Vec<FinalizedEffects>,
+    span: &Span,
+) -> Result<Option<FinalizedEffects>, ParsingError> {
+    // Skips the i64 that was already tried
+    for integer in literal_integers().into_iter().skip(1) {
+        let calling_type = FinalizedTypes::Struct(integer);
+        let mut finalized_effects = arguments.clone();
+        for argument in &mut finalized_effects {
+            coerce_literal(argument, &calling_type, span)?;
+        }
+        let mut calling = calling.clone();
+        coerce_literal(&mut calling, &calling_type, span)?;
+        finalized_effects.insert(0, calling);
+
+        let checker = ImplCheckerData {
+            code_verifier: data.code_verifier,
+            trait_type: data.trait_type,
+            returning: data.returning,
+            explicit_generics: data.explicit_generics,
+            method: data.method,
+            calling_type: &calling_type,
+            finalized_effects: &mut finalized_effects,
+            variables: data.variables,
+        };
+        // Types without any impl of the trait error instead of returning None
+        if let Ok(Some(found)) = try_get_impl(&checker, span, &mut Vec::default()).await {
+            return Ok(Some(found));
+        }
+    }
+    return Ok(None);
+}
+
 /// Checks an implementation call to see if it should be a virtual call (a method call on a trait instead of a struct)
 async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Result<Option<FinalizedEffects>, ParsingError> {
     // If calling_type doesn't extend trait_type, then it's not a virtual call
@@ -120,9 +359,17 @@ async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Res
         return Ok(None);
     }
 
-    let mut i = 0;
-    for found in &data.trait_type.inner_struct().data.functions {
-        let returning = match data.explicit_generics {
+    let traits = &data.trait_type.inner_struct().data;
+    for found in &traits.functions {
+        // The slot in the receiver's vtable, which is a subtrait's if the method is inherited from this trait
+        let slot = match data.calling_type.inner_struct_safe() {
+            Some(calling) if data.calling_type.is_trait() => match calling.virtual_method(&found.name) {
+                Some((slot, _)) => slot,
+                None => traits.vtable_slot(&found.name).unwrap(),
+            },
+            _ => traits.vtable_slot(&found.name).unwrap(),
+        };
+        let returning = match data.returning {
             Some(inner) => Some((
                 Syntax::parse_type(
                     data.code_verifier.syntax.clone(),
@@ -138,19 +385,22 @@ async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Res
             )),
             None => None,
         };
-        // If the names match, it works
-        if found.name == *data.method {
+        if found.name != *data.method && found.name.split("::").last().unwrap() != data.method {
+            continue;
+        }
+        let declared = data.code_verifier.process_manager.functions.get(&data.code_verifier.syntax, found).await;
+        check_self_bounds(&data.code_verifier.syntax, &declared, data.calling_type, token).await?;
+
+        // If the names match, or it's called on a trait object that has a slot for it, it works
+        if found.name == *data.method || data.calling_type.is_trait() {
             let mut temp = vec![];
             mem::swap(&mut temp, data.finalized_effects);
-            let function = AsyncDataGetter::new(data.code_verifier.syntax.clone(), found.clone()).await;
+            let function = data.code_verifier.process_manager.functions.get(&data.code_verifier.syntax, &found).await;
 
             return Ok(Some(FinalizedEffects::new(
                 token.clone(),
-                FinalizedEffectType::VirtualCall(i, function, temp, returning),
+                FinalizedEffectType::VirtualCall(slot, function, temp, returning),
             )));
-        } else if found.name.split("::").last().unwrap() != data.method {
-            i += 1;
-            continue;
         }
 
         // Now, try and check the calling type's functions to try and find the method.
@@ -170,9 +420,9 @@ async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Res
             return Ok(Some(FinalizedEffects::new(
                 token.clone(),
                 FinalizedEffectType::GenericVirtualCall(
-                    i,
+                    slot,
                     target,
-                    AsyncDataGetter::new(data.code_verifier.syntax.clone(), found.clone()).await,
+                    data.code_verifier.process_manager.functions.get(&data.code_verifier.syntax, &found).await,
                     temp,
                     returning,
                 ),
@@ -188,14 +438,18 @@ async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Res
                 Box::new(data.code_verifier.process_manager.clone()),
                 data.finalized_effects.clone(),
                 data.variables.clone(),
+                data.explicit_generics.clone(),
                 token.clone(),
             ),
         );
 
-        let output = AsyncDataGetter::new(data.code_verifier.syntax.clone(), target.clone()).await;
+        let output = data.code_verifier.process_manager.functions.get(&data.code_verifier.syntax, &target).await;
         let mut temp = vec![];
         mem::swap(&mut temp, data.finalized_effects);
-        return Ok(Some(FinalizedEffects::new(token.clone(), FinalizedEffectType::VirtualCall(i, output, temp, returning))));
+        return Ok(Some(FinalizedEffects::new(
+            token.clone(),
+            FinalizedEffectType::VirtualCall(slot, output, temp, returning),
+        )));
     }
 
     if !data.method.is_empty() {
@@ -204,51 +458,139 @@ async fn check_virtual_type(data: &mut ImplCheckerData<'_>, token: &Span) -> Res
     return Ok(None);
 }
 
-/// Tries to get an implementation matching the types passed in
-async fn try_get_impl(data: &ImplCheckerData<'_>, span: &Span) -> Result<Option<FinalizedEffects>, ParsingError> {
+/// Tries to get an implementation matching the types passed in, logging why each candidate was rejected
+async fn try_get_impl(
+    data: &ImplCheckerData<'_>,
+    span: &Span,
+    log: &mut Vec<String>,
+) -> Result<Option<FinalizedEffects>, ParsingError> {
     let result = ImplWaiter {
         syntax: data.code_verifier.syntax.clone(),
         base_type: data.calling_type.clone(),
         trait_type: data.trait_type.clone(),
-        error: span.make_error(ParsingMessage::NoTraitImpl(data.calling_type.clone(), data.trait_type.clone())),
+        error: expression_span(span, data.finalized_effects).make_error(ParsingMessage::NoTraitImpl(
+            data.calling_type.display_with_generics(),
+            data.trait_type.display_with_generics(),
+            None,
+        )),
     }
-    .await?;
+    .await
+    // This is retried whenever an impl is parsed, so the reason is only found once it fails
+    .map_err(|error| error.span.make_error(no_trait_impl(&data.code_verifier.syntax, data.calling_type, data.trait_type)))?;
 
-    for temp in result.iter().flat_map(|(_, inner)| inner) {
-        if temp.name.split("::").last().unwrap() == data.method || data.method.is_empty() {
-            let method = AsyncDataGetter::new(data.code_verifier.syntax.clone(), temp.clone()).await;
-
-            let returning = match &data.explicit_generics {
-                Some(inner) => Some((
-                    Syntax::parse_type(
-                        data.code_verifier.syntax.clone(),
-                        span.clone(),
-                        data.code_verifier.resolver.boxed_clone(),
-                        inner.clone(),
-                        vec![],
-                    )
-                    .await?
-                    .finalize(data.code_verifier.syntax.clone())
-                    .await,
-                    span.clone(),
-                )),
-                None => None,
-            };
+    let trait_struct = data.trait_type.inner_struct_safe();
+    let mut candidates = Vec::default();
+    for (implementor, functions) in &result {
+        for function in functions {
+            candidates.push((implementor, function));
+        }
+    }
 
-            match check_method(
-                method.clone(),
-                data.finalized_effects.clone(),
-                &data.code_verifier.syntax,
-                &data.variables,
-                returning,
-                span,
-            )
-            .await
-            {
-                Ok(found) => return Ok(Some(found)),
-                Err(_error) => {}
+    for (implementor, temp) in candidates {
+        // Impls for a poisoned struct report the struct's error instead of being called
+        if let Some(error) = implementor.base.poisoned().first() {
+            return Err(span.make_error(error.message.clone()));
+        }
+        // Impls of supertraits also match the trait, but only the named trait's methods should be called
+        if implementor.target.inner_struct_safe() != trait_struct {
+            log.push(format!(
+                "  candidate {}: rejected, implements {} instead",
+                temp.name,
+                implementor.target.display_with_generics()
+            ));
+        } else if temp.name.split("::").last().unwrap() != data.method && !data.method.is_empty() {
+            log.push(format!("  candidate {}: rejected, not named {}", temp.name, data.method));
+        } else {
+            match check_candidate(data, temp, span).await? {
+                Ok(found) => {
+                    log.push(format!("  candidate {}: accepted", temp.name));
+                    return Ok(Some(found));
+                }
+                Err(error) => log.push(format!("  candidate {}: rejected, {}", temp.name, error.message)),
             };
         }
     }
     return Ok(None);
 }
+
+/// Tries the methods of impls of the trait for exactly the calling type, which needs no trait solving.
+/// Returns None if the calling type isn't a concrete struct or none of those methods fit, so every impl is searched.
+async fn try_direct_impl(data: &ImplCheckerData<'_>, span: &Span) -> Result<Option<FinalizedEffects>, ParsingError> {
+    // A reference to a struct uses the struct's impls, check_method derefs the receiver if needed
+    let calling_type = data.calling_type.dereferenced();
+    if data.method.is_empty() || !matches!(calling_type, FinalizedTypes::Struct(_)) || calling_type.is_trait() {
+        return Ok(None);
+    }
+
+    let trait_struct = data.trait_type.inner_struct_safe();
+    let candidates = data
+        .code_verifier
+        .syntax
+        .lock()
+        .unwrap()
+        .implementations
+        .iter()
+        // Generic impls need their generics solved, so they're left to the full search
+        .filter(|implementor| {
+            implementor.generics.is_empty()
+                && implementor.base == *calling_type
+                && implementor.target.inner_struct_safe() == trait_struct
+        })
+        .flat_map(|implementor| implementor.functions.clone())
+        .filter(|function| function.name.split("::").last().unwrap() == data.method)
+        .collect::<Vec<_>>();
+
+    for candidate in &candidates {
+        if let Ok(found) = check_candidate(data, candidate, span).await? {
+            return Ok(Some(found));
+        }
+    }
+    return Ok(None);
+}
+
+/// Checks if the arguments fit an impl's method, returning the call if they do or why they don't
+async fn check_candidate(
+    data: &ImplCheckerData<'_>,
+    candidate: &Arc<FunctionData>,
+    span: &Span,
+) -> Result<Result<FinalizedEffects, ParsingError>, ParsingError> {
+    let method = data.code_verifier.process_manager.functions.get(&data.code_verifier.syntax, candidate).await;
+    // Explicit generics are bound before check_method can infer anything
+    let method = bind_explicit_generics(
+        method,
+        data.explicit_generics,
+        data.code_verifier.process_manager.cloned(),
+        &data.code_verifier.syntax,
+        data.variables,
+        span,
+    )
+    .await?;
+
+    let returning = match &data.returning {
+        Some(inner) => Some((
+            Syntax::parse_type(
+                data.code_verifier.syntax.clone(),
+                span.clone(),
+                data.code_verifier.resolver.boxed_clone(),
+                inner.clone(),
+                vec![],
+            )
+            .await?
+            .finalize(data.code_verifier.syntax.clone())
+            .await,
+            span.clone(),
+        )),
+        None => None,
+    };
+
+    return Ok(check_method(
+        method,
+        data.finalized_effects.clone(),
+        &[],
+        data.code_verifier,
+        data.variables,
+        returning,
+        span,
+    )
+    .await);
+}
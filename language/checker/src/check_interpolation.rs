@@ -0,0 +1,82 @@
+use async_recursion::async_recursion;
+use data::tokens::Span;
+
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::syntax::Syntax;
+use syntax::SimpleVariableManager;
+
+use crate::check_code::verify_effect;
+use crate::{get_checked_return, CodeVerifier};
+
+/// The trait interpolated expressions are converted to strings with
+const TO_STRING_TRAIT: &str = "string::ToString";
+/// The trait the parts of an interpolated string are concatenated with
+const ADD_TRAIT: &str = "math::Add";
+
+/// Lowers an interpolated string like "x is {x}" into "x is " + x.to_string(), concatenating the parts in order
+#[async_recursion]
+pub async fn verify_interpolation(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    parts: Vec<Effects>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let to_string = Syntax::get_struct(
+        code_verifier.syntax.clone(),
+        span.clone(),
+        TO_STRING_TRAIT.to_string(),
+        code_verifier.resolver.boxed_clone(),
+        vec![],
+    )
+    .await?
+    .finalize(code_verifier.syntax.clone())
+    .await;
+
+    let mut lowered: Option<Effects> = None;
+    for part in parts {
+        let part = if matches!(part.types, EffectType::StringLiteral(_)) {
+            part
+        } else {
+            // The expression is checked first so a type without a conversion gets a clearer error than a missing impl
+            let found = verify_effect(code_verifier, variables, part.clone()).await?;
+            if matches!(found.types, FinalizedEffectType::Poison(_)) {
+                return Ok(found);
+            }
+            let types = get_checked_return(&found.types, variables, &code_verifier.syntax, &part.span).await?;
+            if !types.of_type(&to_string, code_verifier.syntax.clone()).await {
+                return Err(part.span.make_error(ParsingMessage::NoStringConversion(types)));
+            }
+
+            let span = part.span.clone();
+            Effects::new(
+                span,
+                EffectType::ImplementationCall(
+                    Box::new(part),
+                    TO_STRING_TRAIT.to_string(),
+                    "to_string".to_string(),
+                    vec![],
+                    None,
+                    vec![],
+                ),
+            )
+        };
+
+        lowered = Some(match lowered {
+            Some(previous) => Effects::new(
+                part.span.clone(),
+                EffectType::ImplementationCall(
+                    Box::new(previous),
+                    ADD_TRAIT.to_string(),
+                    "add".to_string(),
+                    vec![part],
+                    None,
+                    vec![],
+                ),
+            ),
+            None => part,
+        });
+    }
+
+    return verify_effect(code_verifier, variables, lowered.unwrap()).await;
+}
@@ -0,0 +1,88 @@
+use async_recursion::async_recursion;
+use data::tokens::Span;
+
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::function::CodeBody;
+use syntax::SimpleVariableManager;
+
+use crate::check_code::verify_code;
+use crate::CodeVerifier;
+
+/// A loop the code being verified is inside of
+pub struct EnclosingLoop {
+    /// The label continue jumps to, which starts the next iteration
+    pub continuing: String,
+    /// The label break jumps to, which is right after the loop
+    pub breaking: String,
+    /// How many variables were owned before the loop, the ones owned after are dropped when jumping out
+    pub scope: usize,
+    /// The loop's name, if it's labeled
+    pub label: Option<String>,
+    /// How many scopes were open before the loop, the variables declared in them are from outside of it
+    pub scopes: usize,
+}
+
+/// Finds the loop a break or continue jumps out of, the innermost one unless it names another
+pub fn find_loop<'a>(loops: &'a [EnclosingLoop], label: &Option<String>) -> Option<&'a EnclosingLoop> {
+    return match label {
+        Some(label) => loops.iter().rev().find(|found| found.label.as_ref() == Some(label)),
+        None => loops.last(),
+    };
+}
+
+/// Checks a for, while, or do while loop's code, which can break or continue out of the loop
+#[async_recursion]
+pub async fn verify_loop(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    body: CodeBody,
+    continuing: String,
+    breaking: String,
+    label: Option<String>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let scopes = variables.scopes.len();
+    code_verifier.loops.push(EnclosingLoop { continuing, breaking, scope: variables.owned.len(), label, scopes });
+    variables.push_scope();
+    let body = verify_code(code_verifier, variables, body, false).await;
+    variables.pop_scope();
+    code_verifier.loops.pop();
+    return Ok(FinalizedEffects::new(span.clone(), FinalizedEffectType::CodeBody(body?)));
+}
+
+/// Checks a break, which jumps to the end of the named or innermost loop
+pub fn verify_break(
+    code_verifier: &CodeVerifier<'_>,
+    label: Option<String>,
+    value: Option<Box<Effects>>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    if let Some(value) = value {
+        return Err(value.span.make_error(ParsingMessage::LoopValue()));
+    }
+    return match find_loop(&code_verifier.loops, &label) {
+        Some(found) => Ok(FinalizedEffects::new(span.clone(), FinalizedEffectType::Jump(found.breaking.clone()))),
+        None => Err(missing_loop(label, "break", span)),
+    };
+}
+
+/// Checks a continue, which jumps to the start of the named or innermost loop's next iteration
+pub fn verify_continue(
+    code_verifier: &CodeVerifier<'_>,
+    label: Option<String>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    return match find_loop(&code_verifier.loops, &label) {
+        Some(found) => Ok(FinalizedEffects::new(span.clone(), FinalizedEffectType::Jump(found.continuing.clone()))),
+        None => Err(missing_loop(label, "continue", span)),
+    };
+}
+
+/// The error for a jump without a loop to jump out of
+fn missing_loop(label: Option<String>, keyword: &str, span: &Span) -> ParsingError {
+    return match label {
+        Some(label) => span.make_error(ParsingMessage::UnknownLabel(label)),
+        None => span.make_error(ParsingMessage::OutsideLoop(keyword.to_string())),
+    };
+}
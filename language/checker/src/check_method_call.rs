@@ -1,39 +1,142 @@
+use std::mem;
 use std::sync::{Arc, Mutex};
 
+use async_recursion::async_recursion;
 use data::tokens::Span;
-use syntax::async_util::AsyncDataGetter;
+use syntax::async_util::{AsyncDataGetter, UnparsedType};
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
 use syntax::program::function::{CodelessFinalizedFunction, FunctionData};
+use syntax::program::r#struct::literal_integers;
 use syntax::program::syntax::Syntax;
-use syntax::program::types::FinalizedTypes;
-use syntax::top_element_manager::TraitImplWaiter;
-use syntax::{is_modifier, FinishedTraitImplementor, Modifier, SimpleVariableManager};
+use syntax::program::types::{FinalizedTypes, Types};
+use syntax::top_element_manager::{find_trait_implementation, TraitImplWaiter};
+use syntax::{
+    is_modifier, FinishedStructImplementor, FinishedTraitImplementor, Modifier, ProcessManager, SimpleVariableManager,
+};
 
-use crate::check_code::verify_effect;
-use crate::{get_return, CodeVerifier};
+use crate::check_closure::check_closure_call;
+use crate::check_code::{coerce_literal, is_int_literal, literal_integer, moves_value, retype_literal, verify_effect};
+use crate::check_enum::{check_option_call, check_variant};
+use crate::check_impl_call::check_impl_call;
+use crate::check_static_assert::{verify_layout_intrinsic, LAYOUT_INTRINSICS};
+use crate::degeneric::{bind_explicit_generics, bind_impl_generics, infer_generics};
+use crate::{check_poison, closest_name, get_checked_return, get_return, CodeVerifier};
+
+/// The trait a type implements to have methods it doesn't have called on the value its deref returns
+const DEREF_TRAIT: &str = "mem::Deref";
+/// How many derefs a method call follows before deciding they loop
+const MAX_DEREFS: usize = 16;
 
 /// Checks a method call to make sure it's valid
+#[async_recursion]
 pub async fn check_method_call(
     code_verifier: &mut CodeVerifier<'_>,
     variables: &mut SimpleVariableManager,
     effect: Effects,
 ) -> Result<FinalizedEffects, ParsingError> {
+    // Layout intrinsics are answered from the type instead of calling anything
+    if let EffectType::MethodCall(None, method, arguments, _, generics) = &effect.types {
+        if LAYOUT_INTRINSICS.contains(&method.as_str()) && arguments.is_empty() && generics.len() == 1 {
+            return verify_layout_intrinsic(code_verifier, method, generics.clone(), &effect.span).await;
+        }
+    }
+    // Calls like Trait::method(value) call the named trait's impl for the first argument
+    if let EffectType::MethodCall(None, method, _, _, _) = &effect.types {
+        if let Some(traits) = qualifying_trait(code_verifier, method.clone()).await {
+            return check_impl_call(code_verifier, variables, into_impl_call(effect, traits)?).await;
+        }
+    }
+
+    // Calls like Enum::Variant(fields) create that variant
+    if let Some(found) = check_variant(code_verifier, variables, &effect).await {
+        return found;
+    }
+
+    // Calls on a variable holding a closure call the closure
+    if let Some(found) = check_closure_call(code_verifier, variables, &effect).await {
+        return found;
+    }
+
     let mut finalized_effects = Vec::default();
+    // Named arguments come after the positional ones, so these are the names of the last arguments
+    let mut named = Vec::default();
     let calling;
     let method;
     let returning;
-    if let EffectType::MethodCall(new_calling, new_method, effects, new_return_type) = effect.types {
+    let explicit_generics;
+    if let EffectType::MethodCall(new_calling, new_method, effects, new_return_type, new_generics) = effect.types {
+        // The value it's called on runs before the arguments, so it's checked first to move variables in that order
+        calling = match new_calling {
+            Some(found) => Some(verify_effect(code_verifier, variables, *found).await?),
+            None => None,
+        };
         for effect in effects {
-            finalized_effects.push(verify_effect(code_verifier, variables, effect).await?)
+            let value = if let EffectType::NamedArgument(name, value) = effect.types {
+                named.push((name, effect.span));
+                *value
+            } else if !named.is_empty() {
+                return Err(effect.span.make_error(ParsingMessage::PositionalAfterNamed()));
+            } else {
+                effect
+            };
+            let moving = match &value.types {
+                EffectType::LoadVariable(name) => Some(name.clone()),
+                _ => None,
+            };
+            let span = value.span.clone();
+            finalized_effects.push(verify_effect(code_verifier, variables, value).await?);
+            // Structs passed by value are moved into the call
+            if let Some(name) = moving {
+                if let Some(types) = variables.variables.get(&name).cloned() {
+                    if moves_value(code_verifier, &types).await {
+                        // The next time around the loop would use the moved value
+                        if let Some(found) = code_verifier.loops.last() {
+                            if !variables.declared_since(found.scopes, &name) {
+                                return Err(span.make_error(ParsingMessage::MovedInLoop(name)));
+                            }
+                        }
+                        variables.moved.insert(name);
+                    }
+                }
+            }
         }
-        calling = new_calling;
         method = new_method;
         returning = new_return_type;
+        explicit_generics = new_generics;
     } else {
         unreachable!()
     }
 
+    return find_method(
+        code_verifier,
+        variables,
+        calling,
+        method,
+        finalized_effects,
+        named,
+        returning,
+        explicit_generics,
+        &effect.span,
+    )
+    .await;
+}
+
+/// Finds the method the call with the checked receiver and arguments calls.
+/// It's separate from check_method_call so only check_method_call's stack frame is on the stack while its arguments
+/// are checked, which happens once for every level of nesting
+#[async_recursion]
+async fn find_method(
+    code_verifier: &mut CodeVerifier<'_>,
+    variables: &mut SimpleVariableManager,
+    calling: Option<FinalizedEffects>,
+    method: String,
+    mut finalized_effects: Vec<FinalizedEffects>,
+    named: Vec<(String, Span)>,
+    returning: Option<(UnparsedType, Span)>,
+    explicit_generics: Vec<UnparsedType>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
     let returning = match returning {
         Some((inner, span)) => Some((
             Syntax::parse_type(
@@ -51,109 +154,24 @@ pub async fn check_method_call(
         None => None,
     };
 
-    // Finds methods based off the calling type.
-    let method = if let Some(found) = calling {
-        let calling = verify_effect(code_verifier, variables, *found).await?;
-        let return_type: FinalizedTypes = get_return(&calling.types, variables, &code_verifier.syntax).await.unwrap();
-
-        // If it's generic, check its trait bounds for the method
-        if return_type.inner_struct_safe().is_none() {
-            if let Some(mut found) = return_type.find_method(&method) {
-                let span = calling.span.clone();
-                finalized_effects.insert(0, calling);
-                let mut output = vec![];
-                for (found_trait, function) in &mut found {
-                    let temp = AsyncDataGetter { getting: function.clone(), syntax: code_verifier.syntax.clone() }.await;
-                    /*
-                    TODO figure out how the hell to typecheck this
-                    println!("Found {} with {:?}", found_trait.name(), finalized_effects.iter()
-                        .map(|inner| inner.get_return(variables).unwrap().to_string()).collect::<Vec<_>>());
-                    if check_args(&temp, &resolver, &mut finalized_effects, &syntax, variables).await {*/
-                    output.push((found_trait, temp));
-                    //}
-                }
-
-                if output.len() > 1 {
-                    return Err(span.make_error(ParsingMessage::AmbiguousMethod(method)));
-                } else if output.is_empty() {
-                    return Err(span.make_error(ParsingMessage::NoMethod(method, return_type)));
-                }
-
-                let (found_trait, found) = output.pop().unwrap();
-
-                return Ok(FinalizedEffects::new(
-                    effect.span.clone(),
-                    FinalizedEffectType::GenericMethodCall(found, found_trait.clone(), finalized_effects),
-                ));
-            }
-        }
-
-        // If it's a trait, handle virtual method calls.
-        if is_modifier(return_type.inner_struct().data.modifiers, Modifier::Trait) {
-            finalized_effects.insert(0, calling);
-
-            let method = Syntax::get_function(
-                code_verifier.syntax.clone(),
-                effect.span.clone(),
-                format!("{}::{}", return_type.inner_struct().data.name, method),
-                code_verifier.resolver.boxed_clone(),
-                false,
-            )
-            .await?;
-            let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
-
-            check_args(&method, &mut finalized_effects, &code_verifier.syntax, variables, &effect.span).await?;
-
-            let index = return_type.inner_struct().data.functions.iter().position(|found| *found == method.data).unwrap();
-            return Ok(FinalizedEffects::new(
-                effect.span.clone(),
-                FinalizedEffectType::VirtualCall(index, method, finalized_effects, returning),
-            ));
-        }
+    let explicit_generics = finalize_explicit_generics(code_verifier, explicit_generics, span).await?;
 
+    // Finds methods based off the calling type.
+    let method = if let Some(calling) = calling {
+        let return_type = get_checked_return(&calling.types, variables, &code_verifier.syntax, span).await?;
         finalized_effects.insert(0, calling);
-
-        if let Ok(value) = Syntax::get_function(
-            code_verifier.syntax.clone(),
-            Span::default(),
-            method.clone(),
-            code_verifier.resolver.boxed_clone(),
-            true,
+        return check_receiver_call(
+            code_verifier,
+            variables,
+            return_type,
+            method,
+            finalized_effects,
+            &named,
+            returning,
+            &explicit_generics,
+            span,
         )
-        .await
-        {
-            value
-        } else {
-            let checker = async |implementor: Arc<FinishedTraitImplementor>,
-                                 method: Arc<FunctionData>|
-                   -> Result<FinalizedEffects, ParsingError> {
-                let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
-                let mut process_manager = code_verifier.process_manager.clone();
-                implementor
-                    .base
-                    .resolve_generic(&return_type, &code_verifier.syntax, &mut process_manager.generics, Span::default())
-                    .await?;
-                check_method(
-                    method,
-                    finalized_effects.clone(),
-                    &code_verifier.syntax,
-                    variables,
-                    returning.clone(),
-                    &effect.span,
-                )
-                .await
-            };
-
-            return TraitImplWaiter {
-                syntax: code_verifier.syntax.clone(),
-                resolver: code_verifier.resolver.boxed_clone(),
-                method: method.clone(),
-                return_type: return_type.clone(),
-                checker,
-                error: effect.span.make_error(ParsingMessage::NoImpl(return_type.clone(), method.clone())),
-            }
-            .await;
-        }
+        .await;
     } else {
         if method.contains("::") {
             let possible = method.split("::").collect::<Vec<_>>();
@@ -177,13 +195,34 @@ pub async fn check_method_call(
                     for function in &implementor.functions {
                         if function.name.split("::").last().unwrap() == possible[possible.len() - 1] {
                             let method = AsyncDataGetter::new(code_verifier.syntax.clone(), function.clone()).await;
+                            let method = if explicit_generics.is_empty() {
+                                infer_generics(
+                                    method,
+                                    &finalized_effects,
+                                    code_verifier.process_manager.cloned(),
+                                    &code_verifier.syntax,
+                                    variables,
+                                )
+                                .await?
+                            } else {
+                                bind_explicit_generics(
+                                    method,
+                                    &explicit_generics,
+                                    code_verifier.process_manager.cloned(),
+                                    &code_verifier.syntax,
+                                    variables,
+                                    span,
+                                )
+                                .await?
+                            };
                             match check_method(
                                 method,
                                 finalized_effects.clone(),
-                                &code_verifier.syntax,
+                                &named,
+                                code_verifier,
                                 variables,
                                 returning.clone(),
-                                &effect.span,
+                                span,
                             )
                             .await
                             {
@@ -196,31 +235,530 @@ pub async fn check_method_call(
             }
         }
 
-        Syntax::get_function(
+        Syntax::get_function(code_verifier.syntax.clone(), span.clone(), method, code_verifier.resolver.boxed_clone(), true)
+            .await?
+    };
+
+    let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
+    let method = bind_explicit_generics(
+        method,
+        &explicit_generics,
+        code_verifier.process_manager.cloned(),
+        &code_verifier.syntax,
+        variables,
+        span,
+    )
+    .await?;
+    return check_method(method, finalized_effects, &named, code_verifier, variables, returning, span).await;
+}
+
+/// Resolves calling the method on a value of the receiver type, like value.method(arguments), the same way
+/// check_method_call would. The receiver's value is the first of the already checked arguments, so tools like a REPL
+/// can resolve calls they built themselves without a parsed Effects tree.
+pub async fn resolve_method_call(
+    code_verifier: &CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+    receiver: FinalizedTypes,
+    method: String,
+    arguments: Vec<FinalizedEffects>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    if arguments.is_empty() {
+        return Err(span.make_error(ParsingMessage::MissingArgument()));
+    }
+    check_poison(&receiver, span)?;
+    return check_receiver_call(code_verifier, variables, receiver, method, arguments, &[], None, &vec![], span).await;
+}
+
+/// Checks calling the method on a receiver of the given type, with the receiver as the first of the checked arguments.
+/// If the type doesn't have the method, each type in its deref chain is tried in order, stopping if the chain loops.
+async fn check_receiver_call(
+    code_verifier: &CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+    return_type: FinalizedTypes,
+    method: String,
+    mut finalized_effects: Vec<FinalizedEffects>,
+    named: &[(String, Span)],
+    returning: Option<(FinalizedTypes, Span)>,
+    explicit_generics: &Vec<FinalizedTypes>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let mut chain = vec![return_type];
+    loop {
+        let error = match check_receiver_method(
+            code_verifier,
+            variables,
+            chain.last().unwrap().clone(),
+            method.clone(),
+            finalized_effects.clone(),
+            named,
+            returning.clone(),
+            explicit_generics,
+            span,
+        )
+        .await
+        {
+            Err(error) if matches!(error.message, ParsingMessage::NoImpl(_, _, _)) => error,
+            found => return found,
+        };
+
+        // Literals are i64 unless only another integer type has the method, like a 0.trait_test() implemented for u64
+        if chain.len() == 1 && is_int_literal(&finalized_effects[0]) {
+            // Skips the i64 that was already tried
+            for integer in literal_integers().into_iter().skip(1) {
+                let integer = FinalizedTypes::Struct(integer);
+                let mut arguments = finalized_effects.clone();
+                coerce_literal(&mut arguments[0], &integer, span)?;
+                match check_receiver_method(
+                    code_verifier,
+                    variables,
+                    integer,
+                    method.clone(),
+                    arguments,
+                    named,
+                    returning.clone(),
+                    explicit_generics,
+                    span,
+                )
+                .await
+                {
+                    Err(error) if matches!(error.message, ParsingMessage::NoImpl(_, _, _)) => {}
+                    found => return found,
+                }
+            }
+        }
+
+        let (receiver, inner) =
+            match follow_deref(code_verifier, variables, chain.last().unwrap(), &finalized_effects[0]).await? {
+                Some(found) => found,
+                None if chain.len() == 1 => return Err(error),
+                None => return Err(error.span.make_error(ParsingMessage::DerefChain(Box::new(error.message), chain))),
+            };
+        // Compared by the full name with generics, since equal structs can have different generics
+        let name = inner.dereferenced().display_with_generics();
+        let looped =
+            chain.iter().any(|other| other.dereferenced().display_with_generics() == name) || chain.len() > MAX_DEREFS;
+        chain.push(inner);
+        if looped {
+            return Err(finalized_effects[0].span.make_error(ParsingMessage::DerefCycle(method, chain)));
+        }
+        finalized_effects[0] = receiver;
+    }
+}
+
+/// Derefs the receiver once, to the value inside of an Rc or what its type's Deref impl returns.
+/// Returns the derefed receiver and its type, or None if the type doesn't deref.
+async fn follow_deref(
+    code_verifier: &CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+    types: &FinalizedTypes,
+    receiver: &FinalizedEffects,
+) -> Result<Option<(FinalizedEffects, FinalizedTypes)>, ParsingError> {
+    let receiver_span = receiver.span.clone();
+    let inner = if types.is_rc() {
+        check_receiver_method(
+            code_verifier,
+            variables,
+            types.clone(),
+            "get".to_string(),
+            vec![receiver.clone()],
+            &[],
+            None,
+            &vec![],
+            &receiver_span,
+        )
+        .await?
+    } else {
+        // Every impl is finished by the time the type's own methods fail to be found
+        let implementations = code_verifier.syntax.lock().unwrap().implementations.clone();
+        let mut found = None;
+        for implementor in implementations {
+            if implementor.target.inner_struct_safe().is_some_and(|inner| inner.data.name == DEREF_TRAIT)
+                && types.dereferenced().of_type(&implementor.base, code_verifier.syntax.clone()).await
+            {
+                found = implementor
+                    .functions
+                    .iter()
+                    .find(|function| function.name.split("::").last().unwrap() == "deref")
+                    .cloned();
+                break;
+            }
+        }
+        let found = match found {
+            Some(found) => AsyncDataGetter::new(code_verifier.syntax.clone(), found).await,
+            None => return Ok(None),
+        };
+        check_method(found, vec![receiver.clone()], &[], code_verifier, variables, None, &receiver_span).await?
+    };
+    let inner_type = get_return(&inner.types, variables, &code_verifier.syntax).await.unwrap();
+    return Ok(Some((inner, inner_type)));
+}
+
+/// Checks calling the method on a receiver of exactly the given type, without following its derefs
+async fn check_receiver_method(
+    code_verifier: &CodeVerifier<'_>,
+    variables: &SimpleVariableManager,
+    return_type: FinalizedTypes,
+    method: String,
+    mut finalized_effects: Vec<FinalizedEffects>,
+    named: &[(String, Span)],
+    returning: Option<(FinalizedTypes, Span)>,
+    explicit_generics: &Vec<FinalizedTypes>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    // Options might be None, so their methods are only reachable after unwrapping them
+    if return_type.is_option(&code_verifier.syntax.lock().unwrap()) {
+        let calling = finalized_effects.remove(0);
+        return check_option_call(calling, return_type, method, &finalized_effects, span);
+    }
+
+    // If it's generic, check its trait bounds for the method
+    if return_type.inner_struct_safe().is_none() {
+        if let Some(mut found) = return_type.find_method(&method) {
+            let receiver_span = finalized_effects[0].span.clone();
+            let mut output = vec![];
+            for (found_trait, function) in &mut found {
+                let temp = AsyncDataGetter { getting: function.clone(), syntax: code_verifier.syntax.clone() }.await;
+                /*
+                TODO figure out how the hell to typecheck this
+                println!("Found {} with {:?}", found_trait.name(), finalized_effects.iter()
+                    .map(|inner| inner.get_return(variables).unwrap().to_string()).collect::<Vec<_>>());
+                if check_args(&temp, &resolver, &mut finalized_effects, &syntax, variables).await {*/
+                output.push((found_trait, temp));
+                //}
+            }
+
+            if output.len() > 1 {
+                return Err(receiver_span.make_error(ParsingMessage::AmbiguousMethod(method)));
+            } else if output.is_empty() {
+                return Err(receiver_span.make_error(ParsingMessage::NoMethod(method, return_type)));
+            }
+
+            let (found_trait, found) = output.pop().unwrap();
+            check_self_bounds(&code_verifier.syntax, &found, &return_type, span).await?;
+            order_named_arguments(&found, &mut finalized_effects, named)?;
+
+            return Ok(FinalizedEffects::new(
+                span.clone(),
+                FinalizedEffectType::GenericMethodCall(found, found_trait.clone(), finalized_effects),
+            ));
+        }
+    }
+
+    // If it's a trait, handle virtual method calls.
+    if return_type.is_trait() {
+        // Methods inherited from a supertrait are declared there, but take this trait's vtable slot for them
+        let (index, declaring) = match return_type.inner_struct().virtual_method(&method) {
+            Some((index, declaring)) => (index, declaring.name.clone()),
+            // Fails to be found below
+            None => (0, return_type.inner_struct().data.name.clone()),
+        };
+        let method = Syntax::get_function(
             code_verifier.syntax.clone(),
-            effect.span.clone(),
-            method,
+            span.clone(),
+            format!("{}::{}", declaring, method),
             code_verifier.resolver.boxed_clone(),
-            true,
+            false,
         )
-        .await?
+        .await?;
+        let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
+        check_self_bounds(&code_verifier.syntax, &method, &return_type, span).await?;
+
+        order_named_arguments(&method, &mut finalized_effects, named)?;
+        check_args(&method, &mut finalized_effects, code_verifier, variables, span).await?;
+
+        return Ok(FinalizedEffects::new(
+            span.clone(),
+            FinalizedEffectType::VirtualCall(index, method, finalized_effects, returning),
+        ));
+    }
+
+    // Methods from an impl without a trait take precedence over trait methods
+    if let Some((implementor, found)) = find_inherent_method(code_verifier, &return_type, &method).await {
+        let found = AsyncDataGetter::new(code_verifier.syntax.clone(), found).await;
+        let found = if explicit_generics.is_empty() {
+            bind_impl_generics(
+                found,
+                &implementor.target,
+                &return_type,
+                code_verifier.process_manager.cloned(),
+                &code_verifier.syntax,
+                variables,
+            )
+            .await?
+        } else {
+            bind_explicit_generics(
+                found,
+                explicit_generics,
+                code_verifier.process_manager.cloned(),
+                &code_verifier.syntax,
+                variables,
+                span,
+            )
+            .await?
+        };
+        return check_method(found, finalized_effects, named, code_verifier, variables, returning, span).await;
+    }
+
+    if let Ok(found) = Syntax::get_function(
+        code_verifier.syntax.clone(),
+        Span::default(),
+        method.clone(),
+        code_verifier.resolver.boxed_clone(),
+        true,
+    )
+    .await
+    {
+        let found = AsyncDataGetter::new(code_verifier.syntax.clone(), found).await;
+        let found = bind_explicit_generics(
+            found,
+            explicit_generics,
+            code_verifier.process_manager.cloned(),
+            &code_verifier.syntax,
+            variables,
+            span,
+        )
+        .await?;
+        return check_method(found, finalized_effects, named, code_verifier, variables, returning, span).await;
+    }
+
+    // Impls whose trait method has a Self bound the type doesn't meet are skipped, keeping why for the error
+    let unmet_bound = Mutex::new(None);
+    let checker = async |implementor: Arc<FinishedTraitImplementor>,
+                         method: Arc<FunctionData>|
+           -> Result<FinalizedEffects, ParsingError> {
+        let name = method.name.split("::").last().unwrap();
+        let declared = implementor
+            .target
+            .inner_struct()
+            .data
+            .functions
+            .iter()
+            .find(|function| function.name.split("::").last().unwrap() == name);
+        if let Some(declared) = declared {
+            let declared = AsyncDataGetter::new(code_verifier.syntax.clone(), declared.clone()).await;
+            if let Err(error) = check_self_bounds(&code_verifier.syntax, &declared, &return_type, span).await {
+                *unmet_bound.lock().unwrap() = Some(error.clone());
+                return Err(error);
+            }
+        }
+        let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
+        let mut process_manager = code_verifier.process_manager.clone();
+        implementor
+            .base
+            .resolve_generic(&return_type, &code_verifier.syntax, &mut process_manager.generics, Span::default())
+            .await?;
+        let method = bind_explicit_generics(
+            method,
+            explicit_generics,
+            code_verifier.process_manager.cloned(),
+            &code_verifier.syntax,
+            variables,
+            span,
+        )
+        .await?;
+        check_method(method, finalized_effects.clone(), named, code_verifier, variables, returning.clone(), span).await
     };
 
-    let method = AsyncDataGetter::new(code_verifier.syntax.clone(), method).await;
-    return check_method(method, finalized_effects, &code_verifier.syntax, variables, returning, &effect.span).await;
+    let found = TraitImplWaiter {
+        syntax: code_verifier.syntax.clone(),
+        resolver: code_verifier.resolver.boxed_clone(),
+        method: method.clone(),
+        return_type: return_type.clone(),
+        checker,
+        error: span.make_error(ParsingMessage::NoImpl(return_type.clone(), method.clone(), None)),
+    }
+    .await;
+    if let Some(error) = unmet_bound.into_inner().unwrap().filter(|_| found.is_err()) {
+        return Err(error);
+    }
+    return match found {
+        Err(mut error) => {
+            // Every impl is finished once it fails, so all the methods it could've meant are known
+            if let ParsingMessage::NoImpl(_, _, similar) = &mut error.message {
+                let imports = code_verifier.resolver.imports();
+                if let Some(traits) = unimported_trait(&code_verifier.syntax, imports, &return_type, &method) {
+                    return Err(error.span.make_error(ParsingMessage::TraitNotImported(method, traits)));
+                }
+                *similar = similar_method(&code_verifier.syntax, &return_type, &method);
+            }
+            Err(error)
+        }
+        found => found,
+    };
+}
+
+/// Errors if the type a trait method is called on doesn't implement every trait in the method's "where Self: Trait"
+pub async fn check_self_bounds(
+    syntax: &Arc<Mutex<Syntax>>,
+    method: &CodelessFinalizedFunction,
+    calling_type: &FinalizedTypes,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    for bound in &method.self_bounds {
+        if !calling_type.of_type(bound, syntax.clone()).await {
+            return Err(span.make_error(ParsingMessage::UnsatisfiedSelfBound(
+                method.data.name.split("::").last().unwrap().to_string(),
+                calling_type.dereferenced().display_with_generics(),
+                bound.display_with_generics(),
+            )));
+        }
+    }
+    return Ok(());
+}
+
+/// Finds a method declared in an impl of the type without a trait along with its impl,
+/// warning at the method's declaration if it shadows a trait's method the first time it's called
+async fn find_inherent_method(
+    code_verifier: &CodeVerifier<'_>,
+    return_type: &FinalizedTypes,
+    method: &String,
+) -> Option<(Arc<FinishedStructImplementor>, Arc<FunctionData>)> {
+    // Impls are stored under the struct itself, without references or generics
+    let target = FinalizedTypes::Struct(return_type.inner_struct_safe()?.clone());
+
+    let implementors = Syntax::get_struct_impl(code_verifier.syntax.clone(), target).await;
+    let found = implementors.into_iter().find_map(|implementor| {
+        let function =
+            implementor.functions.iter().find(|function| function.name.split("::").last().unwrap() == method)?.clone();
+        return Some((implementor, function));
+    })?;
+
+    if let Ok(Some(shadowed)) =
+        find_trait_implementation(&code_verifier.syntax, &*code_verifier.resolver, method, return_type).await
+    {
+        for (implementor, _) in shadowed {
+            let shadowing = ParsingMessage::ShadowedTraitMethod(method.clone(), implementor.target.clone());
+            let warning = found.1.span.make_error(shadowing);
+            code_verifier.syntax.lock().unwrap().warn(warning);
+        }
+    }
+    return Some(found);
+}
+
+/// Finds the method closest to the misspelled one out of the type's impls, with or without a trait
+fn similar_method(syntax: &Arc<Mutex<Syntax>>, types: &FinalizedTypes, method: &str) -> Option<String> {
+    let types = types.dereferenced();
+    let locked = syntax.lock().unwrap();
+    let mut functions = Vec::default();
+    for implementor in locked.struct_implementations.get(types).into_iter().flatten() {
+        functions.extend(implementor.functions.iter());
+    }
+    for implementor in locked.implementations.iter().filter(|implementor| implementor.base == *types) {
+        functions.extend(implementor.functions.iter());
+    }
+    // A method with the same name was called with the wrong arguments, so it isn't a misspelling
+    let names = functions
+        .iter()
+        .map(|function| function.name.split("::").last().unwrap().to_string())
+        .filter(|name| name != method)
+        .collect::<Vec<_>>();
+    return closest_name(method, names.iter());
+}
+
+/// Finds a trait that isn't imported with an impl for the type that has the method, for when it wasn't found because
+/// the trait isn't imported
+fn unimported_trait(
+    syntax: &Arc<Mutex<Syntax>>,
+    imports: &[String],
+    types: &FinalizedTypes,
+    method: &str,
+) -> Option<FinalizedTypes> {
+    let types = types.dereferenced();
+    let locked = syntax.lock().unwrap();
+    return locked
+        .implementations
+        .iter()
+        .filter(|implementor| implementor.base == *types)
+        .filter(|implementor| !imports.contains(&implementor.target.inner_struct().data.name))
+        .find(|implementor| implementor.functions.iter().any(|function| function.name.split("::").last().unwrap() == method))
+        .map(|implementor| implementor.target.clone());
+}
+
+/// Gets the trait qualifying a method call like Trait::method, if the method's parent is a trait
+async fn qualifying_trait(code_verifier: &CodeVerifier<'_>, method: String) -> Option<String> {
+    let possible = method.split("::").collect::<Vec<_>>();
+    if possible.len() < 2 {
+        return None;
+    }
+    let traits = possible[possible.len() - 2].to_string();
+
+    return match Syntax::get_struct(
+        code_verifier.syntax.clone(),
+        Span::default(),
+        traits.clone(),
+        code_verifier.resolver.boxed_clone(),
+        vec![],
+    )
+    .await
+    {
+        Ok(Types::Struct(found)) if is_modifier(found.modifiers, Modifier::Trait) => Some(traits),
+        _ => None,
+    };
+}
+
+/// Turns a trait-qualified method call into an implementation call on its first argument
+fn into_impl_call(effect: Effects, traits: String) -> Result<Effects, ParsingError> {
+    if let EffectType::MethodCall(_, method, mut effects, returning, explicit_generics) = effect.types {
+        let method = method.split("::").last().unwrap().to_string();
+        // Trait methods are found from the type of the first argument, so it's always the receiver
+        if effects.is_empty() {
+            return Err(effect.span.make_error(ParsingMessage::MissingReceiver(method)));
+        }
+        let calling = effects.remove(0);
+        return Ok(Effects::new(
+            effect.span,
+            EffectType::ImplementationCall(
+                Box::new(calling),
+                traits,
+                method,
+                effects,
+                returning.map(|(returning, _)| returning),
+                explicit_generics,
+            ),
+        ));
+    }
+    unreachable!()
+}
+
+/// Finalizes the generics explicitly passed to a method call
+pub async fn finalize_explicit_generics(
+    code_verifier: &CodeVerifier<'_>,
+    explicit_generics: Vec<UnparsedType>,
+    span: &Span,
+) -> Result<Vec<FinalizedTypes>, ParsingError> {
+    let mut output = Vec::default();
+    for generic in explicit_generics {
+        output.push(
+            Syntax::parse_type(
+                code_verifier.syntax.clone(),
+                span.clone(),
+                code_verifier.resolver.boxed_clone(),
+                generic,
+                vec![],
+            )
+            .await?
+            .finalize(code_verifier.syntax.clone())
+            .await,
+        );
+    }
+    return Ok(output);
 }
 
-/// Checks if a method call is valid
+/// Checks if a method call is valid, with the names of the arguments passed by name
 /// The CheckerVariableManager here is used for the effects calling the method
 pub async fn check_method(
     method: Arc<CodelessFinalizedFunction>,
     mut effects: Vec<FinalizedEffects>,
-    syntax: &Arc<Mutex<Syntax>>,
+    named: &[(String, Span)],
+    code_verifier: &CodeVerifier<'_>,
     variables: &SimpleVariableManager,
     generic_returning: Option<(FinalizedTypes, Span)>,
     span: &Span,
 ) -> Result<FinalizedEffects, ParsingError> {
-    check_args(&method, &mut effects, syntax, variables, span).await?;
+    order_named_arguments(&method, &mut effects, named)?;
+    deref_receiver(&method, &mut effects, &code_verifier.syntax, variables).await;
+    check_args(&method, &mut effects, code_verifier, variables, span).await?;
 
     return Ok(match method.return_type.as_ref() {
         Some(returning) => FinalizedEffects::new(
@@ -238,30 +776,147 @@ pub async fn check_method(
     });
 }
 
+/// Loads the receiver of a method call from its reference when the method takes self by value,
+/// so a method declared on a struct can be called on a reference to it
+async fn deref_receiver(
+    method: &CodelessFinalizedFunction,
+    effects: &mut [FinalizedEffects],
+    syntax: &Arc<Mutex<Syntax>>,
+    variables: &SimpleVariableManager,
+) {
+    let receiver = match (&method.parent, method.arguments.first(), effects.first_mut()) {
+        (Some(_), Some(argument), Some(receiver)) if !matches!(argument.field.field_type, FinalizedTypes::Reference(_)) => {
+            receiver
+        }
+        _ => return,
+    };
+
+    while let Some(FinalizedTypes::Reference(_)) = get_return(&receiver.types, variables, syntax).await {
+        let span = receiver.span.clone();
+        let loading = mem::replace(receiver, FinalizedEffects::new(span.clone(), FinalizedEffectType::NOP));
+        *receiver = FinalizedEffects::new(span, FinalizedEffectType::ReferenceLoad(Box::new(loading)));
+    }
+}
+
+/// Moves the arguments passed by name, which are the last arguments, to the position of the argument they name
+fn order_named_arguments(
+    function: &CodelessFinalizedFunction,
+    args: &mut Vec<FinalizedEffects>,
+    named: &[(String, Span)],
+) -> Result<(), ParsingError> {
+    if named.is_empty() {
+        return Ok(());
+    }
+
+    let passed = args.split_off(args.len() - named.len());
+    let mut ordered = args.drain(..).map(Some).collect::<Vec<_>>();
+    ordered.resize(ordered.len().max(function.arguments.len()), None);
+    for ((name, span), arg) in named.iter().zip(passed) {
+        // Variadic arguments can't be named because they take every trailing argument
+        let index = function
+            .arguments
+            .iter()
+            .position(|argument| argument.field.name == *name && !is_modifier(argument.modifiers, Modifier::Variadic));
+        let index = match index {
+            Some(index) => index,
+            None => return Err(span.make_error(ParsingMessage::UnknownArgument(name.clone()))),
+        };
+        if ordered[index].is_some() {
+            return Err(span.make_error(ParsingMessage::DuplicateArgument(name.clone())));
+        }
+        ordered[index] = Some(arg);
+    }
+
+    // Arguments that weren't given use their default, otherwise check_args reports the wrong number of arguments
+    *args = ordered
+        .into_iter()
+        .enumerate()
+        .map_while(|(index, arg)| arg.or_else(|| function.arguments.get(index)?.default.clone()))
+        .collect();
+    return Ok(());
+}
+
 /// Checks to see if arguments are valid
 pub async fn check_args(
     function: &Arc<CodelessFinalizedFunction>,
     args: &mut Vec<FinalizedEffects>,
-    syntax: &Arc<Mutex<Syntax>>,
+    code_verifier: &CodeVerifier<'_>,
     variables: &SimpleVariableManager,
     span: &Span,
 ) -> Result<(), ParsingError> {
-    if function.arguments.len() != args.len() {
+    let syntax = &code_verifier.syntax;
+    if let Some(error) = function.data.poisoned.first() {
+        return Err(span.make_error(error.message.clone()));
+    }
+    if args.is_empty() && function.takes_receiver() {
+        let name = function.data.name.split("::").last().unwrap().to_string();
+        return Err(span.make_error(ParsingMessage::MissingReceiver(name)));
+    }
+    let variadic = function.arguments.last().filter(|last| is_modifier(last.modifiers, Modifier::Variadic));
+    let fixed = function.arguments.len() - variadic.is_some() as usize;
+    // Trailing arguments that weren't passed use their defaults
+    while let Some(default) = function.arguments[..fixed].get(args.len()).and_then(|argument| argument.default.as_ref()) {
+        args.push(default.clone());
+    }
+    if (variadic.is_none() && fixed != args.len()) || args.len() < fixed {
         return Err(span.make_error(ParsingMessage::MissingArgument()));
     }
 
-    for i in 0..function.arguments.len() {
-        let mut arg_return_type = get_return(&args[i].types, variables, syntax).await;
-        if !arg_return_type.is_some() {
-            return Err(span.make_error(ParsingMessage::UnexpectedVoid()));
+    for i in 0..args.len() {
+        let base_field_type = match function.arguments.get(i).filter(|_| i < fixed) {
+            Some(argument) => &argument.field.field_type,
+            // Trailing arguments are checked against the variadic's element type
+            None => variadic_element(&variadic.unwrap().field.field_type),
+        };
+        // Literals passed as a trait are the first integer type implementing it, like 0 passed as a Display
+        if is_int_literal(&args[i]) && !base_field_type.dereferenced().is_integer() {
+            if let Some(integer) = literal_integer(base_field_type, syntax).await {
+                coerce_literal(&mut args[i], &integer, span)?;
+            }
         }
-        let arg_return_type = arg_return_type.as_mut().unwrap();
-        let base_field_type = &function.arguments[i].field.field_type;
+        coerce_literal(&mut args[i], base_field_type, span)?;
 
+        let mut arg_return_type = get_checked_return(&args[i].types, variables, syntax, span).await?;
+
+        if arg_return_type != *base_field_type {
+            let coerced = code_verifier.process_manager.coercions.coerce(&args[i], &arg_return_type, base_field_type);
+            if let Some(coerced) = coerced {
+                args[i] = coerced;
+                arg_return_type = match get_return(&args[i].types, variables, syntax).await {
+                    Some(found) => found,
+                    None => return Err(span.make_error(ParsingMessage::UnexpectedVoid())),
+                };
+            }
+        }
         if !arg_return_type.of_type(base_field_type, syntax.clone()).await {
-            return Err(span.make_error(ParsingMessage::MismatchedTypes(arg_return_type.clone(), base_field_type.clone())));
+            retype_literal(variables, &args[i], &arg_return_type, base_field_type)?;
+            let error = match arg_return_type.explain_mismatch(base_field_type, &syntax.lock().unwrap()) {
+                Some(reason) => ParsingMessage::TraitMismatch(arg_return_type.clone(), base_field_type.clone(), reason),
+                None => ParsingMessage::MismatchedTypes(arg_return_type.clone(), base_field_type.clone()),
+            };
+            return Err(span.make_error(error));
+        }
+    }
+
+    // Externs pass the trailing arguments directly, like C variadics, everything else gets an array.
+    if let Some(variadic) = variadic {
+        if !is_modifier(function.data.modifiers, Modifier::Extern) {
+            let trailing = args.split_off(fixed);
+            args.push(FinalizedEffects::new(
+                span.clone(),
+                FinalizedEffectType::CreateArray(Some(variadic_element(&variadic.field.field_type).clone()), trailing),
+            ));
         }
     }
 
     return Ok(());
 }
+
+/// Gets the type each value passed to a variadic argument must be
+fn variadic_element(types: &FinalizedTypes) -> &FinalizedTypes {
+    return match types {
+        FinalizedTypes::Reference(inner) => variadic_element(inner),
+        FinalizedTypes::Array(inner, _) => inner,
+        _ => types,
+    };
+}
@@ -1,17 +1,35 @@
+use async_recursion::async_recursion;
 use data::tokens::Span;
 use std::mem;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::operation_util::OperationGetter;
-use syntax::program::code::{EffectType, Effects, FinalizedEffects};
-use syntax::program::r#struct::StructData;
-use syntax::{Attribute, SimpleVariableManager};
+use syntax::program::code::{EffectType, Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::r#struct::{StructData, I64, U64};
+use syntax::program::syntax::Syntax;
+use syntax::program::types::FinalizedTypes;
+use syntax::{is_modifier, Attribute, Modifier, SimpleVariableManager};
 
-use crate::check_code::verify_effect;
-use crate::CodeVerifier;
+use crate::check_code::{store, verify_effect};
+use crate::{get_return, CodeVerifier};
+
+/// The built-in arithmetic folded into a literal when both values are literals
+const FOLDED: [&str; 4] = ["math::Add", "math::Subtract", "math::Multiply", "math::Divide"];
+
+/// A literal value of folded arithmetic
+#[derive(Clone, Copy)]
+enum Literal {
+    /// An i64
+    Signed(i64),
+    /// A u64
+    Unsigned(u64),
+    /// An f64
+    Float(f64),
+}
 
 /// Checks if an operator call is valid
+#[async_recursion]
 pub async fn check_operator(
     code_verifier: &mut CodeVerifier<'_>,
     variables: &mut SimpleVariableManager,
@@ -26,15 +44,8 @@ pub async fn check_operator(
         unreachable!()
     }
 
-    let error = effect.span.make_error(ParsingMessage::UnknownOperation());
-    // Check if it's two operations that should be combined, like a list ([])
-    let outer_operation = combine_operation(&operation, &mut values, code_verifier, &effect.span).await?;
-
-    let operation = if let Some(found) = outer_operation {
-        found
-    } else {
-        OperationGetter { syntax: code_verifier.syntax.clone(), operation: vec![operation], error }.await?
-    };
+    // Reordering the operations by priority can make one that was inside the effect the outer one, so its span is used
+    let (operation, span) = resolve_operation(code_verifier, operation, &mut values, &effect.span).await?;
 
     if Attribute::find_attribute("operation", &operation.attributes).unwrap().as_string_attribute().unwrap().contains("{+}")
     {
@@ -53,15 +64,202 @@ pub async fn check_operator(
         calling = Box::new(Effects::new(Span::default(), EffectType::NOP));
     }
 
-    return verify_effect(
+    let untyped = is_untyped(&calling) && values.iter().all(is_untyped);
+    let indexing =
+        Attribute::find_attribute("operation", &operation.attributes).unwrap().as_string_attribute().unwrap() == "{}[{}]";
+    let output = verify_effect(
         code_verifier,
         variables,
         Effects::new(
-            effect.span.clone(),
-            EffectType::ImplementationCall(calling, operation.name.clone(), String::default(), values, None),
+            span.clone(),
+            EffectType::ImplementationCall(calling, operation.name.clone(), String::default(), values, None, vec![]),
         ),
     )
-    .await;
+    .await?;
+
+    if indexing {
+        let in_bounds = check_index(&output, variables, &code_verifier.syntax, &span).await?;
+        return Ok(check_bounds(code_verifier, output, in_bounds));
+    }
+    return fold_constant(output, &operation.name, untyped, &span);
+}
+
+/// Folds built-in arithmetic on two literals into a literal of the result, erroring if it divides by a literal zero.
+/// Math that overflows isn't folded, so it still wraps or fails its overflow check when it runs.
+fn fold_constant(
+    output: FinalizedEffects,
+    operation: &str,
+    untyped: bool,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    if !FOLDED.contains(&operation) {
+        return Ok(output);
+    }
+    // #[checked_math] wraps the call in an overflow check
+    let call = match &output.types {
+        FinalizedEffectType::OverflowCheck(inner) => &inner.types,
+        other => other,
+    };
+    let arguments = match call {
+        FinalizedEffectType::MethodCall(_, function, arguments, _)
+            if is_modifier(function.data.modifiers, Modifier::Internal) && arguments.len() == 2 =>
+        {
+            arguments
+        }
+        _ => return Ok(output),
+    };
+    let (first, second) = match (literal(&arguments[0]), literal(&arguments[1])) {
+        (Some(first), Some(second)) => (first, second),
+        _ => return Ok(output),
+    };
+
+    let divisor_zero = match second {
+        Literal::Signed(value) => value == 0,
+        Literal::Unsigned(value) => value == 0,
+        Literal::Float(value) => value == 0.0,
+    };
+    if operation == "math::Divide" && divisor_zero {
+        return Err(span.make_error(ParsingMessage::DivisionByZero()));
+    }
+
+    let folded = match (first, second) {
+        // Integer literals only get their type from their context after this, so the result has to keep waiting for it
+        (Literal::Signed(first), Literal::Signed(second)) => fold_signed(operation, first, second).map(|value| {
+            if untyped {
+                FinalizedEffectType::Int(value)
+            } else {
+                FinalizedEffectType::TypedInt(value as u64, FinalizedTypes::Struct(I64.clone()))
+            }
+        }),
+        (Literal::Unsigned(first), Literal::Unsigned(second)) => match operation {
+            "math::Add" => first.checked_add(second),
+            "math::Subtract" => first.checked_sub(second),
+            "math::Multiply" => first.checked_mul(second),
+            _ => first.checked_div(second),
+        }
+        .map(|value| FinalizedEffectType::TypedInt(value, FinalizedTypes::Struct(U64.clone()))),
+        (Literal::Float(first), Literal::Float(second)) => Some(FinalizedEffectType::Float(match operation {
+            "math::Add" => first + second,
+            "math::Subtract" => first - second,
+            "math::Multiply" => first * second,
+            _ => first / second,
+        })),
+        _ => None,
+    };
+    return Ok(match folded {
+        Some(folded) => FinalizedEffects::new(output.span.clone(), store(folded)),
+        None => output,
+    });
+}
+
+/// Checks if the value is an integer literal without a suffix, or arithmetic on only those
+fn is_untyped(effect: &Effects) -> bool {
+    return match &effect.types {
+        EffectType::Int(_) => true,
+        EffectType::Paren(inner) => is_untyped(inner),
+        EffectType::Operation(_, values) => values.iter().all(is_untyped),
+        _ => false,
+    };
+}
+
+/// Does the operation on two i64s, or returns None if it overflows
+fn fold_signed(operation: &str, first: i64, second: i64) -> Option<i64> {
+    return match operation {
+        "math::Add" => first.checked_add(second),
+        "math::Subtract" => first.checked_sub(second),
+        "math::Multiply" => first.checked_mul(second),
+        _ => first.checked_div(second),
+    };
+}
+
+/// Gets the value of an i64, u64, or f64 literal
+fn literal(effect: &FinalizedEffects) -> Option<Literal> {
+    return match &effect.types {
+        FinalizedEffectType::Int(value) => Some(Literal::Signed(*value)),
+        FinalizedEffectType::TypedInt(value, types) if *types == FinalizedTypes::Struct(I64.clone()) => {
+            Some(Literal::Signed(*value as i64))
+        }
+        FinalizedEffectType::TypedInt(value, types) if *types == FinalizedTypes::Struct(U64.clone()) => {
+            Some(Literal::Unsigned(*value))
+        }
+        FinalizedEffectType::Float(value) => Some(Literal::Float(*value)),
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => literal(inner),
+        _ => None,
+    };
+}
+
+/// Gets the operation's trait, first combining it with any operation in its values by their priority,
+/// along with the span of the operation that ends up outermost
+pub async fn resolve_operation(
+    code_verifier: &mut CodeVerifier<'_>,
+    operation: String,
+    values: &mut Vec<Effects>,
+    span: &Span,
+) -> Result<(Arc<StructData>, Span), ParsingError> {
+    let error = span.make_error(ParsingMessage::UnknownOperation());
+    // Check if it's two operations that should be combined, like a list ([])
+    let outer_operation = combine_operation(&operation, values, code_verifier, span).await?;
+
+    return match outer_operation {
+        Some(found) => Ok(found),
+        None => Ok((
+            OperationGetter { syntax: code_verifier.syntax.clone(), operation: vec![operation], error }.await?,
+            span.clone(),
+        )),
+    };
+}
+
+/// Checks that constant indices into fixed length arrays are in bounds, returning if the index is known to be
+async fn check_index(
+    effect: &FinalizedEffects,
+    variables: &SimpleVariableManager,
+    syntax: &Arc<Mutex<Syntax>>,
+    span: &Span,
+) -> Result<bool, ParsingError> {
+    let arguments = match &effect.types {
+        FinalizedEffectType::MethodCall(_, _, arguments, _)
+        | FinalizedEffectType::GenericMethodCall(_, _, arguments)
+        | FinalizedEffectType::VirtualCall(_, _, arguments, _) => arguments,
+        _ => return Ok(false),
+    };
+    let (Some(array), Some(index)) = (arguments.get(0), arguments.get(1)) else {
+        return Ok(false);
+    };
+
+    let array = get_return(&array.types, variables, syntax).await;
+    if let Some(length) = array.and_then(|array| array.array_length()) {
+        if let Some(index) = constant_index(index) {
+            if index >= length {
+                return Err(span.make_error(ParsingMessage::IndexOutOfBounds(index, length)));
+            }
+            return Ok(true);
+        }
+    }
+    return Ok(false);
+}
+
+/// Wraps indices into arrays in a bounds check, unless they're already known to be in bounds or checks are turned off
+fn check_bounds(code_verifier: &CodeVerifier<'_>, effect: FinalizedEffects, in_bounds: bool) -> FinalizedEffects {
+    let array_index = match &effect.types {
+        FinalizedEffectType::MethodCall(_, function, _, _) => {
+            is_modifier(function.data.modifiers, Modifier::Internal) && function.data.name.starts_with("array::Index")
+        }
+        _ => false,
+    };
+    if !code_verifier.process_manager.bounds_checks || !array_index || in_bounds {
+        return effect;
+    }
+    return FinalizedEffects::new(effect.span.clone(), FinalizedEffectType::BoundsCheck(Box::new(effect)));
+}
+
+/// Gets the value of the index if it's a constant
+fn constant_index(effect: &FinalizedEffects) -> Option<u64> {
+    return match &effect.types {
+        FinalizedEffectType::Int(index) => Some(*index as u64),
+        FinalizedEffectType::TypedInt(index, _) => Some(*index),
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => constant_index(inner),
+        _ => None,
+    };
 }
 
 /// Checks if two operations can be combined
@@ -70,7 +268,7 @@ async fn combine_operation(
     values: &mut Vec<Effects>,
     code_verifier: &mut CodeVerifier<'_>,
     span: &Span,
-) -> Result<Option<Arc<StructData>>, ParsingError> {
+) -> Result<Option<(Arc<StructData>, Span)>, ParsingError> {
     let error = span.make_error(ParsingMessage::UnknownOperation());
 
     if values.len() > 0 {
@@ -127,7 +325,7 @@ async fn combine_operation(
                                 values.push(effect);
                             }
                         }
-                        Ok(Some(found))
+                        Ok(Some((found, span.clone())))
                     } else {
                         let new_inner = "{}".to_string() + &combined[new_operation.replace("{+}", "{}").len()..];
 
@@ -207,7 +405,7 @@ pub fn operator_pratt_parsing(
     inner_array: bool,
     token: Span,
     inner_token: Span,
-) -> Option<Arc<StructData>> {
+) -> Option<(Arc<StructData>, Span)> {
     let op_priority = Attribute::find_attribute("priority", &found.attributes)
         .map(|inner| inner.as_int_attribute().unwrap_or(0))
         .unwrap_or(0);
@@ -233,9 +431,9 @@ pub fn operator_pratt_parsing(
         inner_effects.insert(0, Effects::new(token, EffectType::Operation(operation, temp)));
         *values = inner_effects;
 
-        Some(inner_data.clone())
+        Some((inner_data.clone(), inner_token))
     } else {
         values.push(Effects::new(inner_token, EffectType::Operation(inner_operator, inner_effects)));
-        Some(found.clone())
+        Some((found.clone(), token))
     };
 }
@@ -0,0 +1,327 @@
+use std::sync::{Arc, Mutex};
+
+use async_recursion::async_recursion;
+use data::tokens::Span;
+use indexmap::IndexMap;
+use syntax::async_util::UnparsedType;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{ConstExpression, EffectType, Effects, FinalizedEffectType, FinalizedEffects};
+use syntax::program::r#struct::{BOOL, U64};
+use syntax::program::syntax::Syntax;
+use syntax::program::types::FinalizedTypes;
+use syntax::{is_modifier, Modifier};
+
+use crate::check_code::find_constant;
+use crate::check_constant::verify_constant;
+use crate::check_method_call::finalize_explicit_generics;
+use crate::check_operator::resolve_operation;
+use crate::degeneric::degeneric_type;
+use crate::CodeVerifier;
+
+/// Intrinsics asking about how a type is laid out, like size_of<T>()
+pub const LAYOUT_INTRINSICS: [&str; 2] = ["size_of", "align_of"];
+/// Operators comparing two values of the same type
+const COMPARISONS: [&str; 2] = ["math::Equal", "math::NotEqual"];
+/// Operators comparing two integers
+const ORDERINGS: [&str; 4] = ["math::GreaterThan", "math::LessThan", "math::GreaterOrEqual", "math::LessOrEqual"];
+/// Operators combining two bools
+const LOGIC: [&str; 3] = ["math::And", "math::Or", "math::XOR"];
+/// Operators combining two integers
+const ARITHMETIC: [&str; 10] = [
+    "math::Add",
+    "math::Subtract",
+    "math::Multiply",
+    "math::Divide",
+    "math::Remainder",
+    "math::BitAnd",
+    "math::BitOr",
+    "math::BitXOR",
+    "math::LeftShift",
+    "math::RightShift",
+];
+
+/// Checks a static assert, erroring if its condition is false.
+/// Returns the condition if it depends on generics, so it can be checked once the function is degenericed.
+#[async_recursion]
+pub async fn verify_static_assert(
+    code_verifier: &mut CodeVerifier<'_>,
+    condition: Effects,
+    span: &Span,
+) -> Result<Option<FinalizedEffects>, ParsingError> {
+    let (condition, is_bool) = const_expression(code_verifier, condition).await?;
+    if !is_bool {
+        return Err(span.make_error(ParsingMessage::MismatchedTypes(
+            FinalizedTypes::Struct(U64.clone()),
+            FinalizedTypes::Struct(BOOL.clone()),
+        )));
+    }
+
+    return match evaluate(&condition) {
+        Some(0) => Err(span.make_error(ParsingMessage::StaticAssertFailed())),
+        Some(_) => Ok(None),
+        None => Ok(Some(FinalizedEffects::new(span.clone(), FinalizedEffectType::StaticAssert(condition)))),
+    };
+}
+
+/// Checks a size_of<T>() or align_of<T>() call, which is a u64 literal unless the type depends on a generic.
+/// Those are kept as a ConstValue, which is replaced by the value once the function is degenericed.
+/// Structs are packed by the compiler, an 8 byte type id followed by each field with no padding between them,
+/// so a struct's size is 8 plus the size of its fields and its alignment is always 1.
+pub async fn verify_layout_intrinsic(
+    code_verifier: &CodeVerifier<'_>,
+    name: &str,
+    generics: Vec<UnparsedType>,
+    span: &Span,
+) -> Result<FinalizedEffects, ParsingError> {
+    let types = finalize_explicit_generics(code_verifier, generics, span).await?;
+    let value = layout_query(name, types.into_iter().next().unwrap());
+    return Ok(FinalizedEffects::new(
+        span.clone(),
+        match evaluate(&value) {
+            Some(found) => FinalizedEffectType::TypedInt(found, FinalizedTypes::Struct(U64.clone())),
+            None => FinalizedEffectType::ConstValue(value),
+        },
+    ));
+}
+
+/// Gets the value of a ConstValue now that the generics are known
+pub async fn degeneric_const_value(
+    value: &ConstExpression,
+    generics: &IndexMap<String, FinalizedTypes>,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> Option<u64> {
+    let mut value = value.clone();
+    degeneric_condition(&mut value, generics, syntax).await;
+    return evaluate(&value);
+}
+
+/// Checks a static assert left in a generic function now that the generics are known
+pub async fn check_static_assert(
+    condition: &ConstExpression,
+    generics: &IndexMap<String, FinalizedTypes>,
+    syntax: &Arc<Mutex<Syntax>>,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    let mut condition = condition.clone();
+    degeneric_condition(&mut condition, generics, syntax).await;
+    return match evaluate(&condition) {
+        Some(0) => Err(span.make_error(ParsingMessage::StaticAssertFailed())),
+        _ => Ok(()),
+    };
+}
+
+/// Converts the condition to a constant expression, along with if it's a bool instead of an integer
+#[async_recursion]
+async fn const_expression(
+    code_verifier: &mut CodeVerifier<'_>,
+    effect: Effects,
+) -> Result<(ConstExpression, bool), ParsingError> {
+    let error = effect.span.make_error(ParsingMessage::NonConstantAssert());
+    return match effect.types {
+        EffectType::Paren(inner) => const_expression(code_verifier, *inner).await,
+        EffectType::Int(value) => Ok((ConstExpression::Int(value as u64), false)),
+        EffectType::UInt(value) => Ok((ConstExpression::Int(value), false)),
+        EffectType::TypedInt(value, _) => Ok((ConstExpression::Int(value), false)),
+        EffectType::Bool(value) => Ok((ConstExpression::Bool(value), true)),
+        EffectType::LoadVariable(name) => {
+            if let Some(constant) = find_constant(code_verifier, &name).await {
+                let resolver = code_verifier.resolver.boxed_clone();
+                let syntax = code_verifier.syntax.clone();
+                let using = code_verifier.constants.clone();
+                let constant =
+                    verify_constant(code_verifier.process_manager, &constant, resolver, &syntax, code_verifier.depth, using)
+                        .await?;
+                return constant_value(&constant.value).ok_or(error);
+            }
+            // Only const generics have a value, other generics are types
+            let bounds = code_verifier.resolver.generic(&name).unwrap_or_default();
+            if !bounds.iter().any(|bound| matches!(bound, UnparsedType::Const(_))) {
+                return Err(error);
+            }
+            let types = finalize_explicit_generics(code_verifier, vec![UnparsedType::Basic(name)], &effect.span).await?;
+            Ok((ConstExpression::Const(types.into_iter().next().unwrap()), false))
+        }
+        EffectType::MethodCall(None, name, arguments, _, generics)
+            if LAYOUT_INTRINSICS.contains(&name.as_str()) && arguments.is_empty() && generics.len() == 1 =>
+        {
+            let types = finalize_explicit_generics(code_verifier, generics, &effect.span).await?;
+            Ok((layout_query(&name, types.into_iter().next().unwrap()), false))
+        }
+        EffectType::Operation(operation, mut values) => {
+            let (operation, span) = resolve_operation(code_verifier, operation, &mut values, &effect.span).await?;
+            let mut arguments = Vec::default();
+            let mut bools = Vec::default();
+            for value in values {
+                let (argument, is_bool) = const_expression(code_verifier, value).await?;
+                arguments.push(argument);
+                bools.push(is_bool);
+            }
+
+            let name = operation.name.as_str();
+            let returns_bool = if name == "math::Not" && bools == [true] {
+                true
+            } else if COMPARISONS.contains(&name) && bools.len() == 2 {
+                check_operands(&bools, bools[0], &span)?;
+                true
+            } else if ORDERINGS.contains(&name) && bools.len() == 2 {
+                check_operands(&bools, false, &span)?;
+                true
+            } else if LOGIC.contains(&name) && bools.len() == 2 {
+                check_operands(&bools, true, &span)?;
+                true
+            } else if ARITHMETIC.contains(&name) && bools.len() == 2 {
+                check_operands(&bools, false, &span)?;
+                false
+            } else {
+                return Err(error);
+            };
+            Ok((ConstExpression::Operation(operation.name.clone(), arguments), returns_bool))
+        }
+        _ => Err(error),
+    };
+}
+
+/// Gets the query for the layout intrinsic with that name
+fn layout_query(name: &str, types: FinalizedTypes) -> ConstExpression {
+    return match name {
+        "align_of" => ConstExpression::AlignOf(types),
+        _ => ConstExpression::SizeOf(types),
+    };
+}
+
+/// Gets the value of a constant, which is only usable if it's an integer or bool
+fn constant_value(value: &FinalizedEffects) -> Option<(ConstExpression, bool)> {
+    return match &value.types {
+        FinalizedEffectType::Int(value) => Some((ConstExpression::Int(*value as u64), false)),
+        FinalizedEffectType::TypedInt(value, _) => Some((ConstExpression::Int(*value), false)),
+        FinalizedEffectType::Bool(value) => Some((ConstExpression::Bool(*value), true)),
+        FinalizedEffectType::HeapStore(inner) | FinalizedEffectType::StackStore(inner) => constant_value(inner),
+        _ => None,
+    };
+}
+
+/// Errors if any operand isn't of the expected type
+fn check_operands(bools: &[bool], expected: bool, span: &Span) -> Result<(), ParsingError> {
+    let types = |is_bool: bool| FinalizedTypes::Struct(if is_bool { BOOL.clone() } else { U64.clone() });
+    return match bools.iter().find(|is_bool| **is_bool != expected) {
+        Some(found) => Err(span.make_error(ParsingMessage::MismatchedTypes(types(*found), types(expected)))),
+        None => Ok(()),
+    };
+}
+
+/// Replaces the generics in the condition's types with their values
+#[async_recursion]
+async fn degeneric_condition(
+    condition: &mut ConstExpression,
+    generics: &IndexMap<String, FinalizedTypes>,
+    syntax: &Arc<Mutex<Syntax>>,
+) {
+    match condition {
+        ConstExpression::Const(types) => degeneric_type(types, generics, syntax).await,
+        ConstExpression::SizeOf(types) | ConstExpression::AlignOf(types) => {
+            degeneric_type(types, generics, syntax).await;
+            // Generics are bound to the reference a struct is passed as, but the layout asked about is the struct's
+            if let FinalizedTypes::Reference(inner) = types {
+                *types = *inner.clone();
+            }
+        }
+        ConstExpression::Operation(_, arguments) => {
+            for argument in arguments {
+                degeneric_condition(argument, generics, syntax).await;
+            }
+        }
+        ConstExpression::Int(_) | ConstExpression::Bool(_) => {}
+    }
+}
+
+/// Evaluates the condition, with bools as 0 or 1, or returns None if it depends on a generic that isn't known yet.
+/// Dividing by zero evaluates to 0, which fails the assert.
+fn evaluate(condition: &ConstExpression) -> Option<u64> {
+    return match condition {
+        ConstExpression::Int(value) => Some(*value),
+        ConstExpression::Bool(value) => Some(*value as u64),
+        ConstExpression::Const(FinalizedTypes::Const(value)) => Some(*value),
+        ConstExpression::Const(_) => None,
+        ConstExpression::SizeOf(types) => size_of(types),
+        ConstExpression::AlignOf(types) => align_of(types),
+        ConstExpression::Operation(name, arguments) => {
+            let mut values = Vec::default();
+            for argument in arguments {
+                values.push(evaluate(argument)?);
+            }
+            let (first, second) = (values[0], values.get(1).copied().unwrap_or_default());
+            Some(match name.as_str() {
+                "math::Not" => (first == 0) as u64,
+                "math::Equal" => (first == second) as u64,
+                "math::NotEqual" => (first != second) as u64,
+                "math::GreaterThan" => (first > second) as u64,
+                "math::LessThan" => (first < second) as u64,
+                "math::GreaterOrEqual" => (first >= second) as u64,
+                "math::LessOrEqual" => (first <= second) as u64,
+                "math::And" => (first != 0 && second != 0) as u64,
+                "math::Or" => (first != 0 || second != 0) as u64,
+                "math::XOR" => ((first != 0) != (second != 0)) as u64,
+                "math::Add" => first.wrapping_add(second),
+                "math::Subtract" => first.wrapping_sub(second),
+                "math::Multiply" => first.wrapping_mul(second),
+                "math::Divide" => first.checked_div(second).unwrap_or_default(),
+                "math::Remainder" => first.checked_rem(second).unwrap_or_default(),
+                "math::BitAnd" => first & second,
+                "math::BitOr" => first | second,
+                "math::BitXOR" => first ^ second,
+                "math::LeftShift" => first.wrapping_shl(second as u32),
+                "math::RightShift" => first.wrapping_shr(second as u32),
+                _ => unreachable!(),
+            })
+        }
+    };
+}
+
+/// Gets the size of the type in bytes, matching how the compiler lays it out, or None if it's still generic.
+/// Structs start with their 8 byte type id, and their fields are packed.
+fn size_of(types: &FinalizedTypes) -> Option<u64> {
+    return match types {
+        FinalizedTypes::Struct(found) => Some(match found.data.name.as_str() {
+            "i64" | "u64" | "f64" | "str" => 8,
+            "i32" | "u32" | "f32" => 4,
+            "i16" | "u16" => 2,
+            "i8" | "u8" | "bool" | "char" => 1,
+            // Traits are a pointer to the value and a pointer to its vtable
+            _ if is_modifier(found.data.modifiers, Modifier::Trait) => 16,
+            _ => {
+                let mut size = 8;
+                for field in &found.fields {
+                    size += size_of(&field.field.field_type)?;
+                }
+                size
+            }
+        }),
+        FinalizedTypes::Reference(_) | FinalizedTypes::Array(_, _) => Some(8),
+        // Closures are a pointer to their function and a pointer to their captured variables
+        FinalizedTypes::Function(_, _) => Some(16),
+        FinalizedTypes::Generic(_, _)
+        | FinalizedTypes::GenericType(_, _)
+        | FinalizedTypes::Const(_)
+        | FinalizedTypes::ConstType(_) => None,
+    };
+}
+
+/// Gets the alignment of the type in bytes, matching how the compiler lays it out like size_of, or None if it's still generic.
+/// Struct fields are packed, so structs can start at any byte.
+fn align_of(types: &FinalizedTypes) -> Option<u64> {
+    return match types {
+        FinalizedTypes::Struct(found) => Some(match found.data.name.as_str() {
+            "i64" | "u64" | "f64" | "str" => 8,
+            "i32" | "u32" | "f32" => 4,
+            "i16" | "u16" => 2,
+            "i8" | "u8" | "bool" | "char" => 1,
+            _ if is_modifier(found.data.modifiers, Modifier::Trait) => 8,
+            _ => 1,
+        }),
+        FinalizedTypes::Reference(_) | FinalizedTypes::Array(_, _) | FinalizedTypes::Function(_, _) => Some(8),
+        FinalizedTypes::Generic(_, _)
+        | FinalizedTypes::GenericType(_, _)
+        | FinalizedTypes::Const(_)
+        | FinalizedTypes::ConstType(_) => None,
+    };
+}
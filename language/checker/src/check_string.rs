@@ -0,0 +1,45 @@
+use data::tokens::Span;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::{FinalizedEffectType, FinalizedEffects};
+
+/// Checks a string literal by decoding its escapes into the literal's bytes
+pub fn verify_string(literal: &str, span: &Span) -> Result<FinalizedEffects, ParsingError> {
+    return match decode_escapes(literal) {
+        Ok(bytes) => Ok(FinalizedEffects::new(span.clone(), FinalizedEffectType::StringLiteral(bytes))),
+        Err((escape, offset)) => Err(span.make_error(ParsingMessage::InvalidEscape(escape, offset))),
+    };
+}
+
+/// Decodes the escapes in a literal's text, or returns the invalid escape and its byte offset in the literal.
+/// Hex escapes are limited to ASCII so the bytes are always valid UTF-8.
+pub fn decode_escapes(literal: &str) -> Result<Vec<u8>, (String, usize)> {
+    let mut bytes = Vec::with_capacity(literal.len());
+    let mut characters = literal.char_indices();
+    while let Some((offset, character)) = characters.next() {
+        if character != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        let escape = match characters.next() {
+            Some((_, escape)) => escape,
+            None => return Err((String::default(), offset)),
+        };
+        bytes.push(match escape {
+            'n' => b'\n',
+            't' => b'\t',
+            'r' => b'\r',
+            '\\' | '\'' | '"' | '{' | '}' => escape as u8,
+            'x' => {
+                let digits = characters.by_ref().take(2).map(|(_, digit)| digit).collect::<String>();
+                let valid = digits.len() == 2 && digits.chars().all(|digit| digit.is_ascii_hexdigit());
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(value) if valid && value.is_ascii() => value,
+                    _ => return Err((format!("x{}", digits), offset)),
+                }
+            }
+            _ => return Err((escape.to_string(), offset)),
+        });
+    }
+    return Ok(bytes);
+}
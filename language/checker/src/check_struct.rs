@@ -1,12 +1,16 @@
-use crate::finalize_generics;
 use crate::output::TypesChecker;
+use crate::{finalize_generics, warn_unused_generics};
+use async_recursion::async_recursion;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::Mutex;
-use syntax::errors::ParsingError;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{FinalizedField, FinalizedMemberField};
-use syntax::program::r#struct::{FinalizedStruct, UnfinalizedStruct};
+use syntax::program::r#struct::{EnumVariant, FinalizedStruct, StructData, StructStub, UnfinalizedStruct};
 use syntax::program::syntax::Syntax;
-use syntax::program::types::FinalizedTypes;
+use syntax::program::types::{FinalizedTypes, Types};
+use syntax::{is_modifier, Modifier};
 
 /// Verifies if a struct is valid
 pub async fn verify_struct(
@@ -15,25 +19,309 @@ pub async fn verify_struct(
     syntax: &Arc<Mutex<Syntax>>,
     include_refs: bool,
 ) -> Result<FinalizedStruct, ParsingError> {
-    let mut finalized_fields = Vec::default();
+    let mut fields = Vec::default();
     for field in structure.fields {
-        let field = field.await?;
-        let mut field_type = field.field.field_type.finalize(syntax.clone()).await;
-        if include_refs {
+        fields.push(field.await?);
+    }
+
+    // Finalizing a field waits for its type to be finalized, so a struct containing itself would never finish
+    let stubs = {
+        let mut contained = Vec::default();
+        let mut mentioned = Vec::default();
+        for field in &fields {
+            if can_contain(&field.field.field_type) {
+                contained.push((field.field.name.clone(), field.field.field_type.clone()));
+            }
+            mentioned_structs(&field.field.field_type, &mut mentioned);
+        }
+        let generics = structure.generics.keys().cloned().collect();
+        let mut locked = syntax.lock().unwrap();
+        locked.contained_structs.insert(structure.data.name.clone(), (generics, contained));
+        check_cycle(&mut locked, &structure.data)?;
+
+        // Structs using this one behind a reference, an array or a generic get a stub for it instead of waiting
+        let names = mentioned.iter().map(|data| data.name.clone()).collect();
+        locked.mentioned_structs.insert(structure.data.name.clone(), names);
+        let mut stubs = HashMap::default();
+        for data in mentioned {
+            if stubs.contains_key(&data.name) || !reaches(&locked.mentioned_structs, &data.name, &structure.data.name) {
+                continue;
+            }
+            let stub = match locked.structures.data.get(&data) {
+                Some(finished) => finished.clone(),
+                None => locked
+                    .struct_stubs
+                    .entry(data.name.clone())
+                    .or_insert_with(|| Arc::new(FinalizedStruct::stub(data.clone())))
+                    .clone(),
+            };
+            stubs.insert(data.name.clone(), stub);
+        }
+        stubs
+    };
+
+    let mut finalized_fields = Vec::default();
+    for field in fields {
+        let mut field_type = finalize_field(&field.field.field_type, syntax, &stubs).await;
+        // Reference fields are already behind a reference
+        if include_refs && !matches!(field_type, FinalizedTypes::Reference(_)) {
             field_type = FinalizedTypes::Reference(Box::new(field_type));
         }
         finalized_fields.push(FinalizedMemberField {
             modifiers: field.modifiers,
             attributes: field.attributes,
             field: FinalizedField { field_type, name: field.field.name },
+            default: None,
         })
     }
+    // Generic structs in the fields are finished now, so cycles through their fields can be found
+    check_cycle(&mut syntax.lock().unwrap(), &structure.data)?;
+
+    let mut traits = Vec::default();
+    for supertrait in structure.traits {
+        traits.push(supertrait.await?.finalize(syntax.clone()).await);
+    }
+
+    let mut constants = IndexMap::default();
+    for (name, types) in structure.constants {
+        constants.insert(name, types.await?.finalize(syntax.clone()).await);
+    }
+
+    let generics = finalize_generics(syntax, structure.generics).await?;
+    // Traits use their generics in their methods instead of fields
+    if !is_modifier(structure.data.modifiers, Modifier::Trait) {
+        let types = finalized_fields.iter().map(|field| &field.field.field_type).collect();
+        warn_unused_generics(syntax, &structure.generic_spans, &generics, types);
+    }
 
     let output = FinalizedStruct {
-        generics: finalize_generics(syntax, structure.generics).await?,
+        generics,
+        arguments: Vec::default(),
         fields: finalized_fields,
+        traits,
+        discriminants: Vec::default(),
+        constants,
         data: structure.data,
+        stub: StructStub::default(),
     };
 
     return Ok(output);
 }
+
+/// Whether a field of the type can contain a struct without a reference, which is a struct, a generic or an instance
+/// of a generic struct
+fn can_contain(types: &Types) -> bool {
+    return matches!(types, Types::Struct(_) | Types::Generic(_, _) | Types::GenericType(_, _));
+}
+
+/// Adds every struct named in the type, including ones behind a reference, in an array or in a generic's arguments
+fn mentioned_structs(types: &Types, output: &mut Vec<Arc<StructData>>) {
+    match types {
+        Types::Struct(data) => output.push(data.clone()),
+        Types::GenericType(base, arguments) | Types::Function(arguments, Some(base)) => {
+            mentioned_structs(base, output);
+            for argument in arguments {
+                mentioned_structs(argument, output);
+            }
+        }
+        Types::Function(arguments, None) => {
+            for argument in arguments {
+                mentioned_structs(argument, output);
+            }
+        }
+        Types::Reference(inner) | Types::Array(inner, _) | Types::ConstType(inner) => mentioned_structs(inner, output),
+        Types::Generic(_, bounds) => {
+            for bound in bounds {
+                mentioned_structs(bound, output);
+            }
+        }
+        Types::Const(_) => {}
+    }
+}
+
+/// Whether the struct names the target in its fields, directly or through other structs
+fn reaches(mentioned: &HashMap<String, Vec<String>>, start: &String, target: &String) -> bool {
+    let mut visited = HashSet::new();
+    let mut next = vec![start];
+    while let Some(current) = next.pop() {
+        if current == target {
+            return true;
+        }
+        if visited.insert(current) {
+            next.extend(mentioned.get(current).into_iter().flatten());
+        }
+    }
+    return false;
+}
+
+/// Finalizes the field's type, using the stubs for the structs in them instead of waiting for those structs
+#[async_recursion]
+async fn finalize_field(
+    types: &Types,
+    syntax: &Arc<Mutex<Syntax>>,
+    stubs: &HashMap<String, Arc<FinalizedStruct>>,
+) -> FinalizedTypes {
+    return match types {
+        Types::Struct(data) => match stubs.get(&data.name) {
+            Some(stub) => FinalizedTypes::Struct(stub.clone()),
+            None => types.finalize(syntax.clone()).await,
+        },
+        Types::Reference(inner) => FinalizedTypes::Reference(Box::new(finalize_field(inner, syntax, stubs).await)),
+        Types::Array(inner, length) => FinalizedTypes::Array(
+            Box::new(finalize_field(inner, syntax, stubs).await),
+            match length {
+                Some(length) => Some(Box::new(length.finalize(syntax.clone()).await)),
+                None => None,
+            },
+        ),
+        Types::GenericType(base, arguments) => {
+            let mut finalized = Vec::default();
+            for argument in arguments {
+                finalized.push(finalize_field(argument, syntax, stubs).await);
+            }
+            FinalizedTypes::GenericType(Box::new(finalize_field(base, syntax, stubs).await), finalized)
+        }
+        _ => types.finalize(syntax.clone()).await,
+    };
+}
+
+/// The struct a type contains without a reference and the generics of that struct, with generics replaced by the
+/// types they are in the struct containing it
+fn contained_struct(
+    types: &Types,
+    generics: &HashMap<String, Types>,
+    contained: &HashMap<String, (Vec<String>, Vec<(String, Types)>)>,
+) -> Option<(String, HashMap<String, Types>)> {
+    return match types {
+        Types::Struct(data) => Some((data.name.clone(), HashMap::default())),
+        Types::Generic(name, _) => match generics.get(name) {
+            Some(found) => contained_struct(found, &HashMap::default(), contained),
+            None => None,
+        },
+        Types::GenericType(base, arguments) => {
+            let (name, _) = contained_struct(base, generics, contained)?;
+            let names = contained.get(&name).map(|(names, _)| names.clone()).unwrap_or_default();
+            let arguments = arguments.iter().map(|argument| replace_generics(argument, generics));
+            Some((name, names.into_iter().zip(arguments).collect()))
+        }
+        _ => None,
+    };
+}
+
+/// Replaces the generics in the type with the types they are
+fn replace_generics(types: &Types, generics: &HashMap<String, Types>) -> Types {
+    return match types {
+        Types::Generic(name, _) => generics.get(name).cloned().unwrap_or_else(|| types.clone()),
+        Types::GenericType(base, arguments) => {
+            Types::GenericType(base.clone(), arguments.iter().map(|argument| replace_generics(argument, generics)).collect())
+        }
+        Types::Reference(inner) => Types::Reference(Box::new(replace_generics(inner, generics))),
+        _ => types.clone(),
+    };
+}
+
+/// Errors if the struct contains itself without a reference
+fn check_cycle(syntax: &mut Syntax, data: &StructData) -> Result<(), ParsingError> {
+    if let Some(cycle) = find_cycle(&syntax.contained_structs, &data.name) {
+        // The struct is poisoned without any fields, so the rest of the cycle doesn't report it again
+        syntax.contained_structs.get_mut(&data.name).unwrap().1.clear();
+        return Err(data.span.make_error(ParsingMessage::InfiniteSize(data.name.clone(), cycle.join(" -> "))));
+    }
+    return Ok(());
+}
+
+/// Finds a path of fields from the struct back to itself, like First.second -> Second.first
+fn find_cycle(contained: &HashMap<String, (Vec<String>, Vec<(String, Types)>)>, start: &String) -> Option<Vec<String>> {
+    let mut path = Vec::default();
+    let mut visited = HashSet::new();
+    return if cycle_from(contained, start, &HashMap::default(), start, &mut path, &mut visited) {
+        Some(path)
+    } else {
+        None
+    };
+}
+
+/// Searches for the target from the struct with the generics, adding each field to the path on the way
+fn cycle_from(
+    contained: &HashMap<String, (Vec<String>, Vec<(String, Types)>)>,
+    current: &String,
+    generics: &HashMap<String, Types>,
+    target: &String,
+    path: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+) -> bool {
+    if !visited.insert(current.clone()) {
+        return false;
+    }
+    let fields = match contained.get(current) {
+        Some((_, fields)) => fields,
+        None => return false,
+    };
+    for (field, types) in fields {
+        let (inner, inner_generics) = match contained_struct(types, generics, contained) {
+            Some(found) => found,
+            None => continue,
+        };
+        path.push(format!("{}.{}", current, field));
+        if inner == *target || cycle_from(contained, &inner, &inner_generics, target, path, visited) {
+            return true;
+        }
+        path.pop();
+    }
+    return false;
+}
+
+/// Verifies if an enum is valid, which is a struct storing its variant and the fields of every variant
+pub async fn verify_enum(
+    process_manager: &TypesChecker,
+    structure: UnfinalizedStruct,
+    syntax: &Arc<Mutex<Syntax>>,
+    include_refs: bool,
+) -> Result<FinalizedStruct, ParsingError> {
+    let variants = &structure.data.variants;
+    for (i, variant) in variants.iter().enumerate() {
+        if let Some(first) = variants[..i].iter().find(|other| other.name == variant.name) {
+            return Err(variant
+                .span
+                .make_error(ParsingMessage::DuplicateVariant(variant.name.clone()))
+                .with_note(first.span.make_error(ParsingMessage::FirstDeclaredHere())));
+        }
+    }
+    let discriminants = evaluate_discriminants(variants)?;
+
+    let mut output = verify_struct(process_manager, structure, syntax, include_refs).await?;
+    output.discriminants = discriminants;
+    return Ok(output);
+}
+
+/// Gets the value of each variant's discriminant. Variants without one are one more than the variant before them,
+/// starting at 0, and no two variants can have the same value.
+fn evaluate_discriminants(variants: &[EnumVariant]) -> Result<Vec<u64>, ParsingError> {
+    let mut discriminants: Vec<u64> = Vec::default();
+    let mut next = Some(0);
+    for variant in variants {
+        let value = match &variant.discriminant {
+            Some(written) => match written.parse() {
+                Ok(value) => value,
+                Err(_) => {
+                    return Err(variant
+                        .span
+                        .make_error(ParsingMessage::InvalidDiscriminant(variant.name.clone(), written.clone())))
+                }
+            },
+            None => match next {
+                Some(value) => value,
+                None => return Err(variant.span.make_error(ParsingMessage::DiscriminantOverflow(variant.name.clone()))),
+            },
+        };
+        if let Some(first) = discriminants.iter().position(|found| *found == value) {
+            return Err(variant
+                .span
+                .make_error(ParsingMessage::DuplicateDiscriminant(variant.name.clone(), value))
+                .with_note(variants[first].span.make_error(ParsingMessage::FirstDeclaredHere())));
+        }
+        discriminants.push(value);
+        next = value.checked_add(1);
+    }
+    return Ok(discriminants);
+}
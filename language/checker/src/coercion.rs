@@ -0,0 +1,30 @@
+use syntax::program::code::FinalizedEffects;
+use syntax::program::types::FinalizedTypes;
+
+/// Implicit conversions of arguments, which embedders can replace to allow coercions like numeric widening.
+/// Rules are asked about every argument that isn't exactly the type of its parameter.
+pub trait CoercionRules: Send + Sync {
+    /// Gets the effect to pass instead of an argument of the found type where the expected type is wanted.
+    /// Returns None to pass the argument as it is, which still has to be of the expected type.
+    fn coerce(
+        &self,
+        argument: &FinalizedEffects,
+        found: &FinalizedTypes,
+        expected: &FinalizedTypes,
+    ) -> Option<FinalizedEffects>;
+}
+
+/// The coercions the checker has always made. With include_refs every argument is a reference, and values are
+/// passed to them by reference implicitly because the type check ignores references, so nothing is inserted.
+pub struct DefaultCoercions;
+
+impl CoercionRules for DefaultCoercions {
+    fn coerce(
+        &self,
+        _argument: &FinalizedEffects,
+        _found: &FinalizedTypes,
+        _expected: &FinalizedTypes,
+    ) -> Option<FinalizedEffects> {
+        return None;
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::future::Future;
 use std::mem;
 use std::ops::Deref;
@@ -8,18 +8,23 @@ use std::task::{Context, Poll};
 
 use async_recursion::async_recursion;
 use data::tokens::Span;
+use indexmap::IndexMap;
 use syntax::async_util::AsyncDataGetter;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
-use syntax::program::code::{FinalizedEffectType, FinalizedEffects, FinalizedMemberField};
+use syntax::program::code::{FinalizedEffectType, FinalizedEffects, FinalizedField, FinalizedMemberField};
 use syntax::program::function::{display_parenless, CodelessFinalizedFunction, FinalizedCodeBody, FunctionData};
-use syntax::program::r#struct::{FinalizedStruct, StructData};
+use syntax::program::r#struct::{instance_name, FinalizedStruct, StructData, U64};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
-use syntax::top_element_manager::ImplWaiter;
+use syntax::top_element_manager::{FinishedImplsWaiter, ImplWaiter};
 use syntax::{ProcessManager, SimpleVariableManager};
 
+use crate::check_static_assert::{check_static_assert, degeneric_const_value};
 use crate::get_return;
 
+/// How many generic instances can be degenericed inside of each other before it's assumed to never end
+pub const MAX_INSTANTIATION_DEPTH: usize = 32;
+
 /// Flattens a type, which is the final step before compilation that gets rid of all generics in the type
 #[async_recursion]
 // skipcq: RS-R1000 Match statements have complexity calculated incorrectly
@@ -30,198 +35,449 @@ pub async fn degeneric_effect(
     variables: &mut SimpleVariableManager,
     span: &Span,
 ) -> Result<(), ParsingError> {
+    // Effects that are replaced by another effect, or only have a type, are degenericed here
     match effect {
-        FinalizedEffectType::CreateVariable(name, value, types) => {
-            *types = get_return(&value.types, variables, syntax).await.unwrap();
-            variables.variables.insert(name.clone(), types.clone());
-            degeneric_effect(&mut value.types, syntax, process_manager, variables, span).await?;
-            degeneric_type(types, process_manager.generics(), syntax).await;
-        }
-        FinalizedEffectType::CompareJump(effect, _, _) => {
-            degeneric_effect(&mut effect.types, syntax, process_manager, variables, span).await?
+        FinalizedEffectType::GenericMethodCall(function, types, arguments) => {
+            *effect =
+                degeneric_generic_method_call(function, types, arguments, syntax, process_manager, variables, span).await?;
+            return Ok(());
         }
-        FinalizedEffectType::CodeBody(body) => degeneric_code_body(body, process_manager, variables, syntax).await?,
-        FinalizedEffectType::MethodCall(calling, function, arguments, return_type) => {
-            if let Some(found) = calling {
-                degeneric_effect(&mut found.types, syntax, process_manager, variables, span).await?;
-            }
-
-            let mut before_arguments = function.arguments.clone();
-            let mut degenericing_process_manager = process_manager.cloned();
-
-            for i in 0..before_arguments.len() {
-                before_arguments[i]
-                    .field
-                    .field_type
-                    .resolve_generic(
-                        &get_return(&arguments[i].types, variables, syntax).await.unwrap(),
-                        syntax,
-                        degenericing_process_manager.mut_generics(),
-                        span.clone(),
-                    )
-                    .await?;
-            }
-
-            for field in &mut before_arguments {
-                degeneric_type_no_generic_types(
-                    &mut field.field.field_type,
-                    degenericing_process_manager.generics(),
-                    syntax,
-                )
-                .await;
-            }
-
-            *function = degeneric_function(
-                function.clone(),
-                process_manager.cloned(),
-                arguments,
+        FinalizedEffectType::GenericVirtualCall(index, target, found, effects, returning) => {
+            *effect = degeneric_generic_virtual_call(
+                *index,
+                target,
+                found,
+                effects,
+                returning,
                 syntax,
+                process_manager,
                 variables,
-                return_type.clone(),
+                span,
             )
-            .await?;
-
-            for argument in &mut *arguments {
-                degeneric_effect(&mut argument.types, syntax, process_manager, variables, span).await?;
-            }
-            degeneric_arguments(&before_arguments, arguments, syntax, variables, process_manager).await?;
+            .await;
+            return degeneric_effect(effect, syntax, process_manager, variables, span).await;
         }
-        FinalizedEffectType::GenericMethodCall(function, types, arguments) => {
-            let mut calling = arguments.remove(0);
-            degeneric_effect(&mut calling.types, syntax, process_manager, variables, span).await?;
-
-            let implementor = get_return(&calling.types, variables, syntax).await.unwrap();
-            let implementation = ImplWaiter {
-                syntax: syntax.clone(),
-                base_type: implementor.clone(),
-                trait_type: types.clone(),
-                error: Span::default().make_error(ParsingMessage::ShouldntSee("Degeneric generic method call")),
+        FinalizedEffectType::ConstValue(value) => {
+            if let Some(found) = degeneric_const_value(value, process_manager.generics(), syntax).await {
+                *effect = FinalizedEffectType::TypedInt(found, FinalizedTypes::Struct(U64.clone()));
             }
-            .await?;
-
-            let name = function.data.name.split("::").last().unwrap();
-            let function =
-                implementation.iter().flat_map(|(_, inner)| inner).find(|inner| inner.name.ends_with(&name)).unwrap();
+            return Ok(());
+        }
+        FinalizedEffectType::HeapAllocate(types) => {
+            degeneric_type(types, process_manager.generics(), syntax).await;
+            return Ok(());
+        }
+        _ => {}
+    }
 
-            arguments.insert(0, calling.clone());
-            let function = AsyncDataGetter::new(syntax.clone(), function.clone()).await;
-            let function =
-                degeneric_function(function.clone(), process_manager.cloned(), &arguments, syntax, variables, None).await?;
-            for argument in &mut *arguments {
-                degeneric_effect(&mut argument.types, syntax, process_manager, variables, span).await?;
-            }
-            degeneric_arguments(&function.arguments, arguments, syntax, variables, process_manager).await?;
-            *effect = FinalizedEffectType::MethodCall(None, function, arguments.clone(), None);
+    // Each effect is degenericed by a boxed future, so this function's frame only holds one of them at a time.
+    // The larger effects are degenericed in their own functions, which keeps this function's stack frame small since
+    // it's on the stack once for each level of nesting.
+    let degenericing: Pin<Box<dyn Future<Output = Result<(), ParsingError>> + Send + Sync + '_>> = match effect {
+        FinalizedEffectType::CreateVariable(name, value, types) => {
+            degeneric_create_variable(name, value, types, syntax, process_manager, variables, span)
         }
-        FinalizedEffectType::Set(base, value) => {
-            degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await?;
-            degeneric_effect(&mut value.types, syntax, process_manager, variables, span).await?;
+        FinalizedEffectType::CompareJump(effect, _, _)
+        | FinalizedEffectType::Switch(effect, _)
+        | FinalizedEffectType::OverflowCheck(effect)
+        | FinalizedEffectType::BoundsCheck(effect)
+        | FinalizedEffectType::VariantCheck(effect, _)
+        | FinalizedEffectType::HeapStore(effect)
+        | FinalizedEffectType::ReferenceLoad(effect)
+        | FinalizedEffectType::StackStore(effect) => {
+            degeneric_effect(&mut effect.types, syntax, process_manager, variables, span)
         }
-        FinalizedEffectType::Load(base, _, types) => {
-            degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await?;
-            degeneric_type(types, process_manager.generics(), syntax).await;
+        FinalizedEffectType::Cast(value, from, to) => {
+            degeneric_cast(value, from, to, syntax, process_manager, variables, span)
         }
+        FinalizedEffectType::CodeBody(body) => degeneric_scope(body, process_manager, variables, syntax),
+        FinalizedEffectType::MethodCall(calling, function, arguments, return_type) => {
+            degeneric_method_call(calling, function, arguments, return_type, syntax, process_manager, variables, span)
+        }
+        FinalizedEffectType::Set(base, value) => degeneric_set(base, value, syntax, process_manager, variables, span),
+        FinalizedEffectType::Load(base, _, types) => degeneric_load(base, types, syntax, process_manager, variables, span),
         FinalizedEffectType::CreateStruct(storing, types, effects) => {
-            if let Some(found) = storing {
-                degeneric_effect(&mut found.types, syntax, process_manager, variables, span).await?;
-            }
-            let fields = types.get_fields();
-            let mut type_generics = process_manager.generics().clone();
-            for i in 0..fields.len() {
-                let found = &mut effects[i].1;
-                found
-                    .types
-                    .get_nongeneric_return(variables)
-                    .unwrap()
-                    .resolve_generic(&fields[i].field.field_type, syntax, &mut type_generics, span.clone())
-                    .await?;
-                degeneric_effect(&mut found.types, syntax, process_manager, variables, span).await?;
-            }
-            degeneric_type(types, &type_generics, syntax).await;
+            degeneric_create_struct(storing, types, effects, syntax, process_manager, variables, span)
         }
         FinalizedEffectType::CreateArray(types, effects) => {
-            if let Some(found) = types {
-                degeneric_type(found, process_manager.generics(), syntax).await;
-            }
-            for effect in effects {
-                degeneric_effect(&mut effect.types, syntax, process_manager, variables, span).await?;
-            }
+            degeneric_create_array(types, effects, syntax, process_manager, variables, span)
         }
         FinalizedEffectType::VirtualCall(_, function, arguments, returning) => {
-            *function = degeneric_function(
-                function.clone(),
-                process_manager.cloned(),
-                arguments,
+            degeneric_virtual_call(function, arguments, returning, syntax, process_manager, variables, span)
+        }
+        FinalizedEffectType::Downcast(base, target, functions) => {
+            degeneric_downcast(base, target, functions, syntax, process_manager, variables, span)
+        }
+        FinalizedEffectType::Closure(captures, arguments, returning, body) => {
+            degeneric_closure(captures, arguments, returning, body, syntax, process_manager)
+        }
+        FinalizedEffectType::ClosureCall(calling, arguments) => {
+            degeneric_closure_call(calling, arguments, syntax, process_manager, variables, span)
+        }
+        _ => return Ok(()),
+    };
+    return degenericing.await;
+}
+
+/// Degenerics a variable being created. The value is degenericed first, so the variable's type has the solidified
+/// generics
+#[async_recursion]
+async fn degeneric_create_variable(
+    name: &String,
+    value: &mut FinalizedEffects,
+    types: &mut FinalizedTypes,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    degeneric_effect(&mut value.types, syntax, process_manager, variables, span).await?;
+    *types = get_return(&value.types, variables, syntax).await.unwrap();
+    degeneric_type(types, process_manager.generics(), syntax).await;
+    variables.declare(name.clone(), types.clone());
+    return Ok(());
+}
+
+/// Degenerics a cast and the types it's cast between
+#[async_recursion]
+async fn degeneric_cast(
+    value: &mut FinalizedEffects,
+    from: &mut FinalizedTypes,
+    to: &mut FinalizedTypes,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    degeneric_effect(&mut value.types, syntax, process_manager, variables, span).await?;
+    degeneric_type(from, process_manager.generics(), syntax).await;
+    degeneric_type(to, process_manager.generics(), syntax).await;
+    return Ok(());
+}
+
+/// Degenerics a nested code body in its own scope
+#[async_recursion]
+async fn degeneric_scope(
+    body: &mut FinalizedCodeBody,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> Result<(), ParsingError> {
+    variables.push_scope();
+    let result = degeneric_code_body(body, process_manager, variables, syntax).await;
+    variables.pop_scope();
+    return result;
+}
+
+/// Degenerics a set of the base to the value
+#[async_recursion]
+async fn degeneric_set(
+    base: &mut FinalizedEffects,
+    value: &mut FinalizedEffects,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await?;
+    return degeneric_effect(&mut value.types, syntax, process_manager, variables, span).await;
+}
+
+/// Degenerics a load of a field and the field's type
+#[async_recursion]
+async fn degeneric_load(
+    base: &mut FinalizedEffects,
+    types: &mut FinalizedTypes,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await?;
+    degeneric_type(types, process_manager.generics(), syntax).await;
+    return Ok(());
+}
+
+/// Degenerics an array being created and its values
+#[async_recursion]
+async fn degeneric_create_array(
+    types: &mut Option<FinalizedTypes>,
+    effects: &mut Vec<FinalizedEffects>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    if let Some(found) = types {
+        degeneric_type(found, process_manager.generics(), syntax).await;
+    }
+    for effect in effects {
+        degeneric_effect(&mut effect.types, syntax, process_manager, variables, span).await?;
+    }
+    return Ok(());
+}
+
+/// Degenerics a call to a closure and its arguments
+#[async_recursion]
+async fn degeneric_closure_call(
+    calling: &mut FinalizedEffects,
+    arguments: &mut Vec<FinalizedEffects>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    degeneric_effect(&mut calling.types, syntax, process_manager, variables, span).await?;
+    for argument in arguments {
+        degeneric_effect(&mut argument.types, syntax, process_manager, variables, span).await?;
+    }
+    return Ok(());
+}
+
+/// Degenerics a method call, degenericing the called function with the types of its arguments
+#[async_recursion]
+async fn degeneric_method_call(
+    calling: &mut Option<Box<FinalizedEffects>>,
+    function: &mut Arc<CodelessFinalizedFunction>,
+    arguments: &mut Vec<FinalizedEffects>,
+    return_type: &mut Option<(FinalizedTypes, Span)>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    if let Some(found) = calling {
+        degeneric_effect(&mut found.types, syntax, process_manager, variables, span).await?;
+    }
+    // The expected return type can use the generics of the function making the call
+    if let Some((returning, _)) = return_type {
+        degeneric_type(returning, process_manager.generics(), syntax).await;
+    }
+
+    let mut before_arguments = function.arguments.clone();
+    let mut degenericing_process_manager = process_manager.cloned();
+
+    for i in 0..before_arguments.len() {
+        before_arguments[i]
+            .field
+            .field_type
+            .resolve_generic(
+                &get_return(&arguments[i].types, variables, syntax).await.unwrap(),
                 syntax,
-                variables,
-                returning.clone(),
+                degenericing_process_manager.mut_generics(),
+                span.clone(),
             )
             .await?;
-            for effect in &mut *arguments {
-                degeneric_effect(&mut effect.types, syntax, process_manager, variables, span).await?;
-            }
-            degeneric_arguments(&function.arguments, arguments, syntax, variables, process_manager).await?;
-        }
-        FinalizedEffectType::GenericVirtualCall(index, target, found, effects, returning) => {
-            syntax.lock().unwrap().process_manager.handle().lock().unwrap().spawn(
-                target.name.clone(),
-                degeneric_header(
-                    target.clone(),
-                    found.data.clone(),
-                    syntax.clone(),
-                    process_manager.cloned(),
-                    effects.clone(),
-                    variables.clone(),
-                    span.clone(),
-                ),
-            );
-
-            let output = AsyncDataGetter::new(syntax.clone(), target.clone()).await;
-            let mut temp = vec![];
-            mem::swap(&mut temp, effects);
-            *effect = FinalizedEffectType::VirtualCall(*index, output, temp, returning.clone());
-            degeneric_effect(effect, syntax, process_manager, variables, span).await?;
-        }
-        FinalizedEffectType::Downcast(base, target, functions) => {
-            let impl_functions = ImplWaiter {
-                syntax: syntax.clone(),
-                trait_type: target.clone(),
-                base_type: get_return(&base.types, variables, syntax).await.unwrap(),
-                error: Span::default().make_error(ParsingMessage::ShouldntSee("Downcasting failed")),
-            }
+    }
+
+    for field in &mut before_arguments {
+        degeneric_type_no_generic_types(&mut field.field.field_type, degenericing_process_manager.generics(), syntax).await;
+    }
+
+    *function =
+        degeneric_function(function.clone(), process_manager.cloned(), arguments, syntax, variables, return_type.clone())
             .await?;
-            if impl_functions.is_empty() {
-                return Err(span.make_error(ParsingMessage::ShouldntSee("Downcast")));
-            }
 
-            let mut manager = process_manager.cloned();
-            let base_types = get_return(&base.types, variables, syntax).await.unwrap();
-            impl_functions[0].0.base.resolve_generic(&base_types, syntax, manager.mut_generics(), span.clone()).await?;
+    for argument in &mut *arguments {
+        degeneric_effect(&mut argument.types, syntax, process_manager, variables, span).await?;
+    }
+    return degeneric_arguments(&before_arguments, arguments, syntax, variables, process_manager).await;
+}
+
+/// Degenerics a call to a trait's method into a call to the implementation's method
+#[async_recursion]
+async fn degeneric_generic_method_call(
+    function: &Arc<CodelessFinalizedFunction>,
+    types: &FinalizedTypes,
+    arguments: &mut Vec<FinalizedEffects>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<FinalizedEffectType, ParsingError> {
+    let mut calling = arguments.remove(0);
+    degeneric_effect(&mut calling.types, syntax, process_manager, variables, span).await?;
+
+    let implementor = get_return(&calling.types, variables, syntax).await.unwrap();
+    let implementation = ImplWaiter {
+        syntax: syntax.clone(),
+        base_type: implementor.clone(),
+        trait_type: types.clone(),
+        error: Span::default().make_error(ParsingMessage::ShouldntSee("Degeneric generic method call")),
+    }
+    .await?;
+
+    let name = function.data.name.split("::").last().unwrap();
+    let function = implementation.iter().flat_map(|(_, inner)| inner).find(|inner| inner.name.ends_with(&name)).unwrap();
+
+    arguments.insert(0, calling.clone());
+    let function = AsyncDataGetter::new(syntax.clone(), function.clone()).await;
+    let function =
+        degeneric_function(function.clone(), process_manager.cloned(), &arguments, syntax, variables, None).await?;
+    for argument in &mut *arguments {
+        degeneric_effect(&mut argument.types, syntax, process_manager, variables, span).await?;
+    }
+    degeneric_arguments(&function.arguments, arguments, syntax, variables, process_manager).await?;
+    return Ok(FinalizedEffectType::MethodCall(None, function, arguments.clone(), None));
+}
+
+/// Degenerics a virtual call, degenericing the called function with the types of its arguments
+#[async_recursion]
+async fn degeneric_virtual_call(
+    function: &mut Arc<CodelessFinalizedFunction>,
+    arguments: &mut Vec<FinalizedEffects>,
+    returning: &Option<(FinalizedTypes, Span)>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    *function =
+        degeneric_function(function.clone(), process_manager.cloned(), arguments, syntax, variables, returning.clone())
+            .await?;
+    for effect in &mut *arguments {
+        degeneric_effect(&mut effect.types, syntax, process_manager, variables, span).await?;
+    }
+    return degeneric_arguments(&function.arguments, arguments, syntax, variables, process_manager).await;
+}
+
+/// Degenerics a virtual call on a generic type into a virtual call, which still has to be degenericed itself
+#[async_recursion]
+async fn degeneric_generic_virtual_call(
+    index: usize,
+    target: &Arc<FunctionData>,
+    found: &Arc<CodelessFinalizedFunction>,
+    effects: &mut Vec<FinalizedEffects>,
+    returning: &Option<(FinalizedTypes, Span)>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &SimpleVariableManager,
+    span: &Span,
+) -> FinalizedEffectType {
+    syntax.lock().unwrap().process_manager.handle().lock().unwrap().spawn(
+        target.name.clone(),
+        degeneric_header(
+            target.clone(),
+            found.data.clone(),
+            syntax.clone(),
+            process_manager.cloned(),
+            effects.clone(),
+            variables.clone(),
+            vec![],
+            span.clone(),
+        ),
+    );
+
+    let output = AsyncDataGetter::new(syntax.clone(), target.clone()).await;
+    let mut temp = vec![];
+    mem::swap(&mut temp, effects);
+    return FinalizedEffectType::VirtualCall(index, output, temp, returning.clone());
+}
 
-            for function in &impl_functions[0].1 {
-                let function = AsyncDataGetter::new(syntax.clone(), function.clone()).await;
-                let function = degeneric_function(function, manager.cloned(), &vec![], syntax, variables, None).await?;
-                functions.push(function)
+/// Degenerics a downcast, finding the implementation's functions for its vtable
+#[async_recursion]
+async fn degeneric_downcast(
+    base: &mut FinalizedEffects,
+    target: &mut FinalizedTypes,
+    functions: &mut Vec<Arc<CodelessFinalizedFunction>>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    // Every impl is needed, the vtable has slots for the supertraits' methods too
+    FinishedImplsWaiter { syntax: syntax.clone() }.await;
+    let impl_functions = ImplWaiter {
+        syntax: syntax.clone(),
+        trait_type: target.clone(),
+        base_type: get_return(&base.types, variables, syntax).await.unwrap(),
+        error: Span::default().make_error(ParsingMessage::ShouldntSee("Downcasting failed")),
+    }
+    .await?;
+
+    let base_types = get_return(&base.types, variables, syntax).await.unwrap();
+    // The vtable is in the trait's layout order, which VirtualCall indices also use
+    for (declaring, method) in target.inner_struct().vtable_layout() {
+        let method = method.name.split("::").last().unwrap();
+        let found = impl_functions.iter().find_map(|(implementor, functions)| {
+            if implementor.target.inner_struct_safe().map_or(true, |inner| inner.data != declaring) {
+                return None;
             }
+            return functions
+                .iter()
+                .find(|function| function.name.split("::").last().unwrap() == method)
+                .map(|function| (implementor, function));
+        });
+        let (implementor, function) = match found {
+            Some(found) => found,
+            None => return Err(span.make_error(ParsingMessage::ShouldntSee("Downcast"))),
+        };
 
-            degeneric_type(target, process_manager.generics(), syntax).await;
-            degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await?;
-        }
-        FinalizedEffectType::HeapStore(storing) => {
-            degeneric_effect(&mut storing.types, syntax, process_manager, variables, span).await?
-        }
-        FinalizedEffectType::HeapAllocate(types) => degeneric_type(types, process_manager.generics(), syntax).await,
-        FinalizedEffectType::ReferenceLoad(base) => {
-            degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await?
-        }
-        FinalizedEffectType::StackStore(storing) => {
-            degeneric_effect(&mut storing.types, syntax, process_manager, variables, span).await?
-        }
-        _ => {}
+        let mut manager = process_manager.cloned();
+        implementor.base.resolve_generic(&base_types, syntax, manager.mut_generics(), span.clone()).await?;
+        let function = AsyncDataGetter::new(syntax.clone(), function.clone()).await;
+        let function = degeneric_function(function, manager.cloned(), &vec![], syntax, variables, None).await?;
+        functions.push(function)
     }
+
+    degeneric_type(target, process_manager.generics(), syntax).await;
+    return degeneric_effect(&mut base.types, syntax, process_manager, variables, span).await;
+}
+
+/// Degenerics a struct being created, solidifying its generics with the types of its fields
+#[async_recursion]
+async fn degeneric_create_struct(
+    storing: &mut Option<Box<FinalizedEffects>>,
+    types: &mut FinalizedTypes,
+    effects: &mut Vec<(usize, FinalizedEffects)>,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+    variables: &mut SimpleVariableManager,
+    span: &Span,
+) -> Result<(), ParsingError> {
+    if let Some(found) = storing {
+        degeneric_effect(&mut found.types, syntax, process_manager, variables, span).await?;
+    }
+    let fields = types.get_fields();
+    let mut type_generics = process_manager.generics().clone();
+    // Enum variants only set some of the fields, so each effect is matched to its own field
+    for (i, found) in effects.iter_mut() {
+        found
+            .types
+            .get_nongeneric_return(variables)
+            .unwrap()
+            .resolve_generic(&fields[*i].field.field_type, syntax, &mut type_generics, span.clone())
+            .await?;
+        degeneric_effect(&mut found.types, syntax, process_manager, variables, span).await?;
+    }
+    degeneric_type(types, &type_generics, syntax).await;
     return Ok(());
 }
 
+/// Degenerics a closure's captures, arguments, return type, and body
+#[async_recursion]
+async fn degeneric_closure(
+    captures: &mut Vec<FinalizedField>,
+    arguments: &mut Vec<FinalizedField>,
+    returning: &mut FinalizedTypes,
+    body: &mut FinalizedCodeBody,
+    syntax: &Arc<Mutex<Syntax>>,
+    process_manager: &dyn ProcessManager,
+) -> Result<(), ParsingError> {
+    let mut inner = SimpleVariableManager::default();
+    for field in captures.iter_mut().chain(arguments.iter_mut()) {
+        degeneric_type(&mut field.field_type, process_manager.generics(), syntax).await;
+    }
+    for field in &*captures {
+        inner.variables.insert(field.name.clone(), field.field_type.clone());
+    }
+    for field in &*arguments {
+        inner.variables.insert(field.name.clone(), FinalizedTypes::Reference(Box::new(field.field_type.clone())));
+    }
+    degeneric_type(returning, process_manager.generics(), syntax).await;
+    return degeneric_code_body(body, process_manager, &mut inner, syntax).await;
+}
+
 pub async fn degeneric_arguments(
     base_arguments: &Vec<FinalizedMemberField>,
     arguments: &mut Vec<FinalizedEffects>,
@@ -229,7 +485,8 @@ pub async fn degeneric_arguments(
     variables: &mut SimpleVariableManager,
     process_manager: &dyn ProcessManager,
 ) -> Result<(), ParsingError> {
-    for i in 0..base_arguments.len() {
+    // Extern variadics can be passed fewer arguments than they declare
+    for i in 0..base_arguments.len().min(arguments.len()) {
         let arg_return_type = get_return(&arguments[i].types, variables, syntax).await.unwrap();
         let base_field_type = &base_arguments[i].field.field_type;
         // Only downcast if an implementation was found and it's not generic. Don't downcast if they're of the same type.
@@ -273,7 +530,12 @@ pub async fn degeneric_function(
     .clone()
     .into_iter()
     .map(|(name, types)| (name.clone(), FinalizedTypes::Generic(name, types)))
-    .collect::<HashMap<_, _>>();*/
+    .collect::<IndexMap<_, _>>();*/
+    // Instances, like functions with explicitly bound generics, are already solidified.
+    if method.data.instantiated {
+        return Ok(method);
+    }
+
     // Degenerics the return type if there is one and returning is some.
     if let Some(inner) = method.return_type.clone() {
         if let Some((returning, span)) = returning {
@@ -315,13 +577,42 @@ pub async fn degeneric_function(
         return Ok(AsyncDataGetter::new(syntax.clone(), data).await);
     }
 
+    // Claim the instance before degenericing it, so other call sites wait for this one instead of redoing it.
+    let mut method_data = FunctionData::clone(&method.data);
+    method_data.name.clone_from(&name);
+    method_data.instantiated = !manager.generics().is_empty();
+    let method_data = Arc::new(method_data);
+    let claimed = {
+        let mut locked = syntax.lock().unwrap();
+        match locked.monomorphized.entry(monomorphization_key(&method.data, manager.generics())) {
+            Entry::Occupied(found) => {
+                let found = found.get().clone();
+                locked.monomorphized_hits += 1;
+                Some(found)
+            }
+            // Polymorphic recursion, like a function calling itself with Wrapper<T>, makes a new instance forever
+            Entry::Vacant(_) if manager.instantiating().len() >= MAX_INSTANTIATION_DEPTH => {
+                let span = arguments.first().map_or_else(Span::default, |argument| argument.span.clone());
+                return Err(span.make_error(ParsingMessage::InstantiationTooDeep(
+                    MAX_INSTANTIATION_DEPTH,
+                    instantiation_chain(manager.instantiating(), &name),
+                )));
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(method_data.clone());
+                None
+            }
+        }
+    };
+    if let Some(found) = claimed {
+        return Ok(AsyncDataGetter::new(syntax.clone(), found).await);
+    }
+
     // Copy the method and degeneric every type inside of it.
     let mut new_method = CodelessFinalizedFunction::clone(&method);
     // Delete the generics because now they are all solidified.
     new_method.generics.clear();
-    let mut method_data = FunctionData::clone(&method.data);
-    method_data.name.clone_from(&name);
-    new_method.data = Arc::new(method_data);
+    new_method.data = method_data;
     // Degeneric the arguments.
     for argument in &mut new_method.arguments {
         degeneric_type(&mut argument.field.field_type, &manager.generics(), syntax).await;
@@ -346,6 +637,7 @@ pub async fn degeneric_function(
     locked.functions.add_data(new_method.data.clone(), new_method.clone());
 
     // Spawn a thread to asynchronously degeneric the code inside the function.
+    manager.mut_instantiating().push(name);
     let handle = manager.handle().clone();
     handle
         .lock()
@@ -355,6 +647,157 @@ pub async fn degeneric_function(
     return Ok(new_method);
 }
 
+/// Lists the instances that led to the one being instantiated, leaving out the middle of long chains
+fn instantiation_chain(instantiating: &[String], name: &String) -> String {
+    let mut chain = instantiating.iter().chain([name]).map(|instance| instance.as_str()).collect::<Vec<_>>();
+    if chain.len() > 6 {
+        chain.splice(3..chain.len() - 3, ["..."]);
+    }
+    return chain.join(" -> ");
+}
+
+/// Binds explicitly given generics (like method<T>()) to the method's generics in declaration order,
+/// solidifying the method before any generics are inferred from the arguments.
+pub async fn bind_explicit_generics(
+    method: Arc<CodelessFinalizedFunction>,
+    explicit: &Vec<FinalizedTypes>,
+    mut manager: Box<dyn ProcessManager>,
+    syntax: &Arc<Mutex<Syntax>>,
+    variables: &SimpleVariableManager,
+    span: &Span,
+) -> Result<Arc<CodelessFinalizedFunction>, ParsingError> {
+    if explicit.is_empty() {
+        return Ok(method);
+    }
+
+    if explicit.len() != method.generics.len() {
+        return Err(span.make_error(ParsingMessage::IncorrectGenericsLength(method.generics.len(), explicit.len())));
+    }
+
+    for ((name, bounds), generic) in method.generics.iter().zip(explicit) {
+        for bound in bounds {
+            if !generic.of_type(bound, syntax.clone()).await {
+                return Err(span.make_error(ParsingMessage::MismatchedTypes(generic.clone(), bound.clone())));
+            }
+        }
+        manager.mut_generics().insert(name.clone(), generic.clone());
+    }
+
+    // Generics of the caller can't be solidified yet, so they're left to be inferred when the caller is degenericed.
+    if explicit.iter().any(|generic| !is_solid(generic)) {
+        return Ok(method);
+    }
+
+    return degeneric_function(method, manager, &vec![], syntax, variables, None).await;
+}
+
+/// Binds the generics of a generic struct's impl to the receiver's, like T in impl<T> Rc<T> being Counter
+/// for a receiver of Rc<Counter>, so the method's return type is already solid while the caller is checked.
+pub async fn bind_impl_generics(
+    method: Arc<CodelessFinalizedFunction>,
+    target: &FinalizedTypes,
+    receiver: &FinalizedTypes,
+    mut manager: Box<dyn ProcessManager>,
+    syntax: &Arc<Mutex<Syntax>>,
+    variables: &SimpleVariableManager,
+) -> Result<Arc<CodelessFinalizedFunction>, ParsingError> {
+    if method.generics.is_empty() {
+        return Ok(method);
+    }
+
+    // Mismatches are errored on by check_args instead
+    if target.resolve_generic(receiver, syntax, manager.mut_generics(), Span::default()).await.is_err() {
+        return Ok(method);
+    }
+    bind_instance_generics(target, receiver, manager.mut_generics());
+    return solidify(method, manager, syntax, variables).await;
+}
+
+/// Binds the generics of the generic type that was degenericed into the instance, like Rc<T> into Rc$Counter.
+/// Instances don't keep what their generics were, so they're found from the types of the instance's fields.
+fn bind_instance_generics(
+    generic: &FinalizedTypes,
+    instance: &FinalizedTypes,
+    generics: &mut IndexMap<String, FinalizedTypes>,
+) {
+    let (base, arguments) = match generic {
+        FinalizedTypes::Generic(name, _) => {
+            generics.entry(name.clone()).or_insert_with(|| instance.dereferenced().clone());
+            return;
+        }
+        FinalizedTypes::Reference(inner) => return bind_instance_generics(inner, instance, generics),
+        FinalizedTypes::GenericType(base, arguments) => (base.inner_struct(), arguments),
+        _ => return,
+    };
+    let found = match instance.dereferenced().inner_struct_safe() {
+        Some(found) if found.data.name.contains('$') && found.fields.len() == base.fields.len() => found,
+        _ => return,
+    };
+
+    // The fields use the base struct's own generics, which the arguments are then bound to
+    let mut inner = IndexMap::default();
+    for (field, found_field) in base.fields.iter().zip(&found.fields) {
+        bind_instance_generics(&field.field.field_type, &found_field.field.field_type, &mut inner);
+    }
+    for (name, argument) in base.generics.keys().zip(arguments) {
+        if let Some(bound) = inner.get(name) {
+            bind_instance_generics(argument, bound, generics);
+        }
+    }
+}
+
+/// Infers the generics of a method called like Struct::method(arguments) from the arguments' types,
+/// so something like Rc::new(value) doesn't need the type of the value written out.
+pub async fn infer_generics(
+    method: Arc<CodelessFinalizedFunction>,
+    arguments: &[FinalizedEffects],
+    mut manager: Box<dyn ProcessManager>,
+    syntax: &Arc<Mutex<Syntax>>,
+    variables: &SimpleVariableManager,
+) -> Result<Arc<CodelessFinalizedFunction>, ParsingError> {
+    if method.generics.is_empty() || method.arguments.len() != arguments.len() {
+        return Ok(method);
+    }
+
+    for (argument, effect) in method.arguments.iter().zip(arguments) {
+        let found = match get_return(&effect.types, variables, syntax).await {
+            Some(found) => found,
+            None => return Ok(method),
+        };
+        if argument.field.field_type.resolve_generic(&found, syntax, manager.mut_generics(), Span::default()).await.is_err()
+        {
+            return Ok(method);
+        }
+    }
+    return solidify(method, manager, syntax, variables).await;
+}
+
+/// Degenerics the method if every one of its generics was bound to a solid type,
+/// otherwise they're left to be inferred when the caller is degenericed
+async fn solidify(
+    method: Arc<CodelessFinalizedFunction>,
+    manager: Box<dyn ProcessManager>,
+    syntax: &Arc<Mutex<Syntax>>,
+    variables: &SimpleVariableManager,
+) -> Result<Arc<CodelessFinalizedFunction>, ParsingError> {
+    if !method.generics.keys().all(|name| manager.generics().get(name).is_some_and(is_solid)) {
+        return Ok(method);
+    }
+    return degeneric_function(method, manager, &vec![], syntax, variables, None).await;
+}
+
+/// Checks if the type doesn't contain any generics
+fn is_solid(types: &FinalizedTypes) -> bool {
+    return match types {
+        FinalizedTypes::Generic(_, _) => false,
+        FinalizedTypes::GenericType(base, bounds) => is_solid(base) && bounds.iter().all(is_solid),
+        FinalizedTypes::Reference(inner) => is_solid(inner),
+        FinalizedTypes::Array(inner, length) => is_solid(inner) && length.iter().all(|length| is_solid(length)),
+        FinalizedTypes::Function(arguments, returning) => arguments.iter().all(is_solid) && is_solid(returning),
+        FinalizedTypes::Struct(_) | FinalizedTypes::Const(_) | FinalizedTypes::ConstType(_) => true,
+    };
+}
+
 /// Degenerics the code body of the method.
 async fn degeneric_code(
     syntax: Arc<Mutex<Syntax>>,
@@ -421,8 +864,14 @@ pub async fn degeneric_code_body(
     syntax: &Arc<Mutex<Syntax>>,
 ) -> Result<(), ParsingError> {
     for expression in &mut code.expressions {
+        if let FinalizedEffectType::StaticAssert(condition) = &expression.effect.types {
+            check_static_assert(condition, process_manager.generics(), syntax, &expression.effect.span).await?;
+            continue;
+        }
         degeneric_effect(&mut expression.effect.types, syntax, process_manager, variables, &expression.effect.span).await?;
     }
+    // Every static assert passed, so they don't need to be compiled
+    code.expressions.retain(|expression| !matches!(expression.effect.types, FinalizedEffectType::StaticAssert(_)));
 
     return Ok(());
 }
@@ -431,7 +880,7 @@ pub async fn degeneric_code_body(
 #[async_recursion]
 pub async fn degeneric_type(
     types: &mut FinalizedTypes,
-    generics: &HashMap<String, FinalizedTypes>,
+    generics: &IndexMap<String, FinalizedTypes>,
     syntax: &Arc<Mutex<Syntax>>,
 ) {
     return match types {
@@ -483,9 +932,11 @@ pub async fn degeneric_type(
                 let mut data = FinalizedStruct::clone(AsyncDataGetter::new(syntax.clone(), base.data.clone()).await.deref());
                 data.data.clone_from(&arc_other);
 
-                // Update the program's fields
+                // Update the program's fields with the struct's own generics, which aren't always named like the
+                // generics of the code using it
+                let struct_generics = data.generics.keys().cloned().zip(bounds.iter().cloned()).collect();
                 for field in &mut data.fields {
-                    degeneric_type(&mut field.field.field_type, generics, syntax).await;
+                    degeneric_type(&mut field.field.field_type, &struct_generics, syntax).await;
                 }
 
                 let data = Arc::new(data);
@@ -496,14 +947,22 @@ pub async fn degeneric_type(
             }
         }
         FinalizedTypes::Reference(inner) => degeneric_type(inner, generics, syntax).await,
-        FinalizedTypes::Array(inner) => degeneric_type(inner, generics, syntax).await,
-        FinalizedTypes::Struct(inner) => {
-            let mut temp = FinalizedStruct::clone(inner);
-            for field in &mut temp.fields {
-                degeneric_type(&mut field.field.field_type, generics, syntax).await;
+        FinalizedTypes::Array(inner, length) => {
+            degeneric_type(inner, generics, syntax).await;
+            if let Some(length) = length {
+                degeneric_type(length, generics, syntax).await;
             }
-            *inner = degeneric_struct(temp, generics, syntax).await;
         }
+        FinalizedTypes::Function(arguments, returning) => {
+            for argument in arguments {
+                degeneric_type(argument, generics, syntax).await;
+            }
+            degeneric_type(returning, generics, syntax).await;
+        }
+        FinalizedTypes::Const(_) | FinalizedTypes::ConstType(_) => {}
+        // degeneric_struct replaces the generics in the fields, replacing them twice would degeneric a generic
+        // replaced with an instance of the same struct forever
+        FinalizedTypes::Struct(inner) => *inner = degeneric_struct(FinalizedStruct::clone(inner), generics, syntax).await,
     };
 }
 
@@ -512,7 +971,7 @@ pub async fn degeneric_type(
 #[async_recursion]
 pub async fn degeneric_type_no_generic_types(
     types: &mut FinalizedTypes,
-    generics: &HashMap<String, FinalizedTypes>,
+    generics: &IndexMap<String, FinalizedTypes>,
     syntax: &Arc<Mutex<Syntax>>,
 ) {
     return match types {
@@ -529,7 +988,19 @@ pub async fn degeneric_type_no_generic_types(
             }
         }
         FinalizedTypes::Reference(inner) => degeneric_type_no_generic_types(inner, generics, syntax).await,
-        FinalizedTypes::Array(inner) => degeneric_type_no_generic_types(inner, generics, syntax).await,
+        FinalizedTypes::Array(inner, length) => {
+            degeneric_type_no_generic_types(inner, generics, syntax).await;
+            if let Some(length) = length {
+                degeneric_type_no_generic_types(length, generics, syntax).await;
+            }
+        }
+        FinalizedTypes::Function(arguments, returning) => {
+            for argument in arguments {
+                degeneric_type_no_generic_types(argument, generics, syntax).await;
+            }
+            degeneric_type_no_generic_types(returning, generics, syntax).await;
+        }
+        FinalizedTypes::Const(_) | FinalizedTypes::ConstType(_) => {}
         FinalizedTypes::Struct(inner) => {
             let mut temp = FinalizedStruct::clone(inner);
             for field in &mut temp.fields {
@@ -544,7 +1015,7 @@ pub async fn degeneric_type_no_generic_types(
 #[async_recursion]
 pub async fn degeneric_type_fields(
     types: &mut FinalizedTypes,
-    generics: &HashMap<String, FinalizedTypes>,
+    generics: &IndexMap<String, FinalizedTypes>,
     syntax: &Arc<Mutex<Syntax>>,
 ) {
     return match types {
@@ -555,7 +1026,7 @@ pub async fn degeneric_type_fields(
         }
         FinalizedTypes::GenericType(base, bounds) => {
             let mut i = 0;
-            let mut base_generics = HashMap::new();
+            let mut base_generics = IndexMap::new();
             for (generic, _bound) in &base.inner_struct().generics {
                 base_generics.insert(generic.clone(), bounds[i].clone());
                 i += 1;
@@ -569,7 +1040,19 @@ pub async fn degeneric_type_fields(
             }
         }
         FinalizedTypes::Reference(inner) => degeneric_type_fields(inner, generics, syntax).await,
-        FinalizedTypes::Array(inner) => degeneric_type_fields(inner, generics, syntax).await,
+        FinalizedTypes::Array(inner, length) => {
+            degeneric_type_fields(inner, generics, syntax).await;
+            if let Some(length) = length {
+                degeneric_type_fields(length, generics, syntax).await;
+            }
+        }
+        FinalizedTypes::Function(arguments, returning) => {
+            for argument in arguments {
+                degeneric_type_fields(argument, generics, syntax).await;
+            }
+            degeneric_type_fields(returning, generics, syntax).await;
+        }
+        FinalizedTypes::Const(_) | FinalizedTypes::ConstType(_) => {}
         FinalizedTypes::Struct(inner) => {
             let mut temp = FinalizedStruct::clone(inner);
             for field in &mut temp.fields {
@@ -580,6 +1063,17 @@ pub async fn degeneric_type_fields(
     };
 }
 
+/// The key of the function's instance with the generics in Syntax::monomorphized.
+/// Types are keyed by their full name, because FinalizedTypes equality ignores a struct's generics.
+fn monomorphization_key(
+    function: &FunctionData,
+    generics: &IndexMap<String, FinalizedTypes>,
+) -> (String, Vec<(String, String)>) {
+    let mut generics: Vec<_> = generics.iter().map(|(name, types)| (name.clone(), types.to_string())).collect();
+    generics.sort_by(|(first, _), (second, _)| first.cmp(second));
+    return (function.name.split('$').next().unwrap().to_string(), generics);
+}
+
 /// Degenerics a function header, for virtual function calls
 pub async fn degeneric_header(
     degenericed: Arc<FunctionData>,
@@ -588,13 +1082,18 @@ pub async fn degeneric_header(
     mut manager: Box<dyn ProcessManager>,
     arguments: Vec<FinalizedEffects>,
     variables: SimpleVariableManager,
+    explicit_generics: Vec<FinalizedTypes>,
     span: Span,
 ) -> Result<(), ParsingError> {
     let function: Arc<CodelessFinalizedFunction> = AsyncDataGetter { getting: base, syntax: syntax.clone() }.await;
 
+    // Explicit generics are bound directly, otherwise they're taken from the calling type
     let return_type = arguments[0].types.get_nongeneric_return(&variables).unwrap();
-    let (_, generics) = return_type.inner_generic_type().unwrap();
-    assert_eq!(function.generics.len(), generics.len());
+    let generics =
+        if explicit_generics.is_empty() { return_type.inner_generic_type().unwrap().1 } else { &explicit_generics };
+    if function.generics.len() != generics.len() {
+        return Err(span.make_error(ParsingMessage::IncorrectGenericsLength(function.generics.len(), generics.len())));
+    }
 
     let mut iterator = function.generics.iter();
     for generic in generics {
@@ -608,6 +1107,24 @@ pub async fn degeneric_header(
         manager.mut_generics().insert(name.clone(), generic.clone());
     }
 
+    // Every call site with the same generics makes the same header, so only the first one has to.
+    // Instances named differently, like ones made by degeneric_function, still get their own header.
+    let own_generics: IndexMap<_, _> = function.generics.keys().cloned().zip(generics.iter().cloned()).collect();
+    {
+        let mut locked = syntax.lock().unwrap();
+        match locked.monomorphized.entry(monomorphization_key(&function.data, &own_generics)) {
+            Entry::Occupied(found) => {
+                if found.get().name == degenericed.name {
+                    locked.monomorphized_hits += 1;
+                    return Ok(());
+                }
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(degenericed.clone());
+            }
+        }
+    }
+
     // Copy the method and degeneric every type inside of it.
     let mut new_method = CodelessFinalizedFunction::clone(&function);
     // Delete the generics because now they are all solidified.
@@ -640,20 +1157,21 @@ pub async fn degeneric_header(
 /// Degenerics a finalized struct
 pub async fn degeneric_struct(
     mut structure: FinalizedStruct,
-    generics: &HashMap<String, FinalizedTypes>,
+    generics: &IndexMap<String, FinalizedTypes>,
     syntax: &Arc<Mutex<Syntax>>,
 ) -> Arc<FinalizedStruct> {
-    let targets: Vec<_> =
-        generics.iter().filter(|(key, _)| structure.generics.contains_key(*key)).map(|(_, value)| value).collect();
+    // Ordered by the struct's generics so the same arguments always give the same name
+    let targets: Vec<_> = structure.generics.keys().filter_map(|key| generics.get(key).cloned()).collect();
     if targets.is_empty() {
         return Arc::new(structure);
     }
     let mut data = StructData::clone(&structure.data);
-    let name = format!("{}${}", data.name, display_parenless(&targets, "_"));
+    let name = instance_name(&data.name, &targets);
     data.name.clone_from(&name);
 
     // TODO check if this is safe, handle generics calling generics
     structure.generics.clear();
+    structure.arguments = targets;
     for field in &mut structure.fields {
         degeneric_type(&mut field.field.field_type, generics, syntax).await;
     }
@@ -0,0 +1,79 @@
+use data::SourceSet;
+use syntax::errors::{ParsingError, ParsingMessage};
+
+/// Serializes the errors and warnings to JSON, like {"diagnostics": [...]}.
+/// Each diagnostic has its severity, code, message, file and the character offsets, 1-based line and column it starts at,
+/// plus the diagnostic of its note or null, and a note for the code it was expanded from or null.
+pub fn diagnostics_json(errors: &[ParsingError], warnings: &[ParsingError], sources: &[Box<dyn SourceSet>]) -> String {
+    let diagnostics = errors
+        .iter()
+        .map(|error| diagnostic_json(error, "error", sources))
+        .chain(warnings.iter().map(|warning| diagnostic_json(warning, "warning", sources)))
+        .collect::<Vec<_>>();
+    return format!("{{\"diagnostics\":[{}]}}", diagnostics.join(","));
+}
+
+/// Serializes a single diagnostic and its note
+fn diagnostic_json(error: &ParsingError, severity: &str, sources: &[Box<dyn SourceSet>]) -> String {
+    // The message's variant name, like MismatchedTypes, stays the same as the wording of the message changes
+    let message = format!("{:?}", error.message);
+    let code = message.split('(').next().unwrap();
+
+    let location = match find_location(error, sources) {
+        Some((file, start, end, line, column)) => {
+            format!("\"file\":{},\"start\":{},\"end\":{},\"line\":{},\"column\":{}", escape(&file), start, end, line, column)
+        }
+        None => "\"file\":null,\"start\":null,\"end\":null,\"line\":null,\"column\":null".to_string(),
+    };
+    let note = match &error.note {
+        Some(note) => diagnostic_json(note, "note", sources),
+        None => "null".to_string(),
+    };
+    let expansion = match &error.span.expansion {
+        Some(expansion) => diagnostic_json(
+            &ParsingError::new(expansion.origin.clone(), ParsingMessage::InExpansion(expansion.name.clone())),
+            "note",
+            sources,
+        ),
+        None => "null".to_string(),
+    };
+    return format!(
+        "{{\"severity\":{},\"code\":{},\"message\":{},{},\"note\":{},\"expansion\":{}}}",
+        escape(severity),
+        escape(code),
+        escape(&error.message.to_string()),
+        location,
+        note,
+        expansion
+    );
+}
+
+/// Finds the path, start and end offsets, and starting line and column of the error's span,
+/// or None if the file isn't in the sources
+fn find_location(error: &ParsingError, sources: &[Box<dyn SourceSet>]) -> Option<(String, usize, usize, u32, u32)> {
+    let file = sources.iter().flat_map(|source| source.get_files()).find(|readable| readable.hash() == error.span.file)?;
+    let tokens = file.read();
+    let start = tokens.get(error.span.start)?;
+    let end = tokens.get(error.span.end)?;
+    let ((line, column), _) = error.span.line_columns(&tokens, &file.contents())?;
+    return Some((file.path(), start.start_offset, end.end_offset, line, column));
+}
+
+/// Turns the text into a quoted JSON string
+fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+    output.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", character as u32)),
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+    return output;
+}
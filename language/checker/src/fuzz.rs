@@ -0,0 +1,138 @@
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use data::tokens::{Span, Token, TokenTypes};
+use data::{Readable, SourceSet};
+use parser::parse;
+use parser::tokens::tokenizer::Tokenizer;
+use syntax::async_util::HandleWrapper;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::syntax::Syntax;
+use tokio::runtime::Builder;
+use tokio::time;
+
+use crate::output::TypesChecker;
+
+/// The name of the fuzzed source, which its errors are in
+pub const FUZZED_FILE: &str = "fuzzed";
+/// How long checking can take before it's assumed to be waiting forever
+const FUZZ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parses and checks the input as a source named fuzzed alongside the libraries, like core, without compiling it.
+/// This is what a fuzz target calls: it never panics, any panic while parsing or checking is returned as an
+/// InternalError, and checking that never finishes is a CheckTimedOut error.
+/// Code stuck in a loop that never awaits can't be timed out, so fuzzers still need their own timeout for those.
+pub fn fuzz_check(input: &[u8], libraries: &[Box<dyn SourceSet>]) -> Result<(), Vec<ParsingError>> {
+    let runtime = match Builder::new_current_thread().enable_time().build() {
+        Ok(runtime) => runtime,
+        Err(error) => return Err(vec![Span::default().make_error(ParsingMessage::InternalError(error.to_string()))]),
+    };
+    let handle = Arc::new(Mutex::new(HandleWrapper::new(runtime.handle().clone())));
+    let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(TypesChecker::new(handle.clone(), true)))));
+    let source = FuzzedSource { contents: String::from_utf8_lossy(input).to_string() };
+
+    // Parsing panics on this thread, checking panics inside the tasks, which return them when joined
+    let checked = panic::catch_unwind(AssertUnwindSafe(|| runtime.block_on(check_all(&syntax, &handle, source, libraries))));
+    let mut errors = match checked {
+        Ok(errors) => errors,
+        Err(payload) => vec![internal_error(payload)],
+    };
+    // A panic while the program was locked poisons it, but the errors found before the panic are still valid
+    errors.append(&mut syntax.lock().unwrap_or_else(PoisonError::into_inner).errors);
+    ParsingError::sort(&mut errors, libraries);
+    return if errors.is_empty() { Ok(()) } else { Err(errors) };
+}
+
+/// Parses the libraries and source, then waits for every checking task to finish, returning their errors
+async fn check_all(
+    syntax: &Arc<Mutex<Syntax>>,
+    handle: &Arc<Mutex<HandleWrapper>>,
+    source: FuzzedSource,
+    libraries: &[Box<dyn SourceSet>],
+) -> Vec<ParsingError> {
+    for library in libraries {
+        for file in library.get_files() {
+            if file.path().ends_with("rv") {
+                parse(syntax.clone(), handle.clone(), library.relative(&*file), file).await;
+            }
+        }
+    }
+    parse(syntax.clone(), handle.clone(), FUZZED_FILE.to_string(), Box::new(source)).await;
+    syntax.lock().unwrap().finish();
+
+    let mut errors = Vec::default();
+    let joined = time::timeout(FUZZ_TIMEOUT, async {
+        loop {
+            // Tasks keep spawning more tasks, so this runs until none are left
+            let next = handle.lock().unwrap_or_else(PoisonError::into_inner).joining.pop();
+            match next {
+                Some(task) => match task.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(error)) => errors.push(error),
+                    Err(error) => {
+                        if let Ok(payload) = error.try_into_panic() {
+                            errors.push(internal_error(payload));
+                        }
+                    }
+                },
+                None => break,
+            }
+        }
+    })
+    .await;
+    if joined.is_err() {
+        errors.push(Span::default().make_error(ParsingMessage::CheckTimedOut(FUZZ_TIMEOUT.as_secs())));
+    }
+    return errors;
+}
+
+/// Converts a caught panic into an error with the panic's message
+fn internal_error(payload: Box<dyn Any + Send>) -> ParsingError {
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    };
+    return Span::default().make_error(ParsingMessage::InternalError(message));
+}
+
+/// The fuzzed source, which only exists in memory
+struct FuzzedSource {
+    /// The source's code
+    contents: String,
+}
+
+impl Readable for FuzzedSource {
+    fn read(&self) -> Vec<Token> {
+        let mut tokenizer = Tokenizer::new(self.contents.as_bytes());
+        let mut tokens = Vec::default();
+        loop {
+            tokens.push(tokenizer.next());
+            if tokens.last().unwrap().token_type == TokenTypes::EOF {
+                break;
+            }
+        }
+        return tokens;
+    }
+
+    fn contents(&self) -> String {
+        return self.contents.clone();
+    }
+
+    fn path(&self) -> String {
+        return format!("{}.rv", FUZZED_FILE);
+    }
+
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::default();
+        Hash::hash(&self.path(), &mut hasher);
+        return hasher.finish();
+    }
+}
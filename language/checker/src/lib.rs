@@ -2,7 +2,6 @@
 
 extern crate core;
 
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -10,30 +9,60 @@ use async_recursion::async_recursion;
 use data::tokens::Span;
 use indexmap::IndexMap;
 
+use crate::check_loop::EnclosingLoop;
 use crate::degeneric::degeneric_type_no_generic_types;
 use syntax::async_util::NameResolver;
-use syntax::errors::ParsingError;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::FinalizedEffectType;
+use syntax::program::function::CodelessFinalizedFunction;
 use syntax::program::syntax::Syntax;
 use syntax::program::types::{FinalizedTypes, Types};
-use syntax::{ParsingFuture, SimpleVariableManager};
+use syntax::{Attribute, ParsingFuture, SimpleVariableManager};
 
 use crate::output::TypesChecker;
 
+/// Checks mutable borrows don't overlap
+pub mod check_borrow;
+/// Checks casts between types
+pub mod check_cast;
+/// Checks closures and calls to them
+pub mod check_closure;
 /// Checks code to perform internal linking and find any errors
 pub mod check_code;
+/// Checks module-level constants
+pub mod check_constant;
+/// Checks lets destructuring their value into a pattern
+pub mod check_destructure;
+/// Checks enum variants and switches on them
+pub mod check_enum;
 /// Checks functions
 pub mod check_function;
+/// Checks impl blocks against their trait
+pub mod check_impl;
 /// Checks the impl call effect
 pub mod check_impl_call;
+/// Lowers interpolated strings
+pub mod check_interpolation;
+/// Checks loops and the breaks and continues in them
+pub mod check_loop;
 /// Checks the method call effect
 pub mod check_method_call;
 /// Checks the operator effect
 pub mod check_operator;
+/// Checks static asserts and the size_of and align_of intrinsics
+pub mod check_static_assert;
+/// Decodes and checks string literals
+pub mod check_string;
 /// Checks structs
 pub mod check_struct;
+/// Implicit conversions of arguments to the types of their parameters
+pub mod coercion;
 /// Degenerics types
 pub mod degeneric;
+/// Serializes errors and warnings to JSON for external tools
+pub mod diagnostics;
+/// An entry point for fuzzers that parses and checks a source without ever panicking
+pub mod fuzz;
 /// Used to send data to be checked by the checker and then send the result to the compiler
 pub mod output;
 
@@ -53,12 +82,134 @@ pub async fn finalize_generics(
     return Ok(output);
 }
 
+/// Warns about each declared generic that none of the types use, unless its name starts with an underscore.
+/// A generic in the bounds of another generic, like T in U: Into<T>, counts as used.
+pub fn warn_unused_generics(
+    syntax: &Arc<Mutex<Syntax>>,
+    declared: &IndexMap<String, Span>,
+    generics: &IndexMap<String, Vec<FinalizedTypes>>,
+    types: Vec<&FinalizedTypes>,
+) {
+    for (name, span) in declared {
+        if name.starts_with('_') {
+            continue;
+        }
+        let bounds = generics.iter().filter(|(generic, _)| *generic != name).flat_map(|(_, bounds)| bounds);
+        if !types.iter().copied().chain(bounds).any(|types| uses_generic(types, name)) {
+            syntax.lock().unwrap().warn(span.make_error(ParsingMessage::UnusedGeneric(name.clone())));
+        }
+    }
+}
+
+/// Checks if the generic is anywhere in the type
+fn uses_generic(types: &FinalizedTypes, name: &str) -> bool {
+    return match types {
+        FinalizedTypes::Generic(found, bounds) => found == name || bounds.iter().any(|bound| uses_generic(bound, name)),
+        FinalizedTypes::GenericType(base, arguments) => {
+            uses_generic(base, name) || arguments.iter().any(|argument| uses_generic(argument, name))
+        }
+        FinalizedTypes::Reference(inner) | FinalizedTypes::ConstType(inner) => uses_generic(inner, name),
+        FinalizedTypes::Array(inner, length) => {
+            uses_generic(inner, name) || length.iter().any(|length| uses_generic(length, name))
+        }
+        FinalizedTypes::Function(arguments, returning) => {
+            arguments.iter().any(|argument| uses_generic(argument, name)) || uses_generic(returning, name)
+        }
+        FinalizedTypes::Struct(_) | FinalizedTypes::Const(_) => false,
+    };
+}
+
 /// Simple wrapper program for the types used in code verification
 pub struct CodeVerifier<'a> {
     process_manager: &'a TypesChecker,
     resolver: Box<dyn NameResolver>,
     return_type: Option<FinalizedTypes>,
     syntax: Arc<Mutex<Syntax>>,
+    /// Whether to log how each implementation call is resolved, set by #[debug_impls]
+    debug_impls: bool,
+    /// Whether integer math traps on overflow instead of wrapping, set by #[checked_math]
+    checked_math: bool,
+    /// How many effects the effect being verified is nested in
+    depth: usize,
+    /// The loops the effect being verified is in, innermost last
+    loops: Vec<EnclosingLoop>,
+    /// The constants whose values are being verified, outermost first, to find constants using themselves
+    constants: Vec<String>,
+    /// The types variables declared from untyped literals are used as, by where they're declared, see RetypedLiteral
+    literal_types: Vec<(Span, FinalizedTypes)>,
+}
+
+impl<'a> CodeVerifier<'a> {
+    /// Creates a verifier for effects outside of any function, like ones made with an EffectBuilder
+    pub fn new(process_manager: &'a TypesChecker, resolver: Box<dyn NameResolver>, syntax: Arc<Mutex<Syntax>>) -> Self {
+        return Self {
+            process_manager,
+            resolver,
+            return_type: None,
+            syntax,
+            debug_impls: false,
+            checked_math: false,
+            depth: 0,
+            loops: Vec::default(),
+            constants: Vec::default(),
+            literal_types: Vec::default(),
+        };
+    }
+}
+
+/// Errors with the original error if the type was poisoned, instead of causing confusing errors later on
+pub fn check_poison(types: &FinalizedTypes, span: &Span) -> Result<(), ParsingError> {
+    return match types.poisoned().first() {
+        Some(error) => Err(span.make_error(error.message.clone())),
+        None => Ok(()),
+    };
+}
+
+/// Gets the type the effect returns, erroring if it returns nothing or returns a poisoned struct,
+/// so the struct's original error is reported where it's used instead of a confusing error later on
+pub async fn get_checked_return(
+    types: &FinalizedEffectType,
+    variables: &SimpleVariableManager,
+    syntax: &Arc<Mutex<Syntax>>,
+    span: &Span,
+) -> Result<FinalizedTypes, ParsingError> {
+    let found = match get_return(types, variables, syntax).await {
+        Some(found) => found,
+        None => return Err(span.make_error(ParsingMessage::UnexpectedVoid())),
+    };
+    check_poison(&found, span)?;
+    return Ok(found);
+}
+
+/// Checks if the function returns its first argument unchanged, like the array operator.
+/// These functions are marked with #[returns_argument].
+pub fn returns_first_argument(function: &CodelessFinalizedFunction) -> bool {
+    return Attribute::find_attribute("returns_argument", &function.data.attributes).is_some();
+}
+
+/// Finds the name closest to the misspelled one, if any are close enough to be what was meant
+pub fn closest_name<'a>(name: &str, names: impl Iterator<Item = &'a String>) -> Option<String> {
+    let limit = name.chars().count() / 3 + 1;
+    return names
+        .map(|other| (edit_distance(name, other), other))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, other)| other.clone());
+}
+
+/// The number of characters that have to be inserted, removed or replaced to turn the first string into the second
+fn edit_distance(first: &str, second: &str) -> usize {
+    let second = second.chars().collect::<Vec<_>>();
+    let mut previous = (0..=second.len()).collect::<Vec<_>>();
+    for (i, first_char) in first.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, second_char) in second.iter().enumerate() {
+            let replaced = previous[j] + (first_char != *second_char) as usize;
+            current.push(replaced.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    return previous[second.len()];
 }
 
 /// Gets the return type of the effect, requiring a variable manager to get
@@ -70,28 +221,44 @@ pub async fn get_return(
     syntax: &Arc<Mutex<Syntax>>,
 ) -> Option<FinalizedTypes> {
     return match types {
+        // Keeps the exact type of the argument, like an array literal's length through the array operator
+        FinalizedEffectType::MethodCall(_, function, args, None) if !args.is_empty() && returns_first_argument(function) => {
+            get_return(&args[0].types, variables, syntax).await.map(|found| {
+                if let FinalizedTypes::Reference(_) = found {
+                    found
+                } else {
+                    FinalizedTypes::Reference(Box::new(found))
+                }
+            })
+        }
         FinalizedEffectType::MethodCall(_, function, args, return_type) => match function.return_type.as_ref().cloned() {
             Some(mut inner) => {
-                if let Some((return_type, _)) = return_type {
-                    let generics = function
-                        .generics
-                        .iter()
-                        .map(|(name, _)| (name.clone(), return_type.clone()))
-                        .collect::<HashMap<_, _>>();
+                // The type expected by the call's context solves generics only found in the return type
+                if let Some((return_type, span)) = return_type {
+                    let mut generics = IndexMap::new();
+                    let _ = inner.resolve_generic(return_type, syntax, &mut generics, span.clone()).await;
                     degeneric_type_no_generic_types(&mut inner, &generics, syntax).await;
-                } else if let Some(calling) = args.get(0) {
+                }
+                if let (Some(calling), Some(parent)) = (args.get(0), function.parent.as_ref()) {
                     let other = get_return(&calling.types, variables, syntax).await;
                     if let Some(found) = other {
-                        let mut generics = HashMap::new();
-                        function
-                            .parent
-                            .as_ref()
-                            .unwrap()
-                            .resolve_generic(&found, syntax, &mut generics, Span::default())
-                            .await
-                            .unwrap();
+                        let mut generics = IndexMap::new();
+                        parent.resolve_generic(&found, syntax, &mut generics, Span::default()).await.unwrap();
                         degeneric_type_no_generic_types(&mut inner, &generics, syntax).await;
                     }
+                } else if !function.generics.is_empty() {
+                    // Generic functions get their generics from the arguments, mismatches are errored on by check_args
+                    let mut generics = IndexMap::new();
+                    for (argument, effect) in function.arguments.iter().zip(args) {
+                        if let Some(found) = get_return(&effect.types, variables, syntax).await {
+                            let _ = argument
+                                .field
+                                .field_type
+                                .resolve_generic(&found, syntax, &mut generics, Span::default())
+                                .await;
+                        }
+                    }
+                    degeneric_type_no_generic_types(&mut inner, &generics, syntax).await;
                 }
                 Some(FinalizedTypes::Reference(Box::new(inner)))
             }
@@ -104,7 +271,7 @@ pub async fn get_return(
                 if let Some(calling) = args.get(0) {
                     let other = get_return(&calling.types, variables, syntax).await;
                     if let Some(found) = other {
-                        let mut generics = HashMap::new();
+                        let mut generics = IndexMap::new();
                         function
                             .parent
                             .as_ref()
@@ -119,10 +286,13 @@ pub async fn get_return(
             }
             None => None,
         },
-        // Stores just return their inner type.
+        // Stores and overflow, bounds and variant checks just return their inner type.
         FinalizedEffectType::HeapStore(inner)
         | FinalizedEffectType::StackStore(inner)
-        | FinalizedEffectType::Set(_, inner) => get_return(&inner.types, variables, syntax).await,
+        | FinalizedEffectType::Set(_, inner)
+        | FinalizedEffectType::OverflowCheck(inner)
+        | FinalizedEffectType::BoundsCheck(inner)
+        | FinalizedEffectType::VariantCheck(inner, _) => get_return(&inner.types, variables, syntax).await,
         // References return their inner type as well.
         FinalizedEffectType::ReferenceLoad(inner) => match get_return(&inner.types, variables, syntax).await.unwrap() {
             FinalizedTypes::Reference(inner) => Some(*inner),
@@ -1,21 +1,26 @@
 use async_trait::async_trait;
-use data::tokens::Span;
 use indexmap::IndexMap;
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
+use tokio::sync::mpsc::UnboundedReceiver;
 
+use crate::check_constant::verify_constant;
 use crate::check_function::{verify_function, verify_function_code};
-use crate::check_struct::verify_struct;
+use crate::check_impl::verify_signatures;
+use crate::check_struct::{verify_enum, verify_struct};
+use crate::coercion::{CoercionRules, DefaultCoercions};
 use crate::degeneric::degeneric_function;
-use syntax::async_util::{HandleWrapper, NameResolver};
+use syntax::async_util::{DataGetterCache, HandleWrapper, NameResolver};
+use syntax::errors::ParsingError;
+use syntax::program::constant::{FinalizedConstant, UnfinalizedConstant};
 use syntax::program::function::{
-    CodeBody, CodelessFinalizedFunction, FinalizedCodeBody, FinalizedFunction, FunctionData, UnfinalizedFunction,
+    CodeBody, CodelessFinalizedFunction, FinalizedCodeBody, FinalizedFunction, FunctionData, ParsedFunction,
+    UnfinalizedFunction,
 };
-use syntax::program::r#struct::{FinalizedStruct, StructData, UnfinalizedStruct};
+use syntax::program::r#struct::{FinalizedStruct, StructData, StructStub, UnfinalizedStruct};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::FinalizedTypes;
-use syntax::{ProcessManager, SimpleVariableManager};
+use syntax::{is_modifier, FinishedTraitImplementor, Modifier, ProcessManager, SimpleVariableManager, TopElement};
 
 /// Wrapper around data used by checkers
 #[derive(Clone)]
@@ -23,15 +28,98 @@ pub struct TypesChecker {
     /// Runtime for ProcessManager
     runtime: Arc<Mutex<HandleWrapper>>,
     /// Generics in the current type
-    pub generics: HashMap<String, FinalizedTypes>,
+    pub generics: IndexMap<String, FinalizedTypes>,
     /// Whether to include references
     include_refs: bool,
+    /// How deep effects can be nested inside each other before erroring
+    pub max_nesting: usize,
+    /// Functions already finalized, shared by every clone of the checker
+    pub functions: Arc<DataGetterCache<FunctionData>>,
+    /// Whether to panic on errors without a span, to find effects made without one while developing the compiler
+    pub check_spans: bool,
+    /// The generic instances being degenericed to reach the current code, to catch ones instantiating forever
+    pub instantiating: Vec<String>,
+    /// The implicit conversions made to arguments passed to methods
+    pub coercions: Arc<dyn CoercionRules>,
+    /// Whether indices into arrays that can't be checked while compiling are checked when they run
+    pub bounds_checks: bool,
+    /// Whether NaN and Inf literals are errors
+    pub reject_special_floats: bool,
+    /// How the code of functions is scheduled
+    pub scheduling: CodeScheduling,
+    /// Held while verifying code with sequential scheduling, shared by every clone of the checker
+    pub sequential: Arc<tokio::sync::Mutex<()>>,
 }
 
+/// How the code of functions is scheduled while verifying
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CodeScheduling {
+    /// The code of each function is verified in its own task at the same time as the others
+    Concurrent,
+    /// The code of one function is verified at a time
+    Sequential,
+}
+
+/// The default limit on how deep effects can be nested, the same as the parser's limit on nested lines
+pub const DEFAULT_MAX_NESTING: usize = 64;
+
 impl TypesChecker {
     /// Makes a new TypesChecker
     pub fn new(runtime: Arc<Mutex<HandleWrapper>>, include_refs: bool) -> Self {
-        return Self { runtime, generics: HashMap::default(), include_refs };
+        return Self {
+            runtime,
+            generics: IndexMap::default(),
+            include_refs,
+            max_nesting: DEFAULT_MAX_NESTING,
+            functions: Arc::default(),
+            check_spans: false,
+            instantiating: Vec::default(),
+            coercions: Arc::new(DefaultCoercions),
+            bounds_checks: true,
+            reject_special_floats: false,
+            scheduling: CodeScheduling::Concurrent,
+            sequential: Arc::default(),
+        };
+    }
+
+    /// Verifies a struct or enum, returning its error instead of adding it to the program's errors and poisoning it
+    pub async fn try_verify_struct(
+        &self,
+        structure: UnfinalizedStruct,
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Result<FinalizedStruct, ParsingError> {
+        return if is_modifier(structure.data.modifiers, Modifier::Enum) {
+            verify_enum(self, structure, syntax, self.include_refs).await
+        } else {
+            verify_struct(self, structure, syntax, self.include_refs).await
+        };
+    }
+
+    /// Checks the functions parse_streaming sends as they arrive, each in its own task like the parser spawns otherwise,
+    /// until every sender is dropped. This has to be spawned on the handle as the named task before parsing starts,
+    /// so waiting for the handle's tasks waits for every function it receives too.
+    pub async fn check_stream(
+        self,
+        mut functions: UnboundedReceiver<ParsedFunction>,
+        syntax: Arc<Mutex<Syntax>>,
+        name: String,
+    ) -> Result<(), ParsingError> {
+        while let Some(parsed) = functions.recv().await {
+            let function = parsed.function.data.name.clone();
+            let verifying =
+                FunctionData::verify(self.runtime.clone(), parsed.function, syntax.clone(), parsed.resolver, self.cloned());
+            self.runtime.lock().unwrap().spawn(function, verifying);
+        }
+        self.runtime.lock().unwrap().finish_task(&name);
+        return Ok(());
+    }
+
+    /// Adds the error to the syntax's errors, first making sure it points somewhere if check_spans is set
+    fn add_error(&self, syntax: &Arc<Mutex<Syntax>>, error: ParsingError) {
+        if self.check_spans && error.span.is_default() {
+            panic!("Error without a span, whatever made it is missing one: {}", error.message);
+        }
+        syntax.lock().unwrap().errors.push(error);
     }
 }
 
@@ -44,16 +132,22 @@ impl ProcessManager for TypesChecker {
     async fn verify_func(
         &self,
         function: UnfinalizedFunction,
+        resolver: Box<dyn NameResolver>,
         syntax: &Arc<Mutex<Syntax>>,
     ) -> (CodelessFinalizedFunction, CodeBody) {
-        return verify_function(function, syntax, self.include_refs).await.unwrap_or_else(|error| {
-            syntax.lock().unwrap().errors.push(error.clone());
+        let data = function.data.clone();
+        return verify_function(self, function, resolver, syntax, self.include_refs).await.unwrap_or_else(|error| {
+            self.add_error(syntax, error.clone());
+            // Keeps the function's data, poisoned so calls to it report this error instead of waiting on it
+            let mut data = FunctionData::clone(&data);
+            data.poisoned.push(error);
             (
                 CodelessFinalizedFunction {
                     generics: IndexMap::default(),
                     arguments: vec![],
                     return_type: None,
-                    data: Arc::new(FunctionData::new(Vec::default(), 0, String::default(), Span::default())),
+                    self_bounds: vec![],
+                    data: Arc::new(data),
                     parent: None,
                 },
                 CodeBody::new(Vec::default(), String::default()),
@@ -68,14 +162,15 @@ impl ProcessManager for TypesChecker {
         resolver: Box<dyn NameResolver>,
         syntax: &Arc<Mutex<Syntax>>,
     ) -> FinalizedFunction {
+        let data = function.data.clone();
         return verify_function_code(self, resolver, code, function, syntax).await.unwrap_or_else(|error| {
-            syntax.lock().unwrap().errors.push(error.clone());
+            self.add_error(syntax, error.clone());
             FinalizedFunction {
                 generics: IndexMap::default(),
                 fields: vec![],
                 code: FinalizedCodeBody::default(),
                 return_type: None,
-                data: Arc::new(FunctionData::new(Vec::default(), 0, String::default(), Span::default())),
+                data,
             }
         });
     }
@@ -88,33 +183,73 @@ impl ProcessManager for TypesChecker {
         };
     }
 
+    async fn verify_constant(
+        &self,
+        constant: Arc<UnfinalizedConstant>,
+        resolver: Box<dyn NameResolver>,
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Option<FinalizedConstant> {
+        return match verify_constant(self, &constant, resolver, syntax, 0, vec![]).await {
+            Ok(constant) => Some(constant),
+            Err(error) => {
+                self.add_error(syntax, error);
+                None
+            }
+        };
+    }
+
     async fn verify_struct(
         &self,
         structure: UnfinalizedStruct,
         _resolver: Box<dyn NameResolver>,
         syntax: &Arc<Mutex<Syntax>>,
     ) -> FinalizedStruct {
-        match verify_struct(self, structure, &syntax, self.include_refs).await {
+        let data = structure.data.clone();
+        match self.try_verify_struct(structure, syntax).await {
             Ok(output) => return output,
             Err(error) => {
-                syntax.lock().unwrap().errors.push(error.clone());
+                self.add_error(syntax, error.clone());
+                // Keeps the struct's data, poisoned so anything using it reports this error instead
+                let mut data = StructData::clone(&data);
+                data.poisoned.push(error);
                 FinalizedStruct {
                     generics: IndexMap::default(),
+                    arguments: vec![],
                     fields: vec![],
-                    data: Arc::new(StructData::new(Vec::default(), Vec::default(), 0, Span::default(), String::default())),
+                    traits: vec![],
+                    discriminants: vec![],
+                    constants: IndexMap::default(),
+                    data: Arc::new(data),
+                    stub: StructStub::default(),
                 }
             }
         }
     }
 
-    fn generics(&self) -> &HashMap<String, FinalizedTypes> {
+    async fn verify_impl_signatures(
+        &self,
+        implementor: &FinishedTraitImplementor,
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Vec<ParsingError> {
+        return verify_signatures(self, implementor, syntax).await;
+    }
+
+    fn generics(&self) -> &IndexMap<String, FinalizedTypes> {
         return &self.generics;
     }
 
-    fn mut_generics(&mut self) -> &mut HashMap<String, FinalizedTypes> {
+    fn mut_generics(&mut self) -> &mut IndexMap<String, FinalizedTypes> {
         return &mut self.generics;
     }
 
+    fn instantiating(&self) -> &Vec<String> {
+        return &self.instantiating;
+    }
+
+    fn mut_instantiating(&mut self) -> &mut Vec<String> {
+        return &mut self.instantiating;
+    }
+
     fn cloned(&self) -> Box<dyn ProcessManager> {
         return Box::new(self.clone());
     }
@@ -12,12 +12,13 @@ use tokio::time;
 
 use data::tokens::Span;
 use data::CompilerArguments;
-use syntax::async_util::EmptyNameResolver;
+use syntax::async_util::{AsyncDataGetter, EmptyNameResolver};
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::function::{CodelessFinalizedFunction, FinalizedFunction};
 use syntax::program::r#struct::FinalizedStruct;
 use syntax::program::syntax::Syntax;
 
-use crate::function_compiler::{compile_block, instance_function};
+use crate::function_compiler::compile_block;
 use crate::main_future::MainFuture;
 use crate::type_getter::CompilerTypeGetter;
 //use crate::util::print_formatted;
@@ -71,14 +72,38 @@ impl<'ctx> CompilerImpl<'ctx> {
         return Some(Arc::new(function.to_codeless()));
     }
 
+    /// Finds a function to compile as an entry point, which can't have generics because nothing fills them in
+    pub async fn get_entry(name: &str, syntax: &Arc<Mutex<Syntax>>) -> Result<Arc<CodelessFinalizedFunction>, ParsingError> {
+        let found =
+            Syntax::get_function(syntax.clone(), Span::default(), name.to_string(), Box::new(EmptyNameResolver {}), false)
+                .await?;
+        let found = AsyncDataGetter::new(syntax.clone(), found).await;
+        if !found.generics.is_empty() {
+            return Err(Span::default().make_error(ParsingMessage::InvalidEntryPoint(name.to_string())));
+        }
+        return Ok(found);
+    }
+
     /// Compiles the main function
     pub fn compile(
         main: Arc<CodelessFinalizedFunction>,
         type_getter: &mut CompilerTypeGetter<'ctx>,
         functions: &Arc<DashMap<String, Arc<FinalizedFunction>>>,
+        structures: &Arc<DashMap<String, Arc<FinalizedStruct>>>,
+    ) {
+        Self::compile_all(&[main], type_getter, functions, structures);
+    }
+
+    /// Compiles every entry point into the same module, so the generic instances they share are only compiled once
+    pub fn compile_all(
+        entries: &[Arc<CodelessFinalizedFunction>],
+        type_getter: &mut CompilerTypeGetter<'ctx>,
+        functions: &Arc<DashMap<String, Arc<FinalizedFunction>>>,
         _structures: &Arc<DashMap<String, Arc<FinalizedStruct>>>,
     ) {
-        instance_function(main, type_getter);
+        for entry in entries {
+            type_getter.get_function(entry);
+        }
 
         let start = Instant::now();
         while !type_getter.compiling.borrow().is_empty() {
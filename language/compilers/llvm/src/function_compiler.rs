@@ -1,22 +1,23 @@
-use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::sync::Arc;
 
 use inkwell::basic_block::BasicBlock;
 use inkwell::module::Linkage;
-use inkwell::types::{BasicType, BasicTypeEnum};
-use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue};
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType, StructType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallableValue, FunctionValue, PointerValue};
 use inkwell::AddressSpace;
 
-use syntax::program::code::{ExpressionType, FinalizedEffectType, FinalizedEffects};
+use syntax::errors::{ErrorSource, ParsingMessage};
+use syntax::program::code::{ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedField};
 use syntax::program::function::{CodelessFinalizedFunction, FinalizedCodeBody};
 use syntax::program::types::FinalizedTypes;
 use syntax::{is_modifier, Attribute, Modifier};
 
-use crate::internal::instructions::{compile_internal, malloc_type};
+use crate::internal::instructions::{compile_bounds_check, compile_internal, compile_variant_check, malloc_type};
 use crate::internal::intrinsics::compile_llvm_intrinsics;
+use crate::internal::math_internal::{compile_cast, compile_overflow_check};
 use crate::type_getter::CompilerTypeGetter;
-use crate::util::create_function_value;
+use crate::util::{create_function_value, is_ptr_len};
 
 /// Instances a FunctionValue from its CodelessFinalizedFunction
 pub fn instance_function<'a, 'ctx>(
@@ -36,7 +37,11 @@ pub fn instance_function<'a, 'ctx>(
         value = create_function_value(&function, type_getter, None);
         compile_internal(&type_getter, &type_getter.compiler, &function.data.name, value);
     } else if is_modifier(function.data.modifiers, Modifier::Extern) {
-        value = create_function_value(&function, type_getter, Some(Linkage::External))
+        value = create_function_value(&function, type_getter, Some(Linkage::External));
+        // Natives added from Rust are called through their pointer instead of a linked symbol
+        if let Some(address) = type_getter.syntax.lock().unwrap().natives.get(&function.data.name) {
+            type_getter.compiler.execution_engine.add_global_mapping(&value, *address);
+        }
     } else {
         value = create_function_value(&function, type_getter, None);
         type_getter.compiling.borrow_mut().push((value, function));
@@ -48,9 +53,11 @@ pub fn instance_function<'a, 'ctx>(
 pub fn instance_types<'ctx>(types: &FinalizedTypes, type_getter: &mut CompilerTypeGetter<'ctx>) -> BasicTypeEnum<'ctx> {
     return match types {
         FinalizedTypes::Reference(inner) => type_getter.get_type(inner),
-        FinalizedTypes::Array(inner) => type_getter.get_type(inner),
+        FinalizedTypes::Array(inner, _) => type_getter.get_type(inner),
+        // Closures are a pointer to their function and a pointer to their captured variables
+        FinalizedTypes::Function(_, _) => closure_struct(type_getter).as_basic_type_enum(),
         _ => {
-            if is_modifier(types.inner_struct().data.modifiers, Modifier::Trait) {
+            if types.is_trait() {
                 type_getter
                     .compiler
                     .context
@@ -63,12 +70,15 @@ pub fn instance_types<'ctx>(types: &FinalizedTypes, type_getter: &mut CompilerTy
                     )
                     .as_basic_type_enum()
             } else {
+                // Named before compiling the fields, so a struct containing itself behind a reference finds itself
+                let named = type_getter.compiler.context.opaque_struct_type(&types.name());
                 let mut fields = vec![type_getter.compiler.context.i64_type().as_basic_type_enum()];
                 for field in &types.inner_struct().fields {
                     fields.push(type_getter.get_type(&field.field.field_type));
                 }
 
-                type_getter.compiler.context.struct_type(fields.as_slice(), true).as_basic_type_enum()
+                named.set_body(fields.as_slice(), true);
+                named.as_basic_type_enum()
             }
         }
     };
@@ -102,7 +112,9 @@ pub fn compile_block<'ctx>(
                         type_getter.compiler.builder.build_unconditional_branch(destination);
                     }
                     compile_effect(type_getter, function, &line.effect, id);
+                    // Every branch of the block returns its own value
                     broke = true;
+                    continue;
                 }
 
                 if matches!(&line.effect.types, FinalizedEffectType::NOP) {
@@ -145,7 +157,9 @@ pub fn compile_block<'ctx>(
                                 type_getter.compiler.builder.position_at_end(temp);
                             }
                         }
-                        FinalizedEffectType::Jump(_) | FinalizedEffectType::CompareJump(_, _, _) => {
+                        FinalizedEffectType::Jump(_)
+                        | FinalizedEffectType::CompareJump(_, _, _)
+                        | FinalizedEffectType::Switch(_, _) => {
                             broke = true;
                             compile_effect(type_getter, function, &line.effect, id);
                         }
@@ -170,10 +184,32 @@ pub fn compile_effect<'ctx>(
     effect: &FinalizedEffects,
     id: &mut u64,
 ) -> Option<BasicValueEnum<'ctx>> {
+    type_getter.source_map.borrow_mut().add(function.get_name().to_str().unwrap(), *id, effect.span.clone());
     return match &effect.types {
         FinalizedEffectType::NOP => {
             panic!("Tried to compile a NOP! For {}", function.get_name().to_str().unwrap())
         }
+        FinalizedEffectType::OverflowCheck(inner) => compile_overflow_check(type_getter, function, inner, id),
+        FinalizedEffectType::BoundsCheck(inner) => compile_bounds_check(type_getter, function, inner, id),
+        FinalizedEffectType::VariantCheck(inner, discriminant) => {
+            compile_variant_check(type_getter, function, inner, *discriminant, id)
+        }
+        FinalizedEffectType::Cast(inner, from, to) => compile_cast(type_getter, function, inner, from, to, id),
+        // The runner never compiles a program with errors, so poisoned code can't be compiled
+        FinalizedEffectType::Poison(error) => {
+            panic!("Tried to compile poisoned code! For {}: {}", function.get_name().to_str().unwrap(), error.message)
+        }
+        // Static asserts are removed once they're checked, which is always before compiling
+        FinalizedEffectType::StaticAssert(_) => {
+            panic!("Tried to compile a static assert! For {}", function.get_name().to_str().unwrap())
+        }
+        // Generic constants are replaced by their value when the function is degenericed too, so this is an error
+        // that stops the program from running. The zero keeps the rest of the function compiling.
+        FinalizedEffectType::ConstValue(_) => {
+            let name = function.get_name().to_str().unwrap().to_string();
+            type_getter.errors.borrow_mut().push(effect.span.make_error(ParsingMessage::UncompiledConstValue(name)));
+            Some(type_getter.compiler.context.i64_type().const_zero().as_basic_value_enum())
+        }
         FinalizedEffectType::CreateVariable(name, inner, types) => {
             let compiled = compile_effect(type_getter, function, inner, id).unwrap();
             type_getter.variables.insert(name.clone(), (types.clone(), compiled.as_basic_value_enum()));
@@ -199,7 +235,36 @@ pub fn compile_effect<'ctx>(
             type_getter.compiler.builder.build_conditional_branch(effect, then, else_block);
             None
         }
-        FinalizedEffectType::CodeBody(body) => compile_block(body, function, type_getter, id),
+        //Tag of the variant, and the label to jump to for each variant
+        FinalizedEffectType::Switch(effect, labels) => {
+            let effect = compile_effect(type_getter, function, effect, id).unwrap();
+            let effect = if effect.is_pointer_value() {
+                *id += 1;
+                type_getter.compiler.builder.build_load(effect.into_pointer_value(), &(*id - 1).to_string()).into_int_value()
+            } else {
+                effect.into_int_value()
+            };
+            let mut cases = Vec::default();
+            for (discriminant, label) in labels {
+                cases.push((
+                    effect.get_type().const_int(*discriminant, false),
+                    get_block_or_create(label, function, type_getter),
+                ));
+            }
+            // Every variant has a case, so the default block is never jumped to
+            match cases.last() {
+                Some((_, default)) => type_getter.compiler.builder.build_switch(effect, *default, &cases),
+                None => type_getter.compiler.builder.build_unreachable(),
+            };
+            None
+        }
+        FinalizedEffectType::CodeBody(body) => {
+            // Variables declared in the block end with it, uncovering any variables they shadowed
+            let outer = type_getter.variables.clone();
+            let output = compile_block(body, function, type_getter, id);
+            type_getter.variables = outer;
+            output
+        }
         //Calling function, function arguments
         FinalizedEffectType::MethodCall(pointer, calling_function, arguments, _) => {
             let mut final_arguments = Vec::default();
@@ -212,20 +277,18 @@ pub fn compile_effect<'ctx>(
                     compile_effect(type_getter, function, pointer.as_ref().unwrap(), id).unwrap().into_pointer_value();
                 final_arguments.push(From::from(pointer));
 
-                add_args(&mut final_arguments, type_getter, function, arguments, true, id);
+                add_args(&mut final_arguments, type_getter, function, calling_function, arguments, true, id);
 
                 *id += 1;
                 type_getter.compiler.builder.build_call(calling, final_arguments.as_slice(), &(*id - 1).to_string());
                 Some(pointer.as_basic_value_enum())
             } else {
-                add_args(&mut final_arguments, type_getter, function, arguments, false, id);
+                add_args(&mut final_arguments, type_getter, function, calling_function, arguments, false, id);
 
-                let call = type_getter
-                    .compiler
-                    .builder
-                    .build_call(calling, final_arguments.as_slice(), &id.to_string())
-                    .try_as_basic_value()
-                    .left();
+                let call = type_getter.compiler.builder.build_call(calling, final_arguments.as_slice(), &id.to_string());
+                // Externs can use a different calling convention than the C default
+                call.set_call_convention(calling.get_call_conventions());
+                let call = call.try_as_basic_value().left();
                 *id += 1;
                 return match call {
                     Some(inner) => {
@@ -287,11 +350,11 @@ pub fn compile_effect<'ctx>(
         }
         //Struct to create and a tuple of the index of the argument and the argument
         FinalizedEffectType::CreateStruct(effect, structure, arguments) => {
-            let mut out_arguments = vec![MaybeUninit::uninit(); arguments.len()];
+            let mut out_arguments = Vec::default();
 
             for (index, effect) in arguments {
                 let returned = compile_effect(type_getter, function, effect, id).unwrap();
-                *out_arguments.get_mut(*index).unwrap() = MaybeUninit::new(returned);
+                out_arguments.push((*index, returned));
             }
 
             let pointer = compile_effect(type_getter, function, effect.as_ref().unwrap(), id).unwrap().into_pointer_value();
@@ -308,14 +371,12 @@ pub fn compile_effect<'ctx>(
                 type_getter.compiler.context.i64_type().const_int(structure.id(), false),
             );
 
-            let mut offset = 1;
-            for argument in out_arguments {
-                let value = unsafe { argument.assume_init() };
-
-                let pointer = type_getter.compiler.builder.build_struct_gep(pointer, offset, &id.to_string()).unwrap();
+            // Enum variants only set some fields, so each argument is stored at its own field after the type id
+            for (index, value) in out_arguments {
+                let pointer =
+                    type_getter.compiler.builder.build_struct_gep(pointer, index as u32 + 1, &id.to_string()).unwrap();
                 *id += 1;
                 type_getter.compiler.builder.build_store(pointer, value);
-                offset += 1;
             }
 
             Some(pointer.as_basic_value_enum())
@@ -323,14 +384,31 @@ pub fn compile_effect<'ctx>(
         FinalizedEffectType::Float(float) => {
             Some(type_getter.compiler.context.f64_type().const_float(*float).as_basic_value_enum())
         }
-        FinalizedEffectType::UInt(int) => {
-            Some(type_getter.compiler.context.i64_type().const_int(*int, false).as_basic_value_enum())
+        FinalizedEffectType::Int(int) => {
+            Some(type_getter.compiler.context.i64_type().const_int(*int as u64, true).as_basic_value_enum())
+        }
+        FinalizedEffectType::TypedInt(int, types) => {
+            Some(type_getter.get_type(types).into_int_type().const_int(*int, false).as_basic_value_enum())
         }
         FinalizedEffectType::Bool(bool) => {
             Some(type_getter.compiler.context.bool_type().const_int(*bool as u64, false).as_basic_value_enum())
         }
-        FinalizedEffectType::String(string) => {
-            Some(type_getter.compiler.context.const_string(string.as_bytes(), false).as_basic_value_enum())
+        FinalizedEffectType::StringLiteral(bytes) => {
+            // Literals live in a private constant global, so C can borrow the pointer without it being copied or freed
+            let mut terminated = bytes.clone();
+            terminated.push(0);
+            let value = type_getter.compiler.context.const_string(&terminated, false);
+            let global = type_getter.compiler.module.add_global(
+                value.get_type(),
+                Some(AddressSpace::default()),
+                &format!("string_{}", id),
+            );
+            *id += 1;
+            global.set_initializer(&value);
+            global.set_constant(true);
+            global.set_linkage(Linkage::Private);
+            global.set_unnamed_addr(true);
+            Some(global.as_pointer_value().as_basic_value_enum())
         }
         FinalizedEffectType::Char(char) => {
             Some(type_getter.compiler.context.i8_type().const_int(*char as u64, false).as_basic_value_enum())
@@ -547,13 +625,17 @@ pub fn compile_effect<'ctx>(
             *id += 1;
             let offset = type_getter.compiler.builder.build_load(offset, &id.to_string());
             *id += 1;
-            let offset = type_getter
-                .compiler
-                .builder
-                .build_struct_gep(offset.into_pointer_value(), *func_offset as u32, &id.to_string())
-                .unwrap();
-            *id += 1;
-            let offset = type_getter.compiler.builder.build_load(offset, &id.to_string()).into_pointer_value();
+            let offset = if type_getter.vtable.borrow().position_independent {
+                relative_vtable_entry(type_getter, offset.into_pointer_value(), *func_offset, method, id)
+            } else {
+                let offset = type_getter
+                    .compiler
+                    .builder
+                    .build_struct_gep(offset.into_pointer_value(), *func_offset as u32, &id.to_string())
+                    .unwrap();
+                *id += 1;
+                type_getter.compiler.builder.build_load(offset, &id.to_string()).into_pointer_value()
+            };
             *id += 2;
             type_getter
                 .compiler
@@ -568,7 +650,7 @@ pub fn compile_effect<'ctx>(
         }
         FinalizedEffectType::Downcast(base, target, functions) => {
             let base_return_types = base.types.get_nongeneric_return(type_getter).unwrap();
-            if is_modifier(base_return_types.inner_struct().data.modifiers, Modifier::Trait) {
+            if base_return_types.is_trait() {
                 if !target.eq(&base_return_types) {
                     panic!("Downcasting to a trait that doesn't match! Not implemented yet!")
                 } else {
@@ -623,9 +705,187 @@ pub fn compile_effect<'ctx>(
         FinalizedEffectType::GenericVirtualCall(_, _, _, _, _) => {
             panic!("Generic virtual call not degeneric'd!")
         }
+        FinalizedEffectType::Closure(captures, arguments, returning, body) => {
+            Some(compile_closure(type_getter, function, captures, arguments, returning, body, id))
+        }
+        FinalizedEffectType::ClosureCall(calling, arguments) => {
+            let function_type = calling.types.get_nongeneric_return(type_getter).unwrap();
+            let (argument_types, returning) = function_type.as_function().unwrap();
+            let closure_type = closure_type(type_getter, argument_types, returning);
+
+            let calling = compile_effect(type_getter, function, calling, id).unwrap();
+            let calling = type_getter.compiler.builder.build_pointer_cast(
+                calling.into_pointer_value(),
+                closure_struct(type_getter).ptr_type(AddressSpace::default()),
+                &id.to_string(),
+            );
+            *id += 1;
+            let pointer = type_getter.compiler.builder.build_struct_gep(calling, 0, &id.to_string()).unwrap();
+            *id += 1;
+            let pointer = type_getter.compiler.builder.build_load(pointer, &id.to_string()).into_pointer_value();
+            *id += 1;
+            let pointer = type_getter.compiler.builder.build_pointer_cast(
+                pointer,
+                closure_type.ptr_type(AddressSpace::default()),
+                &id.to_string(),
+            );
+            *id += 1;
+            let environment = type_getter.compiler.builder.build_struct_gep(calling, 1, &id.to_string()).unwrap();
+            *id += 1;
+            let environment = type_getter.compiler.builder.build_load(environment, &id.to_string());
+            *id += 1;
+
+            let mut compiled_args = vec![BasicMetadataValueEnum::from(environment)];
+            for argument in arguments {
+                compiled_args
+                    .push(BasicMetadataValueEnum::from(compile_effect(type_getter, function, argument, id).unwrap()));
+            }
+            *id += 1;
+            type_getter
+                .compiler
+                .builder
+                .build_call(CallableValue::try_from(pointer).unwrap(), compiled_args.as_slice(), &(*id - 1).to_string())
+                .try_as_basic_value()
+                .left()
+        }
+    };
+}
+
+/// Gets the function in a slot of a position independent vtable, whose entries are offsets from the vtable
+fn relative_vtable_entry<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    table: PointerValue<'ctx>,
+    slot: usize,
+    method: &Arc<CodelessFinalizedFunction>,
+    id: &mut u64,
+) -> PointerValue<'ctx> {
+    let context = type_getter.compiler.context;
+    let builder = &type_getter.compiler.builder;
+    let entries = vec![context.i32_type().as_basic_type_enum(); slot + 1];
+    let entries = context.struct_type(entries.as_slice(), false).ptr_type(AddressSpace::default());
+    let table = builder.build_pointer_cast(table, entries, &id.to_string());
+    *id += 1;
+    let entry = builder.build_struct_gep(table, slot as u32, &id.to_string()).unwrap();
+    *id += 1;
+    let entry = builder.build_load(entry, &id.to_string()).into_int_value();
+    *id += 1;
+    let entry = builder.build_int_s_extend(entry, context.i64_type(), &id.to_string());
+    *id += 1;
+    let base = builder.build_ptr_to_int(table, context.i64_type(), &id.to_string());
+    *id += 1;
+    let address = builder.build_int_add(base, entry, &id.to_string());
+    *id += 1;
+    let function_type = type_getter.get_function(method).get_type().ptr_type(AddressSpace::default());
+    return type_getter.compiler.builder.build_int_to_ptr(address, function_type, &id.to_string());
+}
+
+/// The struct holding a closure's function pointer and captured variables pointer
+fn closure_struct<'ctx>(type_getter: &CompilerTypeGetter<'ctx>) -> StructType<'ctx> {
+    let pointer = type_getter.compiler.context.i64_type().ptr_type(AddressSpace::default()).as_basic_type_enum();
+    return type_getter.compiler.context.struct_type(&[pointer, pointer], false);
+}
+
+/// Gets the type of a closure's function, which takes its captured variables before its arguments
+fn closure_type<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    arguments: &Vec<FinalizedTypes>,
+    returning: &FinalizedTypes,
+) -> FunctionType<'ctx> {
+    let mut params: Vec<BasicMetadataTypeEnum> =
+        vec![type_getter.compiler.context.i64_type().ptr_type(AddressSpace::default()).into()];
+    for argument in arguments {
+        params.push(type_getter.get_type(&FinalizedTypes::Reference(Box::new(argument.clone()))).into());
+    }
+
+    return if returning.is_void() {
+        type_getter.compiler.context.void_type().fn_type(params.as_slice(), false)
+    } else {
+        type_getter.get_type(&FinalizedTypes::Reference(Box::new(returning.clone()))).fn_type(params.as_slice(), false)
     };
 }
 
+/// Compiles the closure's code into its own function, then creates the closure from the function
+/// and a copy of the captured variables
+fn compile_closure<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    captures: &Vec<FinalizedField>,
+    arguments: &Vec<FinalizedField>,
+    returning: &FinalizedTypes,
+    body: &FinalizedCodeBody,
+    id: &mut u64,
+) -> BasicValueEnum<'ctx> {
+    let pointer_type = type_getter.compiler.context.i64_type().ptr_type(AddressSpace::default());
+
+    // Captured variables are references, so the closure sees changes made after it's created
+    let captured = captures.iter().map(|capture| type_getter.variables.get(&capture.name).unwrap().1).collect::<Vec<_>>();
+    let environment_type = type_getter
+        .compiler
+        .context
+        .struct_type(captured.iter().map(|value| value.get_type()).collect::<Vec<_>>().as_slice(), false);
+    let environment = malloc_type(type_getter, environment_type.ptr_type(AddressSpace::default()).const_zero(), id);
+    for (i, value) in captured.iter().enumerate() {
+        let field = type_getter.compiler.builder.build_struct_gep(environment, i as u32, &id.to_string()).unwrap();
+        *id += 1;
+        type_getter.compiler.builder.build_store(field, *value);
+    }
+
+    let argument_types = arguments.iter().map(|argument| argument.field_type.clone()).collect::<Vec<_>>();
+    let closure_type = closure_type(type_getter, &argument_types, returning);
+    let closure = type_getter.compiler.module.add_function(
+        &format!("{}$closure{}", function.get_name().to_str().unwrap(), id),
+        closure_type,
+        None,
+    );
+    *id += 1;
+
+    // Loads the captured variables at the start of the closure's code
+    let mut closure_getter = type_getter.for_closure();
+    let block = type_getter.compiler.context.append_basic_block(closure, &body.label);
+    closure_getter.blocks.insert(body.label.clone(), block);
+    closure_getter.current_block = Some(block);
+    closure_getter.compiler.builder.position_at_end(block);
+
+    let loaded = closure_getter.compiler.builder.build_pointer_cast(
+        closure.get_nth_param(0).unwrap().into_pointer_value(),
+        environment_type.ptr_type(AddressSpace::default()),
+        &id.to_string(),
+    );
+    *id += 1;
+    for (i, capture) in captures.iter().enumerate() {
+        let field = closure_getter.compiler.builder.build_struct_gep(loaded, i as u32, &id.to_string()).unwrap();
+        *id += 1;
+        let value = closure_getter.compiler.builder.build_load(field, &id.to_string());
+        *id += 1;
+        closure_getter.variables.insert(capture.name.clone(), (capture.field_type.clone(), value));
+    }
+    for (i, argument) in arguments.iter().enumerate() {
+        closure_getter.variables.insert(
+            argument.name.clone(),
+            (FinalizedTypes::Reference(Box::new(argument.field_type.clone())), closure.get_nth_param(i as u32 + 1).unwrap()),
+        );
+    }
+    compile_block(body, closure, &mut closure_getter, id);
+    type_getter.compiler.builder.position_at_end(type_getter.current_block.unwrap());
+
+    let output = malloc_type(type_getter, closure_struct(type_getter).ptr_type(AddressSpace::default()).const_zero(), id);
+    let pointer = type_getter.compiler.builder.build_pointer_cast(
+        closure.as_global_value().as_pointer_value(),
+        pointer_type,
+        &id.to_string(),
+    );
+    *id += 1;
+    let field = type_getter.compiler.builder.build_struct_gep(output, 0, &id.to_string()).unwrap();
+    *id += 1;
+    type_getter.compiler.builder.build_store(field, pointer);
+    let environment = type_getter.compiler.builder.build_pointer_cast(environment, pointer_type, &id.to_string());
+    *id += 1;
+    let field = type_getter.compiler.builder.build_struct_gep(output, 1, &id.to_string()).unwrap();
+    *id += 1;
+    type_getter.compiler.builder.build_store(field, environment);
+    return output.as_basic_value_enum();
+}
+
 /// Stores a value and then loads it
 fn store_and_load<'ctx, T: BasicType<'ctx>>(
     type_getter: &mut CompilerTypeGetter<'ctx>,
@@ -644,6 +904,7 @@ fn add_args<'ctx, 'a>(
     final_arguments: &'a mut Vec<BasicMetadataValueEnum<'ctx>>,
     type_getter: &mut CompilerTypeGetter<'ctx>,
     function: FunctionValue<'ctx>,
+    calling: &CodelessFinalizedFunction,
     arguments: &'a Vec<FinalizedEffects>,
     offset: bool,
     id: &mut u64,
@@ -652,6 +913,30 @@ fn add_args<'ctx, 'a>(
         let argument = arguments.get(i).unwrap();
         let value = compile_effect(type_getter, function, argument, id).unwrap();
 
+        // Arrays store their length before the elements, so it's split into a pointer to the elements and the length
+        if calling.arguments.get(i).is_some_and(|found| is_ptr_len(calling, &found.field.field_type)) {
+            let length = type_getter.compiler.builder.build_pointer_cast(
+                value.into_pointer_value(),
+                type_getter.compiler.context.i64_type().ptr_type(AddressSpace::default()),
+                &id.to_string(),
+            );
+            *id += 1;
+            let length = type_getter.compiler.builder.build_load(length, &id.to_string());
+            *id += 1;
+            let elements = unsafe {
+                type_getter.compiler.builder.build_gep(
+                    value.into_pointer_value(),
+                    &[type_getter.compiler.context.i64_type().const_int(1, false)],
+                    &id.to_string(),
+                )
+            };
+            *id += 1;
+
+            final_arguments.push(From::from(elements));
+            final_arguments.push(From::from(length));
+            continue;
+        }
+
         final_arguments.push(From::from(value));
     }
 }
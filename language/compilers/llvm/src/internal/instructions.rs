@@ -1,12 +1,16 @@
 use crate::compiler::CompilerImpl;
+use crate::function_compiler::compile_effect;
 use crate::internal::intrinsics::compile_llvm_intrinsics;
 use crate::internal::math_internal::math_internal;
 use crate::internal::string_internal::string_internal;
 use crate::type_getter::CompilerTypeGetter;
 use inkwell::builder::Builder;
+use inkwell::intrinsics::Intrinsic;
 use inkwell::types::{BasicType, BasicTypeEnum};
 use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, PointerValue};
-use inkwell::AddressSpace;
+use inkwell::{AddressSpace, IntPredicate};
+use syntax::program::code::{FinalizedEffectType, FinalizedEffects};
+use syntax::program::r#struct::VARIANT_FIELD;
 
 /// Compiles a method with the internal keyword
 pub fn compile_internal<'ctx>(
@@ -142,6 +146,120 @@ pub fn malloc_type<'a>(
     return malloc.into_pointer_value();
 }
 
+/// Compiles a call to the internal array index, first trapping if the index isn't less than the array's length.
+/// The array and index are only compiled once, then passed to the index call directly.
+pub fn compile_bounds_check<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    effect: &FinalizedEffects,
+    id: &mut u64,
+) -> Option<BasicValueEnum<'ctx>> {
+    let (pointer, calling, arguments) = match &effect.types {
+        FinalizedEffectType::MethodCall(pointer, calling, arguments, _) => (pointer, calling, arguments),
+        _ => panic!("Tried to bounds check a non-index effect!"),
+    };
+    let array = compile_effect(type_getter, function, &arguments[0], id).unwrap();
+    let index = compile_effect(type_getter, function, &arguments[1], id).unwrap();
+
+    let compiler = &type_getter.compiler;
+    // Arrays store their length before the elements
+    let length = compiler.builder.build_pointer_cast(
+        array.into_pointer_value(),
+        compiler.context.i64_type().ptr_type(AddressSpace::default()),
+        &id.to_string(),
+    );
+    *id += 1;
+    let length = compiler.builder.build_load(length, &id.to_string()).into_int_value();
+    *id += 1;
+    let offset = get_loaded(&compiler.builder, &index).into_int_value();
+    let out_of_bounds = compiler.builder.build_int_compare(IntPredicate::UGE, offset, length, &id.to_string());
+    *id += 1;
+
+    // Named after its id like overflow traps, so the source map can find the index's span
+    type_getter.source_map.borrow_mut().add_trap(function.get_name().to_str().unwrap(), *id, effect.span.clone());
+    let trap_block = compiler.context.append_basic_block(function, &format!("bounds{}", id));
+    let next_block = compiler.context.append_basic_block(function, &format!("checked{}", id));
+    compiler.builder.build_conditional_branch(out_of_bounds, trap_block, next_block);
+
+    compiler.builder.position_at_end(trap_block);
+    let trap = Intrinsic::find("llvm.trap").unwrap().get_declaration(&compiler.module, &[]).unwrap();
+    compiler.builder.build_call(trap, &[], "");
+    compiler.builder.build_unreachable();
+
+    compiler.builder.position_at_end(next_block);
+    type_getter.current_block = Some(next_block);
+    let calling = type_getter.get_function(calling);
+    let value = type_getter
+        .compiler
+        .builder
+        .build_call(calling, &[array.into(), index.into()], &id.to_string())
+        .try_as_basic_value()
+        .unwrap_left();
+    *id += 1;
+    if value.is_pointer_value() {
+        return Some(value);
+    }
+
+    // Values returned directly are stored like any other method call's
+    let pointer = compile_effect(type_getter, function, pointer.as_ref().unwrap(), id).unwrap().into_pointer_value();
+    let pointer = type_getter.compiler.builder.build_bitcast(
+        pointer,
+        value.get_type().ptr_type(AddressSpace::default()),
+        &id.to_string(),
+    );
+    *id += 1;
+    type_getter.compiler.builder.build_store(pointer.into_pointer_value(), value);
+    return Some(pointer);
+}
+
+/// Compiles loading a field of an enum variant, first trapping if the enum is a different variant.
+/// The enum is only compiled once, then both its variant and the field are loaded from it.
+pub fn compile_variant_check<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    effect: &FinalizedEffects,
+    discriminant: u64,
+    id: &mut u64,
+) -> Option<BasicValueEnum<'ctx>> {
+    let (loading, field) = match &effect.types {
+        FinalizedEffectType::Load(loading, field, _) => (loading, field),
+        _ => panic!("Tried to variant check a non-load effect!"),
+    };
+    let structure = loading.types.get_nongeneric_return(type_getter).unwrap();
+    // Compensate for type id
+    let offset = |name: &str| {
+        return structure.inner_struct().fields.iter().position(|found| found.field.name == name).unwrap() as u32 + 1;
+    };
+    let from = compile_effect(type_getter, function, loading, id).unwrap().into_pointer_value();
+
+    let compiler = &type_getter.compiler;
+    let variant = compiler.builder.build_struct_gep(from, offset(VARIANT_FIELD), &id.to_string()).unwrap();
+    *id += 1;
+    let variant = compiler.builder.build_load(variant, &id.to_string()).into_int_value();
+    *id += 1;
+    let expected = variant.get_type().const_int(discriminant, false);
+    let wrong_variant = compiler.builder.build_int_compare(IntPredicate::NE, variant, expected, &id.to_string());
+    *id += 1;
+
+    // Named after its id like overflow traps, so the source map can find the load's span
+    type_getter.source_map.borrow_mut().add_trap(function.get_name().to_str().unwrap(), *id, effect.span.clone());
+    let trap_block = compiler.context.append_basic_block(function, &format!("variant{}", id));
+    let next_block = compiler.context.append_basic_block(function, &format!("checked{}", id));
+    compiler.builder.build_conditional_branch(wrong_variant, trap_block, next_block);
+
+    compiler.builder.position_at_end(trap_block);
+    let trap = Intrinsic::find("llvm.trap").unwrap().get_declaration(&compiler.module, &[]).unwrap();
+    compiler.builder.build_call(trap, &[], "");
+    compiler.builder.build_unreachable();
+
+    compiler.builder.position_at_end(next_block);
+    type_getter.current_block = Some(next_block);
+    let compiler = &type_getter.compiler;
+    let gep = compiler.builder.build_struct_gep(from, offset(field), &id.to_string()).unwrap();
+    *id += 2;
+    return Some(compiler.builder.build_load(gep, &(*id - 1).to_string()));
+}
+
 /// Loads the type if it's a pointer
 fn get_loaded<'ctx>(compiler: &Builder<'ctx>, value: &BasicValueEnum<'ctx>) -> BasicValueEnum<'ctx> {
     if value.is_pointer_value() {
@@ -33,6 +33,14 @@ pub fn compile_llvm_intrinsics<'ctx>(name: &str, type_getter: &CompilerTypeGette
                 ],
                 false,
             ),
+            "snprintf" => type_getter.compiler.context.i32_type().fn_type(
+                &[
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i64_type()),
+                    BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default())),
+                ],
+                true,
+            ),
             "strlen" => type_getter.compiler.context.i64_type().fn_type(
                 &[BasicMetadataTypeEnum::from(type_getter.compiler.context.i8_type().ptr_type(AddressSpace::default()))],
                 false,
@@ -1,8 +1,13 @@
 use crate::compiler::CompilerImpl;
+use crate::function_compiler::compile_effect;
 use crate::internal::instructions::malloc_type;
 use crate::type_getter::CompilerTypeGetter;
-use inkwell::values::{BasicValueEnum, FunctionValue};
-use inkwell::{AddressSpace, IntPredicate};
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::{BasicType, BasicTypeEnum};
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
+use syntax::program::code::{FinalizedEffectType, FinalizedEffects};
+use syntax::program::types::FinalizedTypes;
 
 /// Compiles internal math functions
 pub fn math_internal<'ctx>(
@@ -80,18 +85,18 @@ pub fn math_internal<'ctx>(
         compiler.builder.build_store(malloc, returning);
         compiler.builder.build_return(Some(&malloc));
     } else if name.starts_with("math::Equal") {
-        compile_relational_op(IntPredicate::EQ, compiler, &params, type_getter);
+        compile_relational_op(IntPredicate::EQ, FloatPredicate::OEQ, compiler, &params, type_getter);
     } else if name.starts_with("math::GreaterThan") {
         if is_unsigned(name) {
-            compile_relational_op(IntPredicate::UGT, compiler, &params, type_getter)
+            compile_relational_op(IntPredicate::UGT, FloatPredicate::OGT, compiler, &params, type_getter)
         } else {
-            compile_relational_op(IntPredicate::SGT, compiler, &params, type_getter)
+            compile_relational_op(IntPredicate::SGT, FloatPredicate::OGT, compiler, &params, type_getter)
         };
     } else if name.starts_with("math::LessThan") {
         if is_unsigned(name) {
-            compile_relational_op(IntPredicate::ULT, compiler, &params, type_getter)
+            compile_relational_op(IntPredicate::ULT, FloatPredicate::OLT, compiler, &params, type_getter)
         } else {
-            compile_relational_op(IntPredicate::SLT, compiler, &params, type_getter)
+            compile_relational_op(IntPredicate::SLT, FloatPredicate::OLT, compiler, &params, type_getter)
         };
     } else if name.starts_with("math::Not") {
         let malloc = malloc_type(
@@ -207,9 +212,11 @@ pub fn math_internal<'ctx>(
     return true;
 }
 
-/// Compiles relational operators
+/// Compiles relational operators, comparing floats with the float predicate.
+/// The float predicates are ordered ones, which are false if either side is NaN.
 fn compile_relational_op(
     op: IntPredicate,
+    float_op: FloatPredicate,
     compiler: &CompilerImpl,
     params: &Vec<BasicValueEnum>,
     type_getter: &CompilerTypeGetter,
@@ -219,12 +226,13 @@ fn compile_relational_op(
         type_getter.compiler.context.bool_type().ptr_type(AddressSpace::default()).const_zero(),
         &mut 0,
     );
-    let returning = compiler.builder.build_int_compare(
-        op,
-        compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "2").into_int_value(),
-        compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3").into_int_value(),
-        "1",
-    );
+    let first = compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "2");
+    let second = compiler.builder.build_load(params.get(1).unwrap().into_pointer_value(), "3");
+    let returning = if first.is_float_value() {
+        compiler.builder.build_float_compare(float_op, first.into_float_value(), second.into_float_value(), "1")
+    } else {
+        compiler.builder.build_int_compare(op, first.into_int_value(), second.into_int_value(), "1")
+    };
     compiler.builder.build_store(malloc, returning);
     compiler.builder.build_return(Some(&malloc));
 }
@@ -236,3 +244,116 @@ fn is_unsigned(name: &String) -> bool {
     }
     return false;
 }
+
+/// Compiles a call to the internal add, subtract or multiply that traps if it overflows
+pub fn compile_overflow_check<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    effect: &FinalizedEffects,
+    id: &mut u64,
+) -> Option<BasicValueEnum<'ctx>> {
+    let (name, arguments) = match &effect.types {
+        FinalizedEffectType::MethodCall(_, calling, arguments, _) => (&calling.data.name, arguments),
+        _ => panic!("Tried to overflow check a non-math effect!"),
+    };
+
+    let mut values = Vec::default();
+    for argument in arguments {
+        let value = compile_effect(type_getter, function, argument, id).unwrap();
+        let value = if value.is_pointer_value() {
+            *id += 1;
+            type_getter.compiler.builder.build_load(value.into_pointer_value(), &(*id - 1).to_string())
+        } else {
+            value
+        };
+        values.push(value.into_int_value());
+    }
+
+    let operation = if name.starts_with("math::Add") {
+        "add"
+    } else if name.starts_with("math::Subtract") {
+        "sub"
+    } else {
+        "mul"
+    };
+    let sign = if is_unsigned(name) { "u" } else { "s" };
+    let checked = Intrinsic::find(&format!("llvm.{}{}.with.overflow", sign, operation))
+        .unwrap()
+        .get_declaration(&type_getter.compiler.module, &[values[0].get_type().as_basic_type_enum()])
+        .unwrap();
+    let compiler = &type_getter.compiler;
+    let result = compiler
+        .builder
+        .build_call(checked, &[values[0].into(), values[1].into()], &id.to_string())
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_struct_value();
+    *id += 1;
+    let value = compiler.builder.build_extract_value(result, 0, &id.to_string()).unwrap();
+    *id += 1;
+    let overflowed = compiler.builder.build_extract_value(result, 1, &id.to_string()).unwrap().into_int_value();
+    *id += 1;
+
+    // The trap's block is named after its id, so its span can be found from the block's name
+    type_getter.source_map.borrow_mut().add_trap(function.get_name().to_str().unwrap(), *id, effect.span.clone());
+    let trap_block = compiler.context.append_basic_block(function, &format!("overflow{}", id));
+    let next_block = compiler.context.append_basic_block(function, &format!("checked{}", id));
+    compiler.builder.build_conditional_branch(overflowed, trap_block, next_block);
+
+    compiler.builder.position_at_end(trap_block);
+    let trap = Intrinsic::find("llvm.trap").unwrap().get_declaration(&compiler.module, &[]).unwrap();
+    compiler.builder.build_call(trap, &[], "");
+    compiler.builder.build_unreachable();
+
+    compiler.builder.position_at_end(next_block);
+    type_getter.current_block = Some(next_block);
+    let malloc = malloc_type(type_getter, values[0].get_type().ptr_type(AddressSpace::default()).const_zero(), id);
+    type_getter.compiler.builder.build_store(malloc, value);
+    return Some(malloc.as_basic_value_enum());
+}
+
+/// Compiles a cast between built-in numbers, converting between integer sizes, floats, and integers and floats
+pub fn compile_cast<'ctx>(
+    type_getter: &mut CompilerTypeGetter<'ctx>,
+    function: FunctionValue<'ctx>,
+    value: &FinalizedEffects,
+    from: &FinalizedTypes,
+    to: &FinalizedTypes,
+    id: &mut u64,
+) -> Option<BasicValueEnum<'ctx>> {
+    let value = compile_effect(type_getter, function, value, id).unwrap();
+    let value = if value.is_pointer_value() {
+        *id += 1;
+        type_getter.compiler.builder.build_load(value.into_pointer_value(), &(*id - 1).to_string())
+    } else {
+        value
+    };
+    let target = type_getter.get_type(to);
+    let signed = !is_unsigned(&from.inner_struct().data.name);
+    let builder = &type_getter.compiler.builder;
+    let name = id.to_string();
+    *id += 1;
+    let output = match (value, target) {
+        (BasicValueEnum::IntValue(int), BasicTypeEnum::IntType(types)) => {
+            builder.build_int_cast_sign_flag(int, types, signed, &name).as_basic_value_enum()
+        }
+        (BasicValueEnum::IntValue(int), BasicTypeEnum::FloatType(types)) if signed => {
+            builder.build_signed_int_to_float(int, types, &name).as_basic_value_enum()
+        }
+        (BasicValueEnum::IntValue(int), BasicTypeEnum::FloatType(types)) => {
+            builder.build_unsigned_int_to_float(int, types, &name).as_basic_value_enum()
+        }
+        (BasicValueEnum::FloatValue(float), BasicTypeEnum::IntType(types)) if is_unsigned(&to.inner_struct().data.name) => {
+            builder.build_float_to_unsigned_int(float, types, &name).as_basic_value_enum()
+        }
+        (BasicValueEnum::FloatValue(float), BasicTypeEnum::IntType(types)) => {
+            builder.build_float_to_signed_int(float, types, &name).as_basic_value_enum()
+        }
+        (BasicValueEnum::FloatValue(float), BasicTypeEnum::FloatType(types)) => {
+            builder.build_float_cast(float, types, &name).as_basic_value_enum()
+        }
+        _ => panic!("Tried to cast {} to {}, which aren't both numbers!", from, to),
+    };
+    return Some(output);
+}
@@ -15,7 +15,7 @@ pub fn string_internal<'ctx>(
     let params = value.get_params();
     if name.starts_with("string::Cast") {
         type_getter.compiler.builder.build_return(Some(value.get_params().first().unwrap()));
-    } else if name.starts_with("string::Add<char + u64>_char::add") {
+    } else if name.starts_with("string::Add<u64 + char>_char::add") {
         let pointer_type = params.first().unwrap().into_pointer_value();
         let malloc = malloc_type(type_getter, pointer_type.get_type().const_zero(), &mut 0);
         let pointer_type = compiler
@@ -113,7 +113,7 @@ pub fn string_internal<'ctx>(
             .unwrap_left()
             .into_pointer_value();
         type_getter.compiler.builder.build_return(Some(&malloc.as_basic_value_enum()));
-    } else if name.starts_with("string::Add<str + char>_str::add") {
+    } else if name.starts_with("string::Add<char + str>_str::add") {
         let length = type_getter
             .compiler
             .builder
@@ -181,6 +181,35 @@ pub fn string_internal<'ctx>(
             type_getter.compiler.context.i8_type().const_zero(),
         );
         type_getter.compiler.builder.build_return(Some(&malloc.as_basic_value_enum()));
+    } else if name.starts_with("string::ToString_") {
+        // The impl's name has the integer type, like string::ToString_i64::to_string
+        let signed = name.starts_with("string::ToString_i");
+        let number = compiler.builder.build_load(params.first().unwrap().into_pointer_value(), "0").into_int_value();
+        let number = compiler.builder.build_int_cast_sign_flag(number, compiler.context.i64_type(), signed, "1");
+        // The longest 64-bit integer, -9223372036854775808, is 20 characters plus the null terminator
+        let length = compiler.context.i64_type().const_int(21, false);
+        let malloc = compiler
+            .builder
+            .build_call(
+                compiler.module.get_function("malloc").unwrap_or_else(|| compile_llvm_intrinsics("malloc", type_getter)),
+                &[BasicMetadataValueEnum::IntValue(length)],
+                "2",
+            )
+            .try_as_basic_value()
+            .unwrap_left()
+            .into_pointer_value();
+        let format = compiler.builder.build_global_string_ptr(if signed { "%lld" } else { "%llu" }, "3");
+        compiler.builder.build_call(
+            compiler.module.get_function("snprintf").unwrap_or_else(|| compile_llvm_intrinsics("snprintf", type_getter)),
+            &[
+                BasicMetadataValueEnum::PointerValue(malloc),
+                BasicMetadataValueEnum::IntValue(length),
+                BasicMetadataValueEnum::PointerValue(format.as_pointer_value()),
+                BasicMetadataValueEnum::IntValue(number),
+            ],
+            "4",
+        );
+        compiler.builder.build_return(Some(&malloc.as_basic_value_enum()));
     } else {
         return false;
     }
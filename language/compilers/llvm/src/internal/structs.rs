@@ -13,6 +13,8 @@ pub fn get_internal_struct<'ctx>(context: &'ctx Context, name: &str) -> Option<B
         "u16" => Some(context.i16_type().as_basic_type_enum()),
         "u8" => Some(context.i8_type().as_basic_type_enum()),
         "bool" => Some(context.bool_type().as_basic_type_enum()),
+        "f64" => Some(context.f64_type().as_basic_type_enum()),
+        "f32" => Some(context.f32_type().as_basic_type_enum()),
         _ => None,
     };
 }
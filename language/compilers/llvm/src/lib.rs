@@ -1,18 +1,23 @@
 #![feature(get_mut_unchecked, box_into_inner)]
 
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use dashmap::DashMap;
 use inkwell::context::Context;
+use inkwell::execution_engine::ExecutionEngine;
 use tokio::sync::mpsc::Receiver;
 
 use async_trait::async_trait;
+use data::tokens::Span;
 use data::CompilerArguments;
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::function::FinalizedFunction;
 use syntax::program::r#struct::FinalizedStruct;
-use syntax::program::syntax::{Compiler, Syntax};
+use syntax::program::source_map::SourceMap;
+use syntax::program::syntax::{Compiler, Syntax, UnsafeFn};
 
 use crate::compiler::CompilerImpl;
 use crate::type_getter::CompilerTypeGetter;
@@ -37,7 +42,11 @@ pub struct LLVMCompiler {
     compiling: Arc<DashMap<String, Arc<FinalizedFunction>>>,
     struct_compiling: Arc<DashMap<String, Arc<FinalizedStruct>>>,
     arguments: CompilerArguments,
+    /// The engines holding the code compile_all returned handles to. Fields are dropped in order,
+    /// so these are dropped before the context they were made from.
+    engines: Mutex<Vec<ExecutionEngine<'static>>>,
     context: Context,
+    source_map: Mutex<SourceMap>,
 }
 
 /// SAFETY: LLVMCompiler isn't actually multi-threaded, so this is safe
@@ -53,7 +62,14 @@ impl LLVMCompiler {
         struct_compiling: Arc<DashMap<String, Arc<FinalizedStruct>>>,
         arguments: CompilerArguments,
     ) -> Self {
-        return Self { compiling, struct_compiling, arguments, context: Context::create() };
+        return Self {
+            compiling,
+            struct_compiling,
+            arguments,
+            engines: Mutex::new(Vec::default()),
+            context: Context::create(),
+            source_map: Mutex::new(SourceMap::default()),
+        };
     }
 }
 
@@ -64,7 +80,13 @@ impl<T> Compiler<T> for LLVMCompiler {
         if let Some(main) = CompilerImpl::get_main(&self.arguments, syntax).await {
             if receiver.recv().await.is_some() {
                 let mut binding = CompilerTypeGetter::new(Rc::new(CompilerImpl::new(&self.context)), syntax.clone());
+                binding.vtable.borrow_mut().position_independent = self.arguments.position_independent_vtables;
                 CompilerImpl::compile(main, &mut binding, &self.compiling, &self.struct_compiling);
+                *self.source_map.lock().unwrap() = binding.source_map.borrow().clone();
+                // Code that failed to compile isn't run
+                if !binding.errors.borrow().is_empty() {
+                    return None;
+                }
                 return binding.get_target(&self.arguments.target).map(|inner| unsafe { inner.call() });
             }
         } else {
@@ -73,4 +95,58 @@ impl<T> Compiler<T> for LLVMCompiler {
 
         return None;
     }
+
+    /// Compiles every entry point into one program and returns a handle to each of them, with a receiver like
+    /// compile's. The handles can be called until this compiler is dropped
+    async fn compile_all(
+        &self,
+        mut receiver: Receiver<()>,
+        names: &[&str],
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Result<HashMap<String, UnsafeFn>, Vec<ParsingError>> {
+        let mut entries = Vec::default();
+        let mut errors = Vec::default();
+        for name in names {
+            match CompilerImpl::get_entry(name, syntax).await {
+                Ok(found) => entries.push(found),
+                Err(error) => errors.push(error),
+            }
+        }
+        if receiver.recv().await.is_none() || !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut binding = CompilerTypeGetter::new(Rc::new(CompilerImpl::new(&self.context)), syntax.clone());
+        binding.vtable.borrow_mut().position_independent = self.arguments.position_independent_vtables;
+        CompilerImpl::compile_all(&entries, &mut binding, &self.compiling, &self.struct_compiling);
+        *self.source_map.lock().unwrap() = binding.source_map.borrow().clone();
+        errors.append(&mut binding.errors.borrow_mut());
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let engine = &binding.compiler.execution_engine;
+        let mut handles = HashMap::default();
+        for entry in &entries {
+            match engine.get_function_address(&entry.data.name) {
+                Ok(address) => {
+                    handles.insert(entry.data.name.clone(), UnsafeFn(address));
+                }
+                Err(_) => {
+                    errors.push(Span::default().make_error(ParsingMessage::UncompiledEntryPoint(entry.data.name.clone())))
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        // SAFETY: The engine is dropped before the context it borrows, see engines
+        let engine = unsafe { std::mem::transmute::<ExecutionEngine<'_>, ExecutionEngine<'static>>(engine.clone()) };
+        self.engines.lock().unwrap().push(engine);
+        return Ok(handles);
+    }
+
+    /// Gets the source map of the last compiled program
+    fn source_map(&self) -> SourceMap {
+        return self.source_map.lock().unwrap().clone();
+    }
 }
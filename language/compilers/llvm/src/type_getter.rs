@@ -14,7 +14,9 @@ use inkwell::execution_engine::JitFunction;
 use inkwell::types::{BasicType, BasicTypeEnum};
 use inkwell::values::{BasicValueEnum, FunctionValue};
 use inkwell::AddressSpace;
+use syntax::errors::ParsingError;
 use syntax::program::function::{CodelessFinalizedFunction, FinalizedFunction};
+use syntax::program::source_map::SourceMap;
 use syntax::program::syntax::{Main, Syntax};
 use syntax::program::types::FinalizedTypes;
 use syntax::VariableManager;
@@ -35,6 +37,10 @@ pub struct CompilerTypeGetter<'ctx> {
     pub current_block: Option<BasicBlock<'ctx>>,
     /// Current function's variables
     pub variables: HashMap<String, (FinalizedTypes, BasicValueEnum<'ctx>)>,
+    /// Maps the generated code back to the spans of the effects it's compiled from
+    pub source_map: Rc<RefCell<SourceMap>>,
+    /// Errors found while compiling, the program isn't run if there are any
+    pub errors: Rc<RefCell<Vec<ParsingError>>>,
 }
 
 impl<'ctx> CompilerTypeGetter<'ctx> {
@@ -48,6 +54,8 @@ impl<'ctx> CompilerTypeGetter<'ctx> {
             blocks: HashMap::default(),
             current_block: None,
             variables: HashMap::default(),
+            source_map: Rc::new(RefCell::new(SourceMap::default())),
+            errors: Rc::new(RefCell::new(Vec::default())),
         };
     }
 
@@ -67,6 +75,23 @@ impl<'ctx> CompilerTypeGetter<'ctx> {
             blocks: self.blocks.clone(),
             current_block: self.current_block.clone(),
             variables,
+            source_map: self.source_map.clone(),
+            errors: self.errors.clone(),
+        };
+    }
+
+    /// Gets a CompilerTypeGetter for a closure's function, which has its own blocks and variables
+    pub fn for_closure(&self) -> Self {
+        return Self {
+            syntax: self.syntax.clone(),
+            vtable: self.vtable.clone(),
+            compiler: self.compiler.clone(),
+            compiling: self.compiling.clone(),
+            blocks: HashMap::default(),
+            current_block: None,
+            variables: HashMap::default(),
+            source_map: self.source_map.clone(),
+            errors: self.errors.clone(),
         };
     }
 
@@ -88,7 +113,7 @@ impl<'ctx> CompilerTypeGetter<'ctx> {
         }
         .as_basic_type_enum();
         return match types {
-            FinalizedTypes::Struct(_) | FinalizedTypes::Array(_) => found,
+            FinalizedTypes::Struct(_) | FinalizedTypes::Array(_, _) | FinalizedTypes::Function(_, _) => found,
             FinalizedTypes::Reference(_) => found.ptr_type(AddressSpace::default()).as_basic_type_enum(),
             _ => panic!("Can't compile a generic! {:?}", found),
         };
@@ -1,11 +1,13 @@
 use crate::type_getter::CompilerTypeGetter;
+use inkwell::attributes::{Attribute as LLVMAttribute, AttributeLoc};
 use inkwell::module::Linkage;
 use inkwell::types::BasicType;
 use inkwell::values::FunctionValue;
 use std::ops::Deref;
 use std::sync::Arc;
-use syntax::program::function::CodelessFinalizedFunction;
+use syntax::program::function::{Abi, CodelessFinalizedFunction, Inline};
 use syntax::program::types::FinalizedTypes;
+use syntax::{is_modifier, Attribute, Modifier};
 
 /// Prints an unformatted string (like the one returned by LLVM's to_string method
 pub fn print_formatted(input: String) {
@@ -38,9 +40,19 @@ pub fn create_function_value<'ctx>(
     linkage: Option<Linkage>,
 ) -> FunctionValue<'ctx> {
     let mut params = Vec::default();
+    let mut variadic = false;
+    let external = is_modifier(function.data.modifiers, Modifier::Extern);
 
     for param in &function.arguments {
+        // Extern variadics are passed through to the C function as varargs
+        if is_modifier(param.modifiers, Modifier::Variadic) && external {
+            variadic = true;
+            continue;
+        }
         params.push(From::from(type_getter.get_type(&param.field.field_type)));
+        if is_ptr_len(function, &param.field.field_type) {
+            params.push(From::from(type_getter.compiler.context.i64_type()));
+        }
     }
 
     let llvm_function = match &function.return_type {
@@ -56,11 +68,46 @@ pub fn create_function_value<'ctx>(
                 params.insert(0, From::from(types.ptr_type(AddressSpace::default())));
                 type_getter.compiler.context.void_type().fn_type(params.as_slice(), false)
             } else {*/
-            types.fn_type(params.as_slice(), false)
+            types.fn_type(params.as_slice(), variadic)
             //}
         }
-        None => type_getter.compiler.context.void_type().fn_type(params.as_slice(), false),
+        None => type_getter.compiler.context.void_type().fn_type(params.as_slice(), variadic),
+    };
+
+    let llvm_function = type_getter.compiler.module.add_function(&function.data.name, llvm_function, linkage);
+    if external {
+        llvm_function.set_call_conventions(calling_convention(function.data.abi));
+        type_getter.source_map.borrow_mut().add_call_convention(&function.data.name, llvm_function.get_call_conventions());
+    }
+    let inline = match function.data.inline {
+        Inline::Auto => None,
+        Inline::Always => Some("alwaysinline"),
+        Inline::Never => Some("noinline"),
     };
+    if let Some(inline) = inline {
+        let kind = LLVMAttribute::get_named_enum_kind_id(inline);
+        llvm_function.add_attribute(AttributeLoc::Function, type_getter.compiler.context.create_enum_attribute(kind, 0));
+    }
+    return llvm_function;
+}
+
+/// Gets LLVM's calling convention number for the ABI of an extern function
+pub fn calling_convention(abi: Abi) -> u32 {
+    return match abi {
+        Abi::C => 0,
+        // The system ABI is only different from C on 32-bit Windows
+        Abi::System if cfg!(all(windows, target_arch = "x86")) => 64,
+        Abi::System => 0,
+        Abi::StdCall => 64,
+        Abi::FastCall => 65,
+        Abi::SysV64 => 78,
+        Abi::Win64 => 79,
+    };
+}
 
-    return type_getter.compiler.module.add_function(&function.data.name, llvm_function, linkage);
+/// Checks if the argument is a slice passed to an extern as a pointer to the elements and a length
+pub fn is_ptr_len(function: &CodelessFinalizedFunction, types: &FinalizedTypes) -> bool {
+    return is_modifier(function.data.modifiers, Modifier::Extern)
+        && Attribute::find_attribute("ptr_len", &function.data.attributes).is_some()
+        && types.is_array();
 }
@@ -1,5 +1,7 @@
 use crate::type_getter::CompilerTypeGetter;
-use inkwell::values::{BasicValue, GlobalValue};
+use inkwell::module::Linkage;
+use inkwell::types::BasicType;
+use inkwell::values::{BasicValue, BasicValueEnum, GlobalValue};
 use inkwell::AddressSpace;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -12,6 +14,8 @@ use syntax::program::types::FinalizedTypes;
 pub struct VTableManager<'ctx> {
     // All the current generated VTables sorted by the parent type and the implemented trait
     data: HashMap<(Arc<StructData>, Arc<StructData>), GlobalValue<'ctx>>,
+    /// Whether entries are offsets from the vtable instead of function addresses
+    pub position_independent: bool,
 }
 
 impl<'ctx> VTableManager<'ctx> {
@@ -26,22 +30,55 @@ impl<'ctx> VTableManager<'ctx> {
         if let Some(found) = self.data.get(&(structure.inner_struct().data.clone(), target.inner_struct().data.clone())) {
             return *found;
         }
-        let mut values = Vec::default();
-        {
-            for found in functions {
-                let func = type_getter.get_function(found);
-                values.push(func.as_global_value().as_basic_value_enum());
+        let symbol = target.inner_struct().data.vtable_symbol(&structure.inner_struct().data);
+        let global = if self.position_independent {
+            Self::relative_vtable(type_getter, &symbol, functions)
+        } else {
+            let mut values = Vec::default();
+            {
+                // The checker already put the functions in the trait's layout order, matching the index of virtual calls
+                for found in functions {
+                    let func = type_getter.get_function(found);
+                    values.push(func.as_global_value().as_basic_value_enum());
+                }
             }
-        }
+            let value = type_getter.compiler.context.const_struct(values.as_slice(), false);
+            let global = type_getter.compiler.module.add_global(value.get_type(), Some(AddressSpace::default()), &symbol);
+            global.set_initializer(&value.as_basic_value_enum());
+            global
+        };
+        type_getter.source_map.borrow_mut().add_vtable(&symbol, self.position_independent);
         let structure = structure.inner_struct().data.clone();
-        let value = type_getter.compiler.context.const_struct(values.as_slice(), false);
+        self.data.insert((structure.clone(), target.inner_struct().data.clone()), global);
+        return *self.data.get(&(structure.clone(), target.inner_struct().data.clone())).unwrap();
+    }
+
+    /// Makes a vtable whose entries are i32 offsets from the vtable to each function, which don't need relocating.
+    /// It's linkonce_odr, so each module using it can have its own copy and the linker keeps one.
+    fn relative_vtable(
+        type_getter: &mut CompilerTypeGetter<'ctx>,
+        symbol: &str,
+        functions: &Vec<Arc<CodelessFinalizedFunction>>,
+    ) -> GlobalValue<'ctx> {
+        let context = type_getter.compiler.context;
+        let entries = vec![context.i32_type().as_basic_type_enum(); functions.len()];
+        // The global has to exist before its entries can be offsets from its address
         let global = type_getter.compiler.module.add_global(
-            value.get_type(),
+            context.struct_type(entries.as_slice(), false),
             Some(AddressSpace::default()),
-            &format!("{}_vtable", structure.name),
+            symbol,
         );
-        global.set_initializer(&value.as_basic_value_enum());
-        self.data.insert((structure.clone(), target.inner_struct().data.clone()), global);
-        return *self.data.get(&(structure.clone(), target.inner_struct().data.clone())).unwrap();
+        let base = global.as_pointer_value().const_to_int(context.i64_type());
+        let mut values: Vec<BasicValueEnum<'ctx>> = Vec::default();
+        for found in functions {
+            let func = type_getter.get_function(found).as_global_value().as_pointer_value();
+            let offset = func.const_to_int(context.i64_type()).const_sub(base);
+            values.push(offset.const_truncate(context.i32_type()).as_basic_value_enum());
+        }
+        global.set_initializer(&context.const_struct(values.as_slice(), false));
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        global.set_linkage(Linkage::LinkOnceODR);
+        return global;
     }
 }
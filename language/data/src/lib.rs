@@ -32,6 +32,19 @@ pub struct CompilerArguments {
     pub target: String,
     /// The temp folder to use while compiling
     pub temp_folder: PathBuf,
+    /// Whether warnings stop the program from compiling like errors do
+    pub deny_warnings: bool,
+    /// The features enabled for #[cfg(feature)] attributes
+    pub features: Vec<String>,
+    /// Prints the checked code of every function whose name starts with this, for debugging the checker
+    pub dump_ir: Option<String>,
+    /// Whether array indices that can't be checked while compiling are checked against the array's length when they run
+    pub bounds_checks: bool,
+    /// Whether NaN and Inf literals are errors, for programs whose floats should never have those values
+    pub reject_special_floats: bool,
+    /// Whether vtables hold offsets from themselves instead of function addresses, so code loaded as a shared library
+    /// doesn't need its vtables relocated
+    pub position_independent_vtables: bool,
 }
 
 /// Arguments for running Raven
@@ -78,12 +78,29 @@ pub struct Span {
     pub start: usize,
     /// The end index in the list of tokens
     pub end: usize,
+    /// What the code was generated from, if it was expanded from code somewhere else instead of written here
+    pub expansion: Option<Box<Expansion>>,
+}
+
+/// Where generated code came from, like the macro call or operator it was expanded from
+#[derive(Clone, Debug)]
+pub struct Expansion {
+    /// What was expanded, like "operator +"
+    pub name: String,
+    /// The code that was expanded
+    pub origin: Span,
 }
 
 impl Span {
     /// Creates a new span
     pub fn new(file: u64, index: usize) -> Self {
-        return Self { file, start: index, end: index };
+        return Self { file, start: index, end: index, expansion: None };
+    }
+
+    /// Marks the span as code generated by expanding the named code at the origin
+    pub fn expanded_from(mut self, name: String, origin: Span) -> Span {
+        self.expansion = Some(Box::new(Expansion { name, origin }));
+        return self;
     }
 
     /// Extends the span to encompass more tokens
@@ -95,6 +112,55 @@ impl Span {
     pub fn extend_span(&mut self, end: usize) {
         self.end = end;
     }
+
+    /// Gets the smallest span covering both spans, like a receiver through the last argument of a call.
+    /// Spans in another file or without a location can't be covered, so the other span is returned as-is.
+    pub fn merge(&self, other: &Span) -> Span {
+        if other.is_default() || other.file != self.file && !self.is_default() {
+            return self.clone();
+        }
+        if self.is_default() {
+            return other.clone();
+        }
+        return Span {
+            file: self.file,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+            expansion: self.expansion.clone(),
+        };
+    }
+
+    /// Checks if the span was never set to a location, like one from Span::default()
+    pub fn is_default(&self) -> bool {
+        return self.file == 0 && self.start == 0 && self.end == 0;
+    }
+
+    /// Gets the 1-based line and column of the span's start and end in the source, using the file's tokens.
+    /// Returns None if the span is outside of the tokens.
+    pub fn line_columns(&self, tokens: &[Token], source: &str) -> Option<((u32, u32), (u32, u32))> {
+        let start = tokens.get(self.start)?.start_offset;
+        let end = tokens.get(self.end)?.end_offset;
+        return Some((line_column(source, start), line_column(source, end)));
+    }
+}
+
+/// Converts a byte offset in the source to a 1-based line and column.
+/// Columns count characters, so multi-byte UTF-8 characters only take up one column.
+pub fn line_column(source: &str, offset: usize) -> (u32, u32) {
+    let mut line = 1;
+    let mut column = 1;
+    for (index, character) in source.char_indices() {
+        if index >= offset {
+            break;
+        }
+        if character == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    return (line, column);
 }
 
 /// The different types of tokens.
@@ -250,4 +316,89 @@ pub enum TokenTypes {
     Char = 70,
     /// A blank line
     BlankLine = 71,
+    /// A trait inherited by the trait being declared (trait Sub: Super)
+    Supertrait = 72,
+    /// The fn keyword starting a closure inside code
+    Closure = 73,
+    /// The start of an enum ("enum")
+    EnumStart = 74,
+    /// The name of a single variant of an enum
+    Variant = 75,
+    /// The end of a single variant of an enum (",")
+    VariantEnd = 76,
+    /// The calling convention after the extern modifier, like "C" in extern "C"
+    Abi = 77,
+    /// The start of a type alias ("type")
+    TypeAliasStart = 78,
+    /// The name of a type alias with its generics, like Pair<T>
+    TypeAliasName = 79,
+    /// The = between a type alias and its type
+    TypeAliasSeparator = 80,
+    /// The type a type alias stands for
+    TypeAliasType = 81,
+    /// The end of a type alias (";")
+    TypeAliasEnd = 82,
+    /// The start of a constant ("const")
+    ConstStart = 83,
+    /// The = between an argument's type and its default value
+    ArgumentDefault = 84,
+    /// The part of a string before the { starting an interpolated expression, like "x is {" in "x is {x}"
+    InterpolationStart = 85,
+    /// A compile-time assert ("static_assert")
+    StaticAssert = 86,
+    /// A cast to another type ("as")
+    As = 87,
+    /// The start of a function's where clause ("where")
+    Where = 88,
+    /// The type bounded by a where clause, which is always Self
+    WhereType = 89,
+    /// A trait the type in a where clause must implement, like Other in "where Self: Other"
+    WhereBound = 90,
+    /// The continue keyword
+    Continue = 91,
+    /// A loop's label, like 'outer in 'outer: while or break 'outer
+    Label = 92,
+    /// The type a trait's generic defaults to when an impl leaves it out, like the = Self in "Add<E = Self>"
+    GenericDefault = 93,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tokens::{line_column, Span, Token, TokenTypes};
+
+    /// Checks merged spans cover both spans, ignoring spans without a location or in another file
+    #[test]
+    pub fn test_span_merge() {
+        let location = |span: Span| (span.file, span.start, span.end);
+        let first = Span { file: 1, start: 4, end: 6, ..Span::default() };
+        assert_eq!(location(first.merge(&Span { file: 1, start: 8, end: 9, ..Span::default() })), (1, 4, 9));
+        assert_eq!(location(first.merge(&Span { file: 1, start: 2, end: 5, ..Span::default() })), (1, 2, 6));
+        assert_eq!(location(first.merge(&Span { file: 1, start: 5, end: 5, ..Span::default() })), (1, 4, 6));
+        assert_eq!(location(first.merge(&Span::default())), (1, 4, 6));
+        assert_eq!(location(Span::default().merge(&first)), (1, 4, 6));
+        assert_eq!(location(first.merge(&Span { file: 2, start: 0, end: 9, ..Span::default() })), (1, 4, 6));
+    }
+
+    /// Checks byte offsets are converted to lines and columns counting characters instead of bytes
+    #[test]
+    pub fn test_line_column() {
+        let source = "fn main() {\n    let x = 1;\n}";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 3), (1, 4));
+        assert_eq!(line_column(source, 16), (2, 5));
+
+        let source = "// héllo wörld\nlet ünïcode = 1;";
+        let offset = source.find("wörld").unwrap();
+        assert_eq!(line_column(source, offset), (1, 10));
+        let offset = source.find("= 1").unwrap();
+        assert_eq!(line_column(source, offset), (2, 13));
+
+        let tokens = vec![
+            Token::new(TokenTypes::Start, (1, 0), 0, (1, 0), 0),
+            Token::new(TokenTypes::Variable, (2, 4), source.find("ünïcode").unwrap(), (2, 13), offset - 1),
+        ];
+        let span = Span { file: 0, start: 1, end: 1, ..Span::default() };
+        assert_eq!(span.line_columns(&tokens, source), Some(((2, 5), (2, 12))));
+        assert_eq!(Span { file: 0, start: 2, end: 2, ..Span::default() }.line_columns(&tokens, source), None);
+    }
 }
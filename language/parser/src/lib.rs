@@ -11,10 +11,14 @@ use std::sync::Mutex;
 use std::{fs, path};
 
 use anyhow::Error;
+use indexmap::IndexMap;
+use tokio::sync::mpsc::UnboundedSender;
 
 use data::tokens::{Token, TokenTypes};
 use data::{Readable, SourceSet};
 use syntax::async_util::{HandleWrapper, NameResolver, UnparsedType};
+use syntax::program::constant::UnfinalizedConstant;
+use syntax::program::function::ParsedFunction;
 use syntax::program::syntax::Syntax;
 
 use crate::parser::top_parser::parse_top;
@@ -28,6 +32,30 @@ pub mod tokens;
 
 /// Parses a file into the syntax
 pub async fn parse(syntax: Arc<Mutex<Syntax>>, handle: Arc<Mutex<HandleWrapper>>, name: String, file: Box<dyn Readable>) {
+    parse_file(syntax, handle, name, file, None);
+}
+
+/// Parses a file like parse, but sends each top function on the channel as soon as it's parsed,
+/// for a checker consuming it with TypesChecker::check_stream to check while the rest is parsed.
+/// The sender is dropped once the file is parsed, so the checker stops once every file streaming to it is.
+pub async fn parse_streaming(
+    syntax: Arc<Mutex<Syntax>>,
+    handle: Arc<Mutex<HandleWrapper>>,
+    name: String,
+    file: Box<dyn Readable>,
+    functions: UnboundedSender<ParsedFunction>,
+) {
+    parse_file(syntax, handle, name, file, Some(functions));
+}
+
+/// Parses a file, sending its top functions on the channel if there is one
+fn parse_file(
+    syntax: Arc<Mutex<Syntax>>,
+    handle: Arc<Mutex<HandleWrapper>>,
+    name: String,
+    file: Box<dyn Readable>,
+    functions: Option<UnboundedSender<ParsedFunction>>,
+) {
     let buffer = file.contents();
     let mut parser_utils = ParserUtils {
         buffer: buffer.as_bytes(),
@@ -38,6 +66,8 @@ pub async fn parse(syntax: Arc<Mutex<Syntax>>, handle: Arc<Mutex<HandleWrapper>>
         file_name: name.clone(),
         imports: ImportNameResolver::new(name.clone()),
         handle,
+        functions,
+        depth: 0,
     };
 
     parse_top(&mut parser_utils);
@@ -49,7 +79,9 @@ pub struct ImportNameResolver {
     /// The current file imports
     pub imports: Vec<String>,
     /// The current generics
-    pub generics: HashMap<String, Vec<UnparsedType>>,
+    pub generics: IndexMap<String, Vec<UnparsedType>>,
+    /// The constants declared so far in the file
+    pub constants: HashMap<String, Arc<UnfinalizedConstant>>,
     /// The parent type
     pub parent: Option<UnparsedType>,
     /// Last ID used on a code block label
@@ -59,7 +91,13 @@ pub struct ImportNameResolver {
 impl ImportNameResolver {
     /// Creates a new name resolver
     pub fn new(base: String) -> Self {
-        return Self { imports: vec![base], generics: HashMap::default(), parent: None, last_id: 0 };
+        return Self {
+            imports: vec![base],
+            generics: IndexMap::default(),
+            constants: HashMap::default(),
+            parent: None,
+            last_id: 0,
+        };
     }
 }
 
@@ -68,14 +106,27 @@ impl NameResolver for ImportNameResolver {
         return &self.imports;
     }
 
+    fn module(&self) -> &str {
+        // The file's own module is always the first import
+        return &self.imports[0];
+    }
+
     fn generic(&self, name: &String) -> Option<Vec<UnparsedType>> {
         return self.generics.get(name).cloned();
     }
 
-    fn generics(&self) -> &HashMap<String, Vec<UnparsedType>> {
+    fn generics(&self) -> &IndexMap<String, Vec<UnparsedType>> {
         return &self.generics;
     }
 
+    fn constant(&self, name: &String) -> Option<Arc<UnfinalizedConstant>> {
+        return self.constants.get(name).cloned();
+    }
+
+    fn self_type(&self) -> Option<UnparsedType> {
+        return self.parent.clone();
+    }
+
     fn boxed_clone(&self) -> Box<dyn NameResolver> {
         return Box::new(self.clone());
     }
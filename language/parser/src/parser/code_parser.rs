@@ -1,4 +1,4 @@
-use crate::parser::control_parser::{parse_do_while, parse_for, parse_if, parse_while};
+use crate::parser::control_parser::{parse_do_while, parse_for, parse_if, parse_labeled, parse_switch, parse_while};
 use crate::parser::operator_parser::parse_operator;
 use crate::parser::util::{parse_generics, ParserUtils};
 use data::tokens::{Span, Token, TokenTypes};
@@ -6,11 +6,23 @@ use std::mem;
 use syntax::async_util::UnparsedType;
 use syntax::errors::ParsingError;
 use syntax::errors::{ErrorSource, ParsingMessage};
-use syntax::program::code::{EffectType, Effects, Expression, ExpressionType};
+use syntax::program::code::{EffectType, Effects, Expression, ExpressionType, Pattern};
 use syntax::program::function::CodeBody;
+use syntax::program::r#struct::integer_max;
 
 /// Parsers a block of code into its return type (if all code paths lead to a single type, or else a line) and the code body.
 pub fn parse_code(parser_utils: &mut ParserUtils) -> Result<(ExpressionType, CodeBody), ParsingError> {
+    // The block is another level of nesting inside of whatever it's in, like an if statement
+    parser_utils.depth += 1;
+    let lines = parse_lines(parser_utils);
+    parser_utils.depth -= 1;
+    let (types, lines) = lines?;
+    parser_utils.imports.last_id += 1;
+    return Ok((types, CodeBody::new(lines, (parser_utils.imports.last_id - 1).to_string())));
+}
+
+/// Parses every line in a block of code, along with the block's return type
+fn parse_lines(parser_utils: &mut ParserUtils) -> Result<(ExpressionType, Vec<Expression>), ParsingError> {
     let mut lines = Vec::default();
     let mut types = ExpressionType::Line;
     while let Some(expression) = parse_line(parser_utils, ParseState::None)? {
@@ -19,8 +31,7 @@ pub fn parse_code(parser_utils: &mut ParserUtils) -> Result<(ExpressionType, Cod
         }
         lines.push(expression);
     }
-    parser_utils.imports.last_id += 1;
-    return Ok((types, CodeBody::new(lines, (parser_utils.imports.last_id - 1).to_string())));
+    return Ok((types, lines));
 }
 
 /// The state of the parser
@@ -46,9 +57,25 @@ pub enum ParseState {
     New,
 }
 
-/// Parses a single line of code
-// skipcq: RS-R1000 Match statements have complexity calculated incorrectly
+/// The most lines that can be nested inside each other, like the parenthesis in ((1)), before it's an error.
+/// Each level of nesting is another call to parse_line, so this keeps the parser inside of its thread's stack.
+pub const MAX_NESTING: usize = 64;
+
+/// Parses a single line of code, erroring if it's nested too deep inside other lines
 pub fn parse_line(parser_utils: &mut ParserUtils, state: ParseState) -> Result<Option<Expression>, ParsingError> {
+    if parser_utils.depth >= MAX_NESTING {
+        return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::NestingTooDeep(MAX_NESTING)));
+    }
+    parser_utils.depth += 1;
+    let output = parse_nested_line(parser_utils, state);
+    parser_utils.depth -= 1;
+    return output;
+}
+
+/// Parses a single line of code, see parse_line.
+/// Tokens are handled in their own functions, which keeps this function's stack frame small since it's on the stack
+/// once for each level of nesting.
+fn parse_nested_line(parser_utils: &mut ParserUtils, state: ParseState) -> Result<Option<Expression>, ParsingError> {
     // The current effect
     let mut effect: Option<Effects> = None;
     // The current type of expression
@@ -72,178 +99,30 @@ pub fn parse_line(parser_utils: &mut ParserUtils, state: ParseState) -> Result<O
                 | TokenTypes::If
                 | TokenTypes::For
                 | TokenTypes::While
-                | TokenTypes::Do => {
+                | TokenTypes::Do
+                | TokenTypes::Switch => {
                     return Err(span.make_error(ParsingMessage::UnexpectedValue()));
                 }
                 _ => {}
             }
         }
 
-        match parse_basic_line(
-            parser_utils,
-            &mut expression_type,
-            &token,
-            &state,
-            Span::new(parser_utils.file, parser_utils.index - 1),
-            &mut effect,
-        )? {
+        // Parenthesis and operators skip parse_basic_line and parse_token, which would be on the stack for every level of them
+        let flow = match token.token_type {
+            TokenTypes::ParenOpen => parse_paren(parser_utils, span, &mut effect)?,
+            TokenTypes::Equals | TokenTypes::Operator => {
+                parse_operator_token(parser_utils, &mut expression_type, &token, &state, span, &mut effect)?
+            }
+            _ => match parse_basic_line(parser_utils, &mut expression_type, &token, &state, span.clone(), &mut effect)? {
+                ControlFlow::NotFound => parse_token(parser_utils, &mut expression_type, &token, &state, span, &mut effect)?,
+                flow => flow,
+            },
+        };
+        match flow {
             ControlFlow::Returning(returning) => return Ok(Some(returning)),
-            ControlFlow::Skipping => continue,
+            ControlFlow::Ended => return Ok(None),
             ControlFlow::Finish => break,
-            ControlFlow::NotFound => {}
-        }
-
-        match token.token_type {
-            TokenTypes::CodeEnd | TokenTypes::BlockEnd | TokenTypes::EOF => {
-                return Ok(None);
-            }
-            TokenTypes::Variable => {
-                let next = parser_utils.tokens.get(parser_utils.index).unwrap();
-                if TokenTypes::ParenOpen == next.token_type {
-                    //Skip because ParenOpen handles this.
-                } else if TokenTypes::Operator == next.token_type {
-                    //Skip if a generic method is being called next to preserve the last effect.
-                    if is_generic(&token, parser_utils) {
-                        continue;
-                    } else {
-                        effect = Some(Effects::new(
-                            Span::new(parser_utils.file, parser_utils.index),
-                            EffectType::LoadVariable(token.to_string(parser_utils.buffer)),
-                        ))
-                    }
-                } else {
-                    if effect.is_some() {
-                        return Err(span.make_error(ParsingMessage::UnexpectedValue()));
-                    }
-                    effect = Some(Effects::new(
-                        Span::new(parser_utils.file, parser_utils.index),
-                        EffectType::LoadVariable(token.to_string(parser_utils.buffer)),
-                    ))
-                }
-            }
-            TokenTypes::Return => expression_type = ExpressionType::Return(Span::new(parser_utils.file, parser_utils.index)),
-            TokenTypes::New => {
-                if effect.is_some() {
-                    return Err(span.make_error(ParsingMessage::UnexpectedValue()));
-                }
-                effect = Some(parse_new(parser_utils, &span)?);
-            }
-            TokenTypes::BlockStart => {
-                if ParseState::ControlVariable == state || ParseState::ControlOperator == state {
-                    parser_utils.index -= 1;
-                    break;
-                } else {
-                    if effect.is_some() {
-                        return Err(span.make_error(ParsingMessage::UnexpectedValue()));
-                    }
-
-                    // Get the code in the next block.
-                    let (returning, body) = parse_code(parser_utils)?;
-                    // If the inner block returns/breaks, then the outer one should too
-                    if matches!(expression_type, ExpressionType::Line) {
-                        expression_type = returning;
-                    }
-                    effect =
-                        Some(Effects::new(Span::new(parser_utils.file, parser_utils.index), EffectType::CodeBody(body)));
-                }
-            }
-            TokenTypes::Let => {
-                if effect.is_some() {
-                    return Err(span.make_error(ParsingMessage::UnexpectedLet()));
-                }
-            }
-            TokenTypes::If => {
-                if effect.is_some() {
-                    return Err(span.make_error(ParsingMessage::UnexpectedIf()));
-                }
-
-                let expression = parse_if(parser_utils)?;
-                // If the if returns/breaks, the outer block should too
-                if matches!(expression_type, ExpressionType::Line) {
-                    expression_type = expression.expression_type;
-                }
-                return Ok(Some(Expression::new(expression_type, expression.effect)));
-            }
-            TokenTypes::For => {
-                if effect.is_some() {
-                    return Err(span.make_error(ParsingMessage::UnexpectedFor()));
-                }
-                return Ok(Some(Expression::new(expression_type, parse_for(parser_utils)?)));
-            }
-            TokenTypes::While => {
-                if effect.is_some() {
-                    return Err(span.make_error(ParsingMessage::UnexpectedFor()));
-                }
-                return Ok(Some(Expression::new(expression_type, parse_while(parser_utils)?)));
-            }
-            TokenTypes::Do => {
-                if effect.is_some() {
-                    return Err(span.make_error(ParsingMessage::UnexpectedFor()));
-                }
-                return Ok(Some(Expression::new(expression_type, parse_do_while(parser_utils)?)));
-            }
-            TokenTypes::Equals => {
-                let other = parser_utils.tokens.get(parser_utils.index).unwrap().token_type.clone();
-                // Check to make sure this isn't an operation like == or +=
-                if effect.is_some() && other != TokenTypes::Operator && other != TokenTypes::Equals {
-                    let value = parse_line(parser_utils, ParseState::None)?;
-                    if let Some(value) = value {
-                        effect = Some(Effects::new(
-                            Span::new(parser_utils.file, parser_utils.index),
-                            EffectType::Set(Box::new(effect.unwrap()), Box::new(value.effect)),
-                        ));
-                    } else {
-                        return Err(span.make_error(ParsingMessage::UnexpectedVoid()));
-                    }
-                    break;
-                } else {
-                    // It must be an operator, parse it like one.
-                    let operator = parse_operator(effect, parser_utils, &state)?;
-                    if ParseState::InOperator == state || ParseState::ControlOperator == state {
-                        return Ok(Some(Expression::new(expression_type, operator)));
-                    } else {
-                        effect = Some(operator);
-                    }
-                }
-            }
-            TokenTypes::Operator => {
-                let last = parser_utils.tokens.get(parser_utils.index - 2).unwrap();
-                // If there is a variable right next to a less than, it's probably a generic method call.
-                // Example: test<Value>()
-                parser_utils.index -= 1;
-                if (last.token_type == TokenTypes::Variable || last.token_type == TokenTypes::CallingType)
-                    && is_generic(&parser_utils.tokens[parser_utils.index - 1], parser_utils)
-                {
-                    parser_utils.index += 1;
-                    effect = Some(parse_generic_method(effect, parser_utils)?);
-                } else {
-                    parser_utils.index += 1;
-                    let operator = parse_operator(effect, parser_utils, &state)?;
-                    // Operators inside operators return immediately so operators can be combined
-                    // later on for operators like [].
-                    if ParseState::InOperator == state || ParseState::ControlOperator == state {
-                        return Ok(Some(Expression::new(expression_type, operator)));
-                    } else {
-                        effect = Some(operator);
-                    }
-                }
-            }
-            TokenTypes::CallingType => {
-                let next: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
-                if next.token_type == TokenTypes::ParenOpen || is_generic(&token, parser_utils) {
-                    // Ignored, ParenOpen or Operator handles this
-                } else {
-                    if effect.is_none() {
-                        return Err(span.make_error(ParsingMessage::ExtraSymbol()));
-                    }
-                    effect = Some(Effects::new(
-                        Span::new(parser_utils.file, parser_utils.index),
-                        EffectType::Load(Box::new(effect.unwrap()), token.to_string(parser_utils.buffer)),
-                    ))
-                }
-            }
-            TokenTypes::Else => return Err(span.make_error(ParsingMessage::UnexpectedElse())),
-            _ => panic!("How'd you get here? {:?}", token.token_type),
+            ControlFlow::Skipping | ControlFlow::NotFound => {}
         }
     }
 
@@ -259,6 +138,7 @@ enum ControlFlow {
     Skipping,
     Finish,
     Returning(Expression),
+    Ended,
 }
 
 /// Handles some basic cases separately to reduce the complexity of the main function
@@ -277,6 +157,21 @@ fn parse_basic_line(
             *expression_type = ExpressionType::Return(span);
             ControlFlow::Skipping
         }
+        TokenTypes::Break => {
+            let label = parse_jump_label(parser_utils);
+            let value = parse_jump_value(parser_utils)?;
+            ControlFlow::Returning(Expression::new(
+                ExpressionType::Break,
+                Effects::new(span, EffectType::Break(label, value)),
+            ))
+        }
+        TokenTypes::Continue => {
+            let label = parse_jump_label(parser_utils);
+            if parse_jump_value(parser_utils)?.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedValue()));
+            }
+            ControlFlow::Returning(Expression::new(ExpressionType::Break, Effects::new(span, EffectType::Continue(label))))
+        }
         TokenTypes::Float => {
             *effect = Some(Effects::new(
                 Span::new(parser_utils.file, parser_utils.index),
@@ -285,10 +180,8 @@ fn parse_basic_line(
             ControlFlow::Skipping
         }
         TokenTypes::Integer => {
-            *effect = Some(Effects::new(
-                Span::new(parser_utils.file, parser_utils.index),
-                EffectType::Int(token.to_string(parser_utils.buffer).parse().unwrap()),
-            ));
+            let span = Span::new(parser_utils.file, parser_utils.index);
+            *effect = Some(Effects::new(span.clone(), parse_integer(&token.to_string(parser_utils.buffer), &span)?));
             ControlFlow::Skipping
         }
         TokenTypes::Char => {
@@ -320,46 +213,27 @@ fn parse_basic_line(
             }
             ControlFlow::Returning(Expression::new(expression_type, expression.effect))
         }
+        TokenTypes::Switch => {
+            let expression = parse_switch(parser_utils)?;
+            let mut expression_type = expression_type.clone();
+            // If every arm returns, the outer block should too
+            if expression_type == ExpressionType::Line {
+                expression_type = expression.expression_type;
+            }
+            ControlFlow::Returning(Expression::new(expression_type, expression.effect))
+        }
         TokenTypes::For => ControlFlow::Returning(Expression::new(expression_type.clone(), parse_for(parser_utils)?)),
         TokenTypes::While => ControlFlow::Returning(Expression::new(expression_type.clone(), parse_while(parser_utils)?)),
         TokenTypes::Do => ControlFlow::Returning(Expression::new(expression_type.clone(), parse_do_while(parser_utils)?)),
+        TokenTypes::Label if effect.is_none() => {
+            let label = token.to_string(parser_utils.buffer)[1..].to_string();
+            ControlFlow::Returning(Expression::new(expression_type.clone(), parse_labeled(parser_utils, label, &span)?))
+        }
+        TokenTypes::StaticAssert => {
+            ControlFlow::Returning(Expression::new(expression_type.clone(), parse_static_assert(parser_utils, span)?))
+        }
         TokenTypes::LineEnd | TokenTypes::ParenClose | TokenTypes::ArgumentEnd => ControlFlow::Finish,
         TokenTypes::Comment => ControlFlow::Skipping,
-        TokenTypes::ParenOpen => {
-            let last = parser_utils.tokens.get(parser_utils.index - 2).unwrap().clone();
-            match last.token_type {
-                TokenTypes::Variable | TokenTypes::CallingType => {
-                    // Name of the method = the last token
-                    let name = last.to_string(parser_utils.buffer);
-                    let mut temp = None;
-                    mem::swap(&mut temp, effect);
-                    // The calling effect must be boxed if it exists.
-                    *effect = Some(Effects {
-                        types: EffectType::MethodCall(
-                            temp.map(|inner| Box::new(inner)),
-                            name.clone(),
-                            get_effects(parser_utils)?,
-                            None,
-                        ),
-                        span,
-                    });
-                    ControlFlow::Skipping
-                }
-                // If it's not a method call, it's a parenthesized effect.
-                _ => {
-                    if let Some(expression) = parse_line(parser_utils, ParseState::None)? {
-                        *effect = Some(Effects::new(
-                            Span::new(parser_utils.file, parser_utils.index),
-                            EffectType::Paren(Box::new(expression.effect)),
-                        ));
-                        ControlFlow::Skipping
-                    } else {
-                        //effect = None;
-                        panic!("Unknown code path - report this!");
-                    }
-                }
-            }
-        }
         TokenTypes::Period => {
             if parser_utils.tokens[parser_utils.index].token_type == TokenTypes::Period {
                 let mut temp = None;
@@ -379,9 +253,259 @@ fn parse_basic_line(
     });
 }
 
+/// Parses a parenthesis, which is either a method call or a parenthesized effect
+fn parse_paren(
+    parser_utils: &mut ParserUtils,
+    span: Span,
+    effect: &mut Option<Effects>,
+) -> Result<ControlFlow, ParsingError> {
+    let last = parser_utils.tokens.get(parser_utils.index - 2).unwrap().clone();
+    return Ok(match last.token_type {
+        TokenTypes::Variable | TokenTypes::CallingType => {
+            // Name of the method = the last token
+            let name = last.to_string(parser_utils.buffer);
+            let mut temp = None;
+            mem::swap(&mut temp, effect);
+            // The calling effect must be boxed if it exists.
+            *effect = Some(Effects {
+                types: EffectType::MethodCall(
+                    temp.map(|inner| Box::new(inner)),
+                    name.clone(),
+                    get_effects(parser_utils)?,
+                    None,
+                    vec![],
+                ),
+                span,
+            });
+            ControlFlow::Skipping
+        }
+        // If it's not a method call, it's a parenthesized effect.
+        _ => {
+            if let Some(expression) = parse_line(parser_utils, ParseState::None)? {
+                *effect = Some(Effects::new(
+                    Span::new(parser_utils.file, parser_utils.index),
+                    EffectType::Paren(Box::new(expression.effect)),
+                ));
+                ControlFlow::Skipping
+            } else {
+                //effect = None;
+                panic!("Unknown code path - report this!");
+            }
+        }
+    });
+}
+
+/// Handles the tokens that aren't handled by parse_basic_line
+// skipcq: RS-R1000 Match statements have complexity calculated incorrectly
+fn parse_token(
+    parser_utils: &mut ParserUtils,
+    expression_type: &mut ExpressionType,
+    token: &Token,
+    state: &ParseState,
+    span: Span,
+    effect: &mut Option<Effects>,
+) -> Result<ControlFlow, ParsingError> {
+    match token.token_type {
+        TokenTypes::CodeEnd | TokenTypes::BlockEnd | TokenTypes::EOF => {
+            return Ok(ControlFlow::Ended);
+        }
+        TokenTypes::Variable => {
+            let next = parser_utils.tokens.get(parser_utils.index).unwrap();
+            if TokenTypes::ParenOpen == next.token_type {
+                //Skip because ParenOpen handles this.
+            } else if TokenTypes::Operator == next.token_type {
+                //Skip if a generic method is being called next to preserve the last effect.
+                if is_generic(token, parser_utils) {
+                    return Ok(ControlFlow::Skipping);
+                } else {
+                    *effect = Some(Effects::new(
+                        Span::new(parser_utils.file, parser_utils.index),
+                        EffectType::LoadVariable(token.to_string(parser_utils.buffer)),
+                    ))
+                }
+            } else {
+                if effect.is_some() {
+                    return Err(span.make_error(ParsingMessage::UnexpectedValue()));
+                }
+                *effect = Some(Effects::new(
+                    Span::new(parser_utils.file, parser_utils.index),
+                    EffectType::LoadVariable(token.to_string(parser_utils.buffer)),
+                ))
+            }
+        }
+        TokenTypes::Return => *expression_type = ExpressionType::Return(Span::new(parser_utils.file, parser_utils.index)),
+        TokenTypes::New => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedValue()));
+            }
+            *effect = Some(parse_new(parser_utils, &span)?);
+        }
+        TokenTypes::BlockStart => {
+            if ParseState::ControlVariable == *state || ParseState::ControlOperator == *state {
+                parser_utils.index -= 1;
+                return Ok(ControlFlow::Finish);
+            } else {
+                if effect.is_some() {
+                    return Err(span.make_error(ParsingMessage::UnexpectedValue()));
+                }
+
+                // Get the code in the next block.
+                let (returning, body) = parse_code(parser_utils)?;
+                // If the inner block returns/breaks, then the outer one should too
+                if matches!(*expression_type, ExpressionType::Line) {
+                    *expression_type = returning;
+                }
+                *effect = Some(Effects::new(Span::new(parser_utils.file, parser_utils.index), EffectType::CodeBody(body)));
+            }
+        }
+        TokenTypes::Let => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedLet()));
+            }
+        }
+        TokenTypes::Closure => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedValue()));
+            }
+            *effect = Some(parse_closure(parser_utils, &span)?);
+        }
+        TokenTypes::If => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedIf()));
+            }
+
+            let expression = parse_if(parser_utils)?;
+            // If the if returns/breaks, the outer block should too
+            if matches!(*expression_type, ExpressionType::Line) {
+                *expression_type = expression.expression_type;
+            }
+            return Ok(ControlFlow::Returning(Expression::new(expression_type.clone(), expression.effect)));
+        }
+        TokenTypes::For => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedFor()));
+            }
+            return Ok(ControlFlow::Returning(Expression::new(expression_type.clone(), parse_for(parser_utils)?)));
+        }
+        TokenTypes::While => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedFor()));
+            }
+            return Ok(ControlFlow::Returning(Expression::new(expression_type.clone(), parse_while(parser_utils)?)));
+        }
+        TokenTypes::Do => {
+            if effect.is_some() {
+                return Err(span.make_error(ParsingMessage::UnexpectedFor()));
+            }
+            return Ok(ControlFlow::Returning(Expression::new(expression_type.clone(), parse_do_while(parser_utils)?)));
+        }
+        TokenTypes::CallingType => {
+            let next: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
+            if next.token_type == TokenTypes::ParenOpen || is_generic(token, parser_utils) {
+                // Ignored, ParenOpen or Operator handles this
+            } else {
+                if effect.is_none() {
+                    return Err(span.make_error(ParsingMessage::ExtraSymbol()));
+                }
+                *effect = Some(Effects::new(
+                    Span::new(parser_utils.file, parser_utils.index),
+                    EffectType::Load(Box::new(effect.take().unwrap()), token.to_string(parser_utils.buffer)),
+                ))
+            }
+        }
+        TokenTypes::As => {
+            let value = match effect.take() {
+                Some(value) => value,
+                None => return Err(span.make_error(ParsingMessage::ExpectedEffect())),
+            };
+            // The type cast to is a single name, like value as f64
+            let target = &parser_utils.tokens[parser_utils.index];
+            if target.token_type != TokenTypes::Variable {
+                return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedToken()));
+            }
+            parser_utils.index += 1;
+            *effect = Some(Effects::new(
+                span,
+                EffectType::Cast(Box::new(value), UnparsedType::Basic(target.to_string(parser_utils.buffer))),
+            ));
+        }
+        TokenTypes::Else => return Err(span.make_error(ParsingMessage::UnexpectedElse())),
+        _ => panic!("How'd you get here? {:?}", token.token_type),
+    }
+    return Ok(ControlFlow::Skipping);
+}
+
+/// Parses an equals or an operator token, which is a set, a mutable borrow, a generic method call, or an operator
+fn parse_operator_token(
+    parser_utils: &mut ParserUtils,
+    expression_type: &mut ExpressionType,
+    token: &Token,
+    state: &ParseState,
+    span: Span,
+    effect: &mut Option<Effects>,
+) -> Result<ControlFlow, ParsingError> {
+    match token.token_type {
+        TokenTypes::Operator if effect.is_none() && is_mutable_borrow(token, parser_utils) => {
+            let name = parser_utils.tokens[parser_utils.index + 1].to_string(parser_utils.buffer);
+            parser_utils.index += 2;
+            *effect = Some(Effects::new(span, EffectType::MutableBorrow(name)));
+        }
+        TokenTypes::Equals => {
+            let other = parser_utils.tokens.get(parser_utils.index).unwrap().token_type.clone();
+            // Check to make sure this isn't an operation like == or +=
+            if effect.is_some() && other != TokenTypes::Operator && other != TokenTypes::Equals {
+                let value = parse_line(parser_utils, ParseState::None)?;
+                if let Some(value) = value {
+                    *effect = Some(Effects::new(
+                        Span::new(parser_utils.file, parser_utils.index),
+                        EffectType::Set(Box::new(effect.take().unwrap()), Box::new(value.effect)),
+                    ));
+                } else {
+                    return Err(span.make_error(ParsingMessage::UnexpectedVoid()));
+                }
+                return Ok(ControlFlow::Finish);
+            } else {
+                // It must be an operator, parse it like one.
+                let operator = parse_operator(effect.take(), parser_utils, state)?;
+                if ParseState::InOperator == *state || ParseState::ControlOperator == *state {
+                    return Ok(ControlFlow::Returning(Expression::new(expression_type.clone(), operator)));
+                } else {
+                    *effect = Some(operator);
+                }
+            }
+        }
+        TokenTypes::Operator => {
+            let last = parser_utils.tokens.get(parser_utils.index - 2).unwrap();
+            // If there is a variable right next to a less than, it's probably a generic method call.
+            // Example: test<Value>()
+            parser_utils.index -= 1;
+            if (last.token_type == TokenTypes::Variable || last.token_type == TokenTypes::CallingType)
+                && is_generic(&parser_utils.tokens[parser_utils.index - 1], parser_utils)
+            {
+                parser_utils.index += 1;
+                *effect = Some(parse_generic_method(effect.take(), parser_utils)?);
+            } else {
+                parser_utils.index += 1;
+                let operator = parse_operator(effect.take(), parser_utils, state)?;
+                // Operators inside operators return immediately so operators can be combined
+                // later on for operators like [].
+                if ParseState::InOperator == *state || ParseState::ControlOperator == *state {
+                    return Ok(ControlFlow::Returning(Expression::new(expression_type.clone(), operator)));
+                } else {
+                    *effect = Some(operator);
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+    return Ok(ControlFlow::Skipping);
+}
+
 /// Parses tokens from the Raven code into a string
 fn parse_string(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     let mut string = String::default(); //the string from the Raven code
+                                        // The string and expression parts of an interpolated string
+    let mut parts = Vec::default();
 
     loop {
         //loop through the tokens until a StringEnd is reached
@@ -392,84 +516,106 @@ fn parse_string(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError>
 
         match token.token_type {
             TokenTypes::StringEnd => {
-                // End of string, must have a null character at the end
+                // End of string, without the closing quote
                 let found = token.to_string(parser_utils.buffer);
                 string += &found[0..found.len() - 1];
-                return Ok(Effects::new(
-                    Span::new(parser_utils.file, parser_utils.index - 1),
-                    EffectType::String(string + "\0"),
-                ));
+                let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                if parts.is_empty() {
+                    return Ok(Effects::new(span, EffectType::StringLiteral(string)));
+                }
+                if !string.is_empty() {
+                    parts.push(Effects::new(span.clone(), EffectType::StringLiteral(string)));
+                }
+                return Ok(Effects::new(span, EffectType::Interpolation(parts)));
             }
-            TokenTypes::StringEscape => {
-                // Escape token
-
-                // get the text from the Raven file starting at the last token up to the current escape character
+            TokenTypes::InterpolationStart => {
+                // The text before the {, then the expression up to the }
                 let found = token.to_string(parser_utils.buffer);
-
-                // check if it a hex value, because if it is, then it will 4 characters long (\xAA)
-                let is_hex = found.len() >= 3 && &found[found.len() - 3..found.len() - 2] == "x";
-                let string_end = found.len() - (if is_hex { 4 } else { 2 });
-
-                // add the text to the string, because this text is part of the string in the Raven Code
-                string += &found[0..string_end];
-
-                // match the character after the \ to see what type of escape character it is
-                let index = if is_hex { found.len() - 3 } else { found.len() - 1 };
-                match &found[index..=index] {
-                    "n" => {
-                        string += "\n";
-                    }
-                    "t" => {
-                        string += "\t";
-                    }
-                    "r" => {
-                        string += "\r";
-                    }
-                    "\\" => {
-                        string += "\\";
-                    }
-                    "\'" => {
-                        string += "\'";
-                    }
-                    "\"" => {
-                        string += "\"";
-                    }
-                    "x" => {
-                        // Convert the hex to a character, and append it to the string
-                        string.push(
-                            u8::from_str_radix(&found[found.len() - 2..found.len()], 16).expect("Unexpected hex value")
-                                as char,
-                        );
-                    }
-                    _ => {
-                        // not a supported character
-                        panic!("Unexpected escape character: {}", parser_utils.buffer[token.end_offset - 1] as char)
-                    }
+                string += &found[0..found.len() - 1];
+                let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                if !string.is_empty() {
+                    parts.push(Effects::new(span.clone(), EffectType::StringLiteral(mem::take(&mut string))));
+                }
+                match parse_line(parser_utils, ParseState::None)? {
+                    Some(expression) if !matches!(expression.effect.types, EffectType::NOP) => parts.push(expression.effect),
+                    _ => return Err(span.make_error(ParsingMessage::UnexpectedVoid())),
                 }
             }
+            TokenTypes::StringEscape => {
+                // Escapes are kept as they're written, the checker decodes them so a bad one can be reported
+                string += &token.to_string(parser_utils.buffer);
+            }
             TokenTypes::StringStart => {} //the first token is always a StringStart, so skip this
             _ => panic!("How'd you get here? {:?}", token.token_type),
         }
     }
 }
 
+/// Parses a static assert's condition, like the (size_of<T>() == 8) in static_assert(size_of<T>() == 8)
+fn parse_static_assert(parser_utils: &mut ParserUtils, span: Span) -> Result<Effects, ParsingError> {
+    return match parse_line(parser_utils, ParseState::None)? {
+        Some(condition) if !matches!(condition.effect.types, EffectType::NOP) => {
+            Ok(Effects::new(span, EffectType::StaticAssert(Box::new(condition.effect))))
+        }
+        _ => Err(span.make_error(ParsingMessage::UnexpectedVoid())),
+    };
+}
+
+/// Parses the rest of a break or continue's line, which is the value it's breaking with, if any
+fn parse_jump_value(parser_utils: &mut ParserUtils) -> Result<Option<Box<Effects>>, ParsingError> {
+    return Ok(match parse_line(parser_utils, ParseState::None)? {
+        Some(value) if !matches!(value.effect.types, EffectType::NOP) => Some(Box::new(value.effect)),
+        _ => None,
+    });
+}
+
+/// Parses an integer literal, checking it fits in the type of its suffix.
+/// Literals without a suffix too big for an i64 are unsigned.
+fn parse_integer(integer: &str, span: &Span) -> Result<EffectType, ParsingError> {
+    let out_of_range =
+        |types: &str| span.make_error(ParsingMessage::IntegerOutOfRange(integer.to_string(), types.to_string()));
+    let suffix = match integer.find(|character| character == 'i' || character == 'u') {
+        Some(suffix) => suffix,
+        None => {
+            return match (integer.parse::<i64>(), integer.parse::<u64>()) {
+                (Ok(value), _) => Ok(EffectType::Int(value)),
+                (_, Ok(value)) => Ok(EffectType::UInt(value)),
+                _ => Err(out_of_range("u64")),
+            };
+        }
+    };
+
+    let (value, types) = integer.split_at(suffix);
+    let max = match integer_max(types) {
+        Some(max) => max,
+        None => return Err(span.make_error(ParsingMessage::FailedToFind(types.to_string()))),
+    };
+    return match value.parse::<u64>() {
+        Ok(value) if value <= max => Ok(EffectType::TypedInt(value, types.to_string())),
+        _ => Err(out_of_range(types)),
+    };
+}
+
+/// Parses the label after a break or continue, if there is one
+fn parse_jump_label(parser_utils: &mut ParserUtils) -> Option<String> {
+    let token = &parser_utils.tokens[parser_utils.index];
+    if token.token_type != TokenTypes::Label {
+        return None;
+    }
+    parser_utils.index += 1;
+    return Some(token.to_string(parser_utils.buffer)[1..].to_string());
+}
+
 /// Parses a generic method call
 fn parse_generic_method(effect: Option<Effects>, parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     let name = parser_utils.tokens[parser_utils.index - 2].to_string(parser_utils.buffer);
     let token = parser_utils.index - 2;
-    // Get the type being expressed. Should only be one type.
-    let returning: Option<(UnparsedType, Span)> =
-        if let UnparsedType::Generic(_, bounds) = parse_generics(String::default(), parser_utils).0 {
-            /*
-            TODO figure out how to check for ungotten generics with generic method calls
-            if bounds.len() != 1 {
-                Span::new(parser_utils.file, parser_utils.index - 1).make_error("Expected one generic argument!");
-            }*/
-            let types: &UnparsedType = bounds.first().unwrap();
-            Some((types.clone(), Span::new(parser_utils.file, parser_utils.index - 1)))
-        } else {
-            None
-        };
+    // Get the types being expressed, which are bound to the method's generics in order.
+    let explicit_generics = if let UnparsedType::Generic(_, bounds) = parse_generics(String::default(), parser_utils).0 {
+        bounds
+    } else {
+        vec![]
+    };
 
     parser_utils.index += 1;
     return Ok(Effects {
@@ -477,7 +623,8 @@ fn parse_generic_method(effect: Option<Effects>, parser_utils: &mut ParserUtils)
             effect.map(|inner| Box::new(inner)),
             name.clone(),
             get_effects(parser_utils)?,
-            returning,
+            None,
+            explicit_generics,
         ),
         span: Span::new(parser_utils.file, token),
     });
@@ -489,9 +636,28 @@ fn get_effects(parser_utils: &mut ParserUtils) -> Result<Vec<Effects>, ParsingEr
     // Parse the method call arguments
     if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::ParenClose {
         let start = parser_utils.index;
-        while let Some(mut expression) = parse_line(parser_utils, ParseState::None)? {
+        loop {
+            // Arguments like "x: 1" are passed by name
+            let name = if parser_utils.tokens[parser_utils.index].token_type == TokenTypes::Variable
+                && parser_utils.tokens[parser_utils.index + 1].token_type == TokenTypes::Colon
+            {
+                parser_utils.index += 2;
+                Some((parser_utils.tokens[parser_utils.index - 2].to_string(parser_utils.buffer), parser_utils.index - 2))
+            } else {
+                None
+            };
+            let mut expression = match parse_line(parser_utils, ParseState::None)? {
+                Some(expression) => expression,
+                None => break,
+            };
             expression.effect.span.extend_span_backwards(start);
-            effects.push(expression.effect);
+            match name {
+                Some((name, token)) => effects.push(Effects::new(
+                    Span::new(parser_utils.file, token),
+                    EffectType::NamedArgument(name, Box::new(expression.effect)),
+                )),
+                None => effects.push(expression.effect),
+            }
             if parser_utils.tokens[parser_utils.index - 1].token_type != TokenTypes::ArgumentEnd {
                 break;
             }
@@ -502,35 +668,145 @@ fn get_effects(parser_utils: &mut ParserUtils) -> Result<Vec<Effects>, ParsingEr
     return Ok(effects);
 }
 
-/// Parses a let statement
+/// Parses a let statement, like let x = 1; or let x: u64 = 1;, or one destructuring its value like let [a, b] = pair;
 fn parse_let(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     let name;
+    let mut types = None;
     let mut error_token;
     {
         let next = &parser_utils.tokens[parser_utils.index];
-        if TokenTypes::Variable == next.token_type {
-            name = next.to_string(parser_utils.buffer);
+        let pattern = if starts_pattern(parser_utils) {
+            name = None;
+            Some(parse_pattern(parser_utils)?)
+        } else if TokenTypes::Variable == next.token_type {
+            name = Some(next.to_string(parser_utils.buffer));
+            parser_utils.index += 1;
+            None
         } else {
             return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedToken()));
+        };
+
+        // The declared type goes up to the equals sign
+        if TokenTypes::Colon == parser_utils.tokens[parser_utils.index].token_type && pattern.is_none() {
+            parser_utils.index += 1;
+            let found = parse_closure_type(parser_utils, &[TokenTypes::Equals, TokenTypes::LineEnd]);
+            if found.is_empty() {
+                return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedSymbol()));
+            }
+            types = Some(UnparsedType::Basic(found));
         }
 
-        if TokenTypes::Equals != parser_utils.tokens.get(parser_utils.index + 1).unwrap().token_type {
+        if TokenTypes::Equals != parser_utils.tokens.get(parser_utils.index).unwrap().token_type {
             return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedSymbol()));
         }
-        parser_utils.index += 2;
+        parser_utils.index += 1;
         error_token = Span::new(parser_utils.file, parser_utils.index);
+
+        if let Some(pattern) = pattern {
+            return match parse_line(parser_utils, ParseState::None)? {
+                Some(line) => {
+                    error_token.extend_span(parser_utils.index - 2);
+                    Ok(Effects::new(error_token, EffectType::Destructure(pattern, Box::new(line.effect))))
+                }
+                None => Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid())),
+            };
+        }
     }
 
     // If the rest of the line doesn't exist, return an error because the value must be set to something.
     return match parse_line(parser_utils, ParseState::None)? {
         Some(line) => {
             error_token.extend_span(parser_utils.index - 2);
-            Ok(Effects::new(error_token, EffectType::CreateVariable(name, Box::new(line.effect))))
+            Ok(Effects::new(error_token, EffectType::CreateVariable(name.unwrap(), Box::new(line.effect), types)))
         }
         None => Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid())),
     };
 }
 
+/// Checks if the let destructures its value, which starts with an array's [ or a struct's name and {
+fn starts_pattern(parser_utils: &ParserUtils) -> bool {
+    let token = &parser_utils.tokens[parser_utils.index];
+    return match token.token_type {
+        TokenTypes::Operator => token.to_string(parser_utils.buffer) == "[",
+        TokenTypes::Variable => parser_utils.tokens[parser_utils.index + 1].token_type == TokenTypes::BlockStart,
+        _ => false,
+    };
+}
+
+/// Parses the pattern a let destructures its value into, like [a, b] or Point { x, y: other }
+fn parse_pattern(parser_utils: &mut ParserUtils) -> Result<Pattern, ParsingError> {
+    let mut span = Span::new(parser_utils.file, parser_utils.index);
+    let token = &parser_utils.tokens[parser_utils.index];
+    parser_utils.index += 1;
+    let pattern = match token.token_type {
+        TokenTypes::Variable if parser_utils.tokens[parser_utils.index].token_type == TokenTypes::BlockStart => {
+            let name = token.to_string(parser_utils.buffer);
+            parser_utils.index += 1;
+            let mut fields = Vec::default();
+            while parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockEnd {
+                let field = &parser_utils.tokens[parser_utils.index];
+                if field.token_type != TokenTypes::Variable {
+                    return Err(
+                        Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedToken())
+                    );
+                }
+                let field = field.to_string(parser_utils.buffer);
+                // A field without a pattern, like the x in Point { x }, is bound to a variable of the same name
+                if parser_utils.tokens[parser_utils.index + 1].token_type == TokenTypes::Colon {
+                    parser_utils.index += 2;
+                    fields.push((field, parse_pattern(parser_utils)?));
+                } else {
+                    fields.push((field.clone(), Pattern::Variable(field, Span::new(parser_utils.file, parser_utils.index))));
+                    parser_utils.index += 1;
+                }
+                end_pattern_element(parser_utils, TokenTypes::BlockEnd)?;
+            }
+            parser_utils.index += 1;
+            Pattern::Struct(name, fields, span.clone())
+        }
+        TokenTypes::Variable => Pattern::Variable(token.to_string(parser_utils.buffer), span.clone()),
+        TokenTypes::Operator if token.to_string(parser_utils.buffer) == "[" => {
+            let mut elements = Vec::default();
+            while !is_array_end(parser_utils) {
+                elements.push(parse_pattern(parser_utils)?);
+                end_pattern_element(parser_utils, TokenTypes::Operator)?;
+            }
+            parser_utils.index += 1;
+            Pattern::Array(elements, span.clone())
+        }
+        _ => return Err(Span::new(parser_utils.file, parser_utils.index - 1).make_error(ParsingMessage::UnexpectedToken())),
+    };
+    span.extend_span(parser_utils.index - 1);
+    return Ok(match pattern {
+        Pattern::Variable(name, _) => Pattern::Variable(name, span),
+        Pattern::Array(elements, _) => Pattern::Array(elements, span),
+        Pattern::Struct(name, fields, _) => Pattern::Struct(name, fields, span),
+    });
+}
+
+/// Checks if the next token is the ] ending an array pattern
+fn is_array_end(parser_utils: &ParserUtils) -> bool {
+    let token = &parser_utils.tokens[parser_utils.index];
+    return token.token_type == TokenTypes::Operator && token.to_string(parser_utils.buffer) == "]";
+}
+
+/// Skips the comma after an element of a pattern, erroring if the element is followed by anything but a comma or the end
+fn end_pattern_element(parser_utils: &mut ParserUtils, end: TokenTypes) -> Result<(), ParsingError> {
+    let next = &parser_utils.tokens[parser_utils.index];
+    if next.token_type == TokenTypes::ArgumentEnd {
+        parser_utils.index += 1;
+        return Ok(());
+    }
+    let ended = match end {
+        TokenTypes::Operator => is_array_end(parser_utils),
+        _ => next.token_type == end,
+    };
+    if ended {
+        return Ok(());
+    }
+    return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedSymbol()));
+}
+
 /// Parses a new program call
 fn parse_new(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, ParsingError> {
     let mut types: Option<UnparsedType> = None;
@@ -560,6 +836,74 @@ fn parse_new(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, Par
     return Ok(Effects::new(Span::new(parser_utils.file, type_token), EffectType::CreateStruct(types.unwrap(), values)));
 }
 
+/// Parses a closure, like fn(value: u64) -> u64 { return value + 1; }
+fn parse_closure(parser_utils: &mut ParserUtils, span: &Span) -> Result<Effects, ParsingError> {
+    // Skips the parenthesis before the arguments
+    parser_utils.index += 1;
+    let mut arguments = Vec::default();
+    loop {
+        let token: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
+        parser_utils.index += 1;
+        match token.token_type {
+            TokenTypes::Variable => {
+                let name = token.to_string(parser_utils.buffer);
+                if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::Colon {
+                    return Err(span.make_error(ParsingMessage::UnexpectedToken()));
+                }
+                parser_utils.index += 1;
+                let types = parse_closure_type(parser_utils, &[TokenTypes::ArgumentEnd, TokenTypes::ParenClose]);
+                arguments.push((name, UnparsedType::Basic(types)));
+            }
+            TokenTypes::ArgumentEnd => {}
+            TokenTypes::ParenClose => break,
+            _ => return Err(span.make_error(ParsingMessage::UnexpectedToken())),
+        }
+    }
+
+    // The return type comes after an arrow, which is two operator tokens
+    let returning = if parser_utils.tokens[parser_utils.index].token_type == TokenTypes::Operator {
+        parser_utils.index += 2;
+        Some(UnparsedType::Basic(parse_closure_type(parser_utils, &[TokenTypes::BlockStart])))
+    } else {
+        None
+    };
+
+    if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockStart {
+        return Err(span.make_error(ParsingMessage::ExpectedCodeBlock()));
+    }
+    parser_utils.index += 1;
+    // Returns inside the closure only return from the closure
+    let (_, body) = parse_code(parser_utils)?;
+    return Ok(Effects::new(span.clone(), EffectType::Closure(arguments, returning, body)));
+}
+
+/// Gets the source of a type inside code, up to one of the ending tokens that isn't inside brackets
+fn parse_closure_type(parser_utils: &mut ParserUtils, ends: &[TokenTypes]) -> String {
+    let start = parser_utils.tokens[parser_utils.index].start_offset;
+    let mut end = start;
+    let mut depth = 0;
+    loop {
+        let token: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
+        match token.token_type {
+            TokenTypes::ParenOpen => depth += 1,
+            TokenTypes::ParenClose if depth > 0 => depth -= 1,
+            TokenTypes::Operator => match token.to_string(parser_utils.buffer).as_str() {
+                "[" | "<" => depth += 1,
+                "]" => depth -= 1,
+                // The arrow of a function type isn't a closing bracket
+                ">" if parser_utils.tokens[parser_utils.index - 1].to_string(parser_utils.buffer) != "-" => depth -= 1,
+                _ => {}
+            },
+            TokenTypes::EOF => break,
+            _ if depth == 0 && ends.contains(&token.token_type) => break,
+            _ => {}
+        }
+        end = token.end_offset;
+        parser_utils.index += 1;
+    }
+    return String::from_utf8_lossy(&parser_utils.buffer[start..end]).trim().to_string();
+}
+
 /// Parses the arguments to a new program
 fn parse_new_args(parser_utils: &mut ParserUtils, span: &Span) -> Result<Vec<(String, Effects)>, ParsingError> {
     let mut values = Vec::default();
@@ -600,6 +944,20 @@ fn parse_new_args(parser_utils: &mut ParserUtils, span: &Span) -> Result<Vec<(St
     return Ok(values);
 }
 
+/// Checks if the operator starts a mutable borrow of a variable, like &mut value
+fn is_mutable_borrow(token: &Token, parser_utils: &ParserUtils) -> bool {
+    let variable = |offset: usize| {
+        parser_utils
+            .tokens
+            .get(parser_utils.index + offset)
+            .filter(|found| found.token_type == TokenTypes::Variable)
+            .map(|found| found.to_string(parser_utils.buffer))
+    };
+    return token.to_string(parser_utils.buffer) == "&"
+        && variable(0).is_some_and(|keyword| keyword == "mut")
+        && variable(1).is_some();
+}
+
 /// Checks if a type is generic or if it's just followed by an operator
 fn is_generic(token: &Token, parser_utils: &ParserUtils) -> bool {
     let next: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
@@ -1,5 +1,5 @@
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
-use syntax::program::code::{EffectType, Effects, Expression, ExpressionType};
+use syntax::program::code::{EffectType, Effects, Expression, ExpressionType, SwitchArm};
 use syntax::program::function::CodeBody;
 
 use crate::parser::code_parser::{parse_code, parse_line, ParseState};
@@ -76,7 +76,8 @@ pub fn parse_if(parser_utils: &mut ParserUtils) -> Result<Expression, ParsingErr
         returning = ExpressionType::Line;
     }
 
-    let adding = 1 + else_ifs.len() as u32 + else_body.is_some() as u32;
+    // Else ifs need an else to be nested in, so one is added if there isn't one
+    let adding = 1 + else_ifs.len() as u32 + (else_body.is_some() || !else_ifs.is_empty()) as u32;
     parser_utils.imports.last_id += adding;
     return Ok(Expression::new(
         returning,
@@ -141,6 +142,123 @@ pub fn parse_while(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingErr
     return create_while(effect.unwrap().effect, body, parser_utils.imports.last_id - 1);
 }
 
+/// Parses a labeled loop, like 'outer: while condition { ... }, which breaks and continues can name
+pub fn parse_labeled(parser_utils: &mut ParserUtils, label: String, span: &Span) -> Result<Effects, ParsingError> {
+    if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::Colon {
+        return Err(span.make_error(ParsingMessage::ExpectedLoop()));
+    }
+    parser_utils.index += 1;
+
+    let token_type = parser_utils.tokens[parser_utils.index].token_type.clone();
+    parser_utils.index += 1;
+    let mut effect = match token_type {
+        TokenTypes::For => parse_for(parser_utils)?,
+        TokenTypes::While => parse_while(parser_utils)?,
+        TokenTypes::Do => parse_do_while(parser_utils)?,
+        _ => return Err(span.make_error(ParsingMessage::ExpectedLoop())),
+    };
+    if let EffectType::Loop(_, _, _, name) = &mut effect.types {
+        *name = Some(label);
+    }
+    return Ok(effect);
+}
+
+/// Parses a switch statement into a single expression.
+/// ex:
+/// switch shape {
+///     Circle(radius) => { ... }
+///     _ => { ... }
+/// }
+pub fn parse_switch(parser_utils: &mut ParserUtils) -> Result<Expression, ParsingError> {
+    let span = Span::new(parser_utils.file, parser_utils.index - 1);
+    let effect = parse_line(parser_utils, ParseState::ControlVariable)?;
+    if effect.is_none() {
+        return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::UnexpectedVoid()));
+    }
+
+    if parser_utils.tokens[parser_utils.index].token_type != TokenTypes::BlockStart {
+        return Err(Span::new(parser_utils.file, parser_utils.index).make_error(ParsingMessage::ExpectedCodeBlock()));
+    }
+    parser_utils.index += 1;
+
+    let mut returning: Option<ExpressionType> = None;
+    let mut arms = Vec::default();
+    loop {
+        let token = parser_utils.tokens[parser_utils.index].clone();
+        let arm_span = Span::new(parser_utils.file, parser_utils.index);
+        parser_utils.index += 1;
+        match token.token_type {
+            TokenTypes::BlockEnd => break,
+            TokenTypes::Comment => {}
+            TokenTypes::Variable => {
+                let (arm_returning, arm) = parse_switch_arm(parser_utils, token.to_string(parser_utils.buffer), arm_span)?;
+                // The switch is only the return of the block if every arm returns
+                returning = match returning {
+                    Some(found) if found != arm_returning => Some(ExpressionType::Line),
+                    Some(found) => Some(found),
+                    None => Some(arm_returning),
+                };
+                arms.push(arm);
+            }
+            _ => return Err(arm_span.make_error(ParsingMessage::ExpectedSwitchArm())),
+        }
+    }
+
+    parser_utils.imports.last_id += 1;
+    let id = parser_utils.imports.last_id - 1;
+    for arm in &mut arms {
+        arm.body.expressions.push(Expression::new(
+            ExpressionType::Line,
+            Effects::new(Span::default(), EffectType::Jump(id.to_string() + "end")),
+        ));
+    }
+
+    return Ok(Expression::new(
+        returning.unwrap_or(ExpressionType::Line),
+        Effects::new(span, EffectType::Switch(Box::new(effect.unwrap().effect), arms, id.to_string())),
+    ));
+}
+
+/// Parses a single arm of a switch after the variant's name, like the (radius) => { ... } of Circle(radius) => { ... }
+fn parse_switch_arm(
+    parser_utils: &mut ParserUtils,
+    variant: String,
+    span: Span,
+) -> Result<(ExpressionType, SwitchArm), ParsingError> {
+    let mut bindings = Vec::default();
+    if parser_utils.tokens[parser_utils.index].token_type == TokenTypes::ParenOpen {
+        parser_utils.index += 1;
+        loop {
+            let token = &parser_utils.tokens[parser_utils.index];
+            parser_utils.index += 1;
+            match token.token_type {
+                TokenTypes::Variable => bindings.push(token.to_string(parser_utils.buffer)),
+                TokenTypes::ArgumentEnd => {}
+                TokenTypes::ParenClose => break,
+                _ => {
+                    return Err(Span::new(parser_utils.file, parser_utils.index - 1)
+                        .make_error(ParsingMessage::ExpectedVariableName()))
+                }
+            }
+        }
+    }
+
+    // The pattern is followed by a "=>" and the arm's code
+    let tokens = &parser_utils.tokens[parser_utils.index..];
+    if tokens.len() < 3
+        || tokens[0].token_type != TokenTypes::Equals
+        || tokens[1].token_type != TokenTypes::Operator
+        || tokens[1].to_string(parser_utils.buffer) != ">"
+        || tokens[2].token_type != TokenTypes::BlockStart
+    {
+        return Err(span.make_error(ParsingMessage::ExpectedSwitchArm()));
+    }
+    parser_utils.index += 3;
+
+    let (returning, body) = parse_code(parser_utils)?;
+    return Ok((returning, SwitchArm { variant, bindings, body, span }));
+}
+
 /// Parses a do while into a single expression
 pub fn parse_do_while(parser_utils: &mut ParserUtils) -> Result<Effects, ParsingError> {
     if parser_utils.tokens.get(parser_utils.index).unwrap().token_type != TokenTypes::BlockStart {
@@ -178,10 +296,14 @@ fn create_do_while(effect: Effects, mut body: CodeBody, id: u32) -> Result<Effec
     top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
     top.push(Expression::new(
         ExpressionType::Line,
-        Effects::new(Span::default(), EffectType::CompareJump(Box::new(effect), label, id.to_string() + "end")),
+        Effects::new(Span::default(), EffectType::CompareJump(Box::new(effect), label.clone(), id.to_string() + "end")),
     ));
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
+    // Continuing jumps to the condition, which is right after the body
+    return Ok(Effects::new(
+        Span::default(),
+        EffectType::Loop(CodeBody::new(top, id.to_string()), label + "end", id.to_string() + "end", None),
+    ));
 }
 
 /// Creates a while effect from the body and the condition
@@ -196,7 +318,10 @@ fn create_while(effect: Effects, mut body: CodeBody, id: u32) -> Result<Effects,
         .push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::Jump(id.to_string()))));
     top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
+    return Ok(Effects::new(
+        Span::default(),
+        EffectType::Loop(CodeBody::new(top, id.to_string()), id.to_string(), id.to_string() + "end", None),
+    ));
 }
 
 /// Creates an if statement from the body, the effect, the else ifs, and the else body
@@ -217,7 +342,14 @@ fn create_if(
         ));
         Some(body)
     } else if !else_ifs.is_empty() {
-        Some(CodeBody::new(Vec::default(), id.to_string()))
+        // Uses the label after the else ifs' labels, which parse_if saves for it
+        Some(CodeBody::new(
+            vec![Expression::new(
+                ExpressionType::Line,
+                Effects::new(Span::default(), EffectType::Jump(id.to_string() + "end")),
+            )],
+            (id + 1 + else_ifs.len() as u32).to_string(),
+        ))
     } else {
         None
     };
@@ -301,7 +433,7 @@ fn create_for(name: String, effect: Effects, mut body: CodeBody, id: u32) -> Res
         0,
         Expression::new(
             ExpressionType::Line,
-            Effects::new(Span::default(), EffectType::CreateVariable(variable.clone(), Box::new(effect))),
+            Effects::new(Span::default(), EffectType::CreateVariable(variable.clone(), Box::new(effect), None)),
         ),
     );
     top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::Jump((id + 1).to_string()))));
@@ -322,8 +454,10 @@ fn create_for(name: String, effect: Effects, mut body: CodeBody, id: u32) -> Res
                             "next".to_string(),
                             vec![],
                             None,
+                            vec![],
                         ),
                     )),
+                    None,
                 ),
             ),
         ),
@@ -347,6 +481,7 @@ fn create_for(name: String, effect: Effects, mut body: CodeBody, id: u32) -> Res
                             "has_next".to_string(),
                             vec![],
                             None,
+                            vec![],
                         ),
                     )),
                     body.label.clone(),
@@ -361,5 +496,9 @@ fn create_for(name: String, effect: Effects, mut body: CodeBody, id: u32) -> Res
     top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(for_check))));
     top.push(Expression::new(ExpressionType::Line, Effects::new(Span::default(), EffectType::CodeBody(body))));
 
-    return Ok(Effects::new(Span::default(), EffectType::CodeBody(CodeBody::new(top, id.to_string()))));
+    // Continuing jumps to the check for the next value
+    return Ok(Effects::new(
+        Span::default(),
+        EffectType::Loop(CodeBody::new(top, id.to_string()), (id + 1).to_string(), id.to_string() + "end", None),
+    ));
 }
@@ -1,3 +1,4 @@
+use std::future::Future;
 use std::sync::Arc;
 
 use indexmap::IndexMap;
@@ -5,13 +6,13 @@ use indexmap::IndexMap;
 use data::tokens::{Span, TokenTypes};
 use syntax::async_util::NameResolver;
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
-use syntax::program::code::MemberField;
-use syntax::program::function::{CodeBody, FunctionData, UnfinalizedFunction};
+use syntax::program::code::{Effects, MemberField};
+use syntax::program::function::{Abi, CodeBody, FunctionData, Inline, UnfinalizedFunction};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::Types;
 use syntax::{get_modifier, Attribute, Modifier, ParsingFuture};
 
-use crate::parser::code_parser::parse_code;
+use crate::parser::code_parser::{parse_code, parse_line, ParseState};
 use crate::parser::struct_parser::{parse_generics, to_field};
 use crate::parser::util::ParserUtils;
 
@@ -21,15 +22,20 @@ pub fn parse_function(
     trait_function: bool,
     attributes: Vec<Attribute>,
     modifiers: Vec<Modifier>,
+    abi: Option<(String, Span)>,
 ) -> Result<UnfinalizedFunction, ParsingError> {
     let mut name = String::default();
     let mut generics = IndexMap::default();
+    let mut generic_spans = IndexMap::default();
     let mut fields: Vec<ParsingFuture<MemberField>> = Vec::default();
     let mut code = None;
     let mut return_type = None;
+    let mut self_bounds = Vec::default();
 
     let mut last_arg = String::default();
     let mut last_arg_type = String::default();
+    let mut last_default = None;
+    let mut variadic = false;
 
     let token = parser_utils.index;
 
@@ -38,11 +44,25 @@ pub fn parse_function(
         parser_utils.index += 1;
         match token.token_type {
             TokenTypes::Identifier => name = parser_utils.file_name.clone() + "::" + &*token.to_string(parser_utils.buffer),
-            TokenTypes::GenericsStart => parse_generics(parser_utils, &mut generics),
+            TokenTypes::GenericsStart => {
+                let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                if !parse_generics(parser_utils, &mut generics, &mut generic_spans).is_empty() {
+                    return Err(span.make_error(ParsingMessage::GenericDefaultOutsideTrait()));
+                }
+            }
             TokenTypes::ArgumentsStart | TokenTypes::ArgumentSeparator | TokenTypes::ArgumentTypeSeparator => {}
             TokenTypes::ArgumentName => last_arg = token.to_string(parser_utils.buffer),
             TokenTypes::ArgumentType => last_arg_type = token.to_string(parser_utils.buffer),
+            TokenTypes::ArgumentDefault => {
+                last_default = parse_line(parser_utils, ParseState::None)?.map(|line| line.effect)
+            }
             TokenTypes::ArgumentEnd => {
+                if variadic {
+                    return Err(
+                        Span::new(parser_utils.file, parser_utils.index - 1).make_error(ParsingMessage::VariadicNotLast())
+                    );
+                }
+
                 if last_arg_type.is_empty() {
                     if !parser_utils.imports.parent.is_some() {
                         return Err(
@@ -63,12 +83,24 @@ pub fn parse_function(
                         last_arg,
                     )));
                 } else {
-                    fields.push(Box::pin(to_field(
+                    // Variadic arguments are collected into an array of the element type
+                    let mut modifiers = 0;
+                    if let Some(element) = last_arg_type.strip_prefix("...") {
+                        variadic = true;
+                        modifiers = Modifier::Variadic as u8;
+                        last_arg_type = format!("[{}]", element.trim());
+                    }
+
+                    let field = to_field(
                         parser_utils.get_struct(&Span::new(parser_utils.file, parser_utils.index - 1), last_arg_type),
                         Vec::default(),
-                        0,
+                        modifiers,
                         last_arg,
-                    )));
+                    );
+                    match last_default.take() {
+                        Some(default) => fields.push(Box::pin(with_default(field, default))),
+                        None => fields.push(Box::pin(field)),
+                    }
                     last_arg_type = String::default();
                 }
                 last_arg = String::default();
@@ -78,6 +110,20 @@ pub fn parse_function(
                 let ret_name = token.to_string(parser_utils.buffer).clone();
                 return_type = Some(parser_utils.get_struct(&Span::new(parser_utils.file, parser_utils.index - 1), ret_name))
             }
+            TokenTypes::Where => {}
+            TokenTypes::WhereType => {
+                let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                let bounded = token.to_string(parser_utils.buffer);
+                if bounded != "Self" {
+                    return Err(span.make_error(ParsingMessage::WhereNotSelf(bounded)));
+                } else if parser_utils.imports.parent.is_none() {
+                    return Err(span.make_error(ParsingMessage::SelfInStatic()));
+                }
+            }
+            TokenTypes::WhereBound => {
+                let bound = token.to_string(parser_utils.buffer)[1..].trim().to_string();
+                self_bounds.push(parser_utils.get_struct(&Span::new(parser_utils.file, parser_utils.index - 1), bound));
+            }
             TokenTypes::CodeStart => {
                 code = Some(parse_code(parser_utils)?.1);
                 break;
@@ -91,6 +137,15 @@ pub fn parse_function(
             _ => panic!("How'd you get here? {:?}", token.token_type),
         }
     }
+    let abi = match abi {
+        Some((name, span)) => {
+            if !modifiers.contains(&Modifier::Extern) {
+                return Err(span.make_error(ParsingMessage::AbiWithoutExtern()));
+            }
+            Abi::from_name(&name).ok_or_else(|| span.make_error(ParsingMessage::UnknownAbi(name)))?
+        }
+        None => Abi::default(),
+    };
     let mut modifiers = get_modifier(modifiers.as_slice());
 
     if trait_function {
@@ -112,18 +167,42 @@ pub fn parse_function(
     }
 
     let span = Span::new(parser_utils.file, token);
+    let inline = match (
+        Attribute::find_attribute("inline", &attributes).is_some(),
+        Attribute::find_attribute("noinline", &attributes).is_some(),
+    ) {
+        (true, true) => return Err(span.make_error(ParsingMessage::ConflictingInline(name))),
+        (true, false) => Inline::Always,
+        (false, true) => Inline::Never,
+        (false, false) => Inline::Auto,
+    };
+    let mut data = FunctionData::new(attributes, modifiers, name, span.clone());
+    data.abi = abi;
+    data.inline = inline;
     return Ok(UnfinalizedFunction {
         generics,
+        generic_spans,
         fields,
         code: code.unwrap_or_else(|| CodeBody::new(Vec::default(), "empty".to_string())),
         return_type,
-        data: Arc::new(FunctionData::new(attributes, modifiers, name, span.clone())),
+        self_bounds,
+        data: Arc::new(data),
         parent: parser_utils.imports.parent.clone().map(|types| {
             Syntax::parse_type(parser_utils.syntax.clone(), span, Box::new(parser_utils.imports.clone()), types, vec![])
         }),
     });
 }
 
+/// Waits for the argument and gives it its default value
+async fn with_default(
+    field: impl Future<Output = Result<MemberField, ParsingError>>,
+    default: Effects,
+) -> Result<MemberField, ParsingError> {
+    let mut field = field.await?;
+    field.default = Some(default);
+    return Ok(field);
+}
+
 /// Awaits the ParsingFuture for the generics
 pub async fn get_generics(
     generics: IndexMap<String, Vec<ParsingFuture<Types>>>,
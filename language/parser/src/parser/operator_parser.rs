@@ -1,4 +1,4 @@
-use syntax::errors::ParsingError;
+use syntax::errors::{ParsingError, ParsingMessage};
 use syntax::program::code::{EffectType, Effects};
 
 use crate::parser::code_parser::{parse_line, ParseState};
@@ -20,6 +20,8 @@ pub fn parse_operator(
     }
 
     parser_utils.index -= 1;
+    // The operator's own tokens, which the call it's checked as is an expansion of
+    let mut operator = Span::new(parser_utils.file, parser_utils.index);
     while let Some(token) = parser_utils.tokens.get(parser_utils.index) {
         if token.token_type == TokenTypes::Operator
             || token.token_type == TokenTypes::Equals
@@ -31,6 +33,7 @@ pub fn parse_operator(
         }
         parser_utils.index += 1;
     }
+    operator.extend_span(parser_utils.index.saturating_sub(1).max(operator.start));
 
     let mut first_element_token = Span::new(parser_utils.file, parser_utils.index);
     let (mut index, mut tokens) = (parser_utils.index.clone(), parser_utils.tokens.len());
@@ -42,6 +45,8 @@ pub fn parse_operator(
         },
     ) {
         Ok(inner) => inner.map(|inner| inner.effect),
+        // Operators without a right side don't parse one, but nesting too deep is an error no matter what's there
+        Err(error) if matches!(error.message, ParsingMessage::NestingTooDeep(_)) => return Err(error),
         Err(_) => None,
     };
     first_element_token.extend_span(parser_utils.index);
@@ -78,10 +83,9 @@ pub fn parse_operator(
             if matches!(inner.types, EffectType::NOP) {
                 parser_utils.index = index;
                 parser_utils.tokens.truncate(tokens);
-                return Ok(Effects::new(
-                    Span::new(parser_utils.file, parser_utils.index),
-                    EffectType::Operation(operation, effects),
-                ));
+                let span =
+                    Span::new(parser_utils.file, parser_utils.index).expanded_from(expansion_name(&operation), operator);
+                return Ok(Effects::new(span, EffectType::Operation(operation, effects)));
             } else {
                 operation += "{}";
             }
@@ -100,6 +104,7 @@ pub fn parse_operator(
             }
             parser_utils.index += 1;
         }
+        operator.extend_span(parser_utils.index.saturating_sub(1).max(operator.start));
     }
 
     if let Some(found) = right {
@@ -117,8 +122,11 @@ pub fn parse_operator(
         last.clone_from(&parser_utils.tokens[parser_utils.index - 1].token_type);
     }
 
-    return Ok(Effects {
-        types: EffectType::Operation(operation, effects),
-        span: Span::new(parser_utils.file, parser_utils.index),
-    });
+    let span = Span::new(parser_utils.file, parser_utils.index).expanded_from(expansion_name(&operation), operator);
+    return Ok(Effects { types: EffectType::Operation(operation, effects), span });
+}
+
+/// Names the operator the way errors in its expansion show it, like "operator +" for {}+{}
+fn expansion_name(operation: &str) -> String {
+    return format!("operator {}", operation.replace("{}", ""));
 }
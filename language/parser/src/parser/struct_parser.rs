@@ -6,13 +6,14 @@ use data::tokens::{Span, Token, TokenTypes};
 use syntax::async_util::{NameResolver, UnparsedType};
 use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
 use syntax::program::code::{Field, MemberField};
-use syntax::program::r#struct::{get_internal, StructData, UnfinalizedStruct};
+use syntax::program::constant::UnfinalizedConstant;
+use syntax::program::r#struct::{get_internal, EnumVariant, StructData, UnfinalizedStruct, VARIANT_FIELD};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::Types;
 use syntax::{get_modifier, is_modifier, Attribute, Modifier, ParsingFuture, TraitImplementor};
 
 use crate::parser::function_parser::parse_function;
-use crate::parser::top_parser::{parse_attribute, parse_import, parse_modifier};
+use crate::parser::top_parser::{parse_attribute, parse_constant_declaration, parse_import, parse_modifier};
 use crate::parser::util::ParserUtils;
 
 /// Parses a program
@@ -22,15 +23,23 @@ pub fn parse_structure(
     modifiers: Vec<Modifier>,
 ) -> Result<UnfinalizedStruct, ParsingError> {
     let modifiers = get_modifier(modifiers.as_slice());
+    // A disabled struct's functions are left out with it
+    let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&attributes);
 
     let mut member_modifiers = Vec::default();
+    let mut abi = None;
     let mut member_attributes = Vec::default();
 
     let start = Span::new(parser_utils.file, parser_utils.index);
     let mut name = String::default();
     let mut fields = Vec::default();
     let mut generics = IndexMap::default();
+    let mut generic_spans = IndexMap::default();
+    let mut generic_defaults = Vec::default();
     let mut functions = Vec::default();
+    let mut traits = Vec::default();
+    let mut variants = Vec::default();
+    let mut constants = Vec::default();
     while parser_utils.tokens.len() != parser_utils.index {
         let token: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
         let token: Token = token.clone();
@@ -41,12 +50,34 @@ pub fn parse_structure(
                 parser_utils.imports.parent = Some(UnparsedType::Basic(name.clone()));
             }
             TokenTypes::GenericsStart => {
-                parse_generics(parser_utils, &mut generics);
+                let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                let mut defaults = parse_generics(parser_utils, &mut generics, &mut generic_spans);
+                if !defaults.is_empty() && !is_modifier(modifiers, Modifier::Trait) {
+                    return Err(span.make_error(ParsingMessage::GenericDefaultOutsideTrait()));
+                }
+                // Kept in the generics' order, because impls can only leave out the last generics
+                if !defaults.is_empty() {
+                    generic_defaults = generics.keys().map(|generic| defaults.shift_remove(generic)).collect();
+                }
                 parser_utils.imports.parent = Some(UnparsedType::Generic(
                     Box::new(parser_utils.imports.parent.clone().unwrap()),
                     generics.keys().map(|key| UnparsedType::Basic(key.clone())).collect(),
                 ));
             }
+            TokenTypes::Supertrait => {
+                let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                if !is_modifier(modifiers, Modifier::Trait) {
+                    return Err(span.make_error(ParsingMessage::SupertraitOnStruct()));
+                }
+                let supertrait: ParsingFuture<Types> = Box::pin(Syntax::parse_type(
+                    parser_utils.syntax.clone(),
+                    span,
+                    parser_utils.imports.boxed_clone(),
+                    UnparsedType::Basic(token.to_string(parser_utils.buffer)[1..].trim().to_string()),
+                    vec![],
+                ));
+                traits.push(supertrait);
+            }
             TokenTypes::StructTopElement | TokenTypes::Comment => {}
             TokenTypes::InvalidCharacters => {
                 parser_utils.syntax.lock().unwrap().add_poison(Arc::new(StructData::new_poisoned(
@@ -57,7 +88,7 @@ pub fn parse_structure(
             TokenTypes::ImportStart => parse_import(parser_utils),
             TokenTypes::AttributesStart => parse_attribute(parser_utils, &mut member_attributes),
             TokenTypes::ModifiersStart => {
-                parse_modifier(parser_utils, &mut member_modifiers);
+                abi = parse_modifier(parser_utils, &mut member_modifiers);
                 if is_modifier(modifiers, Modifier::Internal) {
                     member_modifiers.push(Modifier::Internal);
                 }
@@ -69,13 +100,21 @@ pub fn parse_structure(
                 } else {
                     parser_utils.file_name = format!("{}::{}", parser_utils.file_name, name);
                 }
+                let function_enabled = enabled && parser_utils.syntax.lock().unwrap().is_enabled(&member_attributes);
                 let function = parse_function(
                     parser_utils,
                     is_modifier(modifiers, Modifier::Trait),
                     member_attributes,
                     member_modifiers,
+                    abi.take(),
                 );
-                functions.push(ParserUtils::add_function(&parser_utils.syntax, parser_utils.file_name.clone(), function));
+                if function_enabled {
+                    functions.push(ParserUtils::add_function(
+                        &parser_utils.syntax,
+                        parser_utils.file_name.clone(),
+                        function,
+                    ));
+                }
                 parser_utils.file_name = file;
                 member_attributes = Vec::default();
                 member_modifiers = Vec::default();
@@ -90,26 +129,59 @@ pub fn parse_structure(
                 member_attributes = Vec::default();
                 member_modifiers = Vec::default();
             }
+            TokenTypes::Variant => {
+                let variant = token.to_string(parser_utils.buffer);
+                variants.push(parse_variant(parser_utils, variant, &mut fields));
+            }
+            TokenTypes::ConstStart => {
+                let (name, types, value, span) = parse_constant_declaration(parser_utils)?;
+                if !is_modifier(modifiers, Modifier::Trait) {
+                    return Err(span.make_error(ParsingMessage::AssociatedConstantInStruct()));
+                }
+                if value.is_some() {
+                    return Err(span.make_error(ParsingMessage::AssociatedConstantValue()));
+                }
+                let types: ParsingFuture<Types> = Box::pin(Syntax::parse_type(
+                    parser_utils.syntax.clone(),
+                    span,
+                    parser_utils.imports.boxed_clone(),
+                    types,
+                    vec![],
+                ));
+                constants.push((name, types));
+                member_attributes = Vec::default();
+                member_modifiers = Vec::default();
+            }
             TokenTypes::StructEnd => break,
             TokenTypes::EOF => break,
             _ => panic!("How'd you get here? {:?}", token.token_type),
         }
     }
 
+    // Enums start with which variant they hold
+    if is_modifier(modifiers, Modifier::Enum) {
+        let tag: ParsingFuture<Types> = Box::pin(async { Ok(Types::Struct(get_internal("u64".to_string()))) });
+        fields.insert(0, Box::pin(to_field(tag, Vec::default(), 0, VARIANT_FIELD.to_string())));
+    }
+
     let data = if is_modifier(modifiers, Modifier::Internal) && !is_modifier(modifiers, Modifier::Trait) {
+        parser_utils.syntax.lock().unwrap().internal_modules.insert(name.clone(), parser_utils.file_name.clone());
         get_internal(name)
     } else {
         let name = format!("{}::{}", parser_utils.file_name, name);
-        Arc::new(StructData::new(
+        let mut data = StructData::new(
             attributes,
             functions.iter().map(|inner| inner.data.clone()).collect::<Vec<_>>(),
             modifiers,
             start,
             name,
-        ))
+        );
+        data.variants = variants;
+        data.generic_defaults = generic_defaults;
+        Arc::new(data)
     };
 
-    return Ok(UnfinalizedStruct { generics, fields, functions, data });
+    return Ok(UnfinalizedStruct { generics, generic_spans, fields, functions, traits, constants, data });
 }
 
 /// Parses an implementor
@@ -125,7 +197,9 @@ pub fn parse_implementor(
 
     let mut member_attributes = Vec::default();
     let mut member_modifiers = Vec::default();
+    let mut abi = None;
     let mut functions = Vec::default();
+    let mut constants = Vec::default();
     let mut generics = IndexMap::default();
 
     let mut state = 0;
@@ -137,6 +211,8 @@ pub fn parse_implementor(
                 let name = token.to_string(parser_utils.buffer);
                 let temp = Some(UnparsedType::Basic(name.clone()));
                 if state == 0 {
+                    // Self is the base unless this impl is for another type
+                    parser_utils.imports.parent = temp.clone();
                     base = temp;
                     base_span = Some(Span::new(parser_utils.file, parser_utils.index - 1));
                     state = 1;
@@ -148,15 +224,20 @@ pub fn parse_implementor(
             }
             TokenTypes::GenericsStart => {
                 if state == 0 {
-                    parse_generics(parser_utils, &mut generics);
+                    let span = Span::new(parser_utils.file, parser_utils.index - 1);
+                    if !parse_generics(parser_utils, &mut generics, &mut IndexMap::default()).is_empty() {
+                        let error = span.make_error(ParsingMessage::GenericDefaultOutsideTrait());
+                        return (Err(error), "error".to_string(), "error".to_string());
+                    }
                 } else {
                     let type_generics = match parse_type_generics(parser_utils) {
                         Ok(generics) => generics,
                         Err(error) => return (Err(error), "error".to_string(), "error".to_string()),
                     };
                     if state == 1 {
-                        let found = UnparsedType::Generic(Box::new(base.unwrap()), type_generics);
-                        base = Some(found);
+                        let found = Some(UnparsedType::Generic(Box::new(base.unwrap()), type_generics));
+                        parser_utils.imports.parent = found.clone();
+                        base = found;
                         base_span.as_mut().unwrap().extend_span(parser_utils.index - 1);
                     } else {
                         let found = Some(UnparsedType::Generic(Box::new(implementor.unwrap()), type_generics));
@@ -169,7 +250,7 @@ pub fn parse_implementor(
             TokenTypes::For => state = 2,
             TokenTypes::AttributesStart => parse_attribute(parser_utils, &mut member_attributes),
             TokenTypes::ModifiersStart => {
-                parse_modifier(parser_utils, &mut member_modifiers);
+                abi = parse_modifier(parser_utils, &mut member_modifiers);
                 if modifiers.contains(&Modifier::Internal) {
                     member_modifiers.push(Modifier::Internal);
                 }
@@ -184,18 +265,43 @@ pub fn parse_implementor(
                 } else {
                     parser_utils.file_name = format!("{}::{}", parser_utils.file_name, base.as_ref().unwrap());
                 }
-                let function = match parse_function(parser_utils, false, member_attributes, member_modifiers) {
+                let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&member_attributes);
+                let function = match parse_function(parser_utils, false, member_attributes, member_modifiers, abi.take()) {
                     Ok(inner) => inner,
                     Err(error) => return (Err(error), "error".to_string(), "error".to_string()),
                 };
-                functions.push(function);
+                if enabled {
+                    functions.push(function);
+                }
                 parser_utils.file_name = file;
                 member_attributes = Vec::default();
                 member_modifiers = Vec::default();
             }
+            TokenTypes::ConstStart => {
+                let (name, types, value, span) = match parse_constant_declaration(parser_utils) {
+                    Ok(declaration) => declaration,
+                    Err(error) => return (Err(error), "error".to_string(), "error".to_string()),
+                };
+                let value = match value {
+                    Some(value) => value,
+                    None => {
+                        let error = span.make_error(ParsingMessage::InvalidConstant());
+                        return (Err(error), "error".to_string(), "error".to_string());
+                    }
+                };
+                let name = match implementor.as_ref() {
+                    Some(implementor) => {
+                        format!("{}::{}_{}::{}", parser_utils.file_name, base.as_ref().unwrap(), implementor, name)
+                    }
+                    None => format!("{}::{}::{}", parser_utils.file_name, base.as_ref().unwrap(), name),
+                };
+                constants.push(Arc::new(UnfinalizedConstant { name, types, value, span }));
+                member_attributes = Vec::default();
+                member_modifiers = Vec::default();
+            }
             TokenTypes::StructTopElement => {}
             TokenTypes::StructEnd | TokenTypes::EOF => break,
-            TokenTypes::InvalidCharacters => {
+            TokenTypes::InvalidCharacters | TokenTypes::Supertrait => {
                 return (
                     Err(Span::new(parser_utils.file, parser_utils.index - 1)
                         .make_error(ParsingMessage::UnexpectedCharacters())),
@@ -213,6 +319,7 @@ pub fn parse_implementor(
         }
     }
 
+    let span = implementor_span.clone().unwrap_or(base_span.clone().unwrap());
     let base_future = Box::pin(Syntax::parse_type(
         parser_utils.syntax.clone(),
         base_span.unwrap(),
@@ -234,7 +341,15 @@ pub fn parse_implementor(
     };
 
     return (
-        Ok(TraitImplementor { base: base_future, generics, implementor: implementor_future, functions, attributes }),
+        Ok(TraitImplementor {
+            base: base_future,
+            generics,
+            implementor: implementor_future,
+            functions,
+            constants,
+            attributes,
+            span,
+        }),
         base.unwrap().to_string(),
         implementor.map(|inner| inner.to_string()).unwrap_or("none".to_string()),
     );
@@ -268,9 +383,17 @@ pub fn parse_type_generics(parser_utils: &mut ParserUtils) -> Result<Vec<Unparse
     return Ok(current);
 }
 
-/// Parses the generics and adds them to the generics map
-pub fn parse_generics(parser_utils: &mut ParserUtils, generics: &mut IndexMap<String, Vec<ParsingFuture<Types>>>) {
+/// Parses the generics and adds them and where they're declared to the maps,
+/// returning the defaults of those that have one
+pub fn parse_generics(
+    parser_utils: &mut ParserUtils,
+    generics: &mut IndexMap<String, Vec<ParsingFuture<Types>>>,
+    spans: &mut IndexMap<String, Span>,
+) -> IndexMap<String, UnparsedType> {
+    let mut defaults = IndexMap::default();
     let mut name = String::default();
+    // Const generics, like "const N: u64", are bound by a constant instead of a type
+    let mut constant = false;
     let mut bounds: Vec<ParsingFuture<Types>> = Vec::default();
     let mut unparsed_bounds: Vec<UnparsedType> = Vec::default();
     while parser_utils.tokens.len() != parser_utils.index {
@@ -283,6 +406,11 @@ pub fn parse_generics(parser_utils: &mut ParserUtils, generics: &mut IndexMap<St
                     name = name[1..].to_string();
                 }
                 name = name.trim().to_string();
+                constant = name.starts_with("const ");
+                if constant {
+                    name = name["const ".len()..].trim().to_string();
+                }
+                spans.insert(name.clone(), Span::new(parser_utils.file, parser_utils.index - 1));
             }
             TokenTypes::GenericEnd => {
                 parser_utils.imports.generics.insert(name.clone(), unparsed_bounds);
@@ -293,15 +421,19 @@ pub fn parse_generics(parser_utils: &mut ParserUtils, generics: &mut IndexMap<St
             TokenTypes::GenericBound => {
                 let token = parser_utils.tokens.get(parser_utils.index - 1).unwrap();
                 let mut name = token.to_string(parser_utils.buffer);
-                if name.starts_with(':') {
+                // Each bound after the first is separated by a + instead of the :
+                if name.starts_with(':') || name.starts_with('+') {
                     name = name[1..].to_string();
                 }
                 let name = name.trim().to_string();
-                let unparsed = if let Some(inner) = parse_bounds(name.clone(), parser_utils) {
+                let mut unparsed = if let Some(inner) = parse_bounds(name.clone(), parser_utils) {
                     inner
                 } else {
                     break;
                 };
+                if constant {
+                    unparsed = UnparsedType::Const(Box::new(unparsed));
+                }
                 unparsed_bounds.push(unparsed.clone());
                 bounds.push(Syntax::parse_type(
                     parser_utils.syntax.clone(),
@@ -311,6 +443,14 @@ pub fn parse_generics(parser_utils: &mut ParserUtils, generics: &mut IndexMap<St
                     vec![],
                 ));
             }
+            TokenTypes::GenericDefault => {
+                let token = parser_utils.tokens.get(parser_utils.index - 1).unwrap();
+                let default = token.to_string(parser_utils.buffer)[1..].trim().to_string();
+                match parse_bounds(default, parser_utils) {
+                    Some(default) => defaults.insert(name.clone(), default),
+                    None => break,
+                };
+            }
             TokenTypes::GenericsEnd => {
                 if !name.is_empty() {
                     parser_utils.imports.generics.insert(name.clone(), unparsed_bounds);
@@ -330,6 +470,7 @@ pub fn parse_generics(parser_utils: &mut ParserUtils, generics: &mut IndexMap<St
             ),
         }
     }
+    return defaults;
 }
 
 /// Parses the bounds of a generic
@@ -346,7 +487,8 @@ pub fn parse_bounds(name: String, parser_utils: &mut ParserUtils) -> Option<Unpa
         match token.token_type {
             TokenTypes::Generic | TokenTypes::GenericBound => {
                 let mut name = token.to_string(parser_utils.buffer);
-                if name.starts_with(':') {
+                // Each bound after the first is separated by a + instead of the :
+                if name.starts_with(':') || name.starts_with('+') {
                     name = name[1..].to_string();
                 }
                 name = name.trim().to_string();
@@ -411,6 +553,44 @@ pub fn parse_field(
     return Box::pin(to_field(types.unwrap(), attributes, get_modifier(modifiers.as_slice()), name));
 }
 
+/// Parses a single variant of an enum, adding each of its fields to the enum's fields
+fn parse_variant(parser_utils: &mut ParserUtils, name: String, fields: &mut Vec<ParsingFuture<MemberField>>) -> EnumVariant {
+    let mut variant = EnumVariant {
+        name,
+        fields: Vec::default(),
+        discriminant: None,
+        span: Span::new(parser_utils.file, parser_utils.index - 1),
+    };
+    let mut field = String::default();
+    while parser_utils.tokens.len() != parser_utils.index {
+        let token = &parser_utils.tokens[parser_utils.index];
+        parser_utils.index += 1;
+        match token.token_type {
+            TokenTypes::ArgumentName => field = token.to_string(parser_utils.buffer),
+            TokenTypes::ArgumentType => {
+                let name = token.to_string(parser_utils.buffer);
+                let types = parser_utils.get_struct(&Span::new(parser_utils.file, parser_utils.index - 1), name);
+                fields.push(Box::pin(to_field(types, Vec::default(), 0, variant.field_name(&field))));
+                variant.fields.push(field.clone());
+            }
+            TokenTypes::Integer => variant.discriminant = Some(token.to_string(parser_utils.buffer)),
+            TokenTypes::ArgumentsStart
+            | TokenTypes::ArgumentTypeSeparator
+            | TokenTypes::ArgumentSeparator
+            | TokenTypes::ArgumentsEnd
+            | TokenTypes::Equals
+            | TokenTypes::Comment => {}
+            TokenTypes::VariantEnd => break,
+            // Anything else, like invalid characters, is left for the enum to handle
+            _ => {
+                parser_utils.index -= 1;
+                break;
+            }
+        }
+    }
+    return variant;
+}
+
 /// Waits for the type to finish and converts it to a field
 pub async fn to_field(
     types: ParsingFuture<Types>,
@@ -1,12 +1,15 @@
 use std::sync::Arc;
 
 use data::tokens::{Span, Token, TokenTypes};
-use syntax::async_util::NameResolver;
-use syntax::errors::{ErrorSource, ParsingMessage};
-use syntax::program::function::FunctionData;
+use syntax::async_util::{TypeAlias, UnparsedType};
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::code::Effects;
+use syntax::program::constant::UnfinalizedConstant;
+use syntax::program::function::{FunctionData, ParsedFunction};
 use syntax::program::r#struct::StructData;
 use syntax::{Attribute, Modifier, TopElement, MODIFIERS};
 
+use crate::parser::code_parser::{parse_line, ParseState};
 use crate::parser::function_parser::parse_function;
 use crate::parser::struct_parser::{parse_implementor, parse_structure};
 use crate::parser::util::ParserUtils;
@@ -14,6 +17,7 @@ use crate::parser::util::ParserUtils;
 /// Parses a top element
 pub fn parse_top(parser_utils: &mut ParserUtils) {
     let mut modifiers = vec![];
+    let mut abi = None;
     let mut attributes = vec![];
     while parser_utils.tokens.len() != parser_utils.index {
         let token: &Token = parser_utils.tokens.get(parser_utils.index).unwrap();
@@ -27,54 +31,114 @@ pub fn parse_top(parser_utils: &mut ParserUtils) {
                 )))
             }
             TokenTypes::ImportStart => parse_import(parser_utils),
+            TokenTypes::ConstStart => {
+                parse_constant(parser_utils);
+                attributes = Vec::default();
+                modifiers = Vec::default();
+            }
+            TokenTypes::TypeAliasStart => {
+                parse_type_alias(parser_utils);
+                attributes = Vec::default();
+                modifiers = Vec::default();
+            }
             TokenTypes::AttributesStart => parse_attribute(parser_utils, &mut attributes),
-            TokenTypes::ModifiersStart => parse_modifier(parser_utils, &mut modifiers),
+            TokenTypes::ModifiersStart => abi = parse_modifier(parser_utils, &mut modifiers),
             TokenTypes::FunctionStart => {
-                let function = parse_function(parser_utils, false, attributes, modifiers);
+                // Top functions aren't inside a struct or impl, so the generics and Self of the last one don't apply
+                parser_utils.imports.generics.clear();
+                parser_utils.imports.parent = None;
+                let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&attributes);
+                let function = parse_function(parser_utils, false, attributes, modifiers, abi.take());
+                // Disabled functions are still parsed to skip past them, but never added or checked
+                if !enabled {
+                    attributes = vec![];
+                    modifiers = vec![];
+                    continue;
+                }
                 let function = ParserUtils::add_function(&parser_utils.syntax, parser_utils.file_name.clone(), function);
-                let process_manager = parser_utils.syntax.lock().unwrap().process_manager.cloned();
-                parser_utils.handle.lock().unwrap().spawn(
-                    function.data.name.clone(),
-                    FunctionData::verify(
-                        parser_utils.handle.clone(),
-                        function,
-                        parser_utils.syntax.clone(),
-                        Box::new(parser_utils.imports.clone()),
-                        process_manager,
-                    ),
-                );
+                let parsed = ParsedFunction { function, resolver: Box::new(parser_utils.imports.clone()) };
+                // When checking is streamed the checker spawns the task once it receives the function,
+                // unless it already stopped receiving
+                let unsent = match &parser_utils.functions {
+                    Some(functions) => functions.send(parsed).err().map(|error| error.0),
+                    None => Some(parsed),
+                };
+                if let Some(parsed) = unsent {
+                    let process_manager = parser_utils.syntax.lock().unwrap().process_manager.cloned();
+                    parser_utils.handle.lock().unwrap().spawn(
+                        parsed.function.data.name.clone(),
+                        FunctionData::verify(
+                            parser_utils.handle.clone(),
+                            parsed.function,
+                            parser_utils.syntax.clone(),
+                            parsed.resolver,
+                            process_manager,
+                        ),
+                    );
+                }
 
                 attributes = vec![];
                 modifiers = vec![];
             }
             TokenTypes::StructStart => {
+                let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&attributes);
                 let structure = parse_structure(parser_utils, attributes, modifiers);
-                parser_utils.add_struct(structure);
+                if enabled {
+                    parser_utils.add_struct(structure);
+                }
                 attributes = vec![];
                 modifiers = vec![];
             }
             TokenTypes::TraitStart => {
                 modifiers.push(Modifier::Trait);
+                let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&attributes);
                 let structure = parse_structure(parser_utils, attributes, modifiers);
-                parser_utils.add_struct(structure);
+                if enabled {
+                    parser_utils.add_struct(structure);
+                }
+                attributes = Vec::default();
+                modifiers = Vec::default();
+            }
+            TokenTypes::EnumStart => {
+                modifiers.push(Modifier::Enum);
+                let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&attributes);
+                let structure = parse_structure(parser_utils, attributes, modifiers);
+                if enabled {
+                    parser_utils.add_struct(structure);
+                }
                 attributes = Vec::default();
                 modifiers = Vec::default();
             }
             TokenTypes::ImplStart => {
+                let enabled = parser_utils.syntax.lock().unwrap().is_enabled(&attributes);
                 let (trait_implementor, base, implementor) = parse_implementor(parser_utils, attributes, modifiers);
+                if !enabled {
+                    attributes = Vec::default();
+                    modifiers = Vec::default();
+                    continue;
+                }
                 let process_manager = {
                     let mut locked = parser_utils.syntax.lock().unwrap();
                     locked.async_manager.parsing_impls += 1;
                     locked.process_manager.cloned()
                 };
 
+                // The impl's associated constants can only be used in its own code, through Self
+                let mut resolver = parser_utils.imports.clone();
+                if let Ok(found) = &trait_implementor {
+                    for constant in &found.constants {
+                        let name = constant.name.split("::").last().unwrap();
+                        resolver.constants.insert(format!("Self::{}", name), constant.clone());
+                    }
+                }
+
                 parser_utils.handle.lock().unwrap().spawn(
                     format!("{}_{}", base, implementor),
                     ParserUtils::add_implementor(
                         parser_utils.handle.clone(),
                         parser_utils.syntax.clone(),
                         trait_implementor,
-                        parser_utils.imports.boxed_clone(),
+                        Box::new(resolver),
                         process_manager,
                         base,
                         implementor,
@@ -111,6 +175,117 @@ pub fn parse_import(parser_utils: &mut ParserUtils) {
     }
 }
 
+/// Parses a constant, adding it to the file's constants and verifying it in a separate task
+pub fn parse_constant(parser_utils: &mut ParserUtils) {
+    let (name, types, value, span) = match parse_constant_declaration(parser_utils) {
+        Ok(declaration) => declaration,
+        Err(error) => {
+            parser_utils.syntax.lock().unwrap().errors.push(error);
+            return;
+        }
+    };
+    let value = match value {
+        Some(value) => value,
+        None => {
+            parser_utils.syntax.lock().unwrap().errors.push(span.make_error(ParsingMessage::InvalidConstant()));
+            return;
+        }
+    };
+
+    let constant =
+        Arc::new(UnfinalizedConstant { name: format!("{}::{}", parser_utils.file_name, name), types, value, span });
+    parser_utils.imports.constants.insert(name, constant.clone());
+    parser_utils.syntax.lock().unwrap().constants.insert(constant.name.clone(), constant.clone());
+    let process_manager = parser_utils.syntax.lock().unwrap().process_manager.cloned();
+    parser_utils.handle.lock().unwrap().spawn(
+        constant.name.clone(),
+        UnfinalizedConstant::verify(
+            parser_utils.handle.clone(),
+            constant,
+            parser_utils.syntax.clone(),
+            Box::new(parser_utils.imports.clone()),
+            process_manager,
+        ),
+    );
+}
+
+/// Parses a constant's name, type and value if it has one, which only constants declared by a trait don't
+pub fn parse_constant_declaration(
+    parser_utils: &mut ParserUtils,
+) -> Result<(String, UnparsedType, Option<Effects>, Span), ParsingError> {
+    let span = Span::new(parser_utils.file, parser_utils.index - 1);
+    let mut name = String::default();
+    let mut types = None;
+    let mut has_value = false;
+    while parser_utils.tokens.len() != parser_utils.index {
+        let next = parser_utils.tokens.get(parser_utils.index).unwrap();
+        parser_utils.index += 1;
+        match next.token_type {
+            TokenTypes::FieldName => name = next.to_string(parser_utils.buffer),
+            TokenTypes::FieldSeparator => {}
+            TokenTypes::FieldType => types = Some(UnparsedType::Basic(next.to_string(parser_utils.buffer))),
+            TokenTypes::FieldValue => {
+                has_value = true;
+                break;
+            }
+            TokenTypes::FieldEnd => break,
+            _ => {
+                parser_utils.index -= 1;
+                return Err(span.make_error(ParsingMessage::InvalidConstant()));
+            }
+        }
+    }
+
+    let value = if has_value {
+        match parse_line(parser_utils, ParseState::None)? {
+            Some(value) => Some(value.effect),
+            None => return Err(span.make_error(ParsingMessage::InvalidConstant())),
+        }
+    } else {
+        None
+    };
+    return match types {
+        Some(types) => Ok((name, types, value, span)),
+        None => Err(span.make_error(ParsingMessage::InvalidConstant())),
+    };
+}
+
+/// Parses a type alias and adds it to the program's aliases
+pub fn parse_type_alias(parser_utils: &mut ParserUtils) {
+    let mut name = String::default();
+    while parser_utils.tokens.len() != parser_utils.index {
+        let next = parser_utils.tokens.get(parser_utils.index).unwrap();
+        parser_utils.index += 1;
+        match next.token_type {
+            TokenTypes::TypeAliasName => name = next.to_string(parser_utils.buffer),
+            TokenTypes::TypeAliasSeparator => {}
+            TokenTypes::TypeAliasType => {
+                // The generics are the names between the <> after the alias's name
+                let (name, generics) = match name.split_once('<') {
+                    Some((name, generics)) => (
+                        name.trim().to_string(),
+                        generics.trim_end_matches('>').split(',').map(|generic| generic.trim().to_string()).collect(),
+                    ),
+                    None => (name.clone(), Vec::default()),
+                };
+                let name = format!("{}::{}", parser_utils.file_name, name);
+                let alias = TypeAlias {
+                    name: name.clone(),
+                    generics,
+                    types: next.to_string(parser_utils.buffer),
+                    imports: parser_utils.imports.imports.clone(),
+                };
+                parser_utils.syntax.lock().unwrap().aliases.insert(name, alias);
+            }
+            TokenTypes::TypeAliasEnd => return,
+            _ => {
+                parser_utils.index -= 1;
+                return;
+            }
+        }
+    }
+}
+
 /// Parses all attributes and adds them to attributes
 pub fn parse_attribute(parser_utils: &mut ParserUtils, attributes: &mut Vec<Attribute>) {
     while parser_utils.index < parser_utils.tokens.len() - 1 {
@@ -145,12 +320,19 @@ pub fn parse_attribute(parser_utils: &mut ParserUtils, attributes: &mut Vec<Attr
     }
 }
 
-/// Parses modifiers and adds them to modifiers
-pub fn parse_modifier(parser_utils: &mut ParserUtils, modifiers: &mut Vec<Modifier>) {
+/// Parses modifiers and adds them to modifiers, returning the name and span of the ABI if one was given like extern "C"
+pub fn parse_modifier(parser_utils: &mut ParserUtils, modifiers: &mut Vec<Modifier>) -> Option<(String, Span)> {
+    let mut abi = None;
     loop {
         let next = parser_utils.tokens.get(parser_utils.index).unwrap();
+        if next.token_type == TokenTypes::Abi {
+            let name = next.to_string(parser_utils.buffer).trim().trim_matches('"').to_string();
+            abi = Some((name, Span::new(parser_utils.file, parser_utils.index)));
+            parser_utils.index += 1;
+            continue;
+        }
         if next.token_type != TokenTypes::Modifier {
-            return;
+            return abi;
         }
         parser_utils.index += 1;
         let name = next.to_string(parser_utils.buffer);
@@ -2,14 +2,17 @@ use std::sync::Arc;
 use std::sync::Mutex;
 
 use indexmap::IndexMap;
+use tokio::sync::mpsc::UnboundedSender;
 
 use data::tokens::{Span, Token, TokenTypes};
 use syntax::async_util::{HandleWrapper, NameResolver, UnparsedType};
-use syntax::errors::ParsingError;
-use syntax::program::function::{CodeBody, FunctionData, UnfinalizedFunction};
+use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+use syntax::program::constant::UnfinalizedConstant;
+use syntax::program::function::{CodeBody, FunctionData, ParsedFunction, UnfinalizedFunction};
 use syntax::program::r#struct::{StructData, UnfinalizedStruct};
 use syntax::program::syntax::Syntax;
 use syntax::program::types::{FinalizedTypes, Types};
+use syntax::top_element_manager::{FinishedImplsWaiter, ImplWaiter};
 use syntax::{
     FinishedStructImplementor, FinishedTraitImplementor, ParsingFuture, ProcessManager, TopElement, TraitImplementor,
 };
@@ -34,6 +37,10 @@ pub struct ParserUtils<'a> {
     pub imports: ImportNameResolver,
     /// Handle for spawning async tasks
     pub handle: Arc<Mutex<HandleWrapper>>,
+    /// Where top functions are sent to be checked, instead of each being checked in a task spawned by the parser
+    pub functions: Option<UnboundedSender<ParsedFunction>>,
+    /// How many lines the line being parsed is nested inside of
+    pub depth: usize,
 }
 
 impl<'a> ParserUtils<'a> {
@@ -43,8 +50,6 @@ impl<'a> ParserUtils<'a> {
             panic!("Empty name!");
         }
 
-        let name = if name == "Self" { self.file_name.clone() } else { name };
-
         return Box::pin(Syntax::get_struct(
             self.syntax.clone(),
             span.clone(),
@@ -58,8 +63,11 @@ impl<'a> ParserUtils<'a> {
     pub fn add_struct(&mut self, structure: Result<UnfinalizedStruct, ParsingError>) {
         let mut structure = structure.unwrap_or_else(|error| UnfinalizedStruct {
             generics: IndexMap::default(),
+            generic_spans: IndexMap::default(),
             fields: Vec::default(),
             functions: Vec::default(),
+            traits: Vec::default(),
+            constants: Vec::default(),
             data: Arc::new(StructData::new_poisoned(format!("${}", self.file), error)),
         });
 
@@ -128,7 +136,10 @@ impl<'a> ParserUtils<'a> {
             generics.insert(generic, final_bounds);
         }
 
-        let target = implementor.base.await?;
+        let mut target = implementor.base.await?;
+        if implementor.implementor.is_some() {
+            target = fill_generic_defaults(&syntax, &*resolver, &implementor.span, target).await?;
+        }
         let target = target.finalize(syntax.clone()).await;
 
         let mut functions = Vec::default();
@@ -141,27 +152,94 @@ impl<'a> ParserUtils<'a> {
             let base = base.finalize(syntax.clone()).await;
 
             let chalk_type = Arc::new(Syntax::make_impldatum(&generics, &target, &base));
+            let supertraits = target.inner_struct_safe().map_or(vec![], |inner| inner.traits.clone());
 
             let output = FinishedTraitImplementor {
                 target,
                 base,
                 attributes: implementor.attributes,
                 functions,
+                constants: implementor.constants,
                 chalk_type,
                 generics,
+                span: implementor.span.clone(),
             };
+            let adding = Arc::new(output.clone());
 
             {
                 let mut locked = syntax.lock().unwrap();
-                locked.implementations.push(Arc::new(output));
+                locked.implementations.push(adding.clone());
 
                 locked.async_manager.parsing_impls -= 1;
                 for waker in &locked.async_manager.impl_waiters {
                     waker.wake_by_ref();
                 }
                 locked.async_manager.impl_waiters.clear();
+                locked.async_manager.pending_impls.clear();
             }
+
+            // Implementing a trait requires implementing all of its supertraits too
+            for supertrait in supertraits {
+                let name = format!("{}_{}_{}", output.base, output.target, supertrait);
+                handle.lock().unwrap().spawn(
+                    name.clone(),
+                    check_supertrait(
+                        handle.clone(),
+                        syntax.clone(),
+                        output.clone(),
+                        supertrait,
+                        implementor.span.clone(),
+                        name,
+                    ),
+                );
+            }
+
+            // Two impls of the same trait for the same type would make calls ambiguous
+            let name = format!("{}_{}_overlap", output.base, output.target);
+            handle.lock().unwrap().spawn(name.clone(), check_overlap(handle.clone(), syntax.clone(), adding.clone(), name));
+
+            // Otherwise two modules that can't see each other could both implement a trait neither owns
+            let name = format!("{}_{}_orphan", output.base, output.target);
+            let module = resolver.module().to_string();
+            handle
+                .lock()
+                .unwrap()
+                .spawn(name.clone(), check_orphan(handle.clone(), syntax.clone(), adding.clone(), module, name));
+
+            let name = format!("{}_{}_constants", output.base, output.target);
+            handle.lock().unwrap().spawn(
+                name.clone(),
+                check_constants(
+                    handle.clone(),
+                    syntax.clone(),
+                    adding.clone(),
+                    resolver.boxed_clone(),
+                    process_manager.cloned(),
+                    name,
+                ),
+            );
+
+            // Calls through the trait expect the signatures it declares, so overriding methods have to match them
+            let name = format!("{}_{}_signatures", output.base, output.target);
+            handle.lock().unwrap().spawn(
+                name.clone(),
+                check_signatures(handle.clone(), syntax.clone(), adding, process_manager.cloned(), name),
+            );
         } else {
+            // Without a trait there's nothing to check the constants against, but their values still have to be valid
+            for constant in implementor.constants {
+                handle.lock().unwrap().spawn(
+                    constant.name.clone(),
+                    UnfinalizedConstant::verify(
+                        handle.clone(),
+                        constant,
+                        syntax.clone(),
+                        resolver.boxed_clone(),
+                        process_manager.cloned(),
+                    ),
+                );
+            }
+
             let output = FinishedStructImplementor { target, attributes: implementor.attributes, functions, generics };
 
             {
@@ -182,6 +260,7 @@ impl<'a> ParserUtils<'a> {
                     waker.wake_by_ref();
                 }
                 locked.async_manager.impl_waiters.clear();
+                locked.async_manager.pending_impls.clear();
             }
         }
 
@@ -211,9 +290,11 @@ impl<'a> ParserUtils<'a> {
             Ok(adding) => adding,
             Err(error) => UnfinalizedFunction {
                 generics: IndexMap::default(),
+                generic_spans: IndexMap::default(),
                 fields: Vec::default(),
                 code: CodeBody::new(Vec::default(), "empty".to_string()),
                 return_type: None,
+                self_bounds: Vec::default(),
                 data: Arc::new(FunctionData::new_poisoned(format!("${}", file), error)),
                 parent: None,
             },
@@ -233,7 +314,7 @@ pub fn parse_generics(input: String, parser_utils: &mut ParserUtils) -> (Unparse
         let token = parser_utils.tokens.get(parser_utils.index).unwrap();
         parser_utils.index += 1;
         match token.token_type {
-            TokenTypes::Variable => {
+            TokenTypes::Variable | TokenTypes::Integer => {
                 last = Some((
                     UnparsedType::Basic(token.to_string(parser_utils.buffer)),
                     Box::pin(Syntax::get_struct(
@@ -296,7 +377,7 @@ fn inner_generic(
         let token = parser_utils.tokens.get(parser_utils.index).unwrap();
         parser_utils.index += 1;
         match token.token_type {
-            TokenTypes::Variable => {
+            TokenTypes::Variable | TokenTypes::Integer => {
                 if let Some((unparsed, found)) = last {
                     unparsed_values.push(unparsed);
                     values.push(Box::pin(found));
@@ -347,3 +428,248 @@ async fn async_to_generic(outer: ParsingFuture<Types>, bounds: Vec<ParsingFuture
     }
     return Ok(Types::GenericType(Box::new(outer.await?), new_bounds));
 }
+
+/// Fills in the generics an impl leaves out of its trait with the trait's defaults, like Self for E in Add<E = Self>.
+/// The defaults are parsed in the impl, so Self is the type the impl is for.
+async fn fill_generic_defaults(
+    syntax: &Arc<Mutex<Syntax>>,
+    resolver: &dyn NameResolver,
+    span: &Span,
+    target: Types,
+) -> Result<Types, ParsingError> {
+    let (base, mut generics) = match target {
+        Types::Struct(base) => (base, Vec::default()),
+        Types::GenericType(base, generics) => match *base {
+            Types::Struct(base) => (base, generics),
+            base => return Ok(Types::GenericType(Box::new(base), generics)),
+        },
+        target => return Ok(target),
+    };
+
+    // Only fills them in if every generic left out has a default
+    let missing = base.generic_defaults.get(generics.len()..).unwrap_or_default();
+    if missing.is_empty() || missing.iter().any(Option::is_none) {
+        return Ok(if generics.is_empty() {
+            Types::Struct(base)
+        } else {
+            Types::GenericType(Box::new(Types::Struct(base)), generics)
+        });
+    }
+    for default in missing.iter().flatten() {
+        generics
+            .push(Syntax::parse_type(syntax.clone(), span.clone(), resolver.boxed_clone(), default.clone(), vec![]).await?);
+    }
+    return Ok(Types::GenericType(Box::new(Types::Struct(base)), generics));
+}
+
+/// Checks that the implementation's base also implements the supertrait, once every impl has been parsed
+async fn check_supertrait(
+    handle: Arc<Mutex<HandleWrapper>>,
+    syntax: Arc<Mutex<Syntax>>,
+    implementor: FinishedTraitImplementor,
+    supertrait: FinalizedTypes,
+    span: Span,
+    name: String,
+) -> Result<(), ParsingError> {
+    let found = ImplWaiter {
+        syntax,
+        base_type: implementor.base.clone(),
+        trait_type: supertrait.clone(),
+        error: span.make_error(ParsingMessage::MissingSupertraitImpl(implementor.base, implementor.target, supertrait)),
+    }
+    .await;
+    handle.lock().unwrap().finish_task(&name);
+    found?;
+    return Ok(());
+}
+
+/// Checks the implementation defines every associated constant its trait declares with the type the trait gives it,
+/// and verifies the value of each constant it defines, even ones no code uses
+async fn check_constants(
+    handle: Arc<Mutex<HandleWrapper>>,
+    syntax: Arc<Mutex<Syntax>>,
+    implementor: Arc<FinishedTraitImplementor>,
+    resolver: Box<dyn NameResolver>,
+    process_manager: Box<dyn ProcessManager>,
+    name: String,
+) -> Result<(), ParsingError> {
+    let declared = &implementor.target.inner_struct().constants;
+    let trait_name = implementor.target.to_string();
+    let short_name = |constant: &UnfinalizedConstant| constant.name.split("::").last().unwrap().to_string();
+
+    let mut errors = Vec::default();
+    for constant in declared.keys() {
+        if !implementor.constants.iter().any(|found| short_name(found) == *constant) {
+            errors.push(
+                implementor.span.make_error(ParsingMessage::MissingAssociatedConstant(constant.clone(), trait_name.clone())),
+            );
+        }
+    }
+
+    for constant in &implementor.constants {
+        let constant_name = short_name(constant);
+        let found = process_manager.verify_constant(constant.clone(), resolver.boxed_clone(), &syntax).await;
+        match (declared.get(&constant_name), found) {
+            (None, _) => {
+                errors.push(constant.span.make_error(ParsingMessage::NotTraitConstant(constant_name, trait_name.clone())))
+            }
+            (Some(expected), Some(found)) if !found.types.of_type_sync(expected, None).0 => {
+                errors.push(constant.span.make_error(ParsingMessage::AssociatedConstantType(
+                    constant_name,
+                    expected.clone(),
+                    found.types,
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    syntax.lock().unwrap().errors.extend(errors);
+    handle.lock().unwrap().finish_task(&name);
+    return Ok(());
+}
+
+/// Checks the implementation's methods take and return the same types as the trait's declarations of them
+async fn check_signatures(
+    handle: Arc<Mutex<HandleWrapper>>,
+    syntax: Arc<Mutex<Syntax>>,
+    implementor: Arc<FinishedTraitImplementor>,
+    process_manager: Box<dyn ProcessManager>,
+    name: String,
+) -> Result<(), ParsingError> {
+    let errors = process_manager.verify_impl_signatures(&implementor, &syntax).await;
+    syntax.lock().unwrap().errors.extend(errors);
+    handle.lock().unwrap().finish_task(&name);
+    return Ok(());
+}
+
+/// Checks the implementation doesn't overlap an implementation of the same trait declared before it,
+/// once every impl has been parsed
+async fn check_overlap(
+    handle: Arc<Mutex<HandleWrapper>>,
+    syntax: Arc<Mutex<Syntax>>,
+    implementor: Arc<FinishedTraitImplementor>,
+    name: String,
+) -> Result<(), ParsingError> {
+    FinishedImplsWaiter { syntax: syntax.clone() }.await;
+    let others = syntax.lock().unwrap().impls_of(&implementor.target);
+
+    let mut found = Ok(());
+    for other in others {
+        // Only the later impl of each pair is reported, so the error doesn't depend on which finished first
+        if (other.span.file, other.span.start) >= (implementor.span.file, implementor.span.start) {
+            continue;
+        }
+        if impls_overlap(&implementor, &other, &syntax).await {
+            found = Err(implementor
+                .span
+                .make_error(ParsingMessage::OverlappingImpls(implementor.base.clone(), implementor.target.clone()))
+                .with_note(other.span.make_error(ParsingMessage::FirstDeclaredHere())));
+            break;
+        }
+    }
+    handle.lock().unwrap().finish_task(&name);
+    return found;
+}
+
+/// Checks the trait or the type of the implementation is declared in the module it's in.
+/// Waits for every impl to be parsed, which means every file declaring an internal type has been too.
+async fn check_orphan(
+    handle: Arc<Mutex<HandleWrapper>>,
+    syntax: Arc<Mutex<Syntax>>,
+    implementor: Arc<FinishedTraitImplementor>,
+    module: String,
+    name: String,
+) -> Result<(), ParsingError> {
+    FinishedImplsWaiter { syntax: syntax.clone() }.await;
+    let found = {
+        let locked = syntax.lock().unwrap();
+        if declared_in(&implementor.target, &module, &locked) || declared_in(&implementor.base, &module, &locked) {
+            Ok(())
+        } else {
+            Err(implementor
+                .span
+                .make_error(ParsingMessage::OrphanImpl(implementor.base.clone(), implementor.target.clone())))
+        }
+    };
+    handle.lock().unwrap().finish_task(&name);
+    return found;
+}
+
+/// Checks if the type is declared in the module. Generics and arrays aren't declared in any module.
+fn declared_in(types: &FinalizedTypes, module: &str, syntax: &Syntax) -> bool {
+    let name = match types.inner_struct_safe() {
+        Some(structure) => &structure.data.name,
+        None => return false,
+    };
+    return match name.rsplit_once("::") {
+        Some((declaring, _)) => declaring == module,
+        None => syntax.internal_modules.get(name).is_some_and(|declaring| declaring == module),
+    };
+}
+
+/// Checks if some concrete type could match both implementations, including the bounds of their generics
+async fn impls_overlap(
+    first: &FinishedTraitImplementor,
+    second: &FinishedTraitImplementor,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> bool {
+    // A poisoned struct already reported its error, so its impls aren't compared against others
+    if !first.base.poisoned().is_empty() || !second.base.poisoned().is_empty() {
+        return false;
+    }
+
+    let mut bounded = Vec::default();
+    if !could_unify(&first.target, &second.target, &mut bounded) || !could_unify(&first.base, &second.base, &mut bounded) {
+        return false;
+    }
+
+    for (types, generic) in bounded {
+        if !types.of_type(&generic, syntax.clone()).await {
+            return false;
+        }
+    }
+    return true;
+}
+
+/// Checks if the two types could be the same type once their generics are filled in.
+/// Adds each type matched against a generic, which only unifies if the type meets the generic's bounds.
+fn could_unify(
+    first: &FinalizedTypes,
+    second: &FinalizedTypes,
+    bounded: &mut Vec<(FinalizedTypes, FinalizedTypes)>,
+) -> bool {
+    return match (first, second) {
+        (FinalizedTypes::Reference(first), _) => could_unify(first, second, bounded),
+        (_, FinalizedTypes::Reference(second)) => could_unify(first, second, bounded),
+        (FinalizedTypes::Generic(_, _), FinalizedTypes::Generic(_, _)) => true,
+        (FinalizedTypes::Generic(_, _), _) => {
+            bounded.push((second.clone(), first.clone()));
+            true
+        }
+        (_, FinalizedTypes::Generic(_, _)) => {
+            bounded.push((first.clone(), second.clone()));
+            true
+        }
+        (FinalizedTypes::Struct(first), FinalizedTypes::Struct(second)) => first.data.name == second.data.name,
+        (FinalizedTypes::GenericType(first, first_generics), FinalizedTypes::GenericType(second, second_generics)) => {
+            first_generics.len() == second_generics.len()
+                && could_unify(first, second, bounded)
+                && first_generics.iter().zip(second_generics).all(|(first, second)| could_unify(first, second, bounded))
+        }
+        (FinalizedTypes::Array(first, first_length), FinalizedTypes::Array(second, second_length)) => {
+            let lengths = match (first_length, second_length) {
+                (Some(first_length), Some(second_length)) => could_unify(first_length, second_length, bounded),
+                _ => true,
+            };
+            lengths && could_unify(first, second, bounded)
+        }
+        (FinalizedTypes::Const(first), FinalizedTypes::Const(second)) => first == second,
+        (FinalizedTypes::Function(first_arguments, first), FinalizedTypes::Function(second_arguments, second)) => {
+            first_arguments.len() == second_arguments.len()
+                && could_unify(first, second, bounded)
+                && first_arguments.iter().zip(second_arguments).all(|(first, second)| could_unify(first, second, bounded))
+        }
+        _ => false,
+    };
+}
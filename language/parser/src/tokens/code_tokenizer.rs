@@ -12,7 +12,13 @@ pub fn next_code_token(tokenizer: &mut Tokenizer) -> Token {
         tokenizer.bracket_depth += 1;
         tokenizer.make_token(TokenTypes::BlockStart)
     } else if tokenizer.matches("}") {
-        if tokenizer.bracket_depth == 0 {
+        if tokenizer.state == TokenizerState::INTERPOLATION
+            && tokenizer.interpolations.last().is_some_and(|(_, depth)| *depth == tokenizer.bracket_depth)
+        {
+            // The interpolated expression ends like a line, then the string continues
+            tokenizer.state = tokenizer.interpolations.pop().unwrap().0;
+            tokenizer.make_token(TokenTypes::LineEnd)
+        } else if tokenizer.bracket_depth == 0 {
             // If it's the last matching bracket, then end the code block.
             if tokenizer.state == TokenizerState::CODE_TO_STRUCT_TOP {
                 tokenizer.state = TokenizerState::TOP_ELEMENT_TO_STRUCT;
@@ -37,11 +43,29 @@ pub fn next_code_token(tokenizer: &mut Tokenizer) -> Token {
         // Changes the state type based on what the current state already is.
         tokenizer.state = if tokenizer.state == TokenizerState::CODE {
             TokenizerState::STRING
+        } else if tokenizer.state == TokenizerState::CONSTANT {
+            TokenizerState::STRING_TO_CONSTANT
+        } else if tokenizer.state == TokenizerState::CONSTANT_TO_STRUCT_TOP {
+            TokenizerState::STRING_TO_CONSTANT_TO_STRUCT_TOP
+        } else if tokenizer.state == TokenizerState::DEFAULT_VALUE {
+            TokenizerState::STRING_TO_DEFAULT_VALUE
+        } else if tokenizer.state == TokenizerState::DEFAULT_VALUE_TO_STRUCT_TOP {
+            TokenizerState::STRING_TO_DEFAULT_VALUE_TO_STRUCT_TOP
+        } else if tokenizer.state == TokenizerState::INTERPOLATION {
+            TokenizerState::STRING_TO_INTERPOLATION
         } else {
             TokenizerState::STRING_TO_CODE_STRUCT_TOP
         };
         tokenizer.make_token(TokenTypes::StringStart)
     } else if tokenizer.matches("'") {
+        if is_label(tokenizer) {
+            while tokenizer.index < tokenizer.len
+                && (tokenizer.buffer[tokenizer.index].is_ascii_alphanumeric() || tokenizer.buffer[tokenizer.index] == b'_')
+            {
+                tokenizer.index += 1;
+            }
+            return tokenizer.make_token(TokenTypes::Label);
+        }
         tokenizer.index += 1;
         if tokenizer.matches("'") {
             tokenizer.make_token(TokenTypes::Char)
@@ -66,9 +90,52 @@ pub fn next_code_token(tokenizer: &mut Tokenizer) -> Token {
     };
 }
 
+/// Checks if the ' just matched starts a label like 'outer instead of a character like 'a'
+fn is_label(tokenizer: &Tokenizer) -> bool {
+    let start = match tokenizer.buffer.get(tokenizer.index) {
+        Some(start) => *start,
+        None => return false,
+    };
+    return (start.is_ascii_alphabetic() || start == b'_') && tokenizer.buffer.get(tokenizer.index + 1) != Some(&b'\'');
+}
+
+/// Gets the next token in an argument's default value, which ends at the first , or ) outside of any parenthesis.
+pub fn next_default_token(tokenizer: &mut Tokenizer) -> Token {
+    if tokenizer.paren_depth == 0 && tokenizer.bracket_depth == 0 {
+        let state = tokenizer.serialize();
+        let next = tokenizer.next_included();
+        tokenizer.load(&state);
+        // The value ends like a line, leaving the , or ) for the function's arguments
+        if matches!(next, Ok(b',') | Ok(b')')) {
+            tokenizer.state = if tokenizer.state == TokenizerState::DEFAULT_VALUE {
+                TokenizerState::FUNCTION
+            } else {
+                TokenizerState::FUNCTION_TO_STRUCT_TOP
+            };
+            return tokenizer.make_token(TokenTypes::LineEnd);
+        }
+    }
+
+    let token = next_code_token(tokenizer);
+    match token.token_type {
+        TokenTypes::ParenOpen => tokenizer.paren_depth += 1,
+        TokenTypes::ParenClose => tokenizer.paren_depth = tokenizer.paren_depth.saturating_sub(1),
+        _ => {}
+    }
+    return token;
+}
+
 /// Seperatae function to check basic keywords to tokenize
 pub fn check_keywords(tokenizer: &mut Tokenizer) -> Option<Token> {
     return Some(if tokenizer.matches(";") {
+        // A constant's value ends at the first ; outside of any block
+        if tokenizer.bracket_depth == 0 {
+            if tokenizer.state == TokenizerState::CONSTANT {
+                tokenizer.state = TokenizerState::TOP_ELEMENT;
+            } else if tokenizer.state == TokenizerState::CONSTANT_TO_STRUCT_TOP {
+                tokenizer.state = TokenizerState::TOP_ELEMENT_TO_STRUCT;
+            }
+        }
         tokenizer.make_token(TokenTypes::LineEnd)
     } else if tokenizer.matches(",") {
         tokenizer.make_token(TokenTypes::ArgumentEnd)
@@ -80,12 +147,17 @@ pub fn check_keywords(tokenizer: &mut Tokenizer) -> Option<Token> {
         tokenizer.make_token(TokenTypes::Return)
     } else if tokenizer.matches_word("break") {
         tokenizer.make_token(TokenTypes::Break)
+    } else if tokenizer.matches_word("continue") {
+        tokenizer.make_token(TokenTypes::Continue)
     } else if tokenizer.matches_word("switch") {
         tokenizer.make_token(TokenTypes::Switch)
     } else if tokenizer.matches_word("true") {
         tokenizer.make_token(TokenTypes::True)
     } else if tokenizer.matches_word("false") {
         tokenizer.make_token(TokenTypes::False)
+    } else if tokenizer.matches_word("NaN") || tokenizer.matches_word("Inf") {
+        // The special float values are float literals, which parse the same way as the name
+        tokenizer.make_token(TokenTypes::Float)
     } else if tokenizer.matches_word("for") {
         tokenizer.make_token(TokenTypes::For)
     } else if tokenizer.matches_word("while") {
@@ -100,10 +172,18 @@ pub fn check_keywords(tokenizer: &mut Tokenizer) -> Option<Token> {
         tokenizer.make_token(TokenTypes::Else)
     } else if tokenizer.matches_word("in") {
         tokenizer.make_token(TokenTypes::In)
+    } else if tokenizer.matches_word("as") {
+        tokenizer.make_token(TokenTypes::As)
     } else if tokenizer.matches(":") {
         tokenizer.make_token(TokenTypes::Colon)
+    } else if tokenizer.matches("fn(") {
+        // Leave the parenthesis for the closure's arguments
+        tokenizer.index -= 1;
+        tokenizer.make_token(TokenTypes::Closure)
     } else if tokenizer.matches_word("let") {
         tokenizer.make_token(TokenTypes::Let)
+    } else if tokenizer.matches_word("static_assert") {
+        tokenizer.make_token(TokenTypes::StaticAssert)
     } else if tokenizer.matches("=") {
         tokenizer.make_token(TokenTypes::Equals)
     } else {
@@ -1,5 +1,7 @@
-use crate::tokens::code_tokenizer::next_code_token;
-use crate::tokens::top_tokenizer::{next_func_token, next_implementation_token, next_struct_token, next_top_token};
+use crate::tokens::code_tokenizer::{next_code_token, next_default_token};
+use crate::tokens::top_tokenizer::{
+    next_enum_token, next_func_token, next_implementation_token, next_struct_token, next_top_token,
+};
 use crate::tokens::util::{next_generic, parse_string};
 use data::tokens::{Token, TokenTypes};
 
@@ -12,6 +14,10 @@ pub struct Tokenizer<'a> {
     pub bracket_depth: u8,
     /// The depth of generics (within a <)
     pub generic_depth: u8,
+    /// The depth of parenthesis inside an argument's default value
+    pub paren_depth: u8,
+    /// The string state and bracket depth to return to at the } ending each interpolated expression
+    pub interpolations: Vec<(u64, u8)>,
     /// The index in the character buffer
     pub index: usize,
     /// The current line number
@@ -33,6 +39,8 @@ impl<'a> Tokenizer<'a> {
             state: TokenizerState::TOP_ELEMENT,
             bracket_depth: 0,
             generic_depth: 1,
+            paren_depth: 0,
+            interpolations: Vec::default(),
             index: 0,
             line: 1,
             line_index: 0,
@@ -81,12 +89,25 @@ impl<'a> Tokenizer<'a> {
             TokenizerState::FUNCTION | TokenizerState::FUNCTION_TO_STRUCT_TOP => next_func_token(self),
             TokenizerState::STRUCTURE => next_struct_token(self),
             TokenizerState::IMPLEMENTATION => next_implementation_token(self),
-            TokenizerState::STRING | TokenizerState::STRING_TO_CODE_STRUCT_TOP => parse_string(self),
-            TokenizerState::CODE | TokenizerState::CODE_TO_STRUCT_TOP => next_code_token(self),
+            TokenizerState::ENUM => next_enum_token(self),
+            TokenizerState::STRING
+            | TokenizerState::STRING_TO_CODE_STRUCT_TOP
+            | TokenizerState::STRING_TO_CONSTANT
+            | TokenizerState::STRING_TO_CONSTANT_TO_STRUCT_TOP
+            | TokenizerState::STRING_TO_DEFAULT_VALUE
+            | TokenizerState::STRING_TO_DEFAULT_VALUE_TO_STRUCT_TOP
+            | TokenizerState::STRING_TO_INTERPOLATION => parse_string(self),
+            TokenizerState::CODE
+            | TokenizerState::CODE_TO_STRUCT_TOP
+            | TokenizerState::CONSTANT
+            | TokenizerState::CONSTANT_TO_STRUCT_TOP
+            | TokenizerState::INTERPOLATION => next_code_token(self),
+            TokenizerState::DEFAULT_VALUE | TokenizerState::DEFAULT_VALUE_TO_STRUCT_TOP => next_default_token(self),
             TokenizerState::GENERIC_TO_IMPL
             | TokenizerState::GENERIC_TO_FUNC
             | TokenizerState::GENERIC_TO_STRUCT
-            | TokenizerState::GENERIC_TO_FUNC_TO_STRUCT_TOP => next_generic(self),
+            | TokenizerState::GENERIC_TO_FUNC_TO_STRUCT_TOP
+            | TokenizerState::GENERIC_TO_ENUM => next_generic(self),
             _ => panic!("Unknown state {}!", self.state),
         };
         return self.last.clone();
@@ -261,4 +282,28 @@ impl TokenizerState {
     pub const CODE: u64 = 0xC;
     /// A block of code that returns to a program
     pub const CODE_TO_STRUCT_TOP: u64 = 0xD;
+    /// Inside an enum declaration, including its variants
+    pub const ENUM: u64 = 0xE;
+    /// Inside the generic declaration of an enum
+    pub const GENERIC_TO_ENUM: u64 = 0xF;
+    /// The value of a constant, which returns to the top at the ;
+    pub const CONSTANT: u64 = 0x10;
+    /// A string inside the value of a constant
+    pub const STRING_TO_CONSTANT: u64 = 0x11;
+    /// The default value of a function's argument, which returns to the function at the , or ) after it
+    pub const DEFAULT_VALUE: u64 = 0x12;
+    /// The default value of an argument of a function in a program or impl
+    pub const DEFAULT_VALUE_TO_STRUCT_TOP: u64 = 0x13;
+    /// A string inside an argument's default value
+    pub const STRING_TO_DEFAULT_VALUE: u64 = 0x14;
+    /// A string inside an argument's default value in a program or impl
+    pub const STRING_TO_DEFAULT_VALUE_TO_STRUCT_TOP: u64 = 0x15;
+    /// An expression interpolated into a string, like the x in "x is {x}", which returns to the string at the }
+    pub const INTERPOLATION: u64 = 0x16;
+    /// A string inside an interpolated expression
+    pub const STRING_TO_INTERPOLATION: u64 = 0x17;
+    /// The value of an associated constant in a trait or impl, which returns to its body at the ;
+    pub const CONSTANT_TO_STRUCT_TOP: u64 = 0x18;
+    /// A string inside the value of an associated constant
+    pub const STRING_TO_CONSTANT_TO_STRUCT_TOP: u64 = 0x19;
 }
@@ -1,5 +1,5 @@
 use crate::tokens::tokenizer::{Tokenizer, TokenizerState};
-use crate::tokens::util::{parse_attribute_val, parse_modifier, parse_to_character};
+use crate::tokens::util::{parse_attribute_val, parse_modifier, parse_to_character, parse_type_to_character};
 use data::tokens::{Token, TokenTypes};
 
 /// Handles when the tokenizer isn't in any other state.
@@ -27,8 +27,17 @@ pub fn next_top_token(tokenizer: &mut Tokenizer) -> Token {
             }
         }
         TokenTypes::AttributeStart => parse_attribute_val(tokenizer, TokenTypes::Attribute),
+        // Extern modifiers can be followed by their ABI, like extern "C"
+        TokenTypes::Modifier => {
+            if tokenizer.matches("\"") {
+                while tokenizer.next_included()? != b'"' {}
+                tokenizer.make_token(TokenTypes::Abi)
+            } else {
+                get_top_element(tokenizer)
+            }
+        }
         // Check for chained modifiers
-        TokenTypes::ModifiersStart | TokenTypes::Modifier => get_top_element(tokenizer),
+        TokenTypes::ModifiersStart | TokenTypes::Abi => get_top_element(tokenizer),
         TokenTypes::FieldName => {
             if tokenizer.matches(":") {
                 tokenizer.make_token(TokenTypes::FieldSeparator)
@@ -36,14 +45,14 @@ pub fn next_top_token(tokenizer: &mut Tokenizer) -> Token {
                 tokenizer.handle_invalid()
             }
         }
-        TokenTypes::FieldSeparator => parse_to_character(tokenizer, TokenTypes::FieldType, &[b'=', b';']),
+        TokenTypes::FieldSeparator => parse_type_to_character(tokenizer, TokenTypes::FieldType, &[b'=', b';']),
         TokenTypes::FieldType => {
             if tokenizer.matches("=") {
-                // Handles the code for the field's value
+                // Handles the value of a constant in a trait or impl, or outside of a struct
                 if tokenizer.state == TokenizerState::TOP_ELEMENT_TO_STRUCT {
-                    tokenizer.state = TokenizerState::CODE_TO_STRUCT_TOP;
+                    tokenizer.state = TokenizerState::CONSTANT_TO_STRUCT_TOP;
                 } else {
-                    tokenizer.state = TokenizerState::CODE;
+                    tokenizer.state = TokenizerState::CONSTANT;
                 }
                 tokenizer.make_token(TokenTypes::FieldValue)
             } else if tokenizer.matches(";") {
@@ -52,6 +61,25 @@ pub fn next_top_token(tokenizer: &mut Tokenizer) -> Token {
                 tokenizer.handle_invalid()
             }
         }
+        // Constants are in the format const NAME: Type = value;
+        TokenTypes::ConstStart => parse_to_character(tokenizer, TokenTypes::FieldName, &[b':']),
+        // Type aliases are in the format type Name<T> = Type;
+        TokenTypes::TypeAliasStart => parse_to_character(tokenizer, TokenTypes::TypeAliasName, &[b'=']),
+        TokenTypes::TypeAliasName => {
+            if tokenizer.matches("=") {
+                tokenizer.make_token(TokenTypes::TypeAliasSeparator)
+            } else {
+                tokenizer.handle_invalid()
+            }
+        }
+        TokenTypes::TypeAliasSeparator => parse_type_to_character(tokenizer, TokenTypes::TypeAliasType, &[b';']),
+        TokenTypes::TypeAliasType => {
+            if tokenizer.matches(";") {
+                tokenizer.make_token(TokenTypes::TypeAliasEnd)
+            } else {
+                tokenizer.handle_invalid()
+            }
+        }
         TokenTypes::Identifier => {
             if tokenizer.matches(";") {
                 tokenizer.make_token(TokenTypes::ImportEnd)
@@ -101,6 +129,17 @@ fn get_top_element(tokenizer: &mut Tokenizer) -> Token {
             tokenizer.state = TokenizerState::STRUCTURE;
             tokenizer.make_token(TokenTypes::TraitStart)
         }
+    } else if tokenizer.state != TokenizerState::TOP_ELEMENT_TO_STRUCT && tokenizer.matches_word("enum") {
+        // Enums can't be inside structures, so fields starting with "enum" are left alone
+        tokenizer.state = TokenizerState::ENUM;
+        tokenizer.make_token(TokenTypes::EnumStart)
+    } else if tokenizer.state == TokenizerState::TOP_ELEMENT && tokenizer.matches_word("type") {
+        tokenizer.make_token(TokenTypes::TypeAliasStart)
+    } else if matches!(tokenizer.state, TokenizerState::TOP_ELEMENT | TokenizerState::TOP_ELEMENT_TO_STRUCT)
+        && tokenizer.matches_word("const")
+    {
+        // Traits and impls can have associated constants too
+        tokenizer.make_token(TokenTypes::ConstStart)
     } else if tokenizer.matches("impl") {
         // What is being implemented is next, so whitespace is skipped.
         tokenizer.next_included().unwrap_or(0);
@@ -178,28 +217,76 @@ pub fn next_func_token(tokenizer: &mut Tokenizer) -> Token {
                 }
             }
         }
-        TokenTypes::ArgumentTypeSeparator => parse_to_character(tokenizer, TokenTypes::ArgumentType, &[b',', b')']),
-        TokenTypes::ArgumentType => {
+        TokenTypes::ArgumentTypeSeparator => {
+            parse_type_to_character(tokenizer, TokenTypes::ArgumentType, &[b',', b')', b'='])
+        }
+        // Default values end with a LineEnd
+        TokenTypes::ArgumentType | TokenTypes::LineEnd => {
             if tokenizer.matches(",") {
                 tokenizer.make_token(TokenTypes::ArgumentSeparator)
+            } else if tokenizer.last.token_type == TokenTypes::ArgumentType && tokenizer.matches("=") {
+                // The default value is code, which ends with the argument
+                tokenizer.state = if tokenizer.state == TokenizerState::FUNCTION {
+                    TokenizerState::DEFAULT_VALUE
+                } else {
+                    TokenizerState::DEFAULT_VALUE_TO_STRUCT_TOP
+                };
+                tokenizer.make_token(TokenTypes::ArgumentDefault)
             } else {
                 tokenizer.make_token(TokenTypes::ArgumentEnd)
             }
         }
         TokenTypes::ArgumentSeparator => tokenizer.make_token(TokenTypes::ArgumentEnd),
         // Parse the return type
-        TokenTypes::ReturnTypeArrow => parse_to_character(tokenizer, TokenTypes::ReturnType, &[b';', b'{']),
+        TokenTypes::ReturnTypeArrow => parse_return_type(tokenizer),
         TokenTypes::ArgumentsEnd | TokenTypes::ReturnType => get_return_token(tokenizer),
+        // Where clauses look like "where Self: First + Second"
+        TokenTypes::Where => parse_to_character(tokenizer, TokenTypes::WhereType, &[b':']),
+        TokenTypes::WhereType | TokenTypes::WhereBound => {
+            if tokenizer.matches(":") || tokenizer.matches("+") {
+                parse_type_to_character(tokenizer, TokenTypes::WhereBound, &[b'+', b'{', b';'])
+            } else {
+                get_return_token(tokenizer)
+            }
+        }
         token => {
             panic!("How'd you get here? {:?}", token);
         }
     };
 }
 
+/// Parses a return type, which ends at the code, a semicolon, or the where keyword
+fn parse_return_type(tokenizer: &mut Tokenizer) -> Token {
+    let mut depth = 0;
+    loop {
+        // Only checked after whitespace so types ending in where, like Somewhere, aren't split
+        if depth == 0 && tokenizer.index < tokenizer.len && tokenizer.buffer[tokenizer.index].is_ascii_whitespace() {
+            let state = tokenizer.serialize();
+            if tokenizer.matches_word("where") {
+                tokenizer.load(&state);
+                return tokenizer.make_token(TokenTypes::ReturnType);
+            }
+        }
+        let next = tokenizer.next_included()?;
+        match next {
+            b'[' | b'(' => depth += 1,
+            b']' | b')' if depth > 0 => depth -= 1,
+            b';' | b'{' if depth == 0 => break,
+            _ => {}
+        }
+    }
+    tokenizer.index -= 1;
+    return tokenizer.make_token(TokenTypes::ReturnType);
+}
+
 /// Gets the next token in a return type
 pub fn get_return_token(tokenizer: &mut Tokenizer) -> Token {
     if tokenizer.last.token_type == TokenTypes::ArgumentsEnd && tokenizer.matches("->") {
         tokenizer.make_token(TokenTypes::ReturnTypeArrow)
+    } else if matches!(tokenizer.last.token_type, TokenTypes::ArgumentsEnd | TokenTypes::ReturnType)
+        && tokenizer.matches_word("where")
+    {
+        tokenizer.make_token(TokenTypes::Where)
     } else if tokenizer.matches("{") {
         if tokenizer.state == TokenizerState::FUNCTION_TO_STRUCT_TOP {
             tokenizer.state = TokenizerState::CODE_TO_STRUCT_TOP;
@@ -224,12 +311,14 @@ pub fn get_return_token(tokenizer: &mut Tokenizer) -> Token {
 pub fn next_struct_token(tokenizer: &mut Tokenizer) -> Token {
     match tokenizer.last.token_type {
         TokenTypes::StructStart | TokenTypes::TraitStart | TokenTypes::For => {
-            parse_to_character(tokenizer, TokenTypes::Identifier, &[b'{', b'<'])
+            parse_to_character(tokenizer, TokenTypes::Identifier, &[b'{', b'<', b':'])
         }
-        TokenTypes::Identifier | TokenTypes::GenericsEnd => {
-            if tokenizer.matches("<") {
+        TokenTypes::Identifier | TokenTypes::GenericsEnd | TokenTypes::Supertrait => {
+            if tokenizer.last.token_type != TokenTypes::Supertrait && tokenizer.matches("<") {
                 tokenizer.state = TokenizerState::GENERIC_TO_STRUCT;
                 tokenizer.make_token(TokenTypes::GenericsStart)
+            } else if tokenizer.matches(":") || tokenizer.matches("+") {
+                parse_to_character(tokenizer, TokenTypes::Supertrait, &[b'{', b'+'])
             } else if tokenizer.matches("{") {
                 tokenizer.state = TokenizerState::TOP_ELEMENT_TO_STRUCT;
                 tokenizer.make_token(TokenTypes::StructTopElement)
@@ -241,6 +330,87 @@ pub fn next_struct_token(tokenizer: &mut Tokenizer) -> Token {
     }
 }
 
+/// Finds the next token of an enum: its name, then each variant with its optional fields in parenthesis
+/// and its optional discriminant after an equals.
+/// For example, "enum Shape { Circle(radius: u64), Empty = 4 }" or "enum Option<T> { Some(value: T), None }".
+pub fn next_enum_token(tokenizer: &mut Tokenizer) -> Token {
+    return match tokenizer.last.token_type {
+        TokenTypes::EnumStart => parse_to_character(tokenizer, TokenTypes::Identifier, &[b'{', b'<']),
+        TokenTypes::Identifier | TokenTypes::GenericsEnd => {
+            if tokenizer.last.token_type == TokenTypes::Identifier && tokenizer.matches("<") {
+                tokenizer.state = TokenizerState::GENERIC_TO_ENUM;
+                tokenizer.make_token(TokenTypes::GenericsStart)
+            } else if tokenizer.matches("{") {
+                tokenizer.make_token(TokenTypes::StructTopElement)
+            } else {
+                tokenizer.handle_invalid()
+            }
+        }
+        TokenTypes::Variant => {
+            if tokenizer.matches("(") {
+                tokenizer.make_token(TokenTypes::ArgumentsStart)
+            } else {
+                end_variant(tokenizer)
+            }
+        }
+        TokenTypes::Equals => parse_to_character(tokenizer, TokenTypes::Integer, &[b',', b'}']),
+        TokenTypes::Integer => end_variant(tokenizer),
+        TokenTypes::ArgumentsStart | TokenTypes::ArgumentSeparator => {
+            if tokenizer.matches(")") {
+                tokenizer.make_token(TokenTypes::ArgumentsEnd)
+            } else {
+                parse_to_character(tokenizer, TokenTypes::ArgumentName, &[b':'])
+            }
+        }
+        TokenTypes::ArgumentName => {
+            if tokenizer.matches(":") {
+                tokenizer.make_token(TokenTypes::ArgumentTypeSeparator)
+            } else {
+                tokenizer.handle_invalid()
+            }
+        }
+        TokenTypes::ArgumentTypeSeparator => parse_type_to_character(tokenizer, TokenTypes::ArgumentType, &[b',', b')']),
+        TokenTypes::ArgumentType => {
+            if tokenizer.matches(",") {
+                tokenizer.make_token(TokenTypes::ArgumentSeparator)
+            } else if tokenizer.matches(")") {
+                tokenizer.make_token(TokenTypes::ArgumentsEnd)
+            } else {
+                tokenizer.handle_invalid()
+            }
+        }
+        TokenTypes::ArgumentsEnd => end_variant(tokenizer),
+        // Anything else is followed by another variant or the end of the enum
+        _ => {
+            if tokenizer.matches("}") {
+                tokenizer.state = TokenizerState::TOP_ELEMENT;
+                tokenizer.make_token(TokenTypes::StructEnd)
+            } else {
+                parse_to_character(tokenizer, TokenTypes::Variant, &[b',', b'(', b'=', b'}'])
+            }
+        }
+    };
+}
+
+/// Ends a variant at its comma, or right before the closing bracket if it's the last variant.
+/// A discriminant can come first, starting with its equals.
+fn end_variant(tokenizer: &mut Tokenizer) -> Token {
+    if tokenizer.last.token_type != TokenTypes::Integer && tokenizer.matches("=") {
+        return tokenizer.make_token(TokenTypes::Equals);
+    }
+    if tokenizer.matches(",") {
+        return tokenizer.make_token(TokenTypes::VariantEnd);
+    }
+
+    let state = tokenizer.serialize();
+    return if tokenizer.matches("}") {
+        tokenizer.load(&state);
+        tokenizer.make_token(TokenTypes::VariantEnd)
+    } else {
+        tokenizer.handle_invalid()
+    };
+}
+
 /// Gets the next token of the implementation.
 /// This ends at the "for" keyword.
 pub fn next_implementation_token(tokenizer: &mut Tokenizer) -> Token {
@@ -9,6 +9,23 @@ pub fn parse_to_character(tokenizer: &mut Tokenizer, token_type: TokenTypes, end
     return tokenizer.make_token(token_type);
 }
 
+/// Parses a type until one of the characters, skipping any inside an array like the ; in [T; N]
+/// or inside a function type like the , in fn(u64, u64)
+pub fn parse_type_to_character(tokenizer: &mut Tokenizer, token_type: TokenTypes, end: &[u8]) -> Token {
+    let mut depth = 0;
+    loop {
+        let next = tokenizer.next_included()?;
+        match next {
+            b'[' | b'(' => depth += 1,
+            b']' | b')' if depth > 0 => depth -= 1,
+            _ if depth == 0 && end.contains(&next) => break,
+            _ => {}
+        }
+    }
+    tokenizer.index -= 1;
+    return tokenizer.make_token(token_type);
+}
+
 /// Parses the value of an attribute
 pub fn parse_attribute_val(tokenizer: &mut Tokenizer, token_type: TokenTypes) -> Token {
     let mut depth = 1;
@@ -77,6 +94,14 @@ pub fn parse_numbers(tokenizer: &mut Tokenizer) -> Token {
                         tokenizer.make_token(TokenTypes::Float)
                     }
                 } else {
+                    // Integers can end in a type suffix, like 5u8
+                    if character == 'i' || character == 'u' {
+                        for suffix in INTEGER_SUFFIXES {
+                            if tokenizer.matches_word(suffix) {
+                                break;
+                            }
+                        }
+                    }
                     tokenizer.make_token(TokenTypes::Integer)
                 };
             }
@@ -85,6 +110,9 @@ pub fn parse_numbers(tokenizer: &mut Tokenizer) -> Token {
     }
 }
 
+/// Type suffixes an integer can have
+static INTEGER_SUFFIXES: [&str; 8] = ["i64", "i32", "i16", "i8", "u64", "u32", "u16", "u8"];
+
 /// Parses any modifiers.
 pub fn parse_modifier(tokenizer: &mut Tokenizer) -> Option<Token> {
     for modifier in MODIFIERS {
@@ -114,6 +142,16 @@ pub fn parse_string(tokenizer: &mut Tokenizer) -> Token {
                 tokenizer.buffer[tokenizer.index - 1] != b'\\' {
                     tokenizer.state = if tokenizer.state == TokenizerState::STRING_TO_CODE_STRUCT_TOP {
                         TokenizerState::CODE_TO_STRUCT_TOP
+                    } else if tokenizer.state == TokenizerState::STRING_TO_CONSTANT {
+                        TokenizerState::CONSTANT
+                    } else if tokenizer.state == TokenizerState::STRING_TO_CONSTANT_TO_STRUCT_TOP {
+                        TokenizerState::CONSTANT_TO_STRUCT_TOP
+                    } else if tokenizer.state == TokenizerState::STRING_TO_DEFAULT_VALUE {
+                        TokenizerState::DEFAULT_VALUE
+                    } else if tokenizer.state == TokenizerState::STRING_TO_DEFAULT_VALUE_TO_STRUCT_TOP {
+                        TokenizerState::DEFAULT_VALUE_TO_STRUCT_TOP
+                    } else if tokenizer.state == TokenizerState::STRING_TO_INTERPOLATION {
+                        TokenizerState::INTERPOLATION
                     } else {
                         TokenizerState::CODE
                     };
@@ -136,6 +174,12 @@ pub fn parse_string(tokenizer: &mut Tokenizer) -> Token {
 
                 return tokenizer.make_token(TokenTypes::StringEscape);
             }
+            // An unescaped { starts an expression, which is tokenized like code until the matching }
+            b'{' => {
+                tokenizer.interpolations.push((tokenizer.state, tokenizer.bracket_depth));
+                tokenizer.state = TokenizerState::INTERPOLATION;
+                return tokenizer.make_token(TokenTypes::InterpolationStart);
+            }
             _ => {}
         }
     }
@@ -145,13 +189,15 @@ pub fn parse_string(tokenizer: &mut Tokenizer) -> Token {
 pub fn next_generic(tokenizer: &mut Tokenizer) -> Token {
     return match &tokenizer.last.token_type {
         TokenTypes::GenericsStart | TokenTypes::GenericEnd => {
-            parse_to_character(tokenizer, TokenTypes::Generic, &[b':', b',', b'>', b'<'])
+            parse_to_character(tokenizer, TokenTypes::Generic, &[b':', b'=', b',', b'>', b'<'])
         }
         //              T       : Test       <             Other   <             Second  >               >               ,          E       : Yep
         //GenericsStart Generic GenericBound GenericsStart Generic GenericsStart Generic GenericBoundEnd GenericBoundEnd GenericEnd Generic GenericBound
-        TokenTypes::Generic | TokenTypes::GenericBound | TokenTypes::GenericBoundEnd => {
+        TokenTypes::Generic | TokenTypes::GenericBound | TokenTypes::GenericBoundEnd | TokenTypes::GenericDefault => {
             if tokenizer.matches(":") || tokenizer.matches("+") {
                 parse_to_character(tokenizer, TokenTypes::GenericBound, &[b',', b'+', b'>', b'<'])
+            } else if tokenizer.matches("=") {
+                parse_to_character(tokenizer, TokenTypes::GenericDefault, &[b',', b'>', b'<'])
             } else if tokenizer.matches("<") {
                 tokenizer.generic_depth += 1;
                 tokenizer.make_token(TokenTypes::GenericsStart)
@@ -166,6 +212,7 @@ pub fn next_generic(tokenizer: &mut Tokenizer) -> Token {
                         TokenizerState::GENERIC_TO_FUNC_TO_STRUCT_TOP => TokenizerState::FUNCTION_TO_STRUCT_TOP,
                         TokenizerState::GENERIC_TO_STRUCT => TokenizerState::STRUCTURE,
                         TokenizerState::GENERIC_TO_IMPL => TokenizerState::IMPLEMENTATION,
+                        TokenizerState::GENERIC_TO_ENUM => TokenizerState::ENUM,
                         _ => panic!("Unexpected generic state!"),
                     };
                     // Reset the generic depth variable in the tokenizer
@@ -1,3 +1,4 @@
+use std::mem;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -12,17 +13,31 @@ use data::{Arguments, CompilerArguments};
 use parser::parse;
 use syntax::async_util::HandleWrapper;
 use syntax::errors::ParsingError;
+use syntax::program::dump::dump_ir;
 use syntax::program::syntax::Syntax;
 
 use crate::{get_compiler, JoinWaiter};
 
 /// Runs Raven to completion with the given arguments
 pub async fn run<T: Send + 'static>(settings: &Arguments) -> Result<Option<T>, Vec<ParsingError>> {
+    return run_with(settings, |_| {}).await;
+}
+
+/// Runs Raven like run, calling setup on the program before anything is parsed, like to add native functions
+pub async fn run_with<T: Send + 'static>(
+    settings: &Arguments,
+    setup: impl FnOnce(&Arc<Mutex<Syntax>>),
+) -> Result<Option<T>, Vec<ParsingError>> {
     let handle = Arc::new(Mutex::new(HandleWrapper::new(settings.cpu_runtime.handle().clone())));
-    let mut syntax = Syntax::new(Box::new(TypesChecker::new(handle.clone(), settings.runner_settings.include_references())));
+    let mut checker = TypesChecker::new(handle.clone(), settings.runner_settings.include_references());
+    checker.bounds_checks = settings.runner_settings.compiler_arguments.bounds_checks;
+    checker.reject_special_floats = settings.runner_settings.compiler_arguments.reject_special_floats;
+    let mut syntax = Syntax::new(Box::new(checker));
     syntax.async_manager.target.clone_from(&settings.runner_settings.compiler_arguments.target);
+    syntax.features.clone_from(&settings.runner_settings.compiler_arguments.features);
 
     let syntax = Arc::new(Mutex::new(syntax));
+    setup(&syntax);
 
     let (sender, mut receiver) = mpsc::channel(1);
     let (go_sender, go_receiver) = mpsc::channel(1);
@@ -87,6 +102,9 @@ pub async fn run<T: Send + 'static>(settings: &Arguments) -> Result<Option<T>, V
             for (name, _) in &handle.lock().unwrap().names {
                 println!("Infinite loop for {}", name);
             }
+            for pending in syntax.lock().unwrap().pending_impls() {
+                println!("Still waiting for {}", pending);
+            }
             panic!(
                 "Failed to parse with {} ({}) infinite loops",
                 handle.lock().unwrap().joining.len(),
@@ -95,7 +113,30 @@ pub async fn run<T: Send + 'static>(settings: &Arguments) -> Result<Option<T>, V
         }
     }
 
+    let mut warnings = mem::take(&mut syntax.lock().unwrap().warnings);
+    if settings.runner_settings.compiler_arguments.deny_warnings {
+        errors.append(&mut warnings);
+    }
+    ParsingError::sort(&mut warnings, &settings.runner_settings.sources);
+    for warning in &warnings {
+        warning.print_warning(&settings.runner_settings.sources);
+    }
+
+    if let Some(prefix) = &settings.runner_settings.compiler_arguments.dump_ir {
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        let mut dumping = compiling
+            .iter()
+            .filter(|entry| entry.key().starts_with(prefix))
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect::<Vec<_>>();
+        dumping.sort_by(|(first, _), (second, _)| first.cmp(second));
+        for (_, function) in dumping {
+            println!("{}", dump_ir(&function));
+        }
+    }
+
     errors.append(&mut syntax.lock().unwrap().errors);
+    ParsingError::sort(&mut errors, &settings.runner_settings.sources);
     return if errors.is_empty() {
         go_sender.send(()).await.unwrap();
         Ok(receiver.recv().await.unwrap())
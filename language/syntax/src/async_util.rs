@@ -1,19 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::{Debug, Display, Formatter};
 use std::future::Future;
 use std::hash::Hash;
+use std::io;
 use std::ops::DerefMut;
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Mutex;
-use std::task::{Context, Poll, Waker};
+use std::sync::{Arc, Condvar, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
 
+use indexmap::IndexMap;
 use tokio::runtime::Handle;
+use tokio::sync::oneshot;
 use tokio::task::{AbortHandle, JoinHandle};
 
 use data::tokens::Span;
 
 use crate::errors::{ErrorSource, ParsingMessage};
+use crate::program::constant::UnfinalizedConstant;
 use crate::program::function::display_parenless;
 use crate::program::syntax::Syntax;
 use crate::program::types::FinalizedTypes;
@@ -45,6 +52,17 @@ pub struct AsyncDataGetter<T: TopElement> {
     pub getting: Arc<T>,
 }
 
+/// Caches the finalized elements found by AsyncDataGetter, so getting an element again returns immediately.
+/// Shared between tasks, so it's locked separately from the program.
+pub struct DataGetterCache<T: TopElement> {
+    /// Elements that have already been finalized
+    found: Mutex<HashMap<Arc<T>, Arc<T::Finalized>>>,
+    /// How many gets had to await an AsyncDataGetter
+    pub awaits: AtomicUsize,
+    /// How many gets were returned from the cache
+    pub hits: AtomicUsize,
+}
+
 /// Asynchronously gets the implementation of a structure
 pub struct AsyncStructImplGetter {
     /// The program
@@ -60,6 +78,21 @@ impl AsyncStructImplGetter {
     }
 }
 
+/// Adds the prefix, which is an import or empty, to the name of a top element
+pub fn prefixed_name(prefix: String, getting: &str) -> String {
+    return if prefix.is_empty() {
+        getting.to_string()
+    } else if prefix.ends_with(getting) {
+        prefix
+    } else {
+        match getting.split_once("::") {
+            // Qualified names can start with the imported module, like array_list::ArrayList with collections::array_list
+            Some((module, rest)) if prefix == module || prefix.ends_with(&format!("::{}", module)) => prefix + "::" + rest,
+            _ => prefix + "::" + getting,
+        }
+    };
+}
+
 impl<T: TopElement> AsyncTypesGetter<T> {
     /// Helper method to try a get a type with the given prefix, and adding a waker if not.
     fn get_types(
@@ -69,14 +102,7 @@ impl<T: TopElement> AsyncTypesGetter<T> {
         waker: Waker,
         not_trait: bool,
     ) -> Option<Result<Arc<T>, ParsingError>> {
-        // Add the prefix to the name, if any.
-        let name = if prefix.is_empty() {
-            self.getting.clone()
-        } else if prefix.ends_with(&self.getting) {
-            prefix
-        } else {
-            prefix + "::" + &*self.getting.clone()
-        };
+        let name = prefixed_name(prefix, &self.getting);
 
         let getting = T::get_manager(locked);
         //Look for a program of that name
@@ -188,6 +214,29 @@ impl<T: TopElement> AsyncDataGetter<T> {
     }
 }
 
+impl<T: TopElement + Debug> DataGetterCache<T> {
+    /// Gets the finalized element, only awaiting it if it isn't cached yet
+    pub async fn get(&self, syntax: &Arc<Mutex<Syntax>>, getting: &Arc<T>) -> Arc<T::Finalized> {
+        if let Some(found) = self.found.lock().unwrap().get(getting) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return found.clone();
+        }
+
+        self.awaits.fetch_add(1, Ordering::Relaxed);
+        // The lock can't be held across the await, so another task may have cached it in the meantime with the same value
+        let found = AsyncDataGetter::new(syntax.clone(), getting.clone()).await;
+        self.found.lock().unwrap().insert(getting.clone(), found.clone());
+        return found;
+    }
+}
+
+/// Rust's derive requires T to be Default, so it's manually implemented
+impl<T: TopElement> Default for DataGetterCache<T> {
+    fn default() -> Self {
+        return Self { found: Mutex::default(), awaits: AtomicUsize::default(), hits: AtomicUsize::default() };
+    }
+}
+
 impl<T> Future for AsyncDataGetter<T>
 where
     T: TopElement + Hash + Eq + Debug,
@@ -240,6 +289,8 @@ pub enum UnparsedType {
     Basic(String),
     /// A generic-bound type, with a base type and bounds
     Generic(Box<UnparsedType>, Vec<UnparsedType>),
+    /// The type of a const generic, like "const N: u64"
+    Const(Box<UnparsedType>),
 }
 
 impl Display for UnparsedType {
@@ -249,20 +300,77 @@ impl Display for UnparsedType {
             UnparsedType::Generic(base, bounds) => {
                 write!(f, "{}<{}>", base, display_parenless(bounds, " + "))
             }
+            UnparsedType::Const(inner) => write!(f, "const {}", inner),
         };
     }
 }
 
+/// A type alias like "type Pair<T> = [T; 2];", which is replaced by its type wherever it's used
+#[derive(Clone, Debug)]
+pub struct TypeAlias {
+    /// The alias's full name, like types::Pair
+    pub name: String,
+    /// The alias's generic parameters, replaced by the arguments it's given
+    pub generics: Vec<String>,
+    /// The type the alias stands for
+    pub types: String,
+    /// The imports of the file declaring the alias, which the aliased type is resolved with
+    pub imports: Vec<String>,
+}
+
+impl TypeAlias {
+    /// The names of the types the aliased type uses, other than its generic parameters
+    pub fn names(&self) -> Vec<String> {
+        return self
+            .types
+            .split(|character: char| !character.is_alphanumeric() && character != '_' && character != ':')
+            .map(|name| name.trim_matches(':'))
+            .filter(|name| name.starts_with(|character: char| character.is_alphabetic() || character == '_'))
+            .filter(|name| !self.generics.iter().any(|generic| generic == name))
+            .map(|name| name.to_string())
+            .collect();
+    }
+
+    /// Gets the aliased type with each generic parameter replaced by its argument
+    pub fn expand(&self, arguments: &[String]) -> String {
+        let mut output = String::default();
+        let mut word = String::default();
+        for character in self.types.chars().chain([' ']) {
+            if character.is_alphanumeric() || character == '_' {
+                word.push(character);
+                continue;
+            }
+            match self.generics.iter().position(|generic| generic == &word) {
+                Some(index) if index < arguments.len() => output.push_str(&arguments[index]),
+                _ => output.push_str(&word),
+            }
+            word.clear();
+            output.push(character);
+        }
+        output.pop();
+        return output;
+    }
+}
+
 /// A name resolver gives the async utils generic access to data used by later compilation steps.
 pub trait NameResolver: Send + Sync {
     /// This function's imports
     fn imports(&self) -> &Vec<String>;
 
+    /// The module being parsed, which is the name of its file like collections::array_list
+    fn module(&self) -> &str;
+
     /// Finds the generic given the name
     fn generic(&self, name: &String) -> Option<Vec<UnparsedType>>;
 
     /// All of this function's generics
-    fn generics(&self) -> &HashMap<String, Vec<UnparsedType>>;
+    fn generics(&self) -> &IndexMap<String, Vec<UnparsedType>>;
+
+    /// Finds the constant given the name
+    fn constant(&self, name: &String) -> Option<Arc<UnfinalizedConstant>>;
+
+    /// The type Self refers to, which is the struct, trait or implementing type being parsed
+    fn self_type(&self) -> Option<UnparsedType>;
 
     /// Clones the name resolver in a box, because it's a trait it can't be directly cloned.
     fn boxed_clone(&self) -> Box<dyn NameResolver>;
@@ -279,23 +387,202 @@ impl NameResolver for EmptyNameResolver {
         return &EMPTY;
     }
 
+    fn module(&self) -> &str {
+        return "";
+    }
+
     fn generic(&self, _name: &String) -> Option<Vec<UnparsedType>> {
         panic!("Should not be called after finalizing!")
     }
 
-    fn generics(&self) -> &HashMap<String, Vec<UnparsedType>> {
+    fn generics(&self) -> &IndexMap<String, Vec<UnparsedType>> {
         panic!("Should not be called after finalizing!")
     }
 
+    fn constant(&self, _name: &String) -> Option<Arc<UnfinalizedConstant>> {
+        return None;
+    }
+
+    fn self_type(&self) -> Option<UnparsedType> {
+        return None;
+    }
+
     fn boxed_clone(&self) -> Box<dyn NameResolver> {
         return Box::new(EmptyNameResolver {});
     }
 }
 
-/// Wraps around a Handle, allowing the program to wait for all spawned tasks to finish
-pub struct HandleWrapper {
-    /// The inner handle
+/// A task spawned while compiling
+pub type Task = Pin<Box<dyn Future<Output = Result<(), ParsingError>> + Send>>;
+
+/// Decides how the tasks spawned while compiling are run
+pub trait Scheduler: Send + Sync {
+    /// Starts running the task, returning a handle to join it
+    fn spawn(&self, task: Task) -> JoinHandle<Result<(), ParsingError>>;
+}
+
+/// The default scheduler, which runs tasks on a Tokio runtime
+pub struct RuntimeScheduler {
+    /// The runtime's handle
     handle: Handle,
+}
+
+impl RuntimeScheduler {
+    /// Creates a scheduler running tasks on the handle's runtime
+    pub fn new(handle: Handle) -> Self {
+        return Self { handle };
+    }
+}
+
+impl Scheduler for RuntimeScheduler {
+    fn spawn(&self, task: Task) -> JoinHandle<Result<(), ParsingError>> {
+        return self.handle.spawn(task);
+    }
+}
+
+/// Runs tasks one at a time on its own thread, polling them from a queue in the order they were spawned or woken,
+/// so the same program is always checked in the same order.
+pub struct DeterministicScheduler {
+    /// The runtime's handle, which the join handles wait on
+    handle: Handle,
+    /// The tasks waiting to be polled, shared with the thread polling them
+    queue: Arc<TaskQueue>,
+}
+
+impl DeterministicScheduler {
+    /// Creates a scheduler with a thread polling its tasks, which can call the handle's runtime.
+    /// Returns an error if the thread can't be started.
+    pub fn new(handle: Handle) -> Result<Self, io::Error> {
+        let queue = Arc::new(TaskQueue { ready: Mutex::new((VecDeque::default(), false)), notify: Condvar::new() });
+        let polling = queue.clone();
+        let runtime = handle.clone();
+        thread::Builder::new().name("deterministic-scheduler".to_string()).spawn(move || polling.run(runtime))?;
+        return Ok(Self { handle, queue });
+    }
+}
+
+impl Scheduler for DeterministicScheduler {
+    fn spawn(&self, task: Task) -> JoinHandle<Result<(), ParsingError>> {
+        let (sender, receiver) = oneshot::channel();
+        self.queue.push(Arc::new(QueuedTask {
+            task: Mutex::new(Some(task)),
+            sender: Mutex::new(Some(sender)),
+            queued: AtomicBool::new(true),
+            queue: Arc::downgrade(&self.queue),
+        }));
+        return self.handle.spawn(async move {
+            return match receiver.await {
+                Ok(result) => result,
+                // The sender is only dropped without sending if the task panicked
+                Err(_) => panic!("Deterministically scheduled task panicked!"),
+            };
+        });
+    }
+}
+
+impl Drop for DeterministicScheduler {
+    fn drop(&mut self) {
+        self.queue.ready.lock().unwrap().1 = true;
+        self.queue.notify.notify_all();
+    }
+}
+
+/// The tasks a DeterministicScheduler is waiting to poll
+struct TaskQueue {
+    /// The tasks in the order they're polled, and whether the scheduler was dropped
+    ready: Mutex<(VecDeque<Arc<QueuedTask>>, bool)>,
+    /// Notified when a task is added or the scheduler is dropped
+    notify: Condvar,
+}
+
+impl TaskQueue {
+    /// Adds the task to the end of the queue
+    fn push(&self, task: Arc<QueuedTask>) {
+        self.ready.lock().unwrap().0.push_back(task);
+        self.notify.notify_one();
+    }
+
+    /// Polls the tasks in the queue one at a time, until the scheduler is dropped
+    fn run(&self, handle: Handle) {
+        let _runtime = handle.enter();
+        loop {
+            let next = {
+                let mut ready = self.ready.lock().unwrap();
+                loop {
+                    if ready.1 {
+                        return;
+                    }
+                    if let Some(next) = ready.0.pop_front() {
+                        break next;
+                    }
+                    ready = self.notify.wait(ready).unwrap();
+                }
+            };
+            next.poll();
+        }
+    }
+}
+
+/// A task spawned by a DeterministicScheduler
+struct QueuedTask {
+    /// The task, or None once it's finished
+    task: Mutex<Option<Task>>,
+    /// Sends the task's result to its join handle
+    sender: Mutex<Option<oneshot::Sender<Result<(), ParsingError>>>>,
+    /// Whether the task is in the queue, so waking it again doesn't poll it twice
+    queued: AtomicBool,
+    /// The queue the task is added back to when it's woken
+    queue: Weak<TaskQueue>,
+}
+
+impl QueuedTask {
+    /// Polls the task once, sending its result to the join handle if it finished
+    fn poll(self: Arc<Self>) {
+        self.queued.store(false, Ordering::Release);
+        let mut task = self.task.lock().unwrap();
+        let polling = match task.as_mut() {
+            Some(polling) => polling,
+            None => return,
+        };
+        // The join handle was aborted, so the task is cancelled
+        if self.sender.lock().unwrap().as_ref().map_or(true, |sender| sender.is_closed()) {
+            *task = None;
+            return;
+        }
+
+        let waker = Waker::from(self.clone());
+        match panic::catch_unwind(AssertUnwindSafe(|| polling.as_mut().poll(&mut Context::from_waker(&waker)))) {
+            Ok(Poll::Pending) => {}
+            Ok(Poll::Ready(result)) => {
+                *task = None;
+                if let Some(sender) = self.sender.lock().unwrap().take() {
+                    // The join handle might've been dropped, in which case nothing wants the result
+                    let _ = sender.send(result);
+                }
+            }
+            // Dropping the sender panics the join handle, like a task panicking on the runtime
+            Err(_) => {
+                *task = None;
+                self.sender.lock().unwrap().take();
+            }
+        }
+    }
+}
+
+impl Wake for QueuedTask {
+    fn wake(self: Arc<Self>) {
+        if !self.queued.swap(true, Ordering::AcqRel) {
+            if let Some(queue) = self.queue.upgrade() {
+                queue.push(self);
+            }
+        }
+    }
+}
+
+/// Wraps around a Scheduler, allowing the program to wait for all spawned tasks to finish
+pub struct HandleWrapper {
+    /// Runs the spawned tasks
+    scheduler: Box<dyn Scheduler>,
     /// Tasks to join to finish
     pub joining: Vec<JoinHandle<Result<(), ParsingError>>>,
     /// The names of running tasks and a handle to abort them
@@ -305,13 +592,19 @@ pub struct HandleWrapper {
 }
 
 impl HandleWrapper {
-    /// Creates a new handle wrapper
+    /// Creates a new handle wrapper running tasks on the handle's runtime
     pub fn new(handle: Handle) -> HandleWrapper {
-        return HandleWrapper { handle, joining: vec![], names: HashMap::default(), waker: None };
+        return HandleWrapper::with_scheduler(Box::new(RuntimeScheduler::new(handle)));
     }
+
+    /// Creates a new handle wrapper running tasks with the scheduler
+    pub fn with_scheduler(scheduler: Box<dyn Scheduler>) -> HandleWrapper {
+        return HandleWrapper { scheduler, joining: vec![], names: HashMap::default(), waker: None };
+    }
+
     /// Spawns a task and adds it to the joining vec
     pub fn spawn<F: Future<Output = Result<(), ParsingError>> + Send + 'static>(&mut self, name: String, future: F) {
-        let handle = self.handle.spawn(future);
+        let handle = self.scheduler.spawn(Box::pin(future));
         self.names.insert(name, handle.abort_handle());
 
         self.joining.push(handle);
@@ -1,9 +1,11 @@
-use crate::program::types::FinalizedTypes;
-use data::tokens::Span;
+use crate::program::function::Abi;
+use crate::program::types::{FinalizedTypes, TypeMismatch};
+use data::tokens::{line_column, Span};
 use data::SourceSet;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
-use colored::Colorize;
+use colored::{Color, Colorize};
 
 #[derive(Debug, Clone)]
 pub enum ParsingMessage {
@@ -17,6 +19,7 @@ pub enum ParsingMessage {
     UnexpectedToken(),
     UnexpectedSymbol(),
     UnexpectedVoid(),
+    MissingReturn(String),
     UnexpectedTopElement(),
     UnexpectedReturnType(FinalizedTypes, FinalizedTypes),
     ExpectedEffect(),
@@ -30,16 +33,112 @@ pub enum ParsingMessage {
     UnexpectedCharacters(),
     DuplicateStructure(),
     DuplicateFunction(),
-    UnknownField(String),
+    UnknownField(String, Option<String>),
+    PrivateField(String, String),
     IncorrectBoundsLength(),
+    IncorrectGenericsLength(usize, usize),
     MismatchedTypes(FinalizedTypes, FinalizedTypes),
     UnknownOperation(),
     UnknownFunction(),
     MissingArgument(),
+    MissingReceiver(String),
+    VariadicNotLast(),
     AmbiguousMethod(String),
     NoMethod(String, FinalizedTypes),
-    NoImpl(FinalizedTypes, String),
-    NoTraitImpl(FinalizedTypes, FinalizedTypes),
+    NoImpl(FinalizedTypes, String, Option<String>),
+    NoTraitImpl(String, String, Option<TypeMismatch>),
+    InstantiateTrait(FinalizedTypes),
+    PtrLenArguments(i64, i64),
+    IndexOutOfBounds(u64, u64),
+    NonUnsignedIndex(FinalizedTypes),
+    UnresolvedImpl(String),
+    FloatAsInteger(FinalizedTypes),
+    SupertraitOnStruct(),
+    MissingSupertraitImpl(FinalizedTypes, FinalizedTypes, FinalizedTypes),
+    DuplicateMethod(String),
+    FirstDeclaredHere(),
+    ShadowedVariable(String),
+    ShadowedTraitMethod(String, FinalizedTypes),
+    VariableOutOfScope(String),
+    ExpectedSwitchArm(),
+    DuplicateVariant(String),
+    InstantiateEnum(FinalizedTypes),
+    UnknownVariant(FinalizedTypes, String),
+    VariantFieldCount(String, usize, usize),
+    SwitchOnNonEnum(FinalizedTypes),
+    NonExhaustiveSwitch(Vec<String>),
+    RedundantSwitchArm(String),
+    UninferredGenerics(String),
+    UnwrappedOption(String, FinalizedTypes),
+    NestingTooDeep(usize),
+    UnknownAbi(String),
+    AbiWithoutExtern(),
+    ExternalWithBody(String),
+    InvalidConstant(),
+    NonConstantCall(String),
+    OverlappingImpls(FinalizedTypes, FinalizedTypes),
+    UnknownArgument(String),
+    DuplicateArgument(String),
+    PositionalAfterNamed(),
+    UnexpectedNamedArgument(String),
+    NoStringConversion(FinalizedTypes),
+    NonConstantAssert(),
+    StaticAssertFailed(),
+    TraitMismatch(FinalizedTypes, FinalizedTypes, TypeMismatch),
+    OperationArguments(String, usize, usize),
+    InfiniteSize(String, String),
+    InvalidCast(FinalizedTypes, FinalizedTypes),
+    InternalError(String),
+    CheckTimedOut(u64),
+    NoTypeInModule(String, String),
+    WhereNotSelf(String),
+    UnsatisfiedSelfBound(String, String, String),
+    UnreachableCode(),
+    OutsideLoop(String),
+    LoopValue(),
+    InvalidEscape(String, usize),
+    DivisionByZero(),
+    UnknownLabel(String),
+    ExpectedLoop(),
+    UseAfterMove(String),
+    InstantiationTooDeep(usize, String),
+    GenericDefaultOutsideTrait(),
+    NoTraitImplTaking(String, String, String),
+    ConflictingInline(String),
+    OrphanImpl(FinalizedTypes, FinalizedTypes),
+    InvalidDiscriminant(String, String),
+    DiscriminantOverflow(String),
+    DuplicateDiscriminant(String, u64),
+    MissingTraitMethod(String, String),
+    TraitSignatureMismatch(String, String, String),
+    NotInTrait(String, String),
+    DeclaredInTrait(),
+    InlineRecursive(String),
+    PatternLength(FinalizedTypes, usize),
+    PatternMismatch(FinalizedTypes, String),
+    MissingAssociatedConstant(String, String),
+    NotTraitConstant(String, String),
+    AssociatedConstantType(String, FinalizedTypes, FinalizedTypes),
+    AssociatedConstantValue(),
+    AssociatedConstantInStruct(),
+    SpecialFloatLiteral(String),
+    AmbiguousTraitMethod(String, Vec<FinalizedTypes>),
+    TraitNotImported(String, FinalizedTypes),
+    InExpansion(String),
+    UnusedGeneric(String),
+    DerefChain(Box<ParsingMessage>, Vec<FinalizedTypes>),
+    DerefCycle(String, Vec<FinalizedTypes>),
+    InvalidEntryPoint(String),
+    IntegerOutOfRange(String, String),
+    MutableBorrowConflict(String),
+    ConstantCycle(String, String),
+    CopiedBorrow(String, FinalizedTypes),
+    MovedInLoop(String),
+    UncompiledEntryPoint(String),
+    OverloadedMethod(String),
+    AliasCycle(String, String),
+    RetypedLiteral(String, FinalizedTypes),
+    UncompiledConstValue(String),
 }
 
 impl Display for ParsingMessage {
@@ -55,6 +154,7 @@ impl Display for ParsingMessage {
             ParsingMessage::UnexpectedToken() => write!(f, "Unexpected token, expected variable name!"),
             ParsingMessage::UnexpectedSymbol() => write!(f, "Unexpected symbol, expected equals!"),
             ParsingMessage::UnexpectedVoid() => write!(f, "Expected a value, found void!"),
+            ParsingMessage::MissingReturn(types) => write!(f, "Expected a value of type {}, found void!", types),
             ParsingMessage::UnexpectedTopElement() => write!(f, "Unexpected top element!"),
             ParsingMessage::UnexpectedReturnType(expected, gotten) => {
                 write!(f, "Unexpected return type! Expected a {} but found {}", fix_type(expected), fix_type(gotten))
@@ -70,21 +170,294 @@ impl Display for ParsingMessage {
             ParsingMessage::UnexpectedCharacters() => write!(f, "Unexpected characters!"),
             ParsingMessage::DuplicateStructure() => write!(f, "Duplicate structure!"),
             ParsingMessage::DuplicateFunction() => write!(f, "Duplicate function!"),
-            ParsingMessage::UnknownField(field) => write!(f, "Unknown field {}!", field),
+            ParsingMessage::UnknownField(field, None) => write!(f, "Unknown field {}!", field),
+            ParsingMessage::UnknownField(field, Some(similar)) => {
+                write!(f, "Unknown field {}, did you mean {}?", field, similar)
+            }
+            ParsingMessage::PrivateField(field, structure) => {
+                write!(f, "Field {} of {} is private, mark it pub to use it outside of its file!", field, structure)
+            }
             ParsingMessage::IncorrectBoundsLength() => write!(f, "Incorrect bounds length!"),
+            ParsingMessage::IncorrectGenericsLength(expected, found) => {
+                write!(f, "Expected {} generic arguments but found {}!", expected, found)
+            }
             ParsingMessage::MismatchedTypes(found, bound) => {
                 write!(f, "{} isn't of type {}", fix_type(found), fix_type(bound))
             }
             ParsingMessage::UnknownOperation() => write!(f, "Unknown operation!"),
             ParsingMessage::UnknownFunction() => write!(f, "Unknown function!"),
             ParsingMessage::MissingArgument() => write!(f, "Incorrect arguments length!"),
+            ParsingMessage::MissingReceiver(method) => write!(f, "Method {} requires a receiver!", method),
+            ParsingMessage::VariadicNotLast() => write!(f, "Only the last argument can be variadic!"),
             ParsingMessage::AmbiguousMethod(name) => write!(f, "Ambiguous method {}!", name),
             ParsingMessage::NoMethod(name, types) => write!(f, "No method {} for generic {}", name, fix_type(types)),
-            ParsingMessage::NoImpl(base, method) => {
+            ParsingMessage::NoImpl(base, method, None) => {
                 write!(f, "No implementation of method {} for {}", method, fix_type(base))
             }
-            ParsingMessage::NoTraitImpl(base, traits) => {
-                write!(f, "No implementation of {} for {}", fix_type(traits), fix_type(base))
+            ParsingMessage::NoImpl(base, method, Some(similar)) => {
+                write!(f, "No implementation of method {} for {}, did you mean {}?", method, fix_type(base), similar)
+            }
+            ParsingMessage::NoTraitImpl(base, traits, None) => write!(f, "No implementation of {} for {}", traits, base),
+            ParsingMessage::NoTraitImpl(base, traits, Some(reason)) => {
+                write!(f, "No implementation of {} for {}, {}!", traits, base, reason)
+            }
+            ParsingMessage::NoTraitImplTaking(base, traits, argument) => {
+                write!(f, "No implementation of {} for {} taking {}!", traits, base, argument)
+            }
+            ParsingMessage::InstantiateTrait(types) => {
+                write!(f, "cannot instantiate trait `{}`; use a concrete implementer", fix_type(types))
+            }
+            ParsingMessage::PtrLenArguments(expected, found) => {
+                write!(f, "Expected {} arguments after expanding slices to pointers and lengths, found {}!", expected, found)
+            }
+            ParsingMessage::IndexOutOfBounds(index, length) => {
+                write!(f, "Index {} is out of bounds for an array of length {}!", index, length)
+            }
+            ParsingMessage::NonUnsignedIndex(found) => {
+                write!(f, "Arrays can only be indexed by u64, found {}!", fix_type(found))
+            }
+            ParsingMessage::SupertraitOnStruct() => write!(f, "Only traits can have supertraits!"),
+            ParsingMessage::MissingSupertraitImpl(base, traits, supertrait) => write!(
+                f,
+                "{} implements {} but not its supertrait {}!",
+                fix_type(base),
+                fix_type(traits),
+                fix_type(supertrait)
+            ),
+            ParsingMessage::DuplicateMethod(name) => {
+                write!(f, "Duplicate method {} with the same argument types!", name)
+            }
+            ParsingMessage::FirstDeclaredHere() => write!(f, "First declared here!"),
+            ParsingMessage::FloatAsInteger(types) => {
+                write!(f, "Expected an integer of type {}, found a float!", fix_type(types))
+            }
+            ParsingMessage::UnresolvedImpl(log) => write!(f, "Failed to resolve implementation call:\n{}", log),
+            ParsingMessage::ShadowedVariable(name) => write!(f, "Variable {} shadows a variable from an outer scope!", name),
+            ParsingMessage::ShadowedTraitMethod(name, traits) => {
+                write!(f, "Method {} shadows the method of the same name from {}!", name, fix_type(traits))
+            }
+            ParsingMessage::VariableOutOfScope(name) => {
+                write!(f, "Variable {} was used outside of the scope it was declared in!", name)
+            }
+            ParsingMessage::ExpectedSwitchArm() => write!(f, "Expected a switch arm like Variant(fields) => {{ ... }}!"),
+            ParsingMessage::DuplicateVariant(name) => write!(f, "Duplicate variant {}!", name),
+            ParsingMessage::InstantiateEnum(types) => {
+                write!(f, "Can't create enum {} with new, create one of its variants instead!", fix_type(types))
+            }
+            ParsingMessage::UnknownVariant(types, name) => write!(f, "{} has no variant {}!", fix_type(types), name),
+            ParsingMessage::VariantFieldCount(name, expected, found) => {
+                write!(f, "Variant {} has {} fields but found {}!", name, expected, found)
+            }
+            ParsingMessage::SwitchOnNonEnum(types) => write!(f, "Only enums can be switched on, found {}!", fix_type(types)),
+            ParsingMessage::NonExhaustiveSwitch(missing) => {
+                write!(f, "Switch doesn't cover every variant, missing {}!", missing.join(", "))
+            }
+            ParsingMessage::RedundantSwitchArm(name) => {
+                write!(f, "Switch arm {} is unreachable, every variant it matches is already covered!", name)
+            }
+            ParsingMessage::UninferredGenerics(name) => {
+                write!(f, "Couldn't infer the generics of {}, give them explicitly like {}<T>()!", name, name)
+            }
+            ParsingMessage::UnknownAbi(name) => write!(
+                f,
+                "Unknown ABI \"{}\", expected one of {}!",
+                name,
+                Abi::ALL.iter().map(|abi| format!("\"{}\"", abi.name())).collect::<Vec<_>>().join(", ")
+            ),
+            ParsingMessage::AbiWithoutExtern() => write!(f, "Only extern functions can have an ABI!"),
+            ParsingMessage::InvalidConstant() => {
+                write!(f, "Constants need a type and a value, like const LIMIT: u64 = 10!")
+            }
+            ParsingMessage::NonConstantCall(name) => {
+                write!(f, "Constant values can only use literals and operators, can't call {}!", name)
+            }
+            ParsingMessage::OverlappingImpls(base, traits) => {
+                write!(f, "Conflicting implementations of {} for {}!", fix_type(traits), fix_type(base))
+            }
+            ParsingMessage::UnknownArgument(name) => write!(f, "Method has no argument named {}!", name),
+            ParsingMessage::DuplicateArgument(name) => write!(f, "Argument {} was given more than once!", name),
+            ParsingMessage::PositionalAfterNamed() => {
+                write!(f, "Positional arguments must come before named arguments!")
+            }
+            ParsingMessage::UnexpectedNamedArgument(name) => {
+                write!(f, "Named argument {} can only be passed to a method call!", name)
+            }
+            ParsingMessage::NoStringConversion(types) => {
+                write!(f, "{} can't be interpolated into a string, it doesn't implement ToString!", fix_type(types))
+            }
+            ParsingMessage::NonConstantAssert() => {
+                write!(f, "Static asserts can only use literals, constants, const generics, size_of and built-in operators!")
+            }
+            ParsingMessage::StaticAssertFailed() => write!(f, "Static assert failed!"),
+            ParsingMessage::TraitMismatch(found, expected, reason) => {
+                write!(f, "{} isn't of type {}, {}!", fix_type(found), fix_type(expected), reason)
+            }
+            ParsingMessage::OperationArguments(operation, expected, found) => {
+                write!(f, "Operation {} takes {} values but was given {}!", operation, expected, found)
+            }
+            ParsingMessage::InfiniteSize(name, cycle) => write!(
+                f,
+                "Struct {} has an infinite size because it contains itself through {}, put one of the fields behind a reference!",
+                name, cycle
+            ),
+            ParsingMessage::InternalError(message) => {
+                write!(f, "Internal compiler error, please report this: {}", message)
+            }
+            ParsingMessage::CheckTimedOut(seconds) => {
+                write!(f, "Checking took longer than {} seconds, something is probably waiting forever!", seconds)
+            }
+            ParsingMessage::NoTypeInModule(name, module) => {
+                write!(f, "No type {} in module {}, is it spelled right?", name, module)
+            }
+            ParsingMessage::WhereNotSelf(name) => {
+                write!(f, "Only Self can be bounded in a where clause, bound {} in its generics instead!", name)
+            }
+            ParsingMessage::UnsatisfiedSelfBound(method, types, bound) => {
+                write!(f, "{} can only be called on types implementing {}, which {} doesn't!", method, bound, types)
+            }
+            ParsingMessage::UnreachableCode() => write!(f, "Unreachable code, it's after a return or break so it never runs!"),
+            ParsingMessage::OutsideLoop(keyword) => write!(f, "{} can only be used inside a for or while loop!", keyword),
+            ParsingMessage::LoopValue() => write!(f, "Loops don't have a value, so they can't break with one!"),
+            ParsingMessage::DivisionByZero() => write!(f, "Division by zero, the divisor is always 0!"),
+            ParsingMessage::UnknownLabel(label) => write!(f, "No loop labeled '{} around this jump!", label),
+            ParsingMessage::ExpectedLoop() => write!(f, "Only for, while, and do while loops can be labeled!"),
+            ParsingMessage::UseAfterMove(name) => {
+                write!(f, "{} was moved into a call and can't be used after it, unless its type implements Copy!", name)
+            }
+            ParsingMessage::InvalidEscape(escape, offset) => {
+                write!(f, "Invalid escape \\{} at offset {} of the string literal!", escape, offset)
+            }
+            ParsingMessage::InvalidCast(from, to) => write!(
+                f,
+                "Can't cast {} to {}, only numbers can be cast to numbers without implementing Cast<{}>!",
+                fix_type(from),
+                fix_type(to),
+                fix_type(to)
+            ),
+            ParsingMessage::ExternalWithBody(name) => {
+                write!(f, "Extern and internal function {} can't have a body, leave it empty like {{}}!", name)
+            }
+            ParsingMessage::NestingTooDeep(limit) => {
+                write!(f, "Expression nesting too deep, it can only be nested {} levels deep!", limit)
+            }
+            ParsingMessage::GenericDefaultOutsideTrait() => write!(f, "Only a trait's generics can have a default type!"),
+            ParsingMessage::ConflictingInline(function) => {
+                write!(f, "Function {} can't be both #[inline] and #[noinline]!", function)
+            }
+            ParsingMessage::OrphanImpl(base, traits) => write!(
+                f,
+                "Can't implement {} for {} here, either the trait or the type must be declared in this module!",
+                fix_type(traits),
+                fix_type(base)
+            ),
+            ParsingMessage::InvalidDiscriminant(variant, written) => {
+                write!(f, "Discriminant {} of variant {} isn't a u64!", written, variant)
+            }
+            ParsingMessage::DiscriminantOverflow(variant) => {
+                write!(f, "Variant {} comes after a variant with the largest u64, so it has no discriminant left!", variant)
+            }
+            ParsingMessage::DuplicateDiscriminant(variant, value) => {
+                write!(f, "Variant {} has the discriminant {}, which another variant already has!", variant, value)
+            }
+            ParsingMessage::MissingTraitMethod(method, traits) => {
+                write!(f, "Missing method {} of {}, every method of the trait has to be implemented!", method, traits)
+            }
+            ParsingMessage::TraitSignatureMismatch(method, expected, found) => {
+                write!(f, "Method {} doesn't match the trait, expected {} but found {}!", method, expected, found)
+            }
+            ParsingMessage::NotInTrait(method, traits) => write!(f, "Method {} isn't a method of {}!", method, traits),
+            ParsingMessage::DeclaredInTrait() => write!(f, "Declared in the trait here!"),
+            ParsingMessage::InlineRecursive(function) => {
+                write!(f, "Function {} is #[inline] but calls itself, so it can't be inlined into itself!", function)
+            }
+            ParsingMessage::PatternLength(types, found) => {
+                write!(f, "Pattern has {} elements, which doesn't match the length of {}!", found, fix_type(types))
+            }
+            ParsingMessage::PatternMismatch(types, pattern) => {
+                write!(f, "Can't destructure {} with the pattern {}!", fix_type(types), pattern)
+            }
+            ParsingMessage::MissingAssociatedConstant(constant, traits) => {
+                write!(f, "Missing associated constant {} of {}, every constant the trait declares has to be defined!", constant, traits)
+            }
+            ParsingMessage::NotTraitConstant(constant, traits) => {
+                write!(f, "Constant {} isn't an associated constant of {}!", constant, traits)
+            }
+            ParsingMessage::AssociatedConstantType(constant, expected, found) => {
+                write!(f, "Associated constant {} should be {} like in the trait, found {}!", constant, fix_type(expected), fix_type(found))
+            }
+            ParsingMessage::AssociatedConstantValue() => {
+                write!(f, "Traits only declare associated constants, like const SIDES: u64; their impls give the values!")
+            }
+            ParsingMessage::AssociatedConstantInStruct() => write!(f, "Only traits and impls can have associated constants!"),
+            ParsingMessage::SpecialFloatLiteral(literal) => {
+                write!(f, "{} literals aren't allowed, special float values are rejected by the compiler's options!", literal)
+            }
+            ParsingMessage::AmbiguousTraitMethod(method, traits) => write!(
+                f,
+                "Method {} is in more than one imported trait ({}), call it like Trait::{}(value) to pick one!",
+                method,
+                traits.iter().map(fix_type).collect::<Vec<_>>().join(", "),
+                method
+            ),
+            ParsingMessage::TraitNotImported(method, traits) => {
+                write!(f, "Method {} is from {}, which has to be imported to call it!", method, fix_type(traits))
+            }
+            ParsingMessage::InExpansion(name) => write!(f, "In expansion of {}!", name),
+            ParsingMessage::UnusedGeneric(name) => {
+                write!(f, "Generic {} is never used, prefix it with an underscore like _{} if that's on purpose!", name, name)
+            }
+            ParsingMessage::DerefChain(message, chain) => {
+                write!(f, "{}, after following the derefs {}!", message.to_string().trim_end_matches('!'), chain.iter().map(fix_type).collect::<Vec<_>>().join(" -> "))
+            }
+            ParsingMessage::InvalidEntryPoint(name) => {
+                write!(f, "Entry point {} can't have generics, nothing can pass them to it!", name)
+            }
+            ParsingMessage::DerefCycle(method, chain) => write!(
+                f,
+                "Method {} wasn't found before the derefs started looping: {}!",
+                method,
+                chain.iter().map(fix_type).collect::<Vec<_>>().join(" -> ")
+            ),
+            ParsingMessage::InstantiationTooDeep(limit, chain) => write!(
+                f,
+                "Generic instantiation too deep, generic functions can only instantiate each other {} levels deep! \
+                The instances keep expanding: {}!",
+                limit, chain
+            ),
+            ParsingMessage::UnwrappedOption(method, types) => write!(
+                f,
+                "Can't call {} on {} because it might be None, unwrap it or switch on it first!",
+                method,
+                fix_type(types)
+            ),
+            ParsingMessage::IntegerOutOfRange(integer, types) => write!(f, "Integer {} doesn't fit in a {}!", integer, types),
+            ParsingMessage::MutableBorrowConflict(name) => {
+                write!(f, "Can't mutably borrow {} while it's already mutably borrowed!", name)
+            }
+            ParsingMessage::ConstantCycle(name, cycle) => {
+                write!(f, "Constant {} has a value that uses itself through {}!", name, cycle)
+            }
+            ParsingMessage::CopiedBorrow(name, types) => write!(
+                f,
+                "Can't mutably borrow {}, a {} is copied instead of borrowed, only structs can be borrowed!",
+                name,
+                fix_type(types)
+            ),
+            ParsingMessage::MovedInLoop(name) => {
+                write!(f, "Can't move {} into a call inside of a loop, the next time around the loop would use it!", name)
+            }
+            ParsingMessage::UncompiledEntryPoint(name) => write!(f, "Entry point {} has no compiled code to call!", name),
+            ParsingMessage::OverloadedMethod(name) => {
+                write!(f, "Method {} is already declared with other arguments, methods can't be overloaded!", name)
+            }
+            ParsingMessage::AliasCycle(name, chain) => {
+                write!(f, "Type alias {} stands for itself through {}, so it can never be expanded!", name, chain)
+            }
+            ParsingMessage::RetypedLiteral(name, types) => {
+                write!(f, "Variable {} is declared from a literal and used as a {}, so it should be one!", name, types)
+            }
+            ParsingMessage::UncompiledConstValue(function) => {
+                write!(f, "Value in {} depends on generics that weren't known before it was compiled!", function)
             }
         };
     }
@@ -93,7 +466,8 @@ impl Display for ParsingMessage {
 fn fix_type(types: &FinalizedTypes) -> String {
     let mut string = types.to_string();
     if let Some(start) = string.find('$') {
-        string.replace_range(start..string.find('<').unwrap_or(string.len()), "");
+        let end = string[start..].find('<').map_or(string.len(), |end| start + end);
+        string.replace_range(start..end, "");
     }
     return string;
 }
@@ -105,6 +479,8 @@ pub struct ParsingError {
     pub span: Span,
     /// The error message
     pub message: ParsingMessage,
+    /// Points to another location relevant to the error, like the first of two duplicates
+    pub note: Option<Box<ParsingError>>,
 }
 
 pub trait ErrorSource {
@@ -120,11 +496,48 @@ impl ErrorSource for Span {
 impl ParsingError {
     /// Creates a new error
     pub fn new(span: Span, message: ParsingMessage) -> Self {
-        return Self { span, message };
+        return Self { span, message, note: None };
+    }
+
+    /// Attaches a note pointing at another location
+    pub fn with_note(mut self, note: ParsingError) -> Self {
+        self.note = Some(Box::new(note));
+        return self;
+    }
+
+    /// Sorts errors by the path of their file in the sources and their position, removing duplicates like the
+    /// same error found by a retried impl lookup. Errors in files outside of the sources go first.
+    pub fn sort(errors: &mut Vec<ParsingError>, sources: &[Box<dyn SourceSet>]) {
+        let mut paths = HashMap::new();
+        for source in sources {
+            for readable in source.get_files() {
+                paths.insert(readable.hash(), readable.path());
+            }
+        }
+        errors.sort_by_cached_key(|error| {
+            let path = paths.get(&error.span.file).cloned().unwrap_or_default();
+            (path, error.span.file, error.span.start, error.span.end, error.message.to_string())
+        });
+        errors.dedup_by(|first, second| {
+            first.span.file == second.span.file
+                && first.span.start == second.span.start
+                && first.span.end == second.span.end
+                && first.message.to_string() == second.message.to_string()
+        });
     }
 
     /// Prints the error to console
     pub fn print(&self, sources: &Vec<Box<dyn SourceSet>>) {
+        self.print_colored(sources, Color::BrightRed);
+    }
+
+    /// Prints the error to console as a warning
+    pub fn print_warning(&self, sources: &Vec<Box<dyn SourceSet>>) {
+        self.print_colored(sources, Color::BrightYellow);
+    }
+
+    /// Prints the error to console, with the message and location in the color
+    fn print_colored(&self, sources: &Vec<Box<dyn SourceSet>>, color: Color) {
         let mut file = None;
         'outer: for source in sources {
             for readable in source.get_files() {
@@ -159,17 +572,89 @@ impl ParsingError {
             token.start_offset -= 1;
         }
 
+        let (line_number, column) = line_column(&contents, token.start_offset);
         let line = contents.lines().nth((token.start.0 as usize).max(1) - 1).unwrap_or("???");
-        println!("{}", self.message.to_string().bright_red());
-        println!("{}", format!("in file {}:{}:{}", file.path(), token.start.0, token.start.1).bright_red());
+        println!("{}", self.message.to_string().color(color));
+        println!("{}", format!("in file {}:{}:{}", file.path(), line_number, column).color(color));
         println!("{} {}", " ".repeat(token.start.0.to_string().len()), "|".bright_cyan());
-        println!("{} {} {}", token.start.0.to_string().bright_cyan(), "|".bright_cyan(), line.bright_red());
+        println!("{} {} {}", token.start.0.to_string().bright_cyan(), "|".bright_cyan(), line.color(color));
         println!(
             "{} {} {}{}",
             " ".repeat(token.start.0.to_string().len()),
             "|".bright_cyan(),
-            " ".repeat(token.start.1 as usize),
-            "^".repeat(token.end_offset - token.start_offset).bright_red()
+            " ".repeat(column as usize - 1),
+            "^".repeat(token.end_offset - token.start_offset).color(color)
         );
+
+        // Generated code also points at what it was expanded from, so the error can be found in the code that was written
+        if let Some(expansion) = &self.span.expansion {
+            ParsingError::new(expansion.origin.clone(), ParsingMessage::InExpansion(expansion.name.clone()))
+                .print_colored(sources, color);
+        }
+        if let Some(note) = &self.note {
+            note.print(sources);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use data::tokens::{Span, Token};
+    use data::{Readable, SourceSet};
+
+    use crate::errors::{ErrorSource, ParsingError, ParsingMessage};
+
+    /// A file that's only a path and its hash
+    struct NamedFile(&'static str, u64);
+
+    impl Readable for NamedFile {
+        fn read(&self) -> Vec<Token> {
+            return Vec::default();
+        }
+
+        fn contents(&self) -> String {
+            return String::default();
+        }
+
+        fn path(&self) -> String {
+            return self.0.to_string();
+        }
+
+        fn hash(&self) -> u64 {
+            return self.1;
+        }
+    }
+
+    /// Files with hashes in the opposite order of their paths
+    #[derive(Debug)]
+    struct Files;
+
+    impl SourceSet for Files {
+        fn get_files(&self) -> Vec<Box<dyn Readable>> {
+            return vec![Box::new(NamedFile("b.rv", 1)), Box::new(NamedFile("a.rv", 2))];
+        }
+
+        fn relative(&self, other: &dyn Readable) -> String {
+            return other.path().replace(".rv", "");
+        }
+
+        fn cloned(&self) -> Box<dyn SourceSet> {
+            return Box::new(Files);
+        }
+    }
+
+    /// Checks errors are sorted by their file's path, then their position, without duplicates
+    #[test]
+    pub fn test_sort() {
+        let error = |file: u64, start: usize, name: &str| {
+            Span { file, start, end: start, ..Span::default() }.make_error(ParsingMessage::FailedToFind(name.to_string()))
+        };
+        let mut errors = vec![error(1, 0, "b"), error(2, 5, "second"), error(2, 1, "first"), error(1, 0, "b")];
+        errors.push(error(3, 0, "outside"));
+        ParsingError::sort(&mut errors, &[Box::new(Files)]);
+
+        let messages = errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>();
+        let expected = ["outside", "first", "second", "b"].map(|name| ParsingMessage::FailedToFind(name.to_string()));
+        assert_eq!(messages, expected.map(|message| message.to_string()));
     }
 }
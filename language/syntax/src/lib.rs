@@ -5,6 +5,7 @@
 #![feature(async_fn_traits)]
 
 use crate::async_util::{HandleWrapper, NameResolver};
+use crate::program::constant::{FinalizedConstant, UnfinalizedConstant};
 use crate::program::function::{CodeBody, CodelessFinalizedFunction, FinalizedFunction, FunctionData, UnfinalizedFunction};
 use crate::program::r#struct::{FinalizedStruct, StructData, UnfinalizedStruct};
 use crate::program::syntax::Syntax;
@@ -23,7 +24,7 @@ use indexmap::IndexMap;
 /// - Data Type trait used a simple wrapper to access the static data (see FunctionData or StructData) of an object with data
 /// - Top Element trait used to allow generic access to function and struct types
 /// - Trait implementors struct for storing implementor data
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::future::Future;
 use std::hash::Hash;
@@ -71,6 +72,10 @@ pub enum Modifier {
     Internal = 0b1000,
     /// Hidden from the user, only used internally
     Trait = 0b1_0000,
+    /// Marks the trailing argument of a function as taking any number of values
+    Variadic = 0b10_0000,
+    /// Hidden from the user, marks a struct as an enum of variants
+    Enum = 0b100_0000,
 }
 
 impl Display for Modifier {
@@ -81,6 +86,8 @@ impl Display for Modifier {
             Modifier::Extern => write!(f, "extern"),
             Modifier::Internal => write!(f, "internal"),
             Modifier::Trait => panic!("Shouldn't display trait modifier!"),
+            Modifier::Variadic => write!(f, "..."),
+            Modifier::Enum => panic!("Shouldn't display enum modifier!"),
         };
     }
 }
@@ -178,6 +185,7 @@ pub trait ProcessManager: Send + Sync {
     async fn verify_func(
         &self,
         function: UnfinalizedFunction,
+        resolver: Box<dyn NameResolver>,
         syntax: &Arc<Mutex<Syntax>>,
     ) -> (CodelessFinalizedFunction, CodeBody);
 
@@ -193,6 +201,14 @@ pub trait ProcessManager: Send + Sync {
     /// Degenerics the code of a function
     async fn degeneric_code(&self, function: Arc<CodelessFinalizedFunction>, syntax: &Arc<Mutex<Syntax>>);
 
+    /// Verifies a constant's value against its type, returning the finalized constant if it's valid
+    async fn verify_constant(
+        &self,
+        constant: Arc<UnfinalizedConstant>,
+        resolver: Box<dyn NameResolver>,
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Option<FinalizedConstant>;
+
     /// Verifies a struct, returning the finalized type
     async fn verify_struct(
         &self,
@@ -201,38 +217,73 @@ pub trait ProcessManager: Send + Sync {
         syntax: &Arc<Mutex<Syntax>>,
     ) -> FinalizedStruct;
 
+    /// Checks the methods of a trait's impl have the signatures the trait declares, returning every mismatch
+    async fn verify_impl_signatures(
+        &self,
+        implementor: &FinishedTraitImplementor,
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Vec<ParsingError>;
+
     /// Gets the current function generics
-    fn generics(&self) -> &HashMap<String, FinalizedTypes>;
+    fn generics(&self) -> &IndexMap<String, FinalizedTypes>;
 
     /// Gets the current function generics mutably
-    fn mut_generics(&mut self) -> &mut HashMap<String, FinalizedTypes>;
+    fn mut_generics(&mut self) -> &mut IndexMap<String, FinalizedTypes>;
+
+    /// The generic instances whose code is being degenericed to reach the current code, outermost first
+    fn instantiating(&self) -> &Vec<String>;
+
+    /// Gets the generic instances being degenericed mutably
+    fn mut_instantiating(&mut self) -> &mut Vec<String>;
 
     /// Clones the process manager, generally pretty fast because most data is Arc'd
     fn cloned(&self) -> Box<dyn ProcessManager>;
 }
 
 /// A simple manager for variables in a function
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SimpleVariableManager {
     /// The variables and their type
     pub variables: HashMap<String, FinalizedTypes>,
+    /// Variables owning a value that implements Drop, in declaration order
+    pub owned: Vec<String>,
+    /// The open scopes, innermost last
+    pub scopes: Vec<VariableScope>,
+    /// Variables removed when their scope ended, so using them can be errored on
+    pub ended: HashSet<String>,
+    /// Variables whose struct was moved into a call, so using them again can be errored on
+    pub moved: HashSet<String>,
+    /// Variables declared from an untyped integer literal or an array of them and where they're declared,
+    /// which are declared as another integer type if they're used as one
+    pub literals: HashMap<String, Span>,
+}
+
+/// The variables declared in a scope, which are removed when it ends
+#[derive(Debug, Clone, Default)]
+pub struct VariableScope {
+    /// Each declared variable and the type of the variable it shadows, if any
+    declared: Vec<(String, Option<FinalizedTypes>)>,
+    /// The owned variables when the scope started
+    owned: Vec<String>,
 }
 
 impl SimpleVariableManager {
     /// Gets the variable manager for the function, filling in the function parameters
     pub fn for_function(codeless: &CodelessFinalizedFunction) -> Self {
-        let mut variable_manager = SimpleVariableManager { variables: HashMap::default() };
+        let mut variable_manager = SimpleVariableManager::default();
 
         for field in &codeless.arguments {
             variable_manager.variables.insert(field.field.name.clone(), field.field.field_type.clone());
         }
+        // The function's code is a scope inside its parameters
+        variable_manager.push_scope();
 
         return variable_manager;
     }
 
     /// Gets the variable manager for the function, filling in the function parameters
     pub fn for_final_function(codeless: &FinalizedFunction) -> Self {
-        let mut variable_manager = SimpleVariableManager { variables: HashMap::default() };
+        let mut variable_manager = SimpleVariableManager::default();
 
         for field in &codeless.fields {
             variable_manager.variables.insert(field.field.name.clone(), field.field.field_type.clone());
@@ -240,6 +291,53 @@ impl SimpleVariableManager {
 
         return variable_manager;
     }
+
+    /// Starts a new scope, like an if statement's block
+    pub fn push_scope(&mut self) {
+        self.scopes.push(VariableScope { declared: Vec::default(), owned: self.owned.clone() });
+    }
+
+    /// Ends the innermost scope, removing its variables and restoring any they shadowed
+    pub fn pop_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+        for (name, shadowed) in scope.declared.into_iter().rev() {
+            self.moved.remove(&name);
+            self.literals.remove(&name);
+            match shadowed {
+                Some(types) => {
+                    self.variables.insert(name, types);
+                }
+                None => {
+                    self.variables.remove(&name);
+                    self.ended.insert(name);
+                }
+            }
+        }
+        self.owned = scope.owned;
+    }
+
+    /// Checks if the variable was declared in a scope opened after the given number of scopes were open
+    pub fn declared_since(&self, scopes: usize, name: &String) -> bool {
+        return self.scopes[scopes..].iter().any(|scope| scope.declared.iter().any(|(declared, _)| declared == name));
+    }
+
+    /// Declares a variable in the innermost scope, returning true if it shadows one from an outer scope
+    pub fn declare(&mut self, name: String, types: FinalizedTypes) -> bool {
+        let shadowed = self.variables.insert(name.clone(), types);
+        self.ended.remove(&name);
+        self.moved.remove(&name);
+        let scope = match self.scopes.last_mut() {
+            Some(scope) => scope,
+            None => return false,
+        };
+        // Redeclaring a variable in the same scope just replaces it
+        if scope.declared.iter().any(|(declared, _)| *declared == name) {
+            return false;
+        }
+        let shadows = shadowed.is_some();
+        scope.declared.push((name, shadowed));
+        return shadows;
+    }
 }
 
 impl VariableManager for SimpleVariableManager {
@@ -319,6 +417,10 @@ pub struct TraitImplementor {
     pub attributes: Vec<Attribute>,
     /// The implementor's functions
     pub functions: Vec<UnfinalizedFunction>,
+    /// The implementor's associated constants, like const SIDES: u64 = 4;
+    pub constants: Vec<Arc<UnfinalizedConstant>>,
+    /// The implementor's span
+    pub span: Span,
 }
 
 /// Finished impl block for a type.
@@ -337,6 +439,10 @@ pub struct FinishedTraitImplementor {
     pub attributes: Vec<Attribute>,
     /// All ths functions in this implementor
     pub functions: Vec<Arc<FunctionData>>,
+    /// The associated constants this implementor defines for the trait
+    pub constants: Vec<Arc<UnfinalizedConstant>>,
+    /// The implementor's span
+    pub span: Span,
 }
 
 /// Finished impl block for a type.
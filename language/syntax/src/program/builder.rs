@@ -0,0 +1,152 @@
+use data::tokens::Span;
+
+use crate::async_util::UnparsedType;
+use crate::errors::{ErrorSource, ParsingError, ParsingMessage};
+use crate::program::code::{EffectType, Effects};
+use crate::program::r#struct::get_integer;
+
+/// Builds effects the same way the parser does, for code generators and tests that don't have source code.
+/// Every built effect gets the builder's span, so errors in generated code point to what generated it.
+#[derive(Clone, Debug, Default)]
+pub struct EffectBuilder {
+    /// The span of every built effect
+    pub span: Span,
+}
+
+impl EffectBuilder {
+    /// Creates a builder giving its effects the span
+    pub fn new(span: Span) -> Self {
+        return Self { span };
+    }
+
+    /// Wraps the effect type with the builder's span
+    fn effect(&self, types: EffectType) -> Effects {
+        return Effects::new(self.span.clone(), types);
+    }
+
+    /// An empty effect, which is only valid as the calling effect of an implementation call without one
+    pub fn nop(&self) -> Effects {
+        return self.effect(EffectType::NOP);
+    }
+
+    /// An integer literal, which takes the integer type its context expects
+    pub fn int(&self, value: i64) -> Effects {
+        return self.effect(EffectType::Int(value));
+    }
+
+    /// An integer literal with a type suffix, like 5u8, erroring if the suffix isn't an integer type
+    pub fn typed_int(&self, value: u64, suffix: &str) -> Result<Effects, ParsingError> {
+        if get_integer(suffix).is_none() {
+            return Err(self.span.make_error(ParsingMessage::FailedToFind(suffix.to_string())));
+        }
+        return Ok(self.effect(EffectType::TypedInt(value, suffix.to_string())));
+    }
+
+    /// A boolean literal
+    pub fn bool(&self, value: bool) -> Effects {
+        return self.effect(EffectType::Bool(value));
+    }
+
+    /// A string literal, with its backslashes escaped so the checker decodes it back to the value
+    pub fn string(&self, value: &str) -> Effects {
+        return self.effect(EffectType::StringLiteral(value.replace('\\', "\\\\")));
+    }
+
+    /// Loads the variable with the name
+    pub fn variable(&self, name: &str) -> Effects {
+        return self.effect(EffectType::LoadVariable(name.to_string()));
+    }
+
+    /// Loads the field with the name from the value
+    pub fn field(&self, value: Effects, name: &str) -> Effects {
+        return self.effect(EffectType::Load(Box::new(value), name.to_string()));
+    }
+
+    /// Creates a variable set to the value, like let name = value;
+    pub fn create_variable(&self, name: &str, value: Effects) -> Effects {
+        return self.effect(EffectType::CreateVariable(name.to_string(), Box::new(value), None));
+    }
+
+    /// Casts the value to the type, like value as f64
+    pub fn cast(&self, value: Effects, types: &str) -> Effects {
+        return self.effect(EffectType::Cast(Box::new(value), UnparsedType::Basic(types.to_string())));
+    }
+
+    /// Sets the target, a variable or field, to the value
+    pub fn set(&self, target: Effects, value: Effects) -> Effects {
+        return self.effect(EffectType::Set(Box::new(target), Box::new(value)));
+    }
+
+    /// An argument passed by name, which must come after the positional arguments of a call
+    pub fn named(&self, name: &str, value: Effects) -> Effects {
+        return self.effect(EffectType::NamedArgument(name.to_string(), Box::new(value)));
+    }
+
+    /// Calls the function with the name, like function(arguments)
+    pub fn call(&self, function: &str, arguments: Vec<Effects>) -> Result<Effects, ParsingError> {
+        return self.call_with_generics(function, vec![], arguments);
+    }
+
+    /// Calls the function with explicit generics, like function<T>(arguments)
+    pub fn call_with_generics(
+        &self,
+        function: &str,
+        generics: Vec<UnparsedType>,
+        arguments: Vec<Effects>,
+    ) -> Result<Effects, ParsingError> {
+        check_named(&arguments)?;
+        return Ok(self.effect(EffectType::MethodCall(None, function.to_string(), arguments, None, generics)));
+    }
+
+    /// Calls the method on the value, like value.method(arguments)
+    pub fn method(&self, calling: Effects, method: &str, arguments: Vec<Effects>) -> Result<Effects, ParsingError> {
+        check_named(&arguments)?;
+        return Ok(self.effect(EffectType::MethodCall(
+            Some(Box::new(calling)),
+            method.to_string(),
+            arguments,
+            None,
+            vec![],
+        )));
+    }
+
+    /// Calls the method of the trait's impl for the calling effect's type, like Trait::method(calling, arguments).
+    /// The calling effect is a nop for traits called without a value.
+    pub fn impl_call(&self, calling: Effects, traits: &str, method: &str, arguments: Vec<Effects>) -> Effects {
+        return self.effect(EffectType::ImplementationCall(
+            Box::new(calling),
+            traits.to_string(),
+            method.to_string(),
+            arguments,
+            None,
+            vec![],
+        ));
+    }
+
+    /// An operation with each {} in it replaced by one of the values in order, like {}+{},
+    /// erroring if the number of values doesn't match
+    pub fn operation(&self, operation: &str, values: Vec<Effects>) -> Result<Effects, ParsingError> {
+        let expected = operation.matches("{}").count();
+        if expected != values.len() {
+            return Err(self.span.make_error(ParsingMessage::OperationArguments(
+                operation.to_string(),
+                expected,
+                values.len(),
+            )));
+        }
+        return Ok(self.effect(EffectType::Operation(operation.to_string(), values)));
+    }
+}
+
+/// Errors if a positional argument comes after a named one, which the checker can't order
+fn check_named(arguments: &[Effects]) -> Result<(), ParsingError> {
+    let first_named = arguments.iter().position(|argument| matches!(argument.types, EffectType::NamedArgument(_, _)));
+    if let Some(first_named) = first_named {
+        if let Some(positional) =
+            arguments[first_named..].iter().find(|argument| !matches!(argument.types, EffectType::NamedArgument(_, _)))
+        {
+            return Err(positional.span.make_error(ParsingMessage::PositionalAfterNamed()));
+        }
+    }
+    return Ok(());
+}
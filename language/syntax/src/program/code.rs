@@ -1,12 +1,13 @@
+use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use data::tokens::Span;
 
 use crate::async_util::UnparsedType;
 use crate::program::function::{CodeBody, CodelessFinalizedFunction, FinalizedCodeBody, FunctionData};
-use crate::program::r#struct::{BOOL, CHAR, F64, STR, U64};
+use crate::program::r#struct::{FinalizedStruct, StructData, BOOL, CHAR, F64, I64, STR, U64};
 use crate::program::types::{FinalizedTypes, Types};
-use crate::{Attribute, VariableManager};
+use crate::{Attribute, ParsingError, VariableManager};
 
 /// An expression is a single line of code, containing an effect and the type of expression.
 #[derive(Clone, Debug)]
@@ -29,7 +30,7 @@ pub struct FinalizedExpression {
 /// the types of expressions: a normal line, a return, or a break (for inside control statements).
 #[derive(Clone, Debug)]
 pub enum ExpressionType {
-    /// Breaks break out of a looping control statement like a for or while loop
+    /// Breaks and continues jump out of their block in a looping control statement like a for or while loop
     Break,
     /// Return returns out of the current function
     Return(Span),
@@ -64,6 +65,8 @@ pub struct MemberField {
     pub attributes: Vec<Attribute>,
     /// The field itself
     pub field: Field,
+    /// The value of a function argument that isn't passed
+    pub default: Option<Effects>,
 }
 
 /// A finalized member field.
@@ -75,6 +78,8 @@ pub struct FinalizedMemberField {
     pub attributes: Vec<Attribute>,
     /// The field itself
     pub field: FinalizedField,
+    /// The checked value of a function argument that isn't passed
+    pub default: Option<FinalizedEffects>,
 }
 
 impl PartialEq for ExpressionType {
@@ -91,7 +96,7 @@ impl PartialEq for ExpressionType {
 impl MemberField {
     /// Creates a new field
     pub fn new(modifiers: u8, attributes: Vec<Attribute>, field: Field) -> Self {
-        return Self { modifiers, attributes, field };
+        return Self { modifiers, attributes, field, default: None };
     }
 }
 
@@ -140,20 +145,26 @@ pub enum EffectType {
     NOP,
     /// An effect wrapped in parenthesis, just a wrapper around the effect to prevent issues with operator merging.
     Paren(Box<Effects>),
-    /// Creates a variable with the given name and value.
-    CreateVariable(String, Box<Effects>),
+    /// Creates a variable with the given name, value, and the type it was declared with, if any.
+    CreateVariable(String, Box<Effects>, Option<UnparsedType>),
     /// Label of jumping to body
     Jump(String),
     /// Comparison effect, and label to jump to the first if true, second if false
     CompareJump(Box<Effects>, String, String),
     /// A block of code inside the block of code.
     CodeBody(CodeBody),
+    /// A for, while, or do while loop's code, the labels continuing and breaking out of it jump to, and its name if labeled.
+    Loop(CodeBody, String, String, Option<String>),
+    /// Jumps out of the named loop, or the loop it's in if unnamed. Loops don't have a value, so breaking with one is an error.
+    Break(Option<String>, Option<Box<Effects>>),
+    /// Jumps to the next iteration of the named loop, or the loop it's in if unnamed.
+    Continue(Option<String>),
     /// Finds the implementation of the given trait for the given calling type, and calls the given method.
-    /// Calling, trait to call, function name, args, and return type (if explicitly required)
-    ImplementationCall(Box<Effects>, String, String, Vec<Effects>, Option<UnparsedType>),
+    /// Calling, trait to call, function name, args, return type (if explicitly required), and explicit generics
+    ImplementationCall(Box<Effects>, String, String, Vec<Effects>, Option<UnparsedType>, Vec<UnparsedType>),
     /// Finds the method with the name and calls it with those arguments.
-    /// Calling, calling function, function arguments, and return type (if explicitly required)
-    MethodCall(Option<Box<Effects>>, String, Vec<Effects>, Option<(UnparsedType, Span)>),
+    /// Calling, calling function, function arguments, return type (if explicitly required), and explicit generics
+    MethodCall(Option<Box<Effects>>, String, Vec<Effects>, Option<(UnparsedType, Span)>, Vec<UnparsedType>),
     /// Sets the variable to a value.
     Set(Box<Effects>, Box<Effects>),
     /// Loads variable with the given name.
@@ -168,16 +179,85 @@ pub enum EffectType {
     CreateArray(Vec<Effects>),
     /// A float
     Float(f64),
-    /// An integer
+    /// An integer without a type suffix
     Int(i64),
+    /// An integer with a type suffix, like 5u8
+    TypedInt(u64, String),
     /// An unsigned integer
     UInt(u64),
     /// A boolean
     Bool(bool),
     /// A character
     Char(char),
-    /// A string
-    String(String),
+    /// A string literal's text as it's written, with its escapes still in it
+    StringLiteral(String),
+    /// A string with interpolated expressions, like "x is {x}", as its string and expression parts in order
+    Interpolation(Vec<Effects>),
+    /// An anonymous function with the given arguments, return type (if any), and code
+    Closure(Vec<(String, UnparsedType)>, Option<UnparsedType>, CodeBody),
+    /// Runs the arm matching the enum's variant, with the enum, the arms, and the label of the switch's block
+    Switch(Box<Effects>, Vec<SwitchArm>, String),
+    /// A method call argument passed by name, like "x: 1" in foo(x: 1), with the argument's name and value
+    NamedArgument(String, Box<Effects>),
+    /// A condition checked at compile time, like static_assert(size_of<T>() == 8)
+    StaticAssert(Box<Effects>),
+    /// Converts the value to the type, like value as f64
+    Cast(Box<Effects>, UnparsedType),
+    /// Splits the value into the variables of the pattern, like let Point { x, y } = point;
+    Destructure(Pattern, Box<Effects>),
+    /// Mutably borrows the struct in the variable with the given name without moving it, like &mut value
+    MutableBorrow(String),
+}
+
+/// The shape a let splits its value into
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// Binds the whole value to the variable
+    Variable(String, Span),
+    /// Binds each element of an array of the same length, like [first, second].
+    /// There are no tuples, so arrays are used for pairs of values.
+    Array(Vec<Pattern>, Span),
+    /// Binds the named fields of the struct, like Point { x, y: other }
+    Struct(String, Vec<(String, Pattern)>, Span),
+}
+
+impl Pattern {
+    /// The span of the pattern
+    pub fn span(&self) -> &Span {
+        return match self {
+            Pattern::Variable(_, span) | Pattern::Array(_, span) | Pattern::Struct(_, _, span) => span,
+        };
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Pattern::Variable(name, _) => write!(f, "{}", name),
+            Pattern::Array(elements, _) => {
+                write!(f, "[{}]", elements.iter().map(|element| element.to_string()).collect::<Vec<_>>().join(", "))
+            }
+            Pattern::Struct(name, fields, _) => write!(
+                f,
+                "{} {{ {} }}",
+                name,
+                fields.iter().map(|(field, pattern)| format!("{}: {}", field, pattern)).collect::<Vec<_>>().join(", ")
+            ),
+        };
+    }
+}
+
+/// A single arm of a switch, matching a variant and binding its fields
+#[derive(Clone, Debug)]
+pub struct SwitchArm {
+    /// The name of the matched variant, or "_" to match every variant not matched by another arm
+    pub variant: String,
+    /// The names the variant's fields are bound to, in order
+    pub bindings: Vec<String>,
+    /// The arm's code, which jumps to the end of the switch
+    pub body: CodeBody,
+    /// The span of the arm's pattern
+    pub span: Span,
 }
 
 /// Effects that have been finalized and are ready for compilation
@@ -230,12 +310,15 @@ pub enum FinalizedEffectType {
     CreateArray(Option<FinalizedTypes>, Vec<FinalizedEffects>),
     /// Creates a float
     Float(f64),
-    /// Creates an unsigned int
-    UInt(u64),
+    /// Creates an integer literal, which is an i64 unless its context expects another integer type
+    Int(i64),
+    /// Creates an integer of the given integer type
+    TypedInt(u64, FinalizedTypes),
     /// Creates a boolean
     Bool(bool),
-    /// Creates a string
-    String(String),
+    /// Creates a string from a literal's bytes with its escapes decoded, which is compiled into a static,
+    /// null-terminated buffer so it can be passed straight to C
+    StringLiteral(Vec<u8>),
     /// Creates a character
     Char(char),
     /// Calls a virtual method, usually a downcasted trait, with the given function index, function, and generic return type (if any)
@@ -260,6 +343,45 @@ pub enum FinalizedEffectType {
     ReferenceLoad(Box<FinalizedEffects>),
     /// Stores an effect on the stack.
     StackStore(Box<FinalizedEffects>),
+    /// Creates a closure with the variables it captures by reference, its arguments, return type (void if none), and code.
+    Closure(Vec<FinalizedField>, Vec<FinalizedField>, FinalizedTypes, FinalizedCodeBody),
+    /// Calls the closure with the given arguments.
+    ClosureCall(Box<FinalizedEffects>, Vec<FinalizedEffects>),
+    /// Jumps to the label of the given enum variant's discriminant, with a discriminant and label for every variant.
+    Switch(Box<FinalizedEffects>, Vec<(u64, String)>),
+    /// Traps if the wrapped call to an internal integer add, subtract or multiply overflows, instead of wrapping.
+    OverflowCheck(Box<FinalizedEffects>),
+    /// Traps if the index passed to the wrapped call to the internal array index isn't less than the array's length.
+    BoundsCheck(Box<FinalizedEffects>),
+    /// Traps if the enum the wrapped load is loading a field from isn't the variant with the discriminant.
+    VariantCheck(Box<FinalizedEffects>, u64),
+    /// An effect that failed to verify, with its already reported error.
+    /// Lets the rest of the function be checked, but refuses to be compiled.
+    Poison(ParsingError),
+    /// A static assert depending on generics, which is checked and removed once the function is degenericed.
+    StaticAssert(ConstExpression),
+    /// A u64 depending on generics, like size_of<T>(), which is replaced by its value once the function is degenericed.
+    ConstValue(ConstExpression),
+    /// Converts a built-in number to another built-in number, with the value, its type, and the type it's converted to.
+    /// Casts of other types are calls to their numbers::Cast impl instead.
+    Cast(Box<FinalizedEffects>, FinalizedTypes, FinalizedTypes),
+}
+
+/// A static assert's condition or a value computed at compile time, which can only use values known at compile time
+#[derive(Clone, Debug)]
+pub enum ConstExpression {
+    /// An integer
+    Int(u64),
+    /// A boolean
+    Bool(bool),
+    /// The value of a const generic, like the N in Buffer<N>
+    Const(FinalizedTypes),
+    /// The size of the type in bytes, from size_of<T>()
+    SizeOf(FinalizedTypes),
+    /// The alignment of the type in bytes, from align_of<T>()
+    AlignOf(FinalizedTypes),
+    /// A built-in operator, with the name of its trait like math::Add and its arguments
+    Operation(String, Vec<ConstExpression>),
 }
 
 impl FinalizedEffectType {
@@ -267,7 +389,12 @@ impl FinalizedEffectType {
     /// This can only be called on degenericed types and as such can be sync
     pub fn get_nongeneric_return(&self, variables: &dyn VariableManager) -> Option<FinalizedTypes> {
         return match self {
-            Self::NOP | Self::Jump(_) | Self::CompareJump(_, _, _) | Self::CodeBody(_) => None,
+            Self::NOP
+            | Self::Jump(_)
+            | Self::CompareJump(_, _, _)
+            | Self::Switch(_, _)
+            | Self::CodeBody(_)
+            | Self::StaticAssert(_) => None,
             // Downcasts simply return the downcasting target.
             Self::CreateVariable(_, _, types) | Self::Downcast(_, types, _) => Some(types.clone()),
             Self::MethodCall(_, function, _, _)
@@ -295,23 +422,53 @@ impl FinalizedEffectType {
             Self::CreateStruct(_, types, _) => Some(FinalizedTypes::Reference(Box::new(types.clone()))),
             // Returns the internal constant type.
             Self::Float(_) => Some(FinalizedTypes::Struct(F64.clone())),
-            Self::UInt(_) => Some(FinalizedTypes::Struct(U64.clone())),
+            Self::Int(_) => Some(FinalizedTypes::Struct(I64.clone())),
+            Self::TypedInt(_, types) => Some(types.clone()),
+            Self::ConstValue(_) => Some(FinalizedTypes::Struct(U64.clone())),
+            // Casts return the type cast to.
+            Self::Cast(_, _, types) => Some(types.clone()),
             Self::Bool(_) => Some(FinalizedTypes::Struct(BOOL.clone())),
-            Self::String(_) => Some(FinalizedTypes::Struct(STR.clone())),
+            Self::StringLiteral(_) => Some(FinalizedTypes::Struct(STR.clone())),
             Self::Char(_) => Some(FinalizedTypes::Struct(CHAR.clone())),
-            // Stores just return their inner type.
-            Self::HeapStore(inner) | Self::StackStore(inner) | Self::Set(_, inner) => {
-                inner.types.get_nongeneric_return(variables)
-            }
+            // Stores and overflow, bounds and variant checks just return their inner type.
+            Self::HeapStore(inner)
+            | Self::StackStore(inner)
+            | Self::Set(_, inner)
+            | Self::OverflowCheck(inner)
+            | Self::BoundsCheck(inner)
+            | Self::VariantCheck(inner, _) => inner.types.get_nongeneric_return(variables),
             // References return their inner type as well.
             Self::ReferenceLoad(inner) => match inner.types.get_nongeneric_return(variables).unwrap() {
                 FinalizedTypes::Reference(inner) => Some(*inner),
                 _ => panic!("Tried to load non-reference!"),
             },
+            // Poisoned types match any type, so one error doesn't cause more.
+            Self::Poison(error) => Some(FinalizedTypes::Struct(Arc::new(FinalizedStruct::empty_of(
+                StructData::new_poisoned("$poison".to_string(), error.clone()),
+            )))),
             // Heap allocations shouldn't get return type checked, even though they have a type.
             Self::HeapAllocate(_) => panic!("Tried to get a type from a heap alloc!"),
-            // Returns the target type as an array type.
-            Self::CreateArray(types, _) => types.clone().map(|inner| FinalizedTypes::Array(Box::new(inner))),
+            // Returns the target type as an array type, with the length of the values.
+            Self::CreateArray(types, values) => types.clone().map(|inner| {
+                FinalizedTypes::Array(Box::new(inner), Some(Box::new(FinalizedTypes::Const(values.len() as u64))))
+            }),
+            // Closures are created on the heap like structs, so they return a reference to the function.
+            Self::Closure(_, arguments, returning, _) => {
+                Some(FinalizedTypes::Reference(Box::new(FinalizedTypes::Function(
+                    arguments.iter().map(|argument| argument.field_type.clone()).collect(),
+                    Box::new(returning.clone()),
+                ))))
+            }
+            // Returns the closure's return type, if it isn't void.
+            Self::ClosureCall(calling, _) => {
+                let function = calling.types.get_nongeneric_return(variables).unwrap();
+                let (_, returning) = function.as_function().unwrap();
+                if returning.is_void() {
+                    None
+                } else {
+                    Some(FinalizedTypes::Reference(Box::new(returning.clone())))
+                }
+            }
         };
     }
 
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use data::tokens::Span;
+
+use crate::async_util::{HandleWrapper, NameResolver, UnparsedType};
+use crate::program::code::{Effects, FinalizedEffects};
+use crate::program::types::FinalizedTypes;
+use crate::{ParsingError, ProcessManager, Syntax};
+
+/// A module-level constant directly after parsing, like "const LIMIT: u64 = 10;"
+#[derive(Clone, Debug)]
+pub struct UnfinalizedConstant {
+    /// The constant's full name
+    pub name: String,
+    /// The constant's declared type
+    pub types: UnparsedType,
+    /// The constant's value, which can't have side effects
+    pub value: Effects,
+    /// The constant's span
+    pub span: Span,
+}
+
+/// A constant with its value checked against its type.
+/// Constants are replaced by their value wherever they're used.
+#[derive(Clone, Debug)]
+pub struct FinalizedConstant {
+    /// The constant's full name
+    pub name: String,
+    /// The constant's type
+    pub types: FinalizedTypes,
+    /// The constant's checked value
+    pub value: FinalizedEffects,
+}
+
+impl UnfinalizedConstant {
+    /// Verifies the constant, even if it's never used, so any errors in it are reported
+    pub async fn verify(
+        handle: Arc<Mutex<HandleWrapper>>,
+        current: Arc<UnfinalizedConstant>,
+        syntax: Arc<Mutex<Syntax>>,
+        resolver: Box<dyn NameResolver>,
+        process_manager: Box<dyn ProcessManager>,
+    ) -> Result<(), ParsingError> {
+        process_manager.verify_constant(current.clone(), resolver, &syntax).await;
+        handle.lock().unwrap().finish_task(&current.name);
+        return Ok(());
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::program::syntax::Syntax;
+
+/// The names of the elements that changed between two programs, each sorted by name
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ElementDiff {
+    /// Elements only in the new program
+    pub added: Vec<String>,
+    /// Elements only in the old program
+    pub removed: Vec<String>,
+    /// Elements in both programs with a different content hash
+    pub modified: Vec<String>,
+}
+
+impl ElementDiff {
+    /// Compares the content hashes of the old and new elements
+    pub fn new(old: &HashMap<String, u64>, new: &HashMap<String, u64>) -> Self {
+        let mut diff = ElementDiff::default();
+        for (name, hash) in new {
+            match old.get(name) {
+                Some(found) if found != hash => diff.modified.push(name.clone()),
+                Some(_) => {}
+                None => diff.added.push(name.clone()),
+            }
+        }
+        for name in old.keys() {
+            if !new.contains_key(name) {
+                diff.removed.push(name.clone());
+            }
+        }
+        diff.added.sort();
+        diff.removed.sort();
+        diff.modified.sort();
+        return diff;
+    }
+
+    /// Checks if no elements changed
+    pub fn is_empty(&self) -> bool {
+        return self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty();
+    }
+}
+
+/// The structs and functions that changed between two parses of a program, so only those have to be checked again
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SyntaxDiff {
+    /// The changed structs, traits, and enums
+    pub structures: ElementDiff,
+    /// The changed functions
+    pub functions: ElementDiff,
+}
+
+impl SyntaxDiff {
+    /// Diffs the old program against the new one.
+    /// Both should be done checking, function bodies are only hashed once they're checked.
+    pub fn new(old: &Syntax, new: &Syntax) -> Self {
+        return SyntaxDiff {
+            structures: ElementDiff::new(&struct_hashes(old), &struct_hashes(new)),
+            functions: ElementDiff::new(&function_hashes(old), &function_hashes(new)),
+        };
+    }
+
+    /// Checks if nothing changed
+    pub fn is_empty(&self) -> bool {
+        return self.structures.is_empty() && self.functions.is_empty();
+    }
+}
+
+/// Hashes every struct's modifiers, attributes, fields, generics, traits, variants, and trait functions by name.
+/// Generic instances and poisoned placeholders have a $ in their name and are left out, they aren't in the source.
+pub fn struct_hashes(syntax: &Syntax) -> HashMap<String, u64> {
+    let mut hashes = HashMap::default();
+    for (name, data) in &syntax.structures.types {
+        if name.contains('$') {
+            continue;
+        }
+        let functions = data.functions.iter().map(|function| &function.name).collect::<Vec<_>>();
+        let content = format!(
+            "{} {:?} {:?} {:?} {:?}",
+            data.modifiers,
+            data.attributes,
+            functions,
+            data.variants,
+            syntax.structures.data.get(data)
+        );
+        hashes.insert(name.clone(), hash(&without_spans(content)));
+    }
+    return hashes;
+}
+
+/// Hashes every function's signature and checked body by name, or only its signature if it hasn't been checked
+pub fn function_hashes(syntax: &Syntax) -> HashMap<String, u64> {
+    let mut hashes = HashMap::default();
+    for (name, data) in &syntax.functions.types {
+        if name.contains('$') {
+            continue;
+        }
+        let content = match syntax.generics.get(name) {
+            Some(function) => relabel(without_spans(format!("{:?}", function.value())), &function.code.label),
+            None => match syntax.functions.data.get(data) {
+                Some(codeless) => without_spans(format!("{:?}", codeless)),
+                None => without_spans(format!("{:?}", data)),
+            },
+        };
+        hashes.insert(name.clone(), hash(&content));
+    }
+    return hashes;
+}
+
+/// Hashes the content
+fn hash(content: &String) -> u64 {
+    let mut hasher = DefaultHasher::default();
+    content.hash(&mut hasher);
+    return hasher.finish();
+}
+
+/// Removes the spans from the debug output, so code moving around in its file doesn't change its hash.
+/// Spans can hold the span they were expanded from, so the braces are matched to remove both.
+fn without_spans(mut content: String) -> String {
+    while let Some(start) = content.find("Span {") {
+        let mut depth = 0;
+        let mut end = None;
+        for (index, character) in content[start..].char_indices() {
+            match character {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(start + index + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        match end {
+            Some(end) => content.replace_range(start..end, ""),
+            None => break,
+        }
+    }
+    return content;
+}
+
+/// Labels count up through the whole file, so they change whenever code above them does.
+/// This numbers every label in the debug output by how far it is from the function's top label instead.
+fn relabel(content: String, top: &String) -> String {
+    let top = match top.parse::<u64>() {
+        Ok(top) => top,
+        Err(_) => return content,
+    };
+    let mut output = String::with_capacity(content.len());
+    let mut quoted: Option<String> = None;
+    let mut escaped = false;
+    for character in content.chars() {
+        match &mut quoted {
+            None => {
+                if character == '"' {
+                    quoted = Some(String::default());
+                }
+                output.push(character);
+            }
+            Some(inner) => {
+                if !escaped && character == '"' {
+                    output.push_str(&relabel_string(inner, top));
+                    output.push(character);
+                    quoted = None;
+                } else {
+                    escaped = !escaped && character == '\\';
+                    inner.push(character);
+                }
+            }
+        }
+    }
+    if let Some(inner) = quoted {
+        output.push_str(&inner);
+    }
+    return output;
+}
+
+/// Renumbers a label, like "12" or "12end", from the top label. Strings that aren't labels are kept.
+fn relabel_string(string: &str, top: u64) -> String {
+    let digits = string.chars().take_while(|character| character.is_ascii_digit()).count();
+    if digits == 0 || !string[digits..].chars().all(|character| character.is_ascii_alphabetic()) {
+        return string.to_string();
+    }
+    return match string[..digits].parse::<u64>() {
+        Ok(label) if label <= top => format!("{}{}", top - label, &string[digits..]),
+        _ => string.to_string(),
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::program::diff::{hash, relabel, without_spans, ElementDiff};
+
+    /// Checks elements are sorted into added, removed, and modified by their hashes
+    #[test]
+    pub fn test_element_diff() {
+        let hashes = |elements: &[(&str, u64)]| {
+            elements.iter().map(|(name, hash)| (name.to_string(), *hash)).collect::<HashMap<_, _>>()
+        };
+        let old = hashes(&[("same", 1), ("changed", 2), ("removed", 3), ("also_removed", 4)]);
+        let new = hashes(&[("same", 1), ("changed", 5), ("added", 6)]);
+
+        let diff = ElementDiff::new(&old, &new);
+        assert_eq!(diff.added, vec!["added"]);
+        assert_eq!(diff.removed, vec!["also_removed", "removed"]);
+        assert_eq!(diff.modified, vec!["changed"]);
+        assert!(ElementDiff::new(&old, &old).is_empty());
+    }
+
+    /// Checks spans are removed along with the spans they were expanded from
+    #[test]
+    pub fn test_without_spans() {
+        let content = "Effect { span: Span { file: 1, start: 2, end: 3, expansion: Some(Span { file: 1, start: 0, \
+            end: 1, expansion: None }) }, value: 1 }";
+        assert_eq!(without_spans(content.to_string()), "Effect { span: , value: 1 }");
+        assert_eq!(without_spans("Span { file: 1".to_string()), "Span { file: 1");
+    }
+
+    /// Checks labels are numbered from the function's top label, so code moving doesn't change the hash
+    #[test]
+    pub fn test_relabel() {
+        // The escaped quote's string isn't a label
+        let code = |top: u64| format!("Jump(\"{}\"), Jump(\"{}end\"), Load(\"x\\\"1\")", top, top - 2);
+        assert_eq!(relabel(code(12), &"12".to_string()), "Jump(\"0\"), Jump(\"2end\"), Load(\"x\\\"1\")");
+        assert_eq!(hash(&relabel(code(12), &"12".to_string())), hash(&relabel(code(40), &"40".to_string())));
+        // Labels past the top aren't in the function
+        assert_eq!(relabel("Jump(\"13\")".to_string(), &"12".to_string()), "Jump(\"13\")");
+        assert_eq!(relabel(code(12), &"top".to_string()), code(12));
+    }
+}
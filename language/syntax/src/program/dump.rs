@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use crate::program::code::{ExpressionType, FinalizedEffectType, FinalizedEffects, FinalizedField};
+use crate::program::function::{FinalizedCodeBody, FinalizedFunction};
+use crate::program::r#struct::{BOOL, CHAR, F64, I64, STR, U64};
+use crate::program::types::FinalizedTypes;
+use crate::program::walk::inner_effects;
+use crate::{SimpleVariableManager, VariableManager};
+
+/// Prints a checked function's code as a tree of effects, one per line with its type and span,
+/// so the checker's output can be compared against what it should have lowered the code to.
+pub fn dump_ir(function: &FinalizedFunction) -> String {
+    let arguments =
+        function.fields.iter().map(|field| format!("{}: {}", field.field.name, field.field.field_type)).collect::<Vec<_>>();
+    let mut output = format!("fn {}({})", function.data.name, arguments.join(", "));
+    if let Some(returning) = &function.return_type {
+        let _ = write!(output, " -> {}", returning);
+    }
+    output.push('\n');
+
+    let mut variables = SimpleVariableManager::for_final_function(function);
+    dump_body(&function.code, 0, &mut variables, &mut output);
+    return output;
+}
+
+/// Prints a code body's label, then each of its lines indented under it
+fn dump_body(code: &FinalizedCodeBody, depth: usize, variables: &mut SimpleVariableManager, output: &mut String) {
+    let _ = writeln!(output, "{}block {}", "  ".repeat(depth), code.label);
+    for expression in &code.expressions {
+        let prefix = match expression.expression_type {
+            ExpressionType::Return(_) => "return ",
+            ExpressionType::Break => "break ",
+            ExpressionType::Line => "",
+        };
+        dump_effect(&expression.effect, prefix, depth + 1, variables, output);
+    }
+}
+
+/// Prints the effect and, indented under it, the effects inside of it
+fn dump_effect(
+    effect: &FinalizedEffects,
+    prefix: &str,
+    depth: usize,
+    variables: &mut SimpleVariableManager,
+    output: &mut String,
+) {
+    let (description, types) = describe(&effect.types, variables);
+    let _ = write!(output, "{}{}{}", "  ".repeat(depth), prefix, description);
+    if let Some(types) = types {
+        let _ = write!(output, ": {}", types);
+    }
+    let _ = writeln!(output, " @{}..{}", effect.span.start, effect.span.end);
+
+    match &effect.types {
+        FinalizedEffectType::CodeBody(body) => dump_body(body, depth + 1, variables, output),
+        FinalizedEffectType::Closure(_, arguments, _, body) => {
+            for argument in arguments {
+                variables.variables.insert(argument.name.clone(), argument.field_type.clone());
+            }
+            dump_body(body, depth + 1, variables, output);
+        }
+        FinalizedEffectType::CreateVariable(name, value, types) => {
+            dump_effect(value, "", depth + 1, variables, output);
+            variables.variables.insert(name.clone(), types.clone());
+        }
+        _ => {
+            for inner in inner_effects(effect) {
+                dump_effect(inner, "", depth + 1, variables, output);
+            }
+        }
+    }
+}
+
+/// Describes a single effect without the effects inside of it, along with its type if it has one.
+/// Only types stored in the effect are shown, so dumping never has to resolve anything.
+fn describe(effect: &FinalizedEffectType, variables: &SimpleVariableManager) -> (String, Option<FinalizedTypes>) {
+    return match effect {
+        FinalizedEffectType::NOP => ("nop".to_string(), None),
+        FinalizedEffectType::CreateVariable(name, _, types) => (format!("let {}", name), Some(types.clone())),
+        FinalizedEffectType::Jump(label) => (format!("jump {}", label), None),
+        FinalizedEffectType::CompareJump(_, success, failure) => {
+            (format!("compare_jump {} else {}", success, failure), None)
+        }
+        FinalizedEffectType::CodeBody(_) => ("body".to_string(), None),
+        FinalizedEffectType::MethodCall(_, function, _, _) => {
+            (format!("call {}", function.data.name), function.return_type.clone())
+        }
+        FinalizedEffectType::GenericMethodCall(function, types, _) => {
+            (format!("generic_call {} of {}", function.data.name, types), function.return_type.clone())
+        }
+        FinalizedEffectType::VirtualCall(index, function, _, _) => {
+            (format!("virtual_call {} slot {}", function.data.name, index), function.return_type.clone())
+        }
+        FinalizedEffectType::GenericVirtualCall(index, target, function, _, _) => (
+            format!("generic_virtual_call {} on {} slot {}", function.data.name, target.name, index),
+            function.return_type.clone(),
+        ),
+        FinalizedEffectType::Set(_, _) => ("set".to_string(), None),
+        FinalizedEffectType::LoadVariable(name) => (format!("load {}", name), variables.get_variable(name)),
+        FinalizedEffectType::Load(_, field, types) => (format!("load_field {} of {}", field, types), None),
+        FinalizedEffectType::CreateStruct(_, types, _) => ("new".to_string(), Some(types.clone())),
+        FinalizedEffectType::CreateArray(_, values) => (format!("array of {}", values.len()), None),
+        FinalizedEffectType::Float(value) => (format!("float {}", value), Some(FinalizedTypes::Struct(F64.clone()))),
+        FinalizedEffectType::Int(value) => (format!("int {}", value), Some(FinalizedTypes::Struct(I64.clone()))),
+        FinalizedEffectType::TypedInt(value, types) => (format!("int {}", value), Some(types.clone())),
+        FinalizedEffectType::Bool(value) => (format!("bool {}", value), Some(FinalizedTypes::Struct(BOOL.clone()))),
+        FinalizedEffectType::StringLiteral(bytes) => {
+            (format!("string {:?}", String::from_utf8_lossy(bytes)), Some(FinalizedTypes::Struct(STR.clone())))
+        }
+        FinalizedEffectType::Char(value) => (format!("char {:?}", value), Some(FinalizedTypes::Struct(CHAR.clone()))),
+        FinalizedEffectType::Downcast(_, types, _) => ("downcast".to_string(), Some(types.clone())),
+        FinalizedEffectType::HeapStore(_) => ("heap_store".to_string(), None),
+        FinalizedEffectType::HeapAllocate(types) => ("heap_allocate".to_string(), Some(types.clone())),
+        FinalizedEffectType::ReferenceLoad(_) => ("reference_load".to_string(), None),
+        FinalizedEffectType::StackStore(_) => ("stack_store".to_string(), None),
+        FinalizedEffectType::Closure(captures, arguments, returning, _) => {
+            (format!("closure({}) capturing [{}]", fields(arguments), fields(captures)), Some(returning.clone()))
+        }
+        FinalizedEffectType::ClosureCall(_, _) => ("closure_call".to_string(), None),
+        FinalizedEffectType::Switch(_, labels) => {
+            let labels = labels.iter().map(|(value, label)| format!("{} => {}", value, label)).collect::<Vec<_>>();
+            (format!("switch {}", labels.join(", ")), None)
+        }
+        FinalizedEffectType::OverflowCheck(_) => ("overflow_check".to_string(), None),
+        FinalizedEffectType::BoundsCheck(_) => ("bounds_check".to_string(), None),
+        FinalizedEffectType::VariantCheck(_, discriminant) => (format!("variant_check {}", discriminant), None),
+        FinalizedEffectType::Poison(error) => (format!("poison \"{}\"", error.message), None),
+        FinalizedEffectType::StaticAssert(condition) => (format!("static_assert {:?}", condition), None),
+        FinalizedEffectType::ConstValue(value) => (format!("const {:?}", value), Some(FinalizedTypes::Struct(U64.clone()))),
+        FinalizedEffectType::Cast(_, from, to) => (format!("cast from {}", from), Some(to.clone())),
+    };
+}
+
+/// Lists the names and types of the fields
+fn fields(fields: &[FinalizedField]) -> String {
+    return fields.iter().map(|field| format!("{}: {}", field.name, field.field_type)).collect::<Vec<_>>().join(", ");
+}
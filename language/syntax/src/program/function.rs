@@ -30,20 +30,96 @@ pub struct FunctionData {
     pub span: Span,
     /// The function's errors if it has been poison'd
     pub poisoned: Vec<ParsingError>,
+    /// The calling convention of an extern function, set by extern "name"
+    pub abi: Abi,
+    /// Whether the compiler should inline the function, set by #[inline] or #[noinline]
+    pub inline: Inline,
+    /// Whether this is an instance of a generic function, with every generic already solidified
+    pub instantiated: bool,
 }
 
 impl FunctionData {
     /// Creates a new function
     pub fn new(attributes: Vec<Attribute>, modifiers: u8, name: String, span: Span) -> Self {
-        return Self { attributes, modifiers, name, span, poisoned: Vec::default() };
+        return Self {
+            attributes,
+            modifiers,
+            name,
+            span,
+            poisoned: Vec::default(),
+            abi: Abi::default(),
+            inline: Inline::default(),
+            instantiated: false,
+        };
     }
 
     /// Creates an empty function data that errored while parsing.
     pub fn poisoned(name: String, error: ParsingError) -> Self {
-        return Self { attributes: Vec::default(), modifiers: 0, name, span: error.span.clone(), poisoned: vec![error] };
+        return Self {
+            attributes: Vec::default(),
+            modifiers: 0,
+            name,
+            span: error.span.clone(),
+            poisoned: vec![error],
+            abi: Abi::default(),
+            inline: Inline::default(),
+            instantiated: false,
+        };
     }
 }
 
+/// The calling convention an extern function is called with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Abi {
+    /// The C calling convention, used when no ABI is given
+    #[default]
+    C,
+    /// The platform's system calling convention, stdcall on 32-bit Windows and C everywhere else
+    System,
+    /// The 32-bit x86 stdcall calling convention
+    StdCall,
+    /// The 32-bit x86 fastcall calling convention
+    FastCall,
+    /// The 64-bit Windows calling convention
+    Win64,
+    /// The 64-bit System V calling convention
+    SysV64,
+}
+
+impl Abi {
+    /// Every ABI, in the same order as their names
+    pub const ALL: [Abi; 6] = [Abi::C, Abi::System, Abi::StdCall, Abi::FastCall, Abi::Win64, Abi::SysV64];
+
+    /// Gets the ABI from the name it's written with, like "C" in extern "C"
+    pub fn from_name(name: &str) -> Option<Abi> {
+        return Abi::ALL.into_iter().find(|abi| abi.name() == name);
+    }
+
+    /// The name the ABI is written with
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Abi::C => "C",
+            Abi::System => "system",
+            Abi::StdCall => "stdcall",
+            Abi::FastCall => "fastcall",
+            Abi::Win64 => "win64",
+            Abi::SysV64 => "sysv64",
+        };
+    }
+}
+
+/// How a function asks to be inlined into its callers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Inline {
+    /// The backend decides, used when neither attribute is given
+    #[default]
+    Auto,
+    /// Always inlined, set by #[inline]
+    Always,
+    /// Never inlined, set by #[noinline]
+    Never,
+}
+
 /// Allows generic access to FunctionData.
 #[async_trait]
 impl TopElement for FunctionData {
@@ -93,7 +169,7 @@ impl TopElement for FunctionData {
         let name = current.data.name.clone();
 
         // Get the codeless finalized function and the code from the function.
-        let (codeless_function, code) = process_manager.verify_func(current, &syntax).await;
+        let (codeless_function, code) = process_manager.verify_func(current, resolver.boxed_clone(), &syntax).await;
 
         // Finalize the code and combine it with the codeless finalized function.
         let finalized_function = process_manager.verify_code(codeless_function.clone(), code, resolver, &syntax).await;
@@ -115,18 +191,30 @@ impl TopElement for FunctionData {
 pub struct UnfinalizedFunction {
     /// The ordered generics of the function
     pub generics: IndexMap<String, Vec<ParsingFuture<Types>>>,
+    /// Where each generic the function declares itself is, which leaves out the generics of its impl
+    pub generic_spans: IndexMap<String, Span>,
     /// The function's fields
     pub fields: Vec<ParsingFuture<MemberField>>,
     /// The function's code
     pub code: CodeBody,
     /// The function's return type
     pub return_type: Option<ParsingFuture<Types>>,
+    /// Traits the type the method is called on must implement, from "where Self: Trait"
+    pub self_bounds: Vec<ParsingFuture<Types>>,
     /// The function's data
     pub data: Arc<FunctionData>,
     /// The function's parent
     pub parent: Option<ParsingFuture<Types>>,
 }
 
+/// A top function the parser finished, sent to be checked while the parser keeps going
+pub struct ParsedFunction {
+    /// The function
+    pub function: UnfinalizedFunction,
+    /// Resolves the names in the function with the imports of its file
+    pub resolver: Box<dyn NameResolver>,
+}
+
 /// Gives generic access to the function data.
 impl DataType<FunctionData> for UnfinalizedFunction {
     fn data(&self) -> &Arc<FunctionData> {
@@ -146,6 +234,8 @@ pub struct CodelessFinalizedFunction {
     pub arguments: Vec<FinalizedMemberField>,
     /// The function's return type
     pub return_type: Option<FinalizedTypes>,
+    /// Traits the type the method is called on must implement
+    pub self_bounds: Vec<FinalizedTypes>,
     /// The function's data
     pub data: Arc<FunctionData>,
     /// The parent structure
@@ -153,6 +243,11 @@ pub struct CodelessFinalizedFunction {
 }
 
 impl CodelessFinalizedFunction {
+    /// Checks if the function is a method taking self, so it can only be called on a value
+    pub fn takes_receiver(&self) -> bool {
+        return self.parent.is_some() && self.arguments.first().is_some_and(|argument| argument.field.name == "self");
+    }
+
     /// Combines the CodelessFinalizedFunction with a FinalizedCodeBody to get a FinalizedFunction.
     pub fn add_code(self, code: FinalizedCodeBody) -> FinalizedFunction {
         return FinalizedFunction {
@@ -187,6 +282,7 @@ impl FinalizedFunction {
             generics: self.generics.clone(),
             arguments: self.fields.clone(),
             return_type: self.return_type.clone(),
+            self_bounds: Vec::default(),
             data: self.data.clone(),
             parent: None,
         };
@@ -1,10 +1,22 @@
+/// Helpers for building code without the parser
+pub mod builder;
 /// Types used to represent code
 pub mod code;
+/// Types used to represent module-level constants
+pub mod constant;
+/// Finds what changed between two parses of a program
+pub mod diff;
+/// Prints checked code as text for debugging the checker
+pub mod dump;
 /// Types used to represent functions
 pub mod function;
+/// Maps compiled code back to its source
+pub mod source_map;
 /// Types used to represent structs
 pub mod r#struct;
 /// The syntax type, used to represent the entire program
 pub mod syntax;
 /// Types used to represent types
 pub mod types;
+/// Walks over the effects in checked code
+pub mod walk;
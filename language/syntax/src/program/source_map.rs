@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use data::tokens::Span;
+
+/// Maps the IR a compiler generated back to the spans of the effects it was compiled from.
+/// Ids are the counter the compiler names each generated function's values and blocks with, which only goes up
+/// while it's compiled. They aren't offsets into the machine code.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    /// Each generated function's ids, and the span the IR named from each id on came from
+    functions: HashMap<String, Vec<(u64, Span)>>,
+    /// The ids of the traps in each generated function, which their blocks are named after
+    traps: HashMap<String, Vec<u64>>,
+    /// The symbol of each generated vtable, and whether its entries are offsets from it instead of addresses
+    vtables: HashMap<String, bool>,
+    /// The calling convention number each extern function was declared with
+    call_conventions: HashMap<String, u32>,
+}
+
+impl SourceMap {
+    /// Marks the generated function's IR from the id on as coming from the span
+    pub fn add(&mut self, function: &str, id: u64, span: Span) {
+        self.functions.entry(function.to_string()).or_default().push((id, span));
+    }
+
+    /// Adds a trap with the id to the generated function, which comes from the span.
+    /// The trap's block is named after the id, like overflow4 or bounds4.
+    pub fn add_trap(&mut self, function: &str, id: u64, span: Span) {
+        self.add(function, id, span);
+        self.traps.entry(function.to_string()).or_default().push(id);
+    }
+
+    /// Gets the span of the generated function's IR with the id, which is the last span added at or before it
+    pub fn resolve(&self, function: &str, id: u64) -> Option<&Span> {
+        let spans = self.functions.get(function)?;
+        let found = spans.partition_point(|(start, _)| *start <= id);
+        return spans[..found].last().map(|(_, span)| span);
+    }
+
+    /// The ids of every trap in the generated function
+    pub fn traps(&self, function: &str) -> &[u64] {
+        return self.traps.get(function).map_or(&[], |traps| traps.as_slice());
+    }
+
+    /// The name of every generated function with IR in the map
+    pub fn functions(&self) -> impl Iterator<Item = &String> {
+        return self.functions.keys();
+    }
+
+    /// Records a generated vtable, and whether its entries are offsets from it
+    pub fn add_vtable(&mut self, symbol: &str, relative: bool) {
+        self.vtables.insert(symbol.to_string(), relative);
+    }
+
+    /// Whether the vtable's entries are offsets from it, or None if it wasn't generated
+    pub fn vtable(&self, symbol: &str) -> Option<bool> {
+        return self.vtables.get(symbol).copied();
+    }
+
+    /// Records the calling convention an extern function was declared with
+    pub fn add_call_convention(&mut self, function: &str, convention: u32) {
+        self.call_conventions.insert(function.to_string(), convention);
+    }
+
+    /// The calling convention the extern function was declared with, or None if it wasn't declared
+    pub fn call_convention(&self, function: &str) -> Option<u32> {
+        return self.call_conventions.get(function).copied();
+    }
+}
@@ -1,8 +1,8 @@
 use std::fmt::{Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::sync::atomic::AtomicU64;
-use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::{Arc, OnceLock};
 
 use chalk_ir::{AdtId, Binders, GenericArg, Substitution, TraitId, Ty, TyKind};
 use chalk_solve::rust_ir::{AdtDatum, AdtDatumBound, AdtFlags, AdtKind, TraitDatum, TraitDatumBound, TraitFlags};
@@ -12,10 +12,11 @@ use lazy_static::lazy_static;
 use async_trait::async_trait;
 use data::tokens::Span;
 
-use crate::async_util::{HandleWrapper, NameResolver};
+use crate::async_util::{HandleWrapper, NameResolver, UnparsedType};
 use crate::chalk_interner::ChalkIr;
+use crate::errors::{ErrorSource, ParsingMessage};
 use crate::program::code::{FinalizedMemberField, MemberField};
-use crate::program::function::{FunctionData, UnfinalizedFunction};
+use crate::program::function::{display_parenless, FunctionData, UnfinalizedFunction};
 use crate::program::types::{FinalizedTypes, Types};
 use crate::top_element_manager::TopElementManager;
 use crate::{is_modifier, DataType, Modifier, ParsingFuture, ProcessManager, Syntax, TopElement};
@@ -72,6 +73,76 @@ pub fn get_internal(name: String) -> Arc<StructData> {
     };
 }
 
+/// The name of a generic struct once its generics are solidified, like Buffer$8.
+pub fn instance_name(base: &str, generics: &Vec<FinalizedTypes>) -> String {
+    return format!("{}${}", base, display_parenless(generics, "_"));
+}
+
+/// Gets the internal integer type from its name, or None if it isn't an integer
+pub fn get_integer(name: &str) -> Option<Arc<FinalizedStruct>> {
+    return match name {
+        "i64" => Some(I64.clone()),
+        "i32" => Some(I32.clone()),
+        "i16" => Some(I16.clone()),
+        "i8" => Some(I8.clone()),
+        "u64" => Some(U64.clone()),
+        "u32" => Some(U32.clone()),
+        "u16" => Some(U16.clone()),
+        "u8" => Some(U8.clone()),
+        _ => None,
+    };
+}
+
+/// The integer types an untyped integer literal can be, in the order they're tried when its default i64 doesn't fit
+pub fn literal_integers() -> [Arc<FinalizedStruct>; 8] {
+    return [I64.clone(), U64.clone(), I32.clone(), U32.clone(), I16.clone(), U16.clone(), I8.clone(), U8.clone()];
+}
+
+/// Gets the largest value of the internal integer type from its name, or None if it isn't an integer
+pub fn integer_max(name: &str) -> Option<u64> {
+    return match name {
+        "i64" => Some(i64::MAX as u64),
+        "i32" => Some(i32::MAX as u64),
+        "i16" => Some(i16::MAX as u64),
+        "i8" => Some(i8::MAX as u64),
+        "u64" => Some(u64::MAX),
+        "u32" => Some(u32::MAX as u64),
+        "u16" => Some(u16::MAX as u64),
+        "u8" => Some(u8::MAX as u64),
+        _ => None,
+    };
+}
+
+/// The name of the field storing which variant an enum holds, which comes before every variant's fields
+pub const VARIANT_FIELD: &str = "$variant";
+/// The core library's Option enum, a value that might be None
+pub const OPTION: &str = "option::Option";
+/// The core library's reference counted pointer, which passes the methods it doesn't have to its value
+pub const RC: &str = "mem::Rc";
+/// The variant of an Option holding a value
+pub const SOME_VARIANT: &str = "Some";
+
+/// A single variant of an enum and the names of its fields.
+/// The fields themselves are stored in the enum's struct fields, see EnumVariant::field_name.
+#[derive(Clone, Debug)]
+pub struct EnumVariant {
+    /// The variant's name
+    pub name: String,
+    /// The names of the variant's fields, in order
+    pub fields: Vec<String>,
+    /// The discriminant written after the variant, like 4 in Empty = 4, which is evaluated when the enum is finalized
+    pub discriminant: Option<String>,
+    /// The variant's span
+    pub span: Span,
+}
+
+impl EnumVariant {
+    /// The name of the enum's struct field storing the given field of this variant
+    pub fn field_name(&self, field: &str) -> String {
+        return format!("{}.{}", self.name, field);
+    }
+}
+
 /// The chalk data of the two different types
 #[derive(Clone, Debug)]
 pub enum ChalkData {
@@ -116,6 +187,10 @@ pub struct StructData {
     pub attributes: Vec<Attribute>,
     /// The program's functions, if it's a trait
     pub functions: Vec<Arc<FunctionData>>,
+    /// The program's variants, if it's an enum
+    pub variants: Vec<EnumVariant>,
+    /// The default of each of a trait's generics, if any of them have one
+    pub generic_defaults: Vec<Option<UnparsedType>>,
     /// The program's errors
     pub poisoned: Vec<ParsingError>,
 }
@@ -124,10 +199,16 @@ pub struct StructData {
 pub struct UnfinalizedStruct {
     /// The program's generics
     pub generics: IndexMap<String, Vec<ParsingFuture<Types>>>,
+    /// Where each of the generics is declared
+    pub generic_spans: IndexMap<String, Span>,
     /// The program's fields
     pub fields: Vec<ParsingFuture<MemberField>>,
     /// The program's functions
     pub functions: Vec<UnfinalizedFunction>,
+    /// The traits this trait inherits from
+    pub traits: Vec<ParsingFuture<Types>>,
+    /// The associated constants this trait declares and their types, which every impl of it has to define
+    pub constants: Vec<(String, ParsingFuture<Types>)>,
     /// The program's data
     pub data: Arc<StructData>,
 }
@@ -143,15 +224,38 @@ impl DataType<StructData> for UnfinalizedStruct {
 pub struct FinalizedStruct {
     /// The program's generics
     pub generics: IndexMap<String, Vec<FinalizedTypes>>,
+    /// The arguments given to the generics of the struct this is an instance of, in the order of its generics
+    pub arguments: Vec<FinalizedTypes>,
     /// The program's fields
     pub fields: Vec<FinalizedMemberField>,
+    /// The traits this trait inherits from
+    pub traits: Vec<FinalizedTypes>,
+    /// The value stored in the variant field for each variant, if it's an enum
+    pub discriminants: Vec<u64>,
+    /// The associated constants this trait declares and their types
+    pub constants: IndexMap<String, FinalizedTypes>,
     /// The program's data
     pub data: Arc<StructData>,
+    /// The finished struct, if this is a stub standing in for it
+    pub stub: StructStub,
+}
+
+/// Set to the finished struct once a stub's struct is finished. Structs that contain themselves behind a reference,
+/// an array or a generic use a stub for themselves in their fields, because they can't wait for themselves to finish.
+#[derive(Clone, Default)]
+pub struct StructStub(Arc<OnceLock<Arc<FinalizedStruct>>>);
+
+impl Debug for StructStub {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // The finished struct contains the stub, so printing it would never end
+        return write!(f, "StructStub({})", self.0.get().is_some());
+    }
 }
 
 impl Hash for FinalizedStruct {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.data.name.hash(state);
+        // Degenericed structs are equal to their base struct, so they have to hash the same
+        self.data.name.split('$').next().unwrap().hash(state);
     }
 }
 
@@ -169,7 +273,7 @@ impl PartialEq for StructData {
 
 impl PartialEq for FinalizedStruct {
     fn eq(&self, other: &Self) -> bool {
-        return self.data.name.splitn(2, '$').next().unwrap() == other.data.name.splitn(2, '$').next().unwrap();
+        return self.data.name.split('$').next().unwrap() == other.data.name.split('$').next().unwrap();
     }
 }
 
@@ -187,6 +291,8 @@ impl StructData {
             name,
             span: Span::default(),
             functions: Vec::default(),
+            variants: Vec::default(),
+            generic_defaults: Vec::default(),
             poisoned: Vec::default(),
         };
     }
@@ -209,10 +315,37 @@ impl StructData {
             name,
             span,
             functions,
+            variants: Vec::default(),
+            generic_defaults: Vec::default(),
             poisoned: Vec::default(),
         };
     }
 
+    /// Gets the vtable slot of one of the trait's methods by name, which is where the trait declares it.
+    /// Slots only depend on the trait, so the checker and compiler agree regardless of how an impl orders its methods.
+    pub fn vtable_slot(&self, method: &str) -> Option<usize> {
+        // Degenericed methods have their generics after a $
+        let method = method.split("::").last().unwrap().split('$').next().unwrap();
+        return self.functions.iter().position(|function| function.name.split("::").last().unwrap() == method);
+    }
+
+    /// The symbol of the trait's vtable for the implementor. It's named after both, so every module that uses the
+    /// vtable refers to the same one.
+    pub fn vtable_symbol(&self, implementor: &StructData) -> String {
+        return format!("{}_{}_vtable", implementor.name, self.name);
+    }
+
+    /// Orders the functions implementing the trait by their vtable slot.
+    /// Functions that aren't one of the trait's methods don't get a slot, so they can't shift the others.
+    pub fn vtable_order<T: Clone>(&self, functions: &[T], name: impl Fn(&T) -> &str) -> Vec<T> {
+        let mut slots = functions
+            .iter()
+            .filter_map(|function| self.vtable_slot(name(function)).map(|slot| (slot, function.clone())))
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|(slot, _)| *slot);
+        return slots.into_iter().map(|(_, function)| function).collect();
+    }
+
     /// Sets the internal chalk data, used to make sure all ids are unique and incremental in an async environment
     pub fn get_chalk_data(id: u64, modifiers: u8) -> ChalkData {
         let temp: &[GenericArg<ChalkIr>] = &[];
@@ -249,6 +382,11 @@ impl StructData {
         };
     }
 
+    /// Gets the index and variant with the given name, if this is an enum with that variant
+    pub fn get_variant(&self, name: &str) -> Option<(usize, &EnumVariant)> {
+        return self.variants.iter().enumerate().find(|(_, variant)| variant.name == name);
+    }
+
     /// Creates a new poison'd struct data
     pub fn new_poisoned(name: String, error: ParsingError) -> Self {
         let mut output = Self::new(Vec::default(), Vec::default(), 0, error.span.clone(), name);
@@ -260,7 +398,84 @@ impl StructData {
 impl FinalizedStruct {
     /// Creates an empty struct from the data, usually for internal structs
     pub fn empty_of(data: StructData) -> Self {
-        return Self { generics: IndexMap::default(), fields: Vec::default(), data: Arc::new(data) };
+        return Self {
+            generics: IndexMap::default(),
+            arguments: Vec::default(),
+            fields: Vec::default(),
+            traits: Vec::default(),
+            discriminants: Vec::default(),
+            constants: IndexMap::default(),
+            data: Arc::new(data),
+            stub: StructStub::default(),
+        };
+    }
+
+    /// Creates a stub for the struct, which stands in for it in fields until it's finished
+    pub fn stub(data: Arc<StructData>) -> Self {
+        return Self {
+            generics: IndexMap::default(),
+            arguments: Vec::default(),
+            fields: Vec::default(),
+            traits: Vec::default(),
+            discriminants: Vec::default(),
+            constants: IndexMap::default(),
+            data,
+            stub: StructStub::default(),
+        };
+    }
+
+    /// Gets the finished struct if this is a finished stub, otherwise the struct itself
+    pub fn finished(self: &Arc<Self>) -> &Arc<Self> {
+        return self.stub.0.get().unwrap_or(self);
+    }
+
+    /// Sets the finished struct the stub stands in for
+    pub fn finish_stub(&self, finished: Arc<FinalizedStruct>) {
+        let _ = self.stub.0.set(finished);
+    }
+
+    /// The trait's vtable layout, as each slot's method and the trait declaring it.
+    /// The trait's own methods come first so their slots match StructData::vtable_slot, then each supertrait's layout.
+    pub fn vtable_layout(&self) -> Vec<(Arc<StructData>, Arc<FunctionData>)> {
+        let mut layout = Vec::default();
+        self.add_vtable_slots(&mut layout);
+        return layout;
+    }
+
+    /// Adds the slots of this trait and its supertraits, skipping a supertrait inherited through two paths
+    fn add_vtable_slots(&self, layout: &mut Vec<(Arc<StructData>, Arc<FunctionData>)>) {
+        if layout.iter().any(|(declaring, _)| declaring == &self.data) {
+            return;
+        }
+        for function in &self.data.functions {
+            layout.push((self.data.clone(), function.clone()));
+        }
+        for supertrait in &self.traits {
+            if let Some(supertrait) = supertrait.inner_struct_safe() {
+                supertrait.add_vtable_slots(layout);
+            }
+        }
+    }
+
+    /// Finds a method's slot in the trait's vtable by name, and the trait declaring it, which may be a supertrait
+    pub fn virtual_method(&self, method: &str) -> Option<(usize, Arc<StructData>)> {
+        // Degenericed methods have their generics after a $
+        let method = method.split("::").last().unwrap().split('$').next().unwrap();
+        return self
+            .vtable_layout()
+            .into_iter()
+            .enumerate()
+            .find(|(_, (_, function))| function.name.split("::").last().unwrap() == method)
+            .map(|(slot, (declaring, _))| (slot, declaring));
+    }
+
+    /// Checks if this trait inherits from the other trait, directly or through its supertraits
+    pub fn extends(&self, other: &FinalizedStruct) -> bool {
+        return self.traits.iter().any(|supertrait| {
+            supertrait
+                .inner_struct_safe()
+                .is_some_and(|supertrait| supertrait.data == other.data || supertrait.extends(other))
+        });
     }
 }
 
@@ -315,9 +530,12 @@ impl TopElement for StructData {
         {
             let mut locked = syntax.lock().unwrap();
             locked.structures.add_data(data.clone(), structure.clone());
+            if let Some(stub) = locked.struct_stubs.remove(&data.name) {
+                stub.finish_stub(structure.clone());
+            }
         }
 
-        for function in functions {
+        for function in check_duplicate_methods(functions, &syntax).await {
             handle.lock().unwrap().spawn(
                 function.data.name.clone(),
                 FunctionData::verify(
@@ -338,6 +556,55 @@ impl TopElement for StructData {
     }
 }
 
+/// Removes any method with the same name as an earlier one, erroring at the duplicate.
+/// Methods are found by their name, so ones with different arguments are errors too instead of overloads.
+/// Arguments are compared once finalized, so aliases and qualified names of the same type have the same arguments.
+async fn check_duplicate_methods(
+    functions: Vec<UnfinalizedFunction>,
+    syntax: &Arc<Mutex<Syntax>>,
+) -> Vec<UnfinalizedFunction> {
+    let mut output: Vec<UnfinalizedFunction> = Vec::default();
+    let mut signatures: Vec<Vec<FinalizedTypes>> = Vec::default();
+    'outer: for mut function in functions {
+        // Resolve the arguments, then put them back so the function can still be verified
+        let mut arguments = Vec::default();
+        for field in &mut function.fields {
+            match field.as_mut().await {
+                Ok(found) => {
+                    arguments.push(found.field.field_type.finalize(syntax.clone()).await);
+                    *field = Box::pin(async move { Ok(found) });
+                }
+                Err(error) => {
+                    *field = Box::pin(async move { Err(error) });
+                    output.push(function);
+                    signatures.push(Vec::default());
+                    continue 'outer;
+                }
+            }
+        }
+
+        for (i, other) in output.iter().enumerate() {
+            if other.data.name == function.data.name {
+                let message = if signatures[i] == arguments {
+                    ParsingMessage::DuplicateMethod(function.data.name.clone())
+                } else {
+                    ParsingMessage::OverloadedMethod(function.data.name.clone())
+                };
+                let error = function.data.span.make_error(message);
+                syntax
+                    .lock()
+                    .unwrap()
+                    .errors
+                    .push(error.with_note(other.data.span.make_error(ParsingMessage::FirstDeclaredHere())));
+                continue 'outer;
+            }
+        }
+        output.push(function);
+        signatures.push(arguments);
+    }
+    return output;
+}
+
 impl Debug for StructData {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name)
@@ -347,3 +614,77 @@ impl Debug for StructData {
 impl Eq for StructData {}
 
 impl Eq for FinalizedStruct {}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use data::tokens::Span;
+
+    use crate::program::function::FunctionData;
+    use crate::program::r#struct::{FinalizedStruct, StructData};
+    use crate::program::types::FinalizedTypes;
+    use crate::Modifier;
+
+    /// Makes a trait declaring the methods, named after the trait
+    fn make_trait(name: &str, methods: &[&str], supertraits: Vec<FinalizedTypes>) -> FinalizedStruct {
+        let functions = methods
+            .iter()
+            .map(|method| Arc::new(FunctionData::new(vec![], 0, format!("{}::{}", name, method), Span::default())))
+            .collect::<Vec<_>>();
+        let mut output = FinalizedStruct::empty_of(StructData::new(
+            vec![],
+            functions,
+            Modifier::Trait as u8,
+            Span::default(),
+            name.to_string(),
+        ));
+        output.traits = supertraits;
+        return output;
+    }
+
+    /// Checks vtable slots follow the trait's order, so the impl's order and the struct's other methods don't change them
+    #[test]
+    pub fn test_vtable_slots() {
+        let shape = make_trait("test::Shape", &["area", "sides"], vec![]);
+        assert_eq!(shape.data.vtable_slot("area"), Some(0));
+        assert_eq!(shape.data.vtable_slot("test::Shape_Square::sides"), Some(1));
+        assert_eq!(shape.data.vtable_slot("sides$u64"), Some(1));
+        assert_eq!(shape.data.vtable_slot("perimeter"), None);
+
+        // Methods outside the trait are ignored instead of taking a slot
+        let functions = ["test::Square::perimeter", "test::Shape_Square::sides", "test::Shape_Square::area"];
+        let slots = shape.data.vtable_order(&functions, |function| function);
+        assert_eq!(slots, vec!["test::Shape_Square::area", "test::Shape_Square::sides"]);
+
+        let square = StructData::empty("test::Square".to_string());
+        assert_eq!(shape.data.vtable_symbol(&square), "test::Square_test::Shape_vtable");
+    }
+
+    /// Checks a subtrait's vtable has its own methods' slots first, then its supertraits' methods
+    #[test]
+    pub fn test_supertrait_slots() {
+        let named = Arc::new(make_trait("test::Named", &["id"], vec![]));
+        let greeter = make_trait("test::Greeter", &["bonus"], vec![FinalizedTypes::Struct(named.clone())]);
+
+        let slot = |method: &str| greeter.virtual_method(method).map(|(slot, declaring)| (slot, declaring.name.clone()));
+        assert_eq!(slot("bonus"), Some((0, "test::Greeter".to_string())));
+        assert_eq!(slot("test::Named::id"), Some((1, "test::Named".to_string())));
+        assert_eq!(slot("id$u64"), Some((1, "test::Named".to_string())));
+        assert_eq!(slot("missing"), None);
+        assert!(greeter.extends(&named));
+        assert!(!named.extends(&greeter));
+    }
+
+    /// Checks a supertrait inherited through two subtraits only gets one set of slots
+    #[test]
+    pub fn test_shared_supertrait_slots() {
+        let named = FinalizedTypes::Struct(Arc::new(make_trait("test::Named", &["id"], vec![])));
+        let first = FinalizedTypes::Struct(Arc::new(make_trait("test::First", &["first"], vec![named.clone()])));
+        let second = FinalizedTypes::Struct(Arc::new(make_trait("test::Second", &["second"], vec![named])));
+        let both = make_trait("test::Both", &[], vec![first, second]);
+
+        let layout = both.vtable_layout().iter().map(|(_, function)| function.name.clone()).collect::<Vec<_>>();
+        assert_eq!(layout, vec!["test::First::first", "test::Named::id", "test::Second::second"]);
+    }
+}
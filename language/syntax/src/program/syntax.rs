@@ -21,13 +21,16 @@ use async_trait::async_trait;
 use data::tokens::Span;
 pub use data::Main;
 
-use crate::async_util::{AsyncStructImplGetter, AsyncTypesGetter, NameResolver, UnparsedType};
+use crate::async_util::{prefixed_name, AsyncStructImplGetter, AsyncTypesGetter, NameResolver, TypeAlias, UnparsedType};
 use crate::chalk_interner::ChalkIr;
 use crate::errors::{ErrorSource, ParsingMessage};
-use crate::program::function::{FinalizedFunction, FunctionData};
+use crate::program::code::{FinalizedField, FinalizedMemberField};
+use crate::program::constant::UnfinalizedConstant;
+use crate::program::function::{CodelessFinalizedFunction, FinalizedCodeBody, FinalizedFunction, FunctionData};
 use crate::program::r#struct::{FinalizedStruct, StructData, BOOL, F32, F64, I16, I32, I64, I8, STR, U16, U32, U64, U8};
+use crate::program::source_map::SourceMap;
 use crate::program::types::FinalizedTypes;
-use crate::top_element_manager::{GetterManager, TopElementManager};
+use crate::top_element_manager::{BoundSatisfaction, GetterManager, PendingImpl, TopElementManager};
 use crate::{
     is_modifier, Attribute, FinishedStructImplementor, FinishedTraitImplementor, Modifier, ParsingError, ProcessManager,
     TopElement, Types,
@@ -48,6 +51,8 @@ pub struct Syntax {
     pub strut_compiling: Arc<DashMap<String, Arc<FinalizedStruct>>>,
     /// All parsing errors on the entire program
     pub errors: Vec<ParsingError>,
+    /// Warnings on the entire program, which don't stop it from compiling
+    pub warnings: Vec<ParsingError>,
     /// All structures in the program
     pub structures: TopElementManager<StructData>,
     /// All functions in the program
@@ -58,11 +63,40 @@ pub struct Syntax {
     pub struct_implementations: HashMap<FinalizedTypes, Vec<Arc<FinishedStructImplementor>>>,
     /// The parsing state
     pub async_manager: GetterManager,
+    /// The module each internal type is declared in, because their names don't include one
+    pub internal_modules: HashMap<String, String>,
     /// All operations, for example Add or Multiply.
     pub operations: HashMap<String, Arc<StructData>>,
     /// Wakers waiting for a specific operation to be finished parsing. Will never deadlock
     /// because types are added before they're finalized.
     pub operation_wakers: HashMap<String, Vec<Waker>>,
+    /// The generics of each verified struct and the type of each of its fields that can contain a struct without a
+    /// reference, to find structs containing themselves
+    pub contained_structs: HashMap<String, (Vec<String>, Vec<(String, Types)>)>,
+    /// The structs named anywhere in the fields of each verified struct, even behind a reference
+    pub mentioned_structs: HashMap<String, Vec<String>>,
+    /// Stubs for structs used in the fields of structs they use, which are finished once the struct is
+    pub struct_stubs: HashMap<String, Arc<FinalizedStruct>>,
+    /// Every module-level constant by its full name, so a constant can be used before it's declared
+    pub constants: HashMap<String, Arc<UnfinalizedConstant>>,
+    /// Every type alias by its full name, so an alias can be used before it's declared and from other files
+    pub aliases: HashMap<String, TypeAlias>,
+    /// The instance of each generic function for each set of generics it's called with, keyed by the generic
+    /// function's name and the generics sorted by name, so every call site with the same generics shares one instance
+    pub monomorphized: HashMap<(String, Vec<(String, String)>), Arc<FunctionData>>,
+    /// How many times an instance in monomorphized was reused instead of degenericing the function again
+    pub monomorphized_hits: usize,
+    /// Whether each type satisfies each trait bound, keyed by the type and the trait.
+    /// Only filled in once every impl is parsed, so an impl that isn't parsed yet can't cause a wrong answer.
+    pub bounds: HashMap<(FinalizedTypes, FinalizedTypes), BoundSatisfaction>,
+    /// How many bound checks searched every impl
+    pub bound_checks: usize,
+    /// How many bound checks were answered by bounds instead of searching every impl
+    pub bound_hits: usize,
+    /// The address of each native function added from Rust, which the compiler calls instead of linking a symbol
+    pub natives: HashMap<String, usize>,
+    /// The features enabled for this build, which #[cfg(feature)] attributes check
+    pub features: Vec<String>,
     /// Manages the next steps of compilation after parsing
     pub process_manager: Box<dyn ProcessManager>,
 }
@@ -76,6 +110,7 @@ impl Syntax {
             compiling_wakers: HashMap::default(),
             strut_compiling: Arc::new(DashMap::default()),
             errors: Vec::default(),
+            warnings: Vec::default(),
             functions: TopElementManager::default(),
             structures: TopElementManager::with_sorted(vec![
                 I64.data.clone(),
@@ -94,8 +129,21 @@ impl Syntax {
             implementations: Vec::default(),
             struct_implementations: HashMap::default(),
             async_manager: GetterManager::default(),
+            internal_modules: HashMap::default(),
             operations: HashMap::default(),
             operation_wakers: HashMap::default(),
+            contained_structs: HashMap::default(),
+            constants: HashMap::default(),
+            aliases: HashMap::default(),
+            mentioned_structs: HashMap::default(),
+            struct_stubs: HashMap::default(),
+            monomorphized: HashMap::default(),
+            monomorphized_hits: 0,
+            bounds: HashMap::default(),
+            bound_checks: 0,
+            bound_hits: 0,
+            natives: HashMap::default(),
+            features: Vec::default(),
             process_manager,
         };
     }
@@ -151,11 +199,65 @@ impl Syntax {
         }
     }
 
+    /// Checks if every cfg attribute is met, #[cfg(feature)] needs the feature enabled and #[cfg(!feature)] needs it
+    /// disabled. Elements that aren't enabled are left out of the program, so nothing can call them.
+    pub fn is_enabled(&self, attributes: &Vec<Attribute>) -> bool {
+        return attributes.iter().all(|attribute| match attribute {
+            Attribute::String(name, condition) if name == "cfg" => match condition.trim().strip_prefix('!') {
+                Some(feature) => !self.features.iter().any(|found| found == feature.trim()),
+                None => self.features.iter().any(|found| found == condition.trim()),
+            },
+            _ => true,
+        });
+    }
+
+    /// Adds the warning, unless the same warning was already given at the same place.
+    /// Code can be checked more than once, like a function retried with a literal's new type, or a method found
+    /// again at each of its calls, and each should still only warn once.
+    pub fn warn(&mut self, warning: ParsingError) {
+        let message = warning.message.to_string();
+        let found = self.warnings.iter().any(|found| {
+            found.span.file == warning.span.file
+                && found.span.start == warning.span.start
+                && found.span.end == warning.span.end
+                && found.message.to_string() == message
+        });
+        if !found {
+            self.warnings.push(warning);
+        }
+    }
+
     /// Checks if the implementations are finished parsing.
     pub fn finished_impls(&self) -> bool {
         return self.async_manager.finished && self.async_manager.parsing_impls == 0;
     }
 
+    /// Gets the trait and method requests still waiting for impls, without duplicates.
+    /// If the build never finishes, these show what each waiting impl call is stuck on.
+    pub fn pending_impls(&self) -> Vec<PendingImpl> {
+        let mut pending = self.async_manager.pending_impls.clone();
+        pending.sort();
+        pending.dedup();
+        return pending;
+    }
+
+    /// Gets every implementation of the trait with any generics, like each impl of Iter<T> for any T.
+    /// Implementations are still being added until finished_impls is true, so this is empty before then.
+    pub fn impls_of(&self, trait_type: &FinalizedTypes) -> Vec<Arc<FinishedTraitImplementor>> {
+        if !self.finished_impls() {
+            return Vec::default();
+        }
+        let name = &trait_type.inner_struct().data.name;
+        return self
+            .implementations
+            .iter()
+            .filter(|implementation| {
+                implementation.target.inner_struct_safe().is_some_and(|target| &target.data.name == name)
+            })
+            .cloned()
+            .collect();
+    }
+
     /// Sets the syntax to be finished, calling all wakers so non-existent functions can be detected.
     pub fn finish(&mut self) {
         if self.async_manager.finished {
@@ -190,6 +292,7 @@ impl Syntax {
         while let Some(found) = self.async_manager.impl_waiters.pop() {
             found.wake();
         }
+        self.async_manager.pending_impls.clear();
     }
 
     /// Converts an implementation into a Chalk ImplDatum. This allows implementations to be used
@@ -257,13 +360,13 @@ impl Syntax {
                 }
                 Some(true)
             }
-            FinalizedTypes::Array(inner) => {
+            FinalizedTypes::Array(inner, _) => {
                 let mut checking = checking;
                 // Unwrap references because references don't matter for type checking.
                 if let FinalizedTypes::Reference(inner_type) = checking {
                     checking = inner_type;
                 }
-                if let FinalizedTypes::Array(other) = checking {
+                if let FinalizedTypes::Array(other, _) = checking {
                     // Check the inner type if both are generics
                     self.solve_nonstruct_types(inner, other)
                 } else {
@@ -320,6 +423,56 @@ impl Syntax {
         locked.add(adding);
     }
 
+    /// Adds a native function implemented in Rust, which is called through the pointer.
+    /// The function can be called from Raven like any extern function, for example "host::print" is called
+    /// with print after importing host.
+    pub fn add_native(
+        syntax: &Arc<Mutex<Syntax>>,
+        name: String,
+        arguments: Vec<FinalizedTypes>,
+        return_type: Option<FinalizedTypes>,
+        pointer: usize,
+    ) {
+        let mut data = Arc::new(FunctionData::new(
+            Vec::default(),
+            Modifier::Public as u8 | Modifier::Extern as u8,
+            name.clone(),
+            Span::default(),
+        ));
+        let codeless = CodelessFinalizedFunction {
+            generics: IndexMap::default(),
+            arguments: arguments
+                .into_iter()
+                .enumerate()
+                .map(|(i, field_type)| FinalizedMemberField {
+                    modifiers: 0,
+                    attributes: Vec::default(),
+                    field: FinalizedField { field_type, name: format!("arg{}", i) },
+                    default: None,
+                })
+                .collect(),
+            return_type,
+            self_bounds: Vec::default(),
+            data: data.clone(),
+            parent: None,
+        };
+
+        let mut locked = syntax.lock().unwrap();
+        locked.natives.insert(name.clone(), pointer);
+        locked.add(&mut data);
+        locked.functions.add_data(data, Arc::new(codeless.clone()));
+
+        // Natives have no code to check, so they're ready to compile right away.
+        let function = Arc::new(codeless.add_code(FinalizedCodeBody::new(Vec::default(), String::default(), true)));
+        locked.generics.insert(name.clone(), function.clone());
+        locked.compiling.insert(name.clone(), function);
+        if let Some(found) = locked.compiling_wakers.get(&name) {
+            for waker in found {
+                waker.wake_by_ref();
+            }
+        }
+    }
+
     pub fn add_struct(syntax: &Arc<Mutex<Syntax>>, adding: &mut Arc<StructData>) {
         let mut locked = syntax.lock().unwrap();
         locked.add(adding);
@@ -418,14 +571,76 @@ impl Syntax {
         name_resolver: Box<dyn NameResolver>,
         mut resolved_generics: Vec<String>,
     ) -> Result<Types, ParsingError> {
-        // Handles arrays by removing the brackets and getting the inner type
+        // Handles arrays by removing the brackets and getting the inner type and length
         if getting.as_bytes()[0] == b'[' {
-            return Ok(Types::Array(Box::new(
-                Self::get_struct(syntax, error, getting[1..getting.len() - 1].to_string(), name_resolver, resolved_generics)
+            let inner = &getting[1..getting.len() - 1];
+            let (inner, length) = match array_length_separator(inner) {
+                Some(index) => (&inner[..index], Some(inner[index + 1..].trim())),
+                None => (inner, None),
+            };
+            let length = match length {
+                Some(length) => Some(Box::new(
+                    Self::get_struct(
+                        syntax.clone(),
+                        error.clone(),
+                        length.to_string(),
+                        name_resolver.boxed_clone(),
+                        resolved_generics.clone(),
+                    )
                     .await?,
+                )),
+                None => None,
+            };
+            return Ok(Types::Array(
+                Box::new(Self::get_struct(syntax, error, inner.trim().to_string(), name_resolver, resolved_generics).await?),
+                length,
+            ));
+        }
+
+        // Handles references by getting the type they point to
+        if let Some(inner) = getting.strip_prefix('&') {
+            return Ok(Types::Reference(Box::new(
+                Self::get_struct(syntax, error, inner.trim().to_string(), name_resolver, resolved_generics).await?,
             )));
         }
 
+        // Handles function types by getting each parameter and the return type
+        if getting.starts_with("fn(") {
+            let (arguments, returning) = split_function_type(&getting);
+            let mut parameters = Vec::default();
+            for argument in arguments {
+                parameters.push(
+                    Self::get_struct(
+                        syntax.clone(),
+                        error.clone(),
+                        argument.to_string(),
+                        name_resolver.boxed_clone(),
+                        resolved_generics.clone(),
+                    )
+                    .await?,
+                );
+            }
+            let returning = match returning {
+                Some(returning) => Some(Box::new(
+                    Self::get_struct(syntax, error, returning.to_string(), name_resolver, resolved_generics).await?,
+                )),
+                None => None,
+            };
+            return Ok(Types::Function(parameters, returning));
+        }
+
+        // Integers are constants, like the 8 in Buffer<8>
+        if let Ok(value) = getting.parse::<u64>() {
+            return Ok(Types::Const(value));
+        }
+
+        // Self is the type of the struct, trait or impl it's used in
+        if getting == "Self" {
+            if let Some(found) = name_resolver.self_type() {
+                return Self::parse_type(syntax, error, name_resolver, found, resolved_generics).await;
+            }
+        }
+
         // Checks if the type is a generic type
         if let Some(found) = name_resolver.generic(&getting) {
             let mut bounds = Vec::default();
@@ -449,10 +664,144 @@ impl Syntax {
             return Ok(Types::Generic(getting, bounds));
         }
 
+        // Type aliases are replaced by the type they stand for, with their generics filled in
+        if let Some(found) = Self::get_alias(&syntax, &error, &getting, &*name_resolver, &resolved_generics).await {
+            return found;
+        }
+
+        // Aliases declared later or in files parsed later are only certain to be found once the type isn't
         if getting.contains('<') {
-            return Ok(Self::parse_bounds(getting.as_bytes(), &syntax, &error, &*name_resolver).await?.remove(0));
+            return match Self::parse_bounds(getting.as_bytes(), &syntax, &error, &*name_resolver).await {
+                Ok(mut found) => Ok(found.remove(0)),
+                Err(failed) => match Self::get_alias(&syntax, &error, &getting, &*name_resolver, &resolved_generics).await {
+                    Some(found) => found,
+                    None => Err(failed),
+                },
+            };
+        }
+        return match AsyncTypesGetter::new(
+            syntax.clone(),
+            error.clone(),
+            getting.clone(),
+            name_resolver.boxed_clone(),
+            false,
+        )
+        .await
+        {
+            Ok(found) => Ok(Types::Struct(found)),
+            Err(failed) => match Self::get_alias(&syntax, &error, &getting, &*name_resolver, &resolved_generics).await {
+                Some(found) => found,
+                None => Err(syntax.lock().unwrap().missing_member(failed, &getting, name_resolver.imports())),
+            },
+        };
+    }
+
+    /// Gets the type the type alias stands for with its arguments filled in, or None if it isn't an alias.
+    /// Aliases given the wrong number of arguments aren't expanded, so they fail to be found
+    async fn get_alias(
+        syntax: &Arc<Mutex<Syntax>>,
+        error: &Span,
+        getting: &str,
+        name_resolver: &dyn NameResolver,
+        resolved_generics: &[String],
+    ) -> Option<Result<Types, ParsingError>> {
+        let (base, arguments) = match getting.split_once('<') {
+            Some((base, arguments)) => {
+                (base.trim(), split_type_arguments(arguments.trim_end().strip_suffix('>').unwrap_or(arguments)))
+            }
+            None => (getting, Vec::default()),
+        };
+        let (alias, cycle) = {
+            let locked = syntax.lock().unwrap();
+            let alias = locked.find_alias(&base.to_string(), name_resolver.imports())?.clone();
+            if alias.generics.len() != arguments.len() {
+                return None;
+            }
+            let cycle = locked.alias_cycle(&alias);
+            (alias, cycle)
+        };
+        if let Some(cycle) = cycle {
+            return Some(Err(error.make_error(ParsingMessage::AliasCycle(alias.name, cycle.join(" -> ")))));
+        }
+
+        // The aliased type can use the imports of the file declaring it, and the arguments the imports of this one
+        let resolver = AliasNameResolver {
+            imports: alias.imports.iter().chain(name_resolver.imports()).cloned().collect(),
+            resolver: name_resolver.boxed_clone(),
+        };
+        return Some(
+            Self::get_struct(
+                syntax.clone(),
+                error.clone(),
+                alias.expand(&arguments),
+                Box::new(resolver),
+                resolved_generics.to_vec(),
+            )
+            .await,
+        );
+    }
+
+    /// Finds the type alias by its name, either its full name or the name from one of the imports
+    pub fn find_alias(&self, name: &String, imports: &[String]) -> Option<&TypeAlias> {
+        if let Some(found) = self.aliases.get(name) {
+            return Some(found);
+        }
+        return imports.iter().find_map(|import| self.aliases.get(&prefixed_name(import.clone(), name)));
+    }
+
+    /// Finds the aliases an alias goes through to stand for itself, which would never finish expanding
+    fn alias_cycle(&self, alias: &TypeAlias) -> Option<Vec<String>> {
+        let mut chain = vec![alias.name.clone()];
+        let mut visited = Vec::default();
+        if self.follow_aliases(alias, &alias.name, &mut chain, &mut visited) {
+            return Some(chain);
         }
-        return Ok(Types::Struct(AsyncTypesGetter::new(syntax, error, getting, name_resolver, false).await?));
+        return None;
+    }
+
+    /// Follows the aliases used by the aliased type, adding them to the chain until one is the starting alias
+    fn follow_aliases(
+        &self,
+        current: &TypeAlias,
+        start: &String,
+        chain: &mut Vec<String>,
+        visited: &mut Vec<String>,
+    ) -> bool {
+        for name in current.names() {
+            let found = match self.find_alias(&name, &current.imports) {
+                Some(found) => found,
+                None => continue,
+            };
+            chain.push(found.name.clone());
+            if &found.name == start {
+                return true;
+            }
+            if !visited.contains(&found.name) {
+                visited.push(found.name.clone());
+                if self.follow_aliases(found, start, chain, visited) {
+                    return true;
+                }
+            }
+            chain.pop();
+        }
+        return false;
+    }
+
+    /// Replaces the error for a qualified name like collections::Map whose module exists with one saying
+    /// the module doesn't have that type, since the import isn't what's wrong
+    fn missing_member(&self, failed: ParsingError, getting: &str, imports: &[String]) -> ParsingError {
+        let (module, member) = match getting.rsplit_once("::") {
+            Some(found) => found,
+            None => return failed,
+        };
+        let exists = |module: String| {
+            let prefix = module + "::";
+            return self.structures.types.keys().chain(self.functions.types.keys()).any(|name| name.starts_with(&prefix));
+        };
+        if exists(module.to_string()) || imports.iter().any(|import| exists(format!("{}::{}", import, module))) {
+            return failed.span.make_error(ParsingMessage::NoTypeInModule(member.to_string(), module.to_string()));
+        }
+        return failed;
     }
 
     /// Parses generic bounds on a type, returning the length parsed and the types found.
@@ -532,6 +881,12 @@ impl Syntax {
         let temp = match types.clone() {
             UnparsedType::Basic(name) => Syntax::get_struct(syntax, error, name, resolver, resolved_generics).await,
             UnparsedType::Generic(name, args) => {
+                // Aliases are expanded with their arguments instead of being given them as generics
+                if let Some(found) =
+                    Self::get_alias(&syntax, &error, &type_name(&types), &*resolver, &resolved_generics).await
+                {
+                    return found;
+                }
                 let mut generics = Vec::default();
                 for arg in args {
                     generics.push(
@@ -549,20 +904,336 @@ impl Syntax {
                 if generics.is_empty() {
                     println!("Found with no generics!");
                 }
-                Ok(Types::GenericType(
-                    Box::new(Self::parse_type(syntax, error, resolver, *name, resolved_generics).await?),
-                    generics,
-                ))
+                let base = match Self::parse_type(
+                    syntax.clone(),
+                    error.clone(),
+                    resolver.boxed_clone(),
+                    *name,
+                    resolved_generics.clone(),
+                )
+                .await
+                {
+                    Ok(base) => base,
+                    // The alias might not have been declared yet when it was first looked for
+                    Err(failed) => {
+                        return match Self::get_alias(&syntax, &error, &type_name(&types), &*resolver, &resolved_generics)
+                            .await
+                        {
+                            Some(found) => found,
+                            None => Err(failed),
+                        }
+                    }
+                };
+                Ok(Types::GenericType(Box::new(base), generics))
+            }
+            UnparsedType::Const(inner) => {
+                Ok(Types::ConstType(Box::new(Self::parse_type(syntax, error, resolver, *inner, resolved_generics).await?)))
             }
         };
         return temp;
     }
 }
 
+/// Resolves the names in an aliased type, which can use the imports of the file declaring the alias
+struct AliasNameResolver {
+    /// The imports of the file declaring the alias, then the imports of the file using it
+    imports: Vec<String>,
+    /// The name resolver of where the alias is used
+    resolver: Box<dyn NameResolver>,
+}
+
+impl NameResolver for AliasNameResolver {
+    fn imports(&self) -> &Vec<String> {
+        return &self.imports;
+    }
+
+    fn module(&self) -> &str {
+        return self.resolver.module();
+    }
+
+    fn generic(&self, name: &String) -> Option<Vec<UnparsedType>> {
+        return self.resolver.generic(name);
+    }
+
+    fn generics(&self) -> &IndexMap<String, Vec<UnparsedType>> {
+        return self.resolver.generics();
+    }
+
+    fn constant(&self, name: &String) -> Option<Arc<UnfinalizedConstant>> {
+        return self.resolver.constant(name);
+    }
+
+    fn self_type(&self) -> Option<UnparsedType> {
+        return self.resolver.self_type();
+    }
+
+    fn boxed_clone(&self) -> Box<dyn NameResolver> {
+        return Box::new(AliasNameResolver { imports: self.imports.clone(), resolver: self.resolver.boxed_clone() });
+    }
+}
+
+/// Gets the name of an unparsed type as it would be written in the code, like Pair<u64, str>
+fn type_name(types: &UnparsedType) -> String {
+    return match types {
+        UnparsedType::Basic(name) => name.clone(),
+        UnparsedType::Generic(base, args) => {
+            format!("{}<{}>", type_name(base), args.iter().map(type_name).collect::<Vec<_>>().join(", "))
+        }
+        UnparsedType::Const(inner) => type_name(inner),
+    };
+}
+
+/// Splits the generic arguments of a type like "u64, Pair<u8, u8>" at the commas not inside another type
+fn split_type_arguments(arguments: &str) -> Vec<String> {
+    let mut found = Vec::default();
+    let mut depth = 0;
+    let mut last = 0;
+    for (i, character) in arguments.bytes().enumerate() {
+        match character {
+            b'<' | b'[' | b'(' => depth += 1,
+            b'>' | b']' | b')' => depth -= 1,
+            b',' if depth == 0 => {
+                found.push(arguments[last..i].trim().to_string());
+                last = i + 1;
+            }
+            _ => {}
+        }
+    }
+    found.push(arguments[last..].trim().to_string());
+    return found;
+}
+
+/// Finds the ; splitting an array's type from its length, skipping any inside nested arrays
+fn array_length_separator(inner: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, character) in inner.bytes().enumerate() {
+        match character {
+            b'[' => depth += 1,
+            b']' => depth -= 1,
+            b';' if depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    return None;
+}
+
+/// Splits a function type like fn(u64, T) -> u64 into its parameters and return type, if there is one
+fn split_function_type(function: &str) -> (Vec<&str>, Option<&str>) {
+    let bytes = function.as_bytes();
+    let mut depth = 0;
+    let mut last = 3;
+    let mut arguments = Vec::default();
+    for i in 3..bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'<' => depth += 1,
+            // The arrow of a nested function type isn't a closing bracket
+            b'>' if bytes[i - 1] == b'-' => {}
+            b']' | b'>' => depth -= 1,
+            b',' if depth == 0 => {
+                arguments.push(function[last..i].trim());
+                last = i + 1;
+            }
+            b')' if depth == 0 => {
+                if !function[last..i].trim().is_empty() {
+                    arguments.push(function[last..i].trim());
+                }
+                return (arguments, function[i + 1..].trim().strip_prefix("->").map(str::trim));
+            }
+            b')' => depth -= 1,
+            _ => {}
+        }
+    }
+    return (arguments, None);
+}
+
+/// The address of a compiled function, which can be called as long as the compiler that compiled it is alive
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnsafeFn(pub usize);
+
+impl UnsafeFn {
+    /// Casts the address to a function pointer, like unsafe extern "C" fn(u64) -> u64
+    ///
+    /// # Safety
+    /// F must be a function pointer with the compiled function's arguments and return type.
+    pub unsafe fn cast<F: Copy>(self) -> F {
+        assert_eq!(
+            std::mem::size_of::<F>(),
+            std::mem::size_of::<usize>(),
+            "Can only cast a function to a function pointer!"
+        );
+        return std::mem::transmute_copy(&self.0);
+    }
+}
+
 /// The compiler
 #[async_trait]
 pub trait Compiler<T> {
     /// Compiles the target function and returns the main runner.
-    /// Waits for the receiver before calling any of the code
+    /// Waits for the receiver before calling any of the code.
+    /// Each function's FunctionData carries the hints the backend should honor, like its inline attribute.
     async fn compile(&self, receiver: Receiver<()>, syntax: &Arc<Mutex<Syntax>>) -> Option<T>;
+
+    /// Compiles every named function into one program and returns a handle to call each of them with, waiting for
+    /// the receiver like compile. Generic instances the functions share are only compiled once.
+    async fn compile_all(
+        &self,
+        receiver: Receiver<()>,
+        names: &[&str],
+        syntax: &Arc<Mutex<Syntax>>,
+    ) -> Result<HashMap<String, UnsafeFn>, Vec<ParsingError>>;
+
+    /// The source map of the code generated by compile, which is finished before the compiled code is called
+    fn source_map(&self) -> SourceMap;
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use data::tokens::Span;
+    use indexmap::IndexMap;
+
+    use crate::async_util::{HandleWrapper, NameResolver};
+    use crate::errors::{ErrorSource, ParsingMessage};
+    use crate::program::constant::{FinalizedConstant, UnfinalizedConstant};
+    use crate::program::function::{CodeBody, CodelessFinalizedFunction, FinalizedFunction, UnfinalizedFunction};
+    use crate::program::r#struct::{FinalizedStruct, StructData, UnfinalizedStruct, U32, U64, U8};
+    use crate::program::syntax::Syntax;
+    use crate::program::types::FinalizedTypes;
+    use crate::{FinishedTraitImplementor, Modifier, ParsingError, ProcessManager};
+
+    /// A process manager for syntax that's filled in directly instead of being parsed, so nothing is ever checked
+    struct Unchecked;
+
+    #[async_trait]
+    impl ProcessManager for Unchecked {
+        fn handle(&self) -> &Arc<Mutex<HandleWrapper>> {
+            unimplemented!()
+        }
+
+        async fn verify_func(
+            &self,
+            _function: UnfinalizedFunction,
+            _resolver: Box<dyn NameResolver>,
+            _syntax: &Arc<Mutex<Syntax>>,
+        ) -> (CodelessFinalizedFunction, CodeBody) {
+            unimplemented!()
+        }
+
+        async fn verify_code(
+            &self,
+            _function: CodelessFinalizedFunction,
+            _code: CodeBody,
+            _resolver: Box<dyn NameResolver>,
+            _syntax: &Arc<Mutex<Syntax>>,
+        ) -> FinalizedFunction {
+            unimplemented!()
+        }
+
+        async fn degeneric_code(&self, _function: Arc<CodelessFinalizedFunction>, _syntax: &Arc<Mutex<Syntax>>) {
+            unimplemented!()
+        }
+
+        async fn verify_constant(
+            &self,
+            _constant: Arc<UnfinalizedConstant>,
+            _resolver: Box<dyn NameResolver>,
+            _syntax: &Arc<Mutex<Syntax>>,
+        ) -> Option<FinalizedConstant> {
+            unimplemented!()
+        }
+
+        async fn verify_struct(
+            &self,
+            _structure: UnfinalizedStruct,
+            _resolver: Box<dyn NameResolver>,
+            _syntax: &Arc<Mutex<Syntax>>,
+        ) -> FinalizedStruct {
+            unimplemented!()
+        }
+
+        async fn verify_impl_signatures(
+            &self,
+            _implementor: &FinishedTraitImplementor,
+            _syntax: &Arc<Mutex<Syntax>>,
+        ) -> Vec<ParsingError> {
+            unimplemented!()
+        }
+
+        fn generics(&self) -> &IndexMap<String, FinalizedTypes> {
+            unimplemented!()
+        }
+
+        fn mut_generics(&mut self) -> &mut IndexMap<String, FinalizedTypes> {
+            unimplemented!()
+        }
+
+        fn instantiating(&self) -> &Vec<String> {
+            unimplemented!()
+        }
+
+        fn mut_instantiating(&mut self) -> &mut Vec<String> {
+            unimplemented!()
+        }
+
+        fn cloned(&self) -> Box<dyn ProcessManager> {
+            return Box::new(Unchecked);
+        }
+    }
+
+    /// Makes a trait without any methods
+    fn make_trait(name: &str) -> FinalizedTypes {
+        let data = StructData::new(vec![], vec![], Modifier::Trait as u8, Span::default(), name.to_string());
+        return FinalizedTypes::Struct(Arc::new(FinalizedStruct::empty_of(data)));
+    }
+
+    /// Makes an implementation of the trait for the base
+    fn make_impl(target: &FinalizedTypes, base: &Arc<FinalizedStruct>) -> Arc<FinishedTraitImplementor> {
+        let base = FinalizedTypes::Struct(base.clone());
+        return Arc::new(FinishedTraitImplementor {
+            chalk_type: Arc::new(Syntax::make_impldatum(&IndexMap::default(), target, &base)),
+            target: target.clone(),
+            base,
+            generics: IndexMap::default(),
+            attributes: vec![],
+            functions: vec![],
+            constants: vec![],
+            span: Span::default(),
+        });
+    }
+
+    /// Checks a warning given again at the same place is only kept once
+    #[test]
+    pub fn test_warn_once() {
+        let mut syntax = Syntax::new(Box::new(Unchecked));
+        let warning = |start: usize| {
+            let span = Span { file: 1, start, end: start, ..Span::default() };
+            return span.make_error(ParsingMessage::ShadowedVariable("value".to_string()));
+        };
+        syntax.warn(warning(3));
+        syntax.warn(warning(3));
+        syntax.warn(warning(5));
+        syntax.warn(Span { file: 1, start: 3, end: 3, ..Span::default() }.make_error(ParsingMessage::UnreachableCode()));
+        assert_eq!(syntax.warnings.len(), 3);
+    }
+
+    /// Checks every implementation of a trait is returned once they're all finished, and no other trait's
+    #[test]
+    pub fn test_impls_of() {
+        let size = make_trait("test::Size");
+        let other = make_trait("test::Other");
+        let mut syntax = Syntax::new(Box::new(Unchecked));
+        for base in [U8.clone(), U32.clone(), U64.clone()] {
+            syntax.implementations.push(make_impl(&size, &base));
+        }
+        syntax.implementations.push(make_impl(&other, &U8));
+        assert!(syntax.impls_of(&size).is_empty());
+
+        syntax.finish();
+        let mut implementors = syntax.impls_of(&size).iter().map(|found| found.base.to_string()).collect::<Vec<_>>();
+        implementors.sort();
+        assert_eq!(implementors, vec!["u32", "u64", "u8"]);
+        assert_eq!(syntax.impls_of(&other).len(), 1);
+    }
 }
@@ -1,23 +1,23 @@
 /// Contains all the code for interacting with types in Raven.
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
 
-use chalk_ir::{BoundVar, DebruijnIndex, GenericArgData, Substitution, Ty, TyKind};
+use chalk_ir::{BoundVar, DebruijnIndex, GenericArgData, Scalar, Substitution, Ty, TyKind, UintTy};
 use chalk_solve::rust_ir::TraitDatum;
 
 use async_recursion::async_recursion;
 use data::tokens::Span;
+use indexmap::IndexMap;
 
 use crate::async_util::AsyncDataGetter;
 use crate::chalk_interner::ChalkIr;
 use crate::errors::{ErrorSource, ParsingMessage};
 use crate::program::code::FinalizedMemberField;
 use crate::program::function::{display, display_parenless, FunctionData};
-use crate::program::r#struct::{ChalkData, FinalizedStruct};
+use crate::program::r#struct::{get_integer, ChalkData, FinalizedStruct, OPTION, RC, VOID};
 use crate::program::syntax::Syntax;
 use crate::top_element_manager::{ImplWaiter, TypeImplementsTypeWaiter};
 use crate::{is_modifier, Modifier, ParsingError, StructData};
@@ -35,8 +35,14 @@ pub enum Types {
     Reference(Box<Types>),
     /// A generic with bounds
     Generic(String, Vec<Types>),
-    /// An array
-    Array(Box<Types>),
+    /// An array, with a length if it's fixed
+    Array(Box<Types>, Option<Box<Types>>),
+    /// A compile-time integer, like the 8 in Buffer<8>
+    Const(u64),
+    /// The bound of a const generic, for example the u64 in "const N: u64"
+    ConstType(Box<Types>),
+    /// A function taking the parameter types and returning the return type, if there is one
+    Function(Vec<Types>, Option<Box<Types>>),
 }
 
 ///A type with a reference to the finalized program instead of the data.
@@ -50,8 +56,14 @@ pub enum FinalizedTypes {
     Reference(Box<FinalizedTypes>),
     /// A generic with bounds
     Generic(String, Vec<FinalizedTypes>),
-    /// An array
-    Array(Box<FinalizedTypes>),
+    /// An array of the element type, with a fixed length if it's known
+    Array(Box<FinalizedTypes>, Option<Box<FinalizedTypes>>),
+    /// A compile-time integer
+    Const(u64),
+    /// The bound of a const generic
+    ConstType(Box<FinalizedTypes>),
+    /// A function taking the parameter types and returning the return type, which is void if it returns nothing
+    Function(Vec<FinalizedTypes>, Box<FinalizedTypes>),
 }
 
 impl Types {
@@ -60,7 +72,10 @@ impl Types {
         return match self {
             Types::Struct(structs) => structs.name.clone(),
             Types::Reference(structs) => structs.name(),
-            Types::Array(types) => format!("[{}]", types.name()),
+            Types::Array(types, _) => format!("[{}]", types.name()),
+            Types::Const(value) => value.to_string(),
+            Types::ConstType(inner) => format!("const {}", inner.name()),
+            Types::Function(_, _) => self.to_string(),
             Types::Generic(_, _) => panic!("Generics should never be named"),
             Types::GenericType(_, _) => panic!("Generics should never be named"),
         };
@@ -72,7 +87,22 @@ impl Types {
         return match self {
             Types::Struct(structs) => FinalizedTypes::Struct(AsyncDataGetter::new(syntax, structs.clone()).await),
             Types::Reference(structs) => FinalizedTypes::Reference(Box::new(structs.finalize(syntax).await)),
-            Types::Array(inner) => FinalizedTypes::Array(Box::new(inner.finalize(syntax).await)),
+            Types::Array(inner, length) => FinalizedTypes::Array(
+                Box::new(inner.finalize(syntax.clone()).await),
+                match length {
+                    Some(length) => Some(Box::new(length.finalize(syntax).await)),
+                    None => None,
+                },
+            ),
+            Types::Const(value) => FinalizedTypes::Const(*value),
+            Types::ConstType(inner) => FinalizedTypes::ConstType(Box::new(inner.finalize(syntax).await)),
+            Types::Function(arguments, returning) => FinalizedTypes::Function(
+                Self::finalize_all(syntax.clone(), arguments).await,
+                Box::new(match returning {
+                    Some(returning) => returning.finalize(syntax).await,
+                    None => FinalizedTypes::Struct(VOID.clone()),
+                }),
+            ),
             Types::Generic(name, bounds) => FinalizedTypes::Generic(name.clone(), Self::finalize_all(syntax, bounds).await),
             Types::GenericType(base, bounds) => FinalizedTypes::GenericType(
                 Box::new(base.finalize(syntax.clone()).await),
@@ -104,7 +134,7 @@ impl FinalizedTypes {
     /// Gets the fields of the type. Useful for creating a new struct or getting data from a field of a struct.
     pub fn get_fields(&self) -> &Vec<FinalizedMemberField> {
         return match self {
-            FinalizedTypes::Struct(inner) => &inner.fields,
+            FinalizedTypes::Struct(inner) => &inner.finished().fields,
             FinalizedTypes::Reference(inner) => inner.get_fields(),
             FinalizedTypes::GenericType(base, _) => base.get_fields(),
             _ => panic!("Tried to get fields of generic!"),
@@ -119,7 +149,9 @@ impl FinalizedTypes {
                 .functions
                 .iter()
                 .find(|inner| inner.name.ends_with(name))
-                .map(|inner| vec![(self.clone(), inner.clone())]),
+                .map(|inner| vec![(self.clone(), inner.clone())])
+                // Traits also have their supertraits' methods
+                .or_else(|| inner.traits.iter().find_map(|supertrait| supertrait.find_method(name))),
             FinalizedTypes::Reference(inner) => inner.find_method(name),
             FinalizedTypes::GenericType(base, _) => base.find_method(name),
             FinalizedTypes::Generic(_, bounds) => {
@@ -137,7 +169,10 @@ impl FinalizedTypes {
                     Some(output)
                 }
             }
-            FinalizedTypes::Array(_) => None,
+            FinalizedTypes::Array(_, _)
+            | FinalizedTypes::Const(_)
+            | FinalizedTypes::ConstType(_)
+            | FinalizedTypes::Function(_, _) => None,
         };
     }
 
@@ -169,7 +204,19 @@ impl FinalizedTypes {
                 }
             }
             FinalizedTypes::Reference(inner) => inner.to_chalk_type(binders),
-            FinalizedTypes::Array(inner) => TyKind::Slice(inner.to_chalk_type(binders)).intern(ChalkIr),
+            FinalizedTypes::Array(inner, _) => TyKind::Slice(inner.to_chalk_type(binders)).intern(ChalkIr),
+            // Chalk only sees the type of constants, the value is checked by Raven
+            FinalizedTypes::Const(_) => TyKind::Scalar(Scalar::Uint(UintTy::U64)).intern(ChalkIr),
+            FinalizedTypes::ConstType(inner) => inner.to_chalk_type(binders),
+            // Chalk sees functions as a tuple of their parameters and return type
+            FinalizedTypes::Function(arguments, returning) => {
+                let components = arguments
+                    .iter()
+                    .chain([returning.as_ref()])
+                    .map(|component| GenericArgData::Ty(component.to_chalk_type(binders)).intern(ChalkIr))
+                    .collect::<Vec<_>>();
+                TyKind::Tuple(components.len(), Substitution::from_iter(ChalkIr, components)).intern(ChalkIr)
+            }
             FinalizedTypes::Generic(name, _bounds) => {
                 let index = binders.iter().position(|found| *found == name).unwrap();
                 TyKind::BoundVar(BoundVar { debruijn: DebruijnIndex::INNERMOST, index }).intern(ChalkIr)
@@ -192,7 +239,7 @@ impl FinalizedTypes {
     /// Assumes the type is a struct and returns that struct.
     pub fn inner_struct(&self) -> &Arc<FinalizedStruct> {
         return match self {
-            FinalizedTypes::Struct(structure) => structure,
+            FinalizedTypes::Struct(structure) => structure.finished(),
             FinalizedTypes::Reference(inner) => inner.inner_struct(),
             FinalizedTypes::GenericType(inner, _) => inner.inner_struct(),
             _ => panic!("Tried to get inner struct of invalid type! {:?}", self),
@@ -202,7 +249,7 @@ impl FinalizedTypes {
     /// Assumes the type is a struct and returns that struct.
     pub fn inner_struct_safe(&self) -> Option<&Arc<FinalizedStruct>> {
         return match self {
-            FinalizedTypes::Struct(structure) => Some(structure),
+            FinalizedTypes::Struct(structure) => Some(structure.finished()),
             FinalizedTypes::Reference(inner) => inner.inner_struct_safe(),
             FinalizedTypes::GenericType(inner, _) => inner.inner_struct_safe(),
             _ => None,
@@ -218,6 +265,85 @@ impl FinalizedTypes {
         };
     }
 
+    /// Checks if the type is a trait instead of a concrete struct.
+    pub fn is_trait(&self) -> bool {
+        return self.inner_struct_safe().map_or(false, |inner| is_modifier(inner.data.modifiers, Modifier::Trait));
+    }
+
+    /// Checks if the type is an enum of variants.
+    pub fn is_enum(&self) -> bool {
+        return self.inner_struct_safe().map_or(false, |inner| is_modifier(inner.data.modifiers, Modifier::Enum));
+    }
+
+    /// Checks if the type is the core Option enum, with or without its generics solidified.
+    pub fn is_option(&self, syntax: &Syntax) -> bool {
+        // Solidified generic structs keep the id of the struct they were solidified from
+        let option = match syntax.structures.types.get(OPTION) {
+            Some(option) => option.id,
+            None => return false,
+        };
+        return self.is_enum() && self.inner_struct_safe().is_some_and(|inner| inner.data.id == option);
+    }
+
+    /// Checks if the type is the core Rc struct, with or without its generics solidified.
+    pub fn is_rc(&self) -> bool {
+        return !self.is_trait()
+            && self.inner_struct_safe().is_some_and(|inner| inner.data.name.split('$').next() == Some(RC));
+    }
+
+    /// Gets the errors the type's struct was poisoned with, if it failed to verify.
+    pub fn poisoned(&self) -> &[ParsingError] {
+        return self.inner_struct_safe().map_or(&[], |inner| inner.data.poisoned.as_slice());
+    }
+
+    /// Checks if the type is one of the internal integer types.
+    pub fn is_integer(&self) -> bool {
+        return self.inner_struct_safe().is_some_and(|inner| get_integer(&inner.data.name).is_some());
+    }
+
+    /// Checks if the type is the void type, which functions return when they return nothing.
+    pub fn is_void(&self) -> bool {
+        return self.inner_struct_safe().is_some_and(|inner| inner.data.name == VOID.data.name);
+    }
+
+    /// Gets the parameters and return type of a function type, ignoring references.
+    pub fn as_function(&self) -> Option<(&Vec<FinalizedTypes>, &FinalizedTypes)> {
+        return match self {
+            FinalizedTypes::Reference(inner) => inner.as_function(),
+            FinalizedTypes::Function(arguments, returning) => Some((arguments, returning)),
+            _ => None,
+        };
+    }
+
+    /// Checks if the type is an array, ignoring references.
+    pub fn is_array(&self) -> bool {
+        return match self {
+            FinalizedTypes::Reference(inner) => inner.is_array(),
+            FinalizedTypes::Array(_, _) => true,
+            _ => false,
+        };
+    }
+
+    /// Gets the length of the array if it's a known constant, ignoring references.
+    pub fn array_length(&self) -> Option<u64> {
+        return match self {
+            FinalizedTypes::Reference(inner) => inner.array_length(),
+            FinalizedTypes::Array(_, Some(length)) => match **length {
+                FinalizedTypes::Const(length) => Some(length),
+                _ => None,
+            },
+            _ => None,
+        };
+    }
+
+    /// Gets the type behind any references, like Point for a &Point.
+    pub fn dereferenced(&self) -> &FinalizedTypes {
+        return match self {
+            FinalizedTypes::Reference(inner) => inner.dereferenced(),
+            _ => self,
+        };
+    }
+
     /// Checks if the type is of the other type, following Raven's type rules.
     /// May block until all implementations are finished parsing, must not be called from
     /// implementation parsing to prevent deadlocking.
@@ -232,6 +358,53 @@ impl FinalizedTypes {
         };
     }
 
+    /// Explains why the type isn't of the other type, like a method of the trait it's missing.
+    /// This searches every impl, so it's only for errors once of_type_sync has already failed.
+    /// Returns None if there's no simple reason, like when a blanket impl could've matched.
+    pub fn explain_mismatch(&self, other: &FinalizedTypes, syntax: &Syntax) -> Option<TypeMismatch> {
+        let types = self.dereferenced();
+        let other = other.dereferenced();
+        if let FinalizedTypes::Generic(_, bounds) = other {
+            return bounds
+                .iter()
+                .find(|bound| types.explain_mismatch(bound, syntax).is_some())
+                .map(|bound| TypeMismatch::UnsatisfiedBound(bound.clone()));
+        }
+        let traits = match other.inner_struct_safe() {
+            Some(traits) if other.is_trait() && types.inner_struct_safe().is_some() => &traits.data,
+            _ => return None,
+        };
+
+        let mut methods = Vec::default();
+        for implementor in syntax.struct_implementations.get(types).into_iter().flatten() {
+            methods.extend(implementor.functions.iter());
+        }
+        for implementor in &syntax.implementations {
+            if implementor.target.inner_struct_safe().map(|inner| &inner.data) == Some(traits) {
+                if implementor.base == *types {
+                    // Implemented, but with other generics, like Add<u64> instead of Add<Meters>
+                    return Some(TypeMismatch::WrongGeneric(implementor.target.clone()))
+                        .filter(|_| implementor.target != *other);
+                } else if implementor.base.inner_struct_safe().is_none() {
+                    return None;
+                }
+            }
+            if implementor.base == *types {
+                methods.extend(implementor.functions.iter());
+            }
+        }
+
+        let short_name = |name: &str| name.split("::").last().unwrap().to_string();
+        return match traits
+            .functions
+            .iter()
+            .find(|declared| !methods.iter().any(|method| short_name(&method.name) == short_name(&declared.name)))
+        {
+            Some(missing) => Some(TypeMismatch::MissingMethod(short_name(&missing.name), other.clone())),
+            None => Some(TypeMismatch::MissingImpl(other.clone())),
+        };
+    }
+
     /// This method doesn't block, instead it returns a future which can be waited on if a blocking
     /// result is wanted. This waiter is only there is syntax is Some.
     // skipcq: RS-R1000 Match statements have complexity calculated incorrectly
@@ -240,12 +413,16 @@ impl FinalizedTypes {
         other: &FinalizedTypes,
         syntax: Option<Arc<Mutex<Syntax>>>,
     ) -> (bool, Option<Pin<Box<dyn Future<Output = bool> + Send + Sync>>>) {
+        // Poisoned types already have an error, so they match any type to avoid more errors.
+        if !self.poisoned().is_empty() || !other.poisoned().is_empty() {
+            return (true, None);
+        }
         return match self {
             FinalizedTypes::Struct(found) => match other {
                 FinalizedTypes::Struct(other_struct) => {
-                    if found == other_struct {
+                    if found == other_struct || found.extends(other_struct) {
                         (true, None)
-                    } else if is_modifier(other.inner_struct().data.modifiers, Modifier::Trait) {
+                    } else if other.is_trait() {
                         if syntax.is_none() {
                             return (false, None);
                         }
@@ -279,15 +456,31 @@ impl FinalizedTypes {
                     }
                     (true, None)
                 }
+                // Constant arguments are part of the type, so a Buffer<8> isn't a Buffer<16>.
+                FinalizedTypes::GenericType(_, bounds)
+                    if found.arguments.iter().zip(bounds).any(|(argument, bound)| {
+                        matches!((argument, bound), (FinalizedTypes::Const(argument), FinalizedTypes::Const(bound)) if argument != bound)
+                    }) =>
+                {
+                    (false, None)
+                }
                 // For structures vs generic types, just check the base.
                 FinalizedTypes::GenericType(base, _) => self.of_type_sync(base, syntax),
                 // References are ignored for type checking.
                 FinalizedTypes::Reference(inner) => self.of_type_sync(inner, syntax),
-                FinalizedTypes::Array(_) => (false, None),
+                FinalizedTypes::Array(_, _)
+                | FinalizedTypes::Const(_)
+                | FinalizedTypes::ConstType(_)
+                | FinalizedTypes::Function(_, _) => (false, None),
             },
-            FinalizedTypes::Array(inner) => match other {
+            FinalizedTypes::Array(inner, length) => match other {
+                // A fixed length array can't be used where a different length is expected.
+                FinalizedTypes::Array(other, Some(other_length)) => match length {
+                    Some(length) if length.of_type_sync(other_length, None).0 => inner.of_type_sync(other, syntax),
+                    _ => (false, None),
+                },
                 // Check the inner type.
-                FinalizedTypes::Array(other) => inner.of_type_sync(other, syntax),
+                FinalizedTypes::Array(other, _) => inner.of_type_sync(other, syntax),
                 // References are ignored for type checking.
                 FinalizedTypes::Reference(other) => self.of_type_sync(other, syntax),
                 // Only arrays can equal arrays
@@ -341,18 +534,60 @@ impl FinalizedTypes {
                 FinalizedTypes::Struct(_) => base.of_type_sync(other, syntax),
                 // References are ignored for type checking.
                 FinalizedTypes::Reference(inner) => self.of_type_sync(inner, syntax),
-                FinalizedTypes::Array(_) => (false, None),
+                FinalizedTypes::Array(_, _)
+                | FinalizedTypes::Const(_)
+                | FinalizedTypes::ConstType(_)
+                | FinalizedTypes::Function(_, _) => (false, None),
             },
             // References are ignored for type checking.
             FinalizedTypes::Reference(referencing) => referencing.of_type_sync(other, syntax),
+            FinalizedTypes::Const(value) => match other {
+                FinalizedTypes::Const(other_value) => (value == other_value, None),
+                FinalizedTypes::ConstType(_) => (true, None),
+                FinalizedTypes::Generic(_, bounds) => (bounds.iter().all(|bound| self.of_type_sync(bound, None).0), None),
+                FinalizedTypes::Reference(inner) => self.of_type_sync(inner, syntax),
+                _ => (false, None),
+            },
+            FinalizedTypes::ConstType(inner) => match other {
+                FinalizedTypes::ConstType(other) => inner.of_type_sync(other, syntax),
+                FinalizedTypes::Reference(other) => self.of_type_sync(other, syntax),
+                _ => (false, None),
+            },
+            FinalizedTypes::Function(arguments, returning) => match other {
+                // Functions must take the same number of arguments, and each argument and the return type must match.
+                FinalizedTypes::Function(other_arguments, other_returning) => {
+                    if arguments.len() != other_arguments.len() {
+                        return (false, None);
+                    }
+
+                    let mut fails = Vec::default();
+                    let pairs = arguments.iter().zip(other_arguments).chain([(&**returning, &**other_returning)]);
+                    for (found, other) in pairs {
+                        let (result, future) = found.of_type_sync(other, syntax.clone());
+                        if !result {
+                            if let Some(found) = future {
+                                fails.push(found);
+                            } else {
+                                return (false, None);
+                            }
+                        }
+                    }
+                    if !fails.is_empty() {
+                        return (false, Some(Box::pin(Self::join(fails))));
+                    }
+                    (true, None)
+                }
+                FinalizedTypes::Reference(other) => self.of_type_sync(other, syntax),
+                _ => (false, None),
+            },
             FinalizedTypes::Generic(_, bounds) => match other {
                 FinalizedTypes::Generic(_, other_bounds) => {
                     let mut outer_fails: Vec<Pin<Box<dyn Future<Output = bool> + Send + Sync>>> = Vec::default();
-                    // For two generics to be the same, each bound must match at least one other bound.
-                    'outer: for bound in bounds {
+                    // Each of the other generic's bounds must be met by at least one of this generic's bounds.
+                    'outer: for other_bound in other_bounds {
                         let mut fails = Vec::default();
-                        for other_bound in other_bounds {
-                            let (result, failure) = other_bound.of_type_sync(bound, syntax.clone());
+                        for bound in bounds {
+                            let (result, failure) = bound.of_type_sync(other_bound, syntax.clone());
                             if result {
                                 continue 'outer;
                             } else if let Some(found) = failure {
@@ -372,7 +607,13 @@ impl FinalizedTypes {
                     (true, None)
                 }
                 FinalizedTypes::Reference(inner) => self.of_type_sync(inner, syntax),
-                FinalizedTypes::Struct(_) | FinalizedTypes::GenericType(_, _) | FinalizedTypes::Array(_) => {
+                // Constants are checked against the generic's bounds.
+                FinalizedTypes::Const(_) => other.of_type_sync(self, syntax),
+                FinalizedTypes::Struct(_)
+                | FinalizedTypes::GenericType(_, _)
+                | FinalizedTypes::Array(_, _)
+                | FinalizedTypes::ConstType(_)
+                | FinalizedTypes::Function(_, _) => {
                     if bounds.is_empty() {
                         return (true, None);
                     }
@@ -419,7 +660,7 @@ impl FinalizedTypes {
         &self,
         other: &FinalizedTypes,
         syntax: &Arc<Mutex<Syntax>>,
-        generics: &mut HashMap<String, FinalizedTypes>,
+        generics: &mut IndexMap<String, FinalizedTypes>,
         bounds_error: Span,
     ) -> Result<(), ParsingError> {
         if !self.of_type_sync(other, None).0 && self.inner_struct_safe().is_some() {
@@ -431,8 +672,15 @@ impl FinalizedTypes {
                     error: bounds_error.make_error(ParsingMessage::ShouldntSee("Resolve generic")),
                 };
                 match waiter.await {
-                    Ok(implementors) => {
+                    Ok(mut implementors) => {
+                        // Impls of supertraits match too, but only this trait's own impl resolves its generics
                         if implementors.len() > 1 {
+                            let data = self.inner_struct_safe().map(|inner| &inner.data);
+                            implementors.retain(|(implementor, _)| {
+                                implementor.target.inner_struct_safe().map(|inner| &inner.data) == data
+                            });
+                        }
+                        if implementors.len() != 1 {
                             panic!("Ambiguous impl! Raven can't handle this yet!");
                         }
                         self.resolve_generic(&implementors[0].0.target, syntax, generics, bounds_error.clone()).await?;
@@ -477,19 +725,36 @@ impl FinalizedTypes {
             FinalizedTypes::Reference(inner) => {
                 return inner.resolve_generic(other, syntax, generics, bounds_error).await;
             }
-            FinalizedTypes::Array(inner) => {
+            FinalizedTypes::Array(inner, length) => {
                 let mut other = other;
                 // Ignore references.
                 while let FinalizedTypes::Reference(inner) = other {
                     other = inner;
                 }
-                // Check on the inner type.
-                if let FinalizedTypes::Array(other) = other {
+                // Check on the inner type, and the length for const generics like [T; N].
+                if let FinalizedTypes::Array(other, other_length) = other {
+                    if let (Some(length), Some(other_length)) = (length, other_length) {
+                        length.resolve_generic(other_length, syntax, generics, bounds_error.clone()).await?;
+                    }
                     return inner.resolve_generic(other, syntax, generics, bounds_error).await;
                 }
 
                 return Err(bounds_error.make_error(ParsingMessage::MismatchedTypes(other.clone(), *inner.clone())));
             }
+            FinalizedTypes::Function(arguments, returning) => {
+                // Check the arguments and return type against the other function's.
+                if let Some((other_arguments, other_returning)) = other.as_function() {
+                    if arguments.len() != other_arguments.len() {
+                        return Err(bounds_error.make_error(ParsingMessage::MismatchedTypes(other.clone(), self.clone())));
+                    }
+                    for (argument, other_argument) in arguments.iter().zip(other_arguments) {
+                        argument.resolve_generic(other_argument, syntax, generics, bounds_error.clone()).await?;
+                    }
+                    return returning.resolve_generic(other_returning, syntax, generics, bounds_error).await;
+                }
+
+                return Err(bounds_error.make_error(ParsingMessage::MismatchedTypes(other.clone(), self.clone())));
+            }
             _ => {}
         }
         return Ok(());
@@ -500,7 +765,10 @@ impl FinalizedTypes {
         return match self {
             FinalizedTypes::Struct(structs) => structs.data.name.clone(),
             FinalizedTypes::Reference(structs) => structs.name(),
-            FinalizedTypes::Array(inner) => format!("[{}]", inner.name()),
+            FinalizedTypes::Array(inner, _) => format!("[{}]", inner.name()),
+            FinalizedTypes::Const(value) => value.to_string(),
+            FinalizedTypes::ConstType(inner) => format!("const {}", inner.name()),
+            FinalizedTypes::Function(_, _) => self.to_string(),
             FinalizedTypes::Generic(name, _) => {
                 panic!("Generics should never be named, tried to get {}", name)
             }
@@ -508,25 +776,97 @@ impl FinalizedTypes {
         };
     }
 
+    /// Renders the type the way it's written in code, like Vec<i64> or &T, for error messages.
+    pub fn display_with_generics(&self) -> String {
+        return match self {
+            // Solidified generic structs are named like Vec$i64, but those generics are shown by GenericType
+            FinalizedTypes::Struct(structure) => structure.data.name.split('$').next().unwrap().to_string(),
+            FinalizedTypes::Reference(inner) => format!("&{}", inner.display_with_generics()),
+            FinalizedTypes::Array(inner, Some(length)) => {
+                format!("[{}; {}]", inner.display_with_generics(), length.display_with_generics())
+            }
+            FinalizedTypes::Array(inner, None) => format!("[{}]", inner.display_with_generics()),
+            FinalizedTypes::Generic(name, _) => name.clone(),
+            FinalizedTypes::GenericType(base, generics) => format!(
+                "{}<{}>",
+                base.display_with_generics(),
+                generics.iter().map(|generic| generic.display_with_generics()).collect::<Vec<_>>().join(", ")
+            ),
+            FinalizedTypes::Const(value) => value.to_string(),
+            FinalizedTypes::ConstType(inner) => format!("const {}", inner.display_with_generics()),
+            FinalizedTypes::Function(arguments, returning) => {
+                let arguments = arguments.iter().map(|argument| argument.display_with_generics()).collect::<Vec<_>>();
+                if returning.is_void() {
+                    format!("fn({})", arguments.join(", "))
+                } else {
+                    format!("fn({}) -> {}", arguments.join(", "), returning.display_with_generics())
+                }
+            }
+        };
+    }
+
     /// The name of the function, not erroring if the name can't be gotten
     /// Can be used to check if a type is generic or not
     pub fn name_safe(&self) -> Option<String> {
         return match self {
             FinalizedTypes::Struct(structs) => Some(structs.data.name.clone()),
             FinalizedTypes::Reference(structs) => structs.name_safe(),
-            FinalizedTypes::Array(inner) => inner.name_safe().map(|inner| format!("[{}]", inner)),
+            FinalizedTypes::Array(inner, _) => inner.name_safe().map(|inner| format!("[{}]", inner)),
+            FinalizedTypes::Const(value) => Some(value.to_string()),
+            FinalizedTypes::ConstType(inner) => inner.name_safe().map(|inner| format!("const {}", inner)),
+            FinalizedTypes::Function(arguments, returning) => {
+                let arguments = arguments.iter().map(|argument| argument.name_safe()).collect::<Option<Vec<_>>>()?;
+                Some(format!("fn({}) -> {}", arguments.join(", "), returning.name_safe()?))
+            }
             FinalizedTypes::Generic(_, _) => None,
             FinalizedTypes::GenericType(_, _) => None,
         };
     }
 }
 
+/// Why a type isn't of a trait or generic, explained in errors about the mismatch
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeMismatch {
+    /// None of the type's impls have one of the trait's methods
+    MissingMethod(String, FinalizedTypes),
+    /// The type implements the trait, but with other generics
+    WrongGeneric(FinalizedTypes),
+    /// The type doesn't meet one of the generic's bounds
+    UnsatisfiedBound(FinalizedTypes),
+    /// The type has every method of the trait, but doesn't implement it
+    MissingImpl(FinalizedTypes),
+}
+
+impl Display for TypeMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            TypeMismatch::MissingMethod(method, traits) => {
+                write!(f, "it has no method {}, which {} requires", method, traits.display_with_generics())
+            }
+            TypeMismatch::WrongGeneric(found) => write!(f, "it implements {} instead", found.display_with_generics()),
+            TypeMismatch::UnsatisfiedBound(bound) => {
+                write!(f, "it doesn't implement the bound {}", bound.display_with_generics())
+            }
+            TypeMismatch::MissingImpl(traits) => {
+                write!(f, "it has every method {} requires, but doesn't implement it", traits.display_with_generics())
+            }
+        };
+    }
+}
+
 impl Display for Types {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Types::Struct(structure) => write!(f, "{}", structure.name),
             Types::Reference(structure) => write!(f, "{}", structure),
-            Types::Array(inner) => write!(f, "[{}]", inner),
+            Types::Array(inner, Some(length)) => write!(f, "[{}; {}]", inner, length),
+            Types::Array(inner, None) => write!(f, "[{}]", inner),
+            Types::Const(value) => write!(f, "{}", value),
+            Types::ConstType(inner) => write!(f, "const {}", inner),
+            Types::Function(arguments, Some(returning)) => {
+                write!(f, "fn({}) -> {}", display_parenless(arguments, ", "), returning)
+            }
+            Types::Function(arguments, None) => write!(f, "fn({})", display_parenless(arguments, ", ")),
             Types::Generic(name, bounds) => write!(f, "{}: {}", name, display(bounds, " + ")),
             Types::GenericType(types, generics) => {
                 write!(f, "{}<{}>", types, display_parenless(generics, ", "))
@@ -540,7 +880,16 @@ impl Display for FinalizedTypes {
         match self {
             FinalizedTypes::Struct(structure) => write!(f, "{}", structure.data.name),
             FinalizedTypes::Reference(structure) => write!(f, "{}", structure),
-            FinalizedTypes::Array(inner) => write!(f, "[{}]", inner),
+            FinalizedTypes::Array(inner, Some(length)) => write!(f, "[{}; {}]", inner, length),
+            FinalizedTypes::Array(inner, None) => write!(f, "[{}]", inner),
+            FinalizedTypes::Const(value) => write!(f, "{}", value),
+            FinalizedTypes::ConstType(inner) => write!(f, "const {}", inner),
+            FinalizedTypes::Function(arguments, returning) if returning.is_void() => {
+                write!(f, "fn({})", display_parenless(arguments, ", "))
+            }
+            FinalizedTypes::Function(arguments, returning) => {
+                write!(f, "fn({}) -> {}", display_parenless(arguments, ", "), returning)
+            }
             FinalizedTypes::Generic(name, bounds) => {
                 write!(f, "{}: {}", name, display(bounds, " + "))
             }
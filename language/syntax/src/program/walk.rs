@@ -0,0 +1,82 @@
+use crate::program::code::{FinalizedEffectType, FinalizedEffects};
+use crate::program::function::FinalizedCodeBody;
+
+/// Calls the visitor on every effect in the code, each before the effects inside of it, with the effect it's inside of.
+/// The effects inside of an effect are only walked if the visitor returns true for it.
+pub fn walk_effects<'a, F>(code: &'a FinalizedCodeBody, visitor: &mut F)
+where
+    F: FnMut(&'a FinalizedEffects, Option<&'a FinalizedEffects>) -> bool,
+{
+    for expression in &code.expressions {
+        walk_effect(&expression.effect, None, visitor);
+    }
+}
+
+/// Calls the visitor on the effect and every effect inside of it, like walk_effects
+pub fn walk_effect<'a, F>(effect: &'a FinalizedEffects, parent: Option<&'a FinalizedEffects>, visitor: &mut F)
+where
+    F: FnMut(&'a FinalizedEffects, Option<&'a FinalizedEffects>) -> bool,
+{
+    if !visitor(effect, parent) {
+        return;
+    }
+    for inner in inner_effects(effect) {
+        walk_effect(inner, Some(effect), visitor);
+    }
+}
+
+/// The effects directly inside of the effect, in the order they run
+pub fn inner_effects(effect: &FinalizedEffects) -> Vec<&FinalizedEffects> {
+    let mut inner = Vec::default();
+    match &effect.types {
+        FinalizedEffectType::CodeBody(body) | FinalizedEffectType::Closure(_, _, _, body) => {
+            inner.extend(body.expressions.iter().map(|expression| &expression.effect));
+        }
+        FinalizedEffectType::CreateVariable(_, value, _)
+        | FinalizedEffectType::CompareJump(value, _, _)
+        | FinalizedEffectType::Switch(value, _)
+        | FinalizedEffectType::Load(value, _, _)
+        | FinalizedEffectType::Downcast(value, _, _)
+        | FinalizedEffectType::HeapStore(value)
+        | FinalizedEffectType::ReferenceLoad(value)
+        | FinalizedEffectType::StackStore(value)
+        | FinalizedEffectType::OverflowCheck(value)
+        | FinalizedEffectType::BoundsCheck(value)
+        | FinalizedEffectType::VariantCheck(value, _)
+        | FinalizedEffectType::Cast(value, _, _) => inner.push(value.as_ref()),
+        FinalizedEffectType::Set(base, value) => {
+            inner.push(base.as_ref());
+            inner.push(value.as_ref());
+        }
+        FinalizedEffectType::MethodCall(calling, _, arguments, _) => {
+            inner.extend(calling.as_deref());
+            inner.extend(arguments);
+        }
+        FinalizedEffectType::ClosureCall(calling, arguments) => {
+            inner.push(calling.as_ref());
+            inner.extend(arguments);
+        }
+        FinalizedEffectType::GenericMethodCall(_, _, arguments)
+        | FinalizedEffectType::VirtualCall(_, _, arguments, _)
+        | FinalizedEffectType::GenericVirtualCall(_, _, _, arguments, _)
+        | FinalizedEffectType::CreateArray(_, arguments) => inner.extend(arguments),
+        FinalizedEffectType::CreateStruct(storing, _, fields) => {
+            inner.extend(storing.as_deref());
+            inner.extend(fields.iter().map(|(_, field)| field));
+        }
+        FinalizedEffectType::NOP
+        | FinalizedEffectType::Jump(_)
+        | FinalizedEffectType::LoadVariable(_)
+        | FinalizedEffectType::Float(_)
+        | FinalizedEffectType::Int(_)
+        | FinalizedEffectType::TypedInt(_, _)
+        | FinalizedEffectType::Bool(_)
+        | FinalizedEffectType::StringLiteral(_)
+        | FinalizedEffectType::Char(_)
+        | FinalizedEffectType::HeapAllocate(_)
+        | FinalizedEffectType::Poison(_)
+        | FinalizedEffectType::StaticAssert(_)
+        | FinalizedEffectType::ConstValue(_) => {}
+    }
+    return inner;
+}
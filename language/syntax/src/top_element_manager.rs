@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::future::Future;
 use std::ops::AsyncFnMut;
 use std::pin::{pin, Pin};
@@ -9,7 +10,7 @@ use std::task::{Context, Poll, Waker};
 use data::tokens::Span;
 
 use crate::async_util::NameResolver;
-use crate::errors::ParsingError;
+use crate::errors::{ErrorSource, ParsingError, ParsingMessage};
 use crate::program::code::FinalizedEffects;
 use crate::program::function::FunctionData;
 use crate::program::syntax::Syntax;
@@ -25,12 +26,36 @@ pub struct GetterManager {
     pub parsing_impls: u32,
     /// Impl waiters, which are woken whenever an impl finishes parsing.
     pub impl_waiters: Vec<Waker>,
+    /// What the impl waiters are waiting for, cleared whenever they're woken
+    pub pending_impls: Vec<PendingImpl>,
     /// The target method to compile
     pub target: String,
     /// Waker to wake when the target method is found
     pub target_waker: Option<Waker>,
 }
 
+/// A request that's waiting for impls to finish parsing, kept to debug builds that never finish
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PendingImpl {
+    /// The trait being resolved, if the request is for a trait
+    pub trait_name: Option<String>,
+    /// The type that needs an impl
+    pub types: String,
+    /// The method being called, if the request is for a method
+    pub method: Option<String>,
+}
+
+impl Display for PendingImpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match (&self.trait_name, &self.method) {
+            (Some(trait_name), Some(method)) => write!(f, "method {} of {} for {}", method, trait_name, self.types),
+            (Some(trait_name), None) => write!(f, "impl of {} for {}", trait_name, self.types),
+            (None, Some(method)) => write!(f, "method {} for {}", method, self.types),
+            (None, None) => write!(f, "impl for {}", self.types),
+        };
+    }
+}
+
 /// Waits for an implementation of the type
 pub struct ImplWaiter {
     /// The program
@@ -43,16 +68,53 @@ pub struct ImplWaiter {
     pub error: ParsingError,
 }
 
+/// Whether a type satisfies a trait bound, found by searching every impl
+#[derive(Clone)]
+pub enum BoundSatisfaction {
+    /// The impls of the trait for the type, with their methods
+    Satisfied(Vec<(Arc<FinishedTraitImplementor>, Vec<Arc<FunctionData>>)>),
+    /// No impl of the trait is for the type
+    Unsatisfied,
+}
+
 impl Future for ImplWaiter {
     type Output = Result<Vec<(Arc<FinishedTraitImplementor>, Vec<Arc<FunctionData>>)>, ParsingError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let finished = self.syntax.lock().unwrap().finished_impls();
+        // A poisoned struct's impls can't be trusted, so its original error is reported where it's used
+        if let Some(error) = self.base_type.poisoned().first() {
+            return Poll::Ready(Err(self.error.span.make_error(error.message.clone())));
+        }
+        let key = (self.base_type.clone(), self.trait_type.clone());
+        let finished = {
+            let mut locked = self.syntax.lock().unwrap();
+            if let Some(found) = locked.bounds.get(&key).cloned() {
+                locked.bound_hits += 1;
+                return Poll::Ready(match found {
+                    BoundSatisfaction::Satisfied(implementations) => Ok(implementations),
+                    BoundSatisfaction::Unsatisfied => Err(self.error.clone()),
+                });
+            }
+            locked.finished_impls()
+        };
         let output = match pin!(Syntax::get_implementation_methods(&self.syntax, &self.base_type, &self.trait_type)).poll(cx)
         {
             Poll::Ready(inner) => inner,
             Poll::Pending => return Poll::Pending,
         };
+
+        {
+            let mut locked = self.syntax.lock().unwrap();
+            locked.bound_checks += 1;
+            // Before every impl is parsed, an unparsed impl could still change the answer
+            if finished {
+                let satisfaction = match &output {
+                    Some(implementations) => BoundSatisfaction::Satisfied(implementations.clone()),
+                    None => BoundSatisfaction::Unsatisfied,
+                };
+                locked.bounds.insert(key, satisfaction);
+            }
+        }
         return match output {
             Some(implementation_methods) => Poll::Ready(Ok(implementation_methods)),
             None => {
@@ -65,6 +127,11 @@ impl Future for ImplWaiter {
                         cx.waker().wake_by_ref();
                     }
                     locked.async_manager.impl_waiters.push(cx.waker().clone());
+                    locked.async_manager.pending_impls.push(PendingImpl {
+                        trait_name: Some(self.trait_type.display_with_generics()),
+                        types: self.base_type.display_with_generics(),
+                        method: None,
+                    });
                     Poll::Pending
                 }
             }
@@ -72,6 +139,51 @@ impl Future for ImplWaiter {
     }
 }
 
+/// Waits for every implementation to finish parsing
+pub struct FinishedImplsWaiter {
+    /// The program
+    pub syntax: Arc<Mutex<Syntax>>,
+}
+
+impl Future for FinishedImplsWaiter {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut locked = self.syntax.lock().unwrap();
+        if locked.finished_impls() {
+            return Poll::Ready(());
+        }
+        locked.async_manager.impl_waiters.push(cx.waker().clone());
+        return Poll::Pending;
+    }
+}
+
+/// Waits for the next impl to finish parsing, so a request none of the current impls satisfied can be retried
+pub struct NextImplWaiter {
+    /// The program
+    pub syntax: Arc<Mutex<Syntax>>,
+    /// What's being waited for
+    pub pending: PendingImpl,
+    /// If this has already been added to the impl waiters
+    pub waiting: bool,
+}
+
+impl Future for NextImplWaiter {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut locked = self.syntax.lock().unwrap();
+        if self.waiting || locked.finished_impls() {
+            return Poll::Ready(());
+        }
+        locked.async_manager.impl_waiters.push(cx.waker().clone());
+        locked.async_manager.pending_impls.push(self.pending.clone());
+        drop(locked);
+        self.waiting = true;
+        return Poll::Pending;
+    }
+}
+
 /// Waits for an implementation of the trait matching the constraints
 pub struct TraitImplWaiter<F> {
     /// The program
@@ -100,13 +212,19 @@ impl<F: AsyncFnMut(Arc<FinishedTraitImplementor>, Arc<FunctionData>) -> Result<F
         return match value {
             Poll::Ready(inner) => match inner {
                 Ok(inner) => {
+                    // Every imported trait's impl is tried, because the method is ambiguous if more than one matches
+                    let mut matched: Vec<(Arc<FinishedTraitImplementor>, FinalizedEffects)> = Vec::default();
                     match inner {
                         Some(found) => {
                             for (types, trying) in found {
                                 for func in trying {
                                     match pin!((self.checker)(types.clone(), func)).poll(cx) {
                                         Poll::Ready(found) => match found {
-                                            Ok(found) => return Poll::Ready(Ok(found)),
+                                            Ok(found) => {
+                                                if !matched.iter().any(|(other, _)| Arc::ptr_eq(other, &types)) {
+                                                    matched.push((types.clone(), found));
+                                                }
+                                            }
                                             Err(_) => {}
                                         },
                                         Poll::Pending => return Poll::Pending,
@@ -116,25 +234,49 @@ impl<F: AsyncFnMut(Arc<FinishedTraitImplementor>, Arc<FunctionData>) -> Result<F
                         }
                         None => {}
                     }
+                    if matched.len() > 1 {
+                        let traits = matched.iter().map(|(implementor, _)| implementor.target.clone()).collect();
+                        let error =
+                            self.error.span.make_error(ParsingMessage::AmbiguousTraitMethod(self.method.clone(), traits));
+                        return Poll::Ready(Err(error));
+                    }
+                    // A match is only certain once every impl is parsed, since a later one could make it ambiguous
                     if finished {
-                        println!("Failed!");
-                        Poll::Ready(Err(self.error.clone()))
+                        match matched.pop() {
+                            Some((_, found)) => Poll::Ready(Ok(found)),
+                            None => {
+                                println!("Failed!");
+                                Poll::Ready(Err(self.error.clone()))
+                            }
+                        }
                     } else {
-                        self.syntax.lock().unwrap().async_manager.impl_waiters.push(cx.waker().clone());
+                        self.wait(cx);
                         Poll::Pending
                     }
                 }
                 Err(error) => return Poll::Ready(Err(error)),
             },
             Poll::Pending => {
-                let mut locked = self.syntax.lock().unwrap();
-                locked.async_manager.impl_waiters.push(cx.waker().clone());
+                self.wait(cx);
                 Poll::Pending
             }
         };
     }
 }
 
+impl<F> TraitImplWaiter<F> {
+    /// Waits to be woken by the next impl that finishes parsing
+    fn wait(&self, cx: &mut Context<'_>) {
+        let mut locked = self.syntax.lock().unwrap();
+        locked.async_manager.impl_waiters.push(cx.waker().clone());
+        locked.async_manager.pending_impls.push(PendingImpl {
+            trait_name: None,
+            types: self.return_type.display_with_generics(),
+            method: Some(self.method.clone()),
+        });
+    }
+}
+
 impl<T> Unpin for TraitImplWaiter<T> {}
 
 /// Finds all the implementations of the type
@@ -202,6 +344,11 @@ impl Future for TypeImplementsTypeWaiter {
 
         if !locked.finished_impls() {
             locked.async_manager.impl_waiters.push(cx.waker().clone());
+            locked.async_manager.pending_impls.push(PendingImpl {
+                trait_name: Some(self.other.display_with_generics()),
+                types: self.current.display_with_generics(),
+                method: None,
+            });
             return Poll::Pending;
         }
 
@@ -70,6 +70,8 @@ fn get_token(last: &TokenTypes, token_type: &TokenTypes) -> u32 {
         | TokenTypes::ReturnType
         | TokenTypes::ArgumentName
         | TokenTypes::ReturnTypeArrow
+        | TokenTypes::WhereType
+        | TokenTypes::WhereBound
         | TokenTypes::Generic => SemanticTokenTypes::Type,
         TokenTypes::Variable | TokenTypes::ImplStart | TokenTypes::TraitStart => SemanticTokenTypes::Property,
         TokenTypes::Modifier
@@ -77,6 +79,7 @@ fn get_token(last: &TokenTypes, token_type: &TokenTypes) -> u32 {
         | TokenTypes::GenericsEnd
         | TokenTypes::GenericBound
         | TokenTypes::GenericBoundEnd
+        | TokenTypes::GenericDefault
         | TokenTypes::For
         | TokenTypes::If
         | TokenTypes::True
@@ -84,14 +87,20 @@ fn get_token(last: &TokenTypes, token_type: &TokenTypes) -> u32 {
         | TokenTypes::Else
         | TokenTypes::Do
         | TokenTypes::While
-        | TokenTypes::In => SemanticTokenTypes::Keyword,
+        | TokenTypes::Break
+        | TokenTypes::Continue
+        | TokenTypes::In
+        | TokenTypes::As
+        | TokenTypes::Where => SemanticTokenTypes::Keyword,
         TokenTypes::Comment => SemanticTokenTypes::Comment,
         TokenTypes::ImportStart | TokenTypes::Return | TokenTypes::New | TokenTypes::FunctionStart | TokenTypes::Let => {
             SemanticTokenTypes::Keyword
         }
         TokenTypes::Integer | TokenTypes::Float => SemanticTokenTypes::Number,
         TokenTypes::CallingType | TokenTypes::Attribute => SemanticTokenTypes::Function,
-        TokenTypes::StringStart | TokenTypes::StringEnd | TokenTypes::StringEscape => SemanticTokenTypes::String,
+        TokenTypes::StringStart | TokenTypes::StringEnd | TokenTypes::StringEscape | TokenTypes::InterpolationStart => {
+            SemanticTokenTypes::String
+        }
         _ => SemanticTokenTypes::None,
     } as u32;
     return temp;
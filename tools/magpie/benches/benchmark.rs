@@ -1,15 +1,213 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{env, fs};
 
-fn fibonacci(n: u64) -> u64 {
-    match n {
-        0 => 1,
-        1 => 1,
-        n => fibonacci(n - 1) + fibonacci(n - 2),
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use checker::output::{CodeScheduling, TypesChecker};
+use data::{Arguments, CompilerArguments, RunnerSettings, SourceSet};
+use parser::{parse, FileSourceSet};
+use syntax::async_util::HandleWrapper;
+use syntax::program::syntax::Syntax;
+
+/// Number of independent functions in the generated program
+static FUNCTIONS: usize = 500;
+
+/// Writes a program with many functions that don't depend on each other
+fn generate_program() -> PathBuf {
+    let folder = env::temp_dir().join("raven-verify-bench");
+    let _ = fs::create_dir_all(&folder);
+
+    let mut program = String::default();
+    for i in 0..FUNCTIONS {
+        program += &format!("fn function{}() -> bool {{\n    let value = {};\n    return value == {};\n}}\n\n", i, i, i);
+    }
+    fs::write(folder.join("verify.rv"), program).unwrap();
+    return folder;
+}
+
+/// Writes a program where every function does arithmetic on concrete integers, so each operator is an impl call
+fn generate_impl_program() -> PathBuf {
+    let folder = env::temp_dir().join("raven-impl-bench");
+    let _ = fs::create_dir_all(&folder);
+
+    let mut program = String::default();
+    for i in 0..FUNCTIONS {
+        program += &format!(
+            "fn function{}() -> bool {{\n    let value = {};\n    let other = value * 2 + 1;\n    \
+            let sum = other + value;\n    return sum - value == other;\n}}\n\n",
+            i, i
+        );
+    }
+    fs::write(folder.join("impls.rv"), program).unwrap();
+    return folder;
+}
+
+/// Writes a program where every generic function is called with the same type, so each call checks the same bound
+fn generate_generic_program() -> PathBuf {
+    let folder = env::temp_dir().join("raven-bounds-bench");
+    let _ = fs::create_dir_all(&folder);
+
+    let mut program = String::from(
+        "import bounds::Total;\n\ntrait Total {\n    fn total(self) -> u64;\n}\n\nstruct Counter {\n    count: u64;\n}\n\n\
+        impl Total for Counter {\n    fn total(self) -> u64 {\n        return self.count;\n    }\n}\n\n",
+    );
+    for i in 0..FUNCTIONS {
+        program += &format!(
+            "fn total{0}<T: Total>(value: T) -> u64 {{\n    return value.total() + {0};\n}}\n\n\
+            fn function{0}() -> bool {{\n    let counter = new Counter {{\n        count: {0},\n    }};\n    \
+            return total{0}(counter) == {1};\n}}\n\n",
+            i,
+            i * 2
+        );
+    }
+    fs::write(folder.join("bounds.rv"), program).unwrap();
+    return folder;
+}
+
+/// Writes a program where every function adds to its value over and over, so each line resolves the same impl method
+fn generate_repeated_program() -> PathBuf {
+    let folder = env::temp_dir().join("raven-repeated-bench");
+    let _ = fs::create_dir_all(&folder);
+
+    let mut program = String::default();
+    for i in 0..FUNCTIONS {
+        program += &format!("fn function{}() -> bool {{\n    let value = {};\n", i, i);
+        for _ in 0..10 {
+            program += "    value += 1;\n";
+        }
+        program += &format!("    return value == {};\n}}\n\n", i + 10);
+    }
+    fs::write(folder.join("repeated.rv"), program).unwrap();
+    return folder;
+}
+
+/// Gets the generated program and the libraries it needs
+fn sources(program: &PathBuf) -> Vec<Box<dyn SourceSet>> {
+    let lib = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../lib");
+    let platform = match env::consts::OS {
+        "windows" => "windows",
+        "macos" => "macos",
+        _ => "linux",
+    };
+
+    return vec![
+        Box::new(FileSourceSet { root: program.clone() }),
+        Box::new(FileSourceSet { root: lib.join("std/universal/src") }),
+        Box::new(FileSourceSet { root: lib.join("std").join(platform).join("src") }),
+        Box::new(FileSourceSet { root: lib.join("core/src") }),
+    ];
+}
+
+/// The arguments to verify the program without running anything
+fn arguments(program: &PathBuf) -> Arguments {
+    return Arguments::build_args(
+        false,
+        RunnerSettings {
+            sources: sources(program),
+            compiler_arguments: CompilerArguments {
+                compiler: "llvm".to_string(),
+                // No target, so nothing is compiled
+                target: String::default(),
+                temp_folder: env::temp_dir().join("raven-verify-bench").join("target"),
+                deny_warnings: false,
+                features: Vec::default(),
+                dump_ir: None,
+                bounds_checks: true,
+                reject_special_floats: false,
+                position_independent_vtables: false,
+            },
+        },
+    );
+}
+
+/// Parses and verifies the program, without running anything
+fn verify(program: &PathBuf) {
+    let arguments = arguments(program);
+    if arguments.cpu_runtime.block_on(runner::runner::run::<()>(&arguments)).is_err() {
+        panic!("Failed to verify the benchmark program!");
     }
 }
 
+/// Parses and checks the program with the scheduling, without compiling it, returning the checked program and its checker
+fn check(program: &PathBuf, scheduling: CodeScheduling) -> (Arc<Mutex<Syntax>>, TypesChecker) {
+    let arguments = arguments(program);
+    let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+    let mut checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+    checker.scheduling = scheduling;
+    let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker.clone()))));
+    arguments.cpu_runtime.block_on(async {
+        for source_set in &arguments.runner_settings.sources {
+            for file in source_set.get_files() {
+                if file.path().ends_with("rv") {
+                    parse(syntax.clone(), handle.clone(), source_set.relative(&*file), file).await;
+                }
+            }
+        }
+        syntax.lock().unwrap().finish();
+        while !handle.lock().unwrap().joining.iter().all(|joining| joining.is_finished()) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+    if !syntax.lock().unwrap().errors.is_empty() {
+        panic!("Failed to check the benchmark program!");
+    }
+    return (syntax, checker);
+}
+
+/// Checks the program, returning how many bound checks searched every impl and how many were answered from the cache
+fn count_bound_checks(program: &PathBuf) -> (usize, usize) {
+    let syntax = check(program, CodeScheduling::Concurrent).0;
+    let locked = syntax.lock().unwrap();
+    return (locked.bound_checks, locked.bound_hits);
+}
+
+/// Checks the program, returning how many impl methods were awaited and how many were answered from the cache
+fn count_function_awaits(program: &PathBuf) -> (usize, usize) {
+    let checker = check(program, CodeScheduling::Concurrent).1;
+    return (checker.functions.awaits.load(Ordering::Relaxed), checker.functions.hits.load(Ordering::Relaxed));
+}
+
+/// Compares verifying the code of functions one at a time against verifying it concurrently, then times resolving
+/// impl calls, checking generic bounds and resolving the same impl method repeatedly
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("fib 20", |b| b.iter(|| fibonacci(black_box(20))));
+    let program = generate_program();
+
+    let mut group = c.benchmark_group("verify functions");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| b.iter(|| check(&program, CodeScheduling::Sequential)));
+    group.bench_function("concurrent", |b| b.iter(|| check(&program, CodeScheduling::Concurrent)));
+    group.finish();
+
+    // Measures resolving the impl calls of monomorphic code, which goes through check_impl_call
+    let program = generate_impl_program();
+
+    let mut group = c.benchmark_group("resolve impl calls");
+    group.sample_size(10);
+    group.bench_function("monomorphic", |b| b.iter(|| verify(&program)));
+    group.finish();
+
+    // Measures checking the same bound at every call of a generic function, which only searches every impl once
+    let program = generate_generic_program();
+    let (checks, hits) = count_bound_checks(&program);
+    println!("Generic bounds: {} full bound checks, {} answered from the cache", checks, hits);
+
+    let mut group = c.benchmark_group("check generic bounds");
+    group.sample_size(10);
+    group.bench_function("generic", |b| b.iter(|| verify(&program)));
+    group.finish();
+
+    // Measures resolving the same impl method at every line, which only awaits the method once
+    let program = generate_repeated_program();
+    let (awaits, hits) = count_function_awaits(&program);
+    println!("Repeated calls: {} awaited functions, {} answered from the cache", awaits, hits);
+
+    let mut group = c.benchmark_group("repeated impl calls");
+    group.sample_size(10);
+    group.bench_function("repeated", |b| b.iter(|| check(&program, CodeScheduling::Concurrent)));
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
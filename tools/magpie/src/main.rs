@@ -2,6 +2,7 @@ use core::fmt::Debug;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
 use std::{env, path};
 
 use include_dir::{include_dir, Dir, DirEntry, File};
@@ -11,6 +12,7 @@ use data::{Arguments, CompilerArguments, RavenExtern, Readable, RunnerSettings,
 use parser::tokens::tokenizer::Tokenizer;
 use parser::FileSourceSet;
 use syntax::errors::ParsingError;
+use syntax::program::syntax::Syntax;
 
 use crate::project::RavenProject;
 
@@ -35,7 +37,12 @@ static MAGPIE: Dir = include_dir!("tools/magpie/lib/src");
 fn main() {
     let args = env::args().collect::<Vec<_>>();
 
-    if args.len() == 2 {
+    // magpie file.rv --dump-ir prefix also prints the checked code of the functions starting with prefix
+    let dump_ir = match args.len() {
+        4 if args[2] == "--dump-ir" => Some(args[3].clone()),
+        _ => None,
+    };
+    if args.len() == 2 || dump_ir.is_some() {
         let target = env::current_dir().unwrap().join(args[1].clone());
         let mut arguments = Arguments::build_args(
             false,
@@ -48,6 +55,12 @@ fn main() {
                     ),
                     compiler: "llvm".to_string(),
                     temp_folder: env::current_dir().unwrap().join("target"),
+                    deny_warnings: false,
+                    features: Vec::default(),
+                    dump_ir,
+                    bounds_checks: true,
+                    reject_special_floats: false,
+                    position_independent_vtables: false,
                 },
             },
         );
@@ -78,6 +91,12 @@ fn main() {
                 target: "build::project".to_string(),
                 compiler: "llvm".to_string(),
                 temp_folder: env::current_dir().unwrap().join("target"),
+                deny_warnings: false,
+                features: Vec::default(),
+                dump_ir: None,
+                bounds_checks: true,
+                reject_special_floats: false,
+                position_independent_vtables: false,
             },
         },
     );
@@ -116,17 +135,7 @@ pub fn build<T: RavenExtern + 'static>(
     arguments: &mut Arguments,
     mut source: Vec<Box<dyn SourceSet>>,
 ) -> Result<Option<T>, ()> {
-    let platform_std = match env::consts::OS {
-        "windows" => &STD_WINDOWS,
-        "linux" => &STD_LINUX,
-        "macos" => &STD_MACOS,
-        _ => panic!("Unsupported platform {}!", env::consts::OS),
-    };
-
-    source.push(Box::new(InnerSourceSet { set: &STD_UNIVERSAL }));
-    source.push(Box::new(InnerSourceSet { set: platform_std }));
-    source.push(Box::new(InnerSourceSet { set: &CORE }));
-
+    add_dependencies(&mut source);
     arguments.runner_settings.sources = source.iter().map(|inner| inner.cloned()).collect::<Vec<_>>();
 
     let value = run::<T>(&arguments);
@@ -142,9 +151,31 @@ pub fn build<T: RavenExtern + 'static>(
     };
 }
 
+/// Adds the standard library and core to the sources
+fn add_dependencies(source: &mut Vec<Box<dyn SourceSet>>) {
+    let platform_std = match env::consts::OS {
+        "windows" => &STD_WINDOWS,
+        "linux" => &STD_LINUX,
+        "macos" => &STD_MACOS,
+        _ => panic!("Unsupported platform {}!", env::consts::OS),
+    };
+
+    source.push(Box::new(InnerSourceSet { set: &STD_UNIVERSAL }));
+    source.push(Box::new(InnerSourceSet { set: platform_std }));
+    source.push(Box::new(InnerSourceSet { set: &CORE }));
+}
+
 /// Runs Raven and blocks until a result is gotten
 fn run<T: RavenExtern + 'static>(arguments: &Arguments) -> Result<Option<T>, Vec<ParsingError>> {
-    let result = arguments.cpu_runtime.block_on(runner::runner::run::<AtomicPtr<T::Input>>(&arguments))?;
+    return run_with(arguments, |_| {});
+}
+
+/// Runs Raven like run, calling setup on the program before it's parsed, like to add native functions
+fn run_with<T: RavenExtern + 'static>(
+    arguments: &Arguments,
+    setup: impl FnOnce(&Arc<Mutex<Syntax>>),
+) -> Result<Option<T>, Vec<ParsingError>> {
+    let result = arguments.cpu_runtime.block_on(runner::runner::run_with::<AtomicPtr<T::Input>>(&arguments, setup))?;
     return Ok(result.map(|inner| unsafe { RavenExtern::translate(inner.load(Ordering::Relaxed)) }));
 }
 
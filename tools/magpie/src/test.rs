@@ -5,14 +5,74 @@ use std::path;
 
 #[cfg(test)]
 mod test {
-    use crate::build;
     use crate::test::InnerFileSourceSet;
-    use data::{Arguments, CompilerArguments, RunnerSettings};
-    use include_dir::{include_dir, Dir, DirEntry};
-    use std::{env, path};
+    use crate::{add_dependencies, build, run, run_with, FileWrapper};
+    use checker::check_code::verify_effect;
+    use checker::check_impl::verify_impl;
+    use checker::check_method_call::resolve_method_call;
+    use checker::coercion::CoercionRules;
+    use checker::diagnostics::diagnostics_json;
+    use checker::fuzz::fuzz_check;
+    use checker::output::{CodeScheduling, TypesChecker};
+    use checker::CodeVerifier;
+    use dashmap::DashMap;
+    use data::tokens::{line_column, Span};
+    use data::{Arguments, CompilerArguments, Readable, RunnerSettings, SourceSet};
+    use include_dir::{include_dir, Dir, DirEntry, File};
+    use parser::{parse, parse_streaming, ImportNameResolver};
+    use runner::get_compiler;
+    use std::ffi::{c_char, CStr};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+    use std::sync::atomic::Ordering;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use std::{env, mem, path};
+    use syntax::async_util::{DeterministicScheduler, HandleWrapper};
+    use syntax::errors::{ErrorSource, ParsingError, ParsingMessage};
+    use syntax::program::builder::EffectBuilder;
+    use syntax::program::code::{Effects, ExpressionType, FinalizedEffectType, FinalizedEffects, MemberField};
+    use syntax::program::diff::SyntaxDiff;
+    use syntax::program::dump::dump_ir;
+    use syntax::program::function::{FinalizedCodeBody, FinalizedFunction, Inline};
+    use syntax::program::r#struct::{FinalizedStruct, StructData, UnfinalizedStruct, I64, STR, U64, U8, VOID};
+    use syntax::program::syntax::Syntax;
+    use syntax::program::types::FinalizedTypes;
+    use syntax::program::walk::walk_effects;
+    use syntax::top_element_manager::BoundSatisfaction;
+    use syntax::{ParsingFuture, SimpleVariableManager};
+    use tokio::runtime::Builder;
+    use tokio::sync::mpsc;
 
     /// Tests directory
     static TESTS: Dir = include_dir!("lib/test/test");
+    /// Tests that must fail to compile, each starting with "// error: " comments of parts of the expected error
+    static FAILING: Dir = include_dir!("lib/test/fail");
+    /// Tests with several errors, each starting with "// error: " comments of parts of the errors in the expected order
+    static ORDERED: Dir = include_dir!("lib/test/order");
+    /// Malformed sources fuzzing starts from, which must error without panicking
+    static FUZZ_SEEDS: Dir = include_dir!("lib/test/fuzz");
+    /// A program, and the same program with one function's body changed, both named changed.rv so they have the same names
+    static DIFF_OLD: File = File::new("changed.rv", include_bytes!("../../../lib/test/diff/old.rv"));
+    static DIFF_NEW: File = File::new("changed.rv", include_bytes!("../../../lib/test/diff/new.rv"));
+    /// Another source set implementing the trait from vtable-slots.rv
+    static VTABLE_CIRCLES: File = File::new("circles.rv", include_bytes!("../../../lib/test/vtable/circles.rv"));
+    /// Tests calling native functions, which only check once the test adds the natives
+    static NATIVES: Dir = include_dir!("lib/test/native");
+    /// Tests that must trap while running, which only run in the process their test starts
+    static TRAPS: Dir = include_dir!("lib/test/trap");
+    /// A file using the type aliases declared in the other file
+    static ALIASES: Dir = include_dir!("lib/test/alias");
+    /// Set in the process a trap test starts, so it runs the trapping test
+    const TRAP_VARIABLE: &str = "RAVEN_TRAP_TEST";
+    /// The signal an illegal instruction sends
+    const SIGILL: i32 = 4;
+    /// The signal a breakpoint sends
+    const SIGTRAP: i32 = 5;
+
+    /// How long a test waits for part of the program to be checked before failing
+    const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
 
     /// Main test
     #[test]
@@ -20,6 +80,1304 @@ mod test {
         test_recursive(&TESTS);
     }
 
+    /// Checks that invalid code fails with the expected error
+    #[test]
+    pub fn test_failing() {
+        failing_recursive(&FAILING);
+    }
+
+    /// Checks that errors are returned in source order
+    #[test]
+    pub fn test_error_order() {
+        for file in ORDERED.files() {
+            let path = file.path().to_str().unwrap().replace(path::MAIN_SEPARATOR, "::");
+            println!("Running {}", path);
+            let expected = expected_errors(file);
+
+            match run::<bool>(&test_arguments(file, &path)) {
+                Ok(_) => assert!(false, "Test {} compiled when it should've failed!", path),
+                Err(errors) => {
+                    let messages = errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>();
+                    assert_eq!(messages.len(), expected.len(), "Test {} failed with {:?}!", path, messages);
+                    for (message, part) in messages.iter().zip(&expected) {
+                        assert!(
+                            message.contains(part),
+                            "Test {} failed with {:?} instead of {:?}!",
+                            path,
+                            messages,
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks the JSON diagnostics have an entry with every field for each error
+    #[test]
+    pub fn test_json_diagnostics() {
+        let file = ORDERED.get_file("two-errors.rv").unwrap();
+        let arguments = test_arguments(file, "two-errors.rv");
+        let errors = match run::<bool>(&arguments) {
+            Ok(_) => panic!("Test two-errors.rv compiled when it should've failed!"),
+            Err(errors) => errors,
+        };
+
+        let json = diagnostics_json(&errors, &[], &arguments.runner_settings.sources);
+        assert!(json.starts_with("{\"diagnostics\":[{\"severity\":\"error\""), "Wrong JSON {}", json);
+        assert!(json.ends_with("}]}"), "Wrong JSON {}", json);
+        assert_eq!(json.matches("\"severity\":\"error\"").count(), 2, "Wrong JSON {}", json);
+        for field in ["code", "message", "file", "start", "end", "line", "column", "note", "expansion"] {
+            assert_eq!(json.matches(&format!("\"{}\":", field)).count(), 2, "Wrong JSON {}", json);
+        }
+        assert!(json.contains("\"code\":\"VariableOutOfScope\""), "Wrong JSON {}", json);
+        assert!(json.contains("\"message\":\"Variable inner was used outside"), "Wrong JSON {}", json);
+        assert!(json.contains("\"file\":\"two-errors.rv\""), "Wrong JSON {}", json);
+    }
+
+    /// Checks a misspelled method suggests the closest method, and an unrelated method suggests nothing
+    #[test]
+    pub fn test_method_suggestions() {
+        let message = |name: &str| {
+            let file = FAILING.get_file(name).unwrap();
+            return match run::<bool>(&test_arguments(file, name)) {
+                Ok(_) => panic!("Test {} compiled when it should've failed!", name),
+                Err(errors) => errors[0].message.to_string(),
+            };
+        };
+
+        assert!(message("misspelled-method.rv").ends_with("did you mean increment?"));
+        assert!(!message("unrelated-method.rv").contains("did you mean"));
+    }
+
+    /// Checks a program with only warnings compiles, unless warnings are denied
+    #[test]
+    pub fn test_deny_warnings() {
+        let file = TESTS.get_file("shadowing.rv").unwrap();
+        let mut arguments = test_arguments(file, "shadowing.rv");
+        assert!(matches!(run::<bool>(&arguments), Ok(Some(true))));
+
+        arguments.runner_settings.compiler_arguments.deny_warnings = true;
+        let errors = match run::<bool>(&arguments) {
+            Ok(_) => panic!("Test shadowing.rv compiled with its warnings denied!"),
+            Err(errors) => errors,
+        };
+        assert!(errors.iter().all(|error| matches!(error.message, ParsingMessage::ShadowedVariable(_))));
+        assert!(!errors.is_empty());
+    }
+
+    /// Checks an inherent method shadowing a trait's method is warned about once at its declaration,
+    /// even though it's called more than once
+    #[test]
+    pub fn test_shadowed_trait_method() {
+        let file = TESTS.get_file("inherent-methods.rv").unwrap();
+        let (syntax, _) = check_sources(&test_arguments(file, "inherent-methods.rv"));
+        let warnings = syntax
+            .lock()
+            .unwrap()
+            .warnings
+            .iter()
+            .filter(|warning| matches!(warning.message, ParsingMessage::ShadowedTraitMethod(_, _)))
+            .map(|warning| warning.span.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1, "Expected one warning, found {:?}", warnings);
+
+        let source = file.contents_utf8().unwrap();
+        let ((line, _), _) = warnings[0].line_columns(&FileWrapper { file }.read(), source).unwrap();
+        // The inherent method is declared after the trait's
+        let (declared, _) = line_column(source, source.rfind("pub fn describe").unwrap());
+        assert_eq!(line, declared, "The warning isn't at the inherent method's declaration");
+    }
+
+    /// Checks NaN and Inf literals compile by default, but are errors once special float values are rejected
+    #[test]
+    pub fn test_reject_special_floats() {
+        let file = TESTS.get_file("special-floats.rv").unwrap();
+        let mut arguments = test_arguments(file, "special-floats.rv");
+        assert!(matches!(run::<bool>(&arguments), Ok(Some(true))));
+
+        arguments.runner_settings.compiler_arguments.reject_special_floats = true;
+        let errors = match run::<bool>(&arguments) {
+            Ok(_) => panic!("Test special-floats.rv compiled with special float values rejected!"),
+            Err(errors) => errors,
+        };
+        assert!(errors.iter().all(|error| matches!(error.message, ParsingMessage::SpecialFloatLiteral(_))));
+        assert!(!errors.is_empty());
+    }
+
+    /// Checks a function behind a feature can only be called once the feature is enabled
+    #[test]
+    pub fn test_cfg_features() {
+        let file = FAILING.get_file("cfg-disabled.rv").unwrap();
+        let mut arguments = test_arguments(file, "cfg-disabled.rv");
+        assert!(run::<bool>(&arguments).is_err(), "Test cfg-disabled.rv called a disabled function!");
+
+        arguments.runner_settings.compiler_arguments.features = vec!["fast".to_string()];
+        assert!(matches!(run::<bool>(&arguments), Ok(Some(true))));
+    }
+
+    /// Checks methods inherited from a supertrait are called through the subtrait's vtable slot for them
+    #[test]
+    pub fn test_supertrait_slots() {
+        let file = TESTS.get_file("supertrait-objects.rv").unwrap();
+        let arguments = test_arguments(file, "supertrait-objects.rv");
+        let compiling = check_sources(&arguments).0.lock().unwrap().compiling.clone();
+        let function = wait_for_function(&compiling, "supertrait-objects::describe");
+
+        // Each virtual call's slot and the method it calls, in the order they're called
+        let calls = effects(&function.code)
+            .into_iter()
+            .filter_map(|effect| match effect {
+                FinalizedEffectType::VirtualCall(slot, function, _, _) => Some((*slot, function.data.name.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            vec![
+                (1, "supertrait-objects::Named::id".to_string()),
+                (0, "supertrait-objects::Greeter::bonus".to_string()),
+                (1, "supertrait-objects::Named::id".to_string())
+            ]
+        );
+    }
+
+    /// Checks position independent vtables are generated when the option is on, and that each separately checked
+    /// program gives the vtable the same symbol and slots, even when another source set implements the same trait
+    #[test]
+    pub fn test_position_independent_vtables() {
+        let file = TESTS.get_file("vtable-slots.rv").unwrap();
+        let layout = |arguments: Arguments| {
+            let (syntax, _) = check_sources(&arguments);
+            let locked = syntax.lock().unwrap();
+            let data = |name: &str| locked.structures.data.get(locked.structures.types.get(name).unwrap()).unwrap().clone();
+            let shape = data("vtable-slots::Shape");
+            let slots = shape
+                .vtable_layout()
+                .iter()
+                .map(|(declaring, function)| (declaring.name.clone(), function.name.clone()))
+                .collect::<Vec<_>>();
+            return (shape.data.vtable_symbol(&data("vtable-slots::Square").data), slots);
+        };
+        let (symbol, slots) = layout(test_arguments(file, "vtable-slots.rv"));
+        assert_eq!(symbol, "vtable-slots::Square_vtable-slots::Shape_vtable");
+        assert_eq!(layout(test_arguments(file, "vtable-slots.rv")), (symbol.clone(), slots.clone()));
+        let mut arguments = test_arguments(file, "vtable-slots.rv");
+        arguments.runner_settings.sources.push(Box::new(InnerFileSourceSet { set: &VTABLE_CIRCLES }));
+        assert_eq!(layout(arguments), (symbol.clone(), slots));
+
+        let mut arguments = test_arguments(file, "vtable-slots.rv");
+        arguments.runner_settings.compiler_arguments.position_independent_vtables = true;
+        let (syntax, _) = check_sources(&arguments);
+        let compiler = {
+            let locked = syntax.lock().unwrap();
+            get_compiler::<bool>(
+                locked.compiling.clone(),
+                locked.strut_compiling.clone(),
+                arguments.runner_settings.compiler_arguments.clone(),
+            )
+        };
+        let (sender, receiver) = mpsc::channel(1);
+        let result = arguments.cpu_runtime.block_on(async {
+            sender.send(()).await.unwrap();
+            compiler.compile(receiver, &syntax).await
+        });
+        assert_eq!(result, Some(true));
+        assert_eq!(compiler.source_map().vtable(&symbol), Some(true));
+    }
+
+    /// Checks each extern is compiled with the calling convention of the ABI it was declared with
+    #[test]
+    pub fn test_extern_abi() {
+        let arguments = test_arguments(TESTS.get_file("extern-abi.rv").unwrap(), "extern-abi.rv");
+        let (syntax, _) = check_sources(&arguments);
+        let compiler = {
+            let locked = syntax.lock().unwrap();
+            get_compiler::<bool>(
+                locked.compiling.clone(),
+                locked.strut_compiling.clone(),
+                arguments.runner_settings.compiler_arguments.clone(),
+            )
+        };
+        let (sender, receiver) = mpsc::channel(1);
+        let result = arguments.cpu_runtime.block_on(async {
+            sender.send(()).await.unwrap();
+            compiler.compile(receiver, &syntax).await
+        });
+        assert_eq!(result, Some(true));
+
+        // LLVM's numbers for the C and stdcall conventions, the system ABI is stdcall on 32-bit Windows
+        let system = if cfg!(all(windows, target_arch = "x86")) { 64 } else { 0 };
+        let source_map = compiler.source_map();
+        assert_eq!(source_map.call_convention("extern-abi::strnlen"), Some(0));
+        assert_eq!(source_map.call_convention("extern-abi::labs"), Some(system));
+        assert_eq!(source_map.call_convention("extern-abi::test"), None);
+    }
+
+    /// Checks walking a function's code visits every virtual call, along with the call it's an argument of
+    #[test]
+    pub fn test_walk_effects() {
+        let file = TESTS.get_file("supertrait-objects.rv").unwrap();
+        let arguments = test_arguments(file, "supertrait-objects.rv");
+        let compiling = check_sources(&arguments).0.lock().unwrap().compiling.clone();
+        let function = wait_for_function(&compiling, "supertrait-objects::describe");
+
+        let mut calls = 0;
+        let mut parents = Vec::default();
+        walk_effects(&function.code, &mut |effect, parent| {
+            if let FinalizedEffectType::VirtualCall(_, _, _, _) = &effect.types {
+                calls += 1;
+                parents.push(parent.map(|parent| matches!(parent.types, FinalizedEffectType::MethodCall(_, _, _, _))));
+            }
+            return true;
+        });
+        assert_eq!(calls, 3);
+        assert!(parents.iter().all(|parent| *parent == Some(true)), "Virtual calls outside of the addition: {:?}", parents);
+    }
+
+    /// Checks the dumped IR of a function calling a method, which should only change when the checker's output does
+    #[test]
+    pub fn test_dump_ir() {
+        let file = TESTS.get_file("dump-ir.rv").unwrap();
+        let arguments = test_arguments(file, "dump-ir.rv");
+        let compiling = check_sources(&arguments).0.lock().unwrap().compiling.clone();
+        let function = wait_for_function(&compiling, "dump-ir::doubled");
+
+        assert_eq!(
+            dump_ir(&function),
+            "fn dump-ir::doubled(value: u64) -> u64
+block 1
+  let point: dump-ir::Point @0..0
+    new: dump-ir::Point @0..0
+      heap_allocate: dump-ir::Point @0..0
+      load value: u64 @41..41
+  return call dump-ir::Point::twice: u64 @48..48
+    heap_allocate: u64 @0..0
+    load point: dump-ir::Point @46..46
+"
+        );
+    }
+
+    /// Checks #[checked_math] wraps integer math in overflow checks, and math without it is left wrapping
+    #[test]
+    pub fn test_checked_math() {
+        let file = TESTS.get_file("checked-math.rv").unwrap();
+        let arguments = test_arguments(file, "checked-math.rv");
+        let compiling = check_sources(&arguments).0.lock().unwrap().compiling.clone();
+        let checks = |name: &str| {
+            let function = wait_for_function(&compiling, name);
+            let found = effects(&function.code);
+            return found.iter().filter(|effect| matches!(effect, FinalizedEffectType::OverflowCheck(_))).count();
+        };
+
+        assert_eq!(checks("checked-math::checked"), 4);
+        assert_eq!(checks("checked-math::wrapping"), 0);
+    }
+
+    /// Checks indices that aren't known while compiling are bounds checked unless the compiler arguments turn it off,
+    /// and constant indices into fixed length arrays never are
+    #[test]
+    pub fn test_bounds_checks() {
+        let file = TESTS.get_file("bounds-checks.rv").unwrap();
+        let checks = |bounds_checks: bool| {
+            let mut arguments = test_arguments(file, "bounds-checks.rv");
+            arguments.runner_settings.compiler_arguments.bounds_checks = bounds_checks;
+            let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+            let mut checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+            checker.bounds_checks = arguments.runner_settings.compiler_arguments.bounds_checks;
+            let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+            parse_into(&arguments, &syntax, &handle);
+            syntax.lock().unwrap().finish();
+
+            let compiling = syntax.lock().unwrap().compiling.clone();
+            return ["pick", "last", "constant"].map(|name| {
+                let function = wait_for_function(&compiling, &format!("bounds-checks::{}", name));
+                let found = effects(&function.code);
+                return found.iter().filter(|effect| matches!(effect, FinalizedEffectType::BoundsCheck(_))).count();
+            });
+        };
+
+        assert_eq!(checks(true), [1, 1, 0]);
+        assert_eq!(checks(false), [0, 0, 0]);
+    }
+
+    /// Checks arithmetic on only literals is folded into the result's literal
+    #[test]
+    pub fn test_constant_folding() {
+        let file = TESTS.get_file("constant-folding.rv").unwrap();
+        let arguments = test_arguments(file, "constant-folding.rv");
+        let compiling = check_sources(&arguments).0.lock().unwrap().compiling.clone();
+        let function = wait_for_function(&compiling, "constant-folding::folded");
+        let found = effects(&function.code);
+
+        let calls = found.iter().filter(|effect| matches!(effect, FinalizedEffectType::MethodCall(_, _, _, _))).count();
+        assert_eq!(calls, 0, "The arithmetic wasn't folded");
+        let folded = found.iter().any(|effect| matches!(effect, FinalizedEffectType::TypedInt(12, _)));
+        assert!(folded, "The arithmetic was folded to the wrong value");
+    }
+
+    /// Checks repeated calls to the same method reuse the cached function instead of awaiting it again
+    #[test]
+    pub fn test_function_cache() {
+        let file = TESTS.get_file("repeated-calls.rv").unwrap();
+        let arguments = test_arguments(file, "repeated-calls.rv");
+        let (syntax, checker) = check_sources(&arguments);
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        wait_for_function(&compiling, "repeated-calls::test");
+
+        let hits = checker.functions.hits.load(Ordering::Relaxed);
+        let awaits = checker.functions.awaits.load(Ordering::Relaxed);
+        assert!(hits > awaits, "Only {} of {} gets were cached!", hits, hits + awaits);
+    }
+
+    /// Checks code that calls impls and other functions still checks when one function's code is checked at a time
+    #[test]
+    pub fn test_sequential_scheduling() {
+        let file = TESTS.get_file("repeated-calls.rv").unwrap();
+        let arguments = test_arguments(file, "repeated-calls.rv");
+        let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+        let mut checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+        checker.scheduling = CodeScheduling::Sequential;
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+        parse_into(&arguments, &syntax, &handle);
+        syntax.lock().unwrap().finish();
+
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        wait_for_function(&compiling, "repeated-calls::test");
+        assert!(syntax.lock().unwrap().errors.is_empty());
+    }
+
+    /// Checks the checker errors on effects nested deeper than its limit, even when the parser allowed them
+    #[test]
+    pub fn test_checker_nesting_limit() {
+        let file = TESTS.get_file("repeated-calls.rv").unwrap();
+        let arguments = test_arguments(file, "repeated-calls.rv");
+        let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+        let mut checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+        checker.max_nesting = 2;
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+        parse_into(&arguments, &syntax, &handle);
+        syntax.lock().unwrap().finish();
+
+        let expected = ParsingMessage::NestingTooDeep(2).to_string();
+        arguments.cpu_runtime.block_on(wait_for("the nesting error", || {
+            syntax.lock().unwrap().errors.iter().any(|error| error.message.to_string() == expected).then_some(())
+        }));
+    }
+
+    /// Checks try_verify_struct returns a struct's error instead of adding it to the program's errors
+    #[test]
+    pub fn test_try_verify_struct() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let checker = TypesChecker::new(Arc::new(Mutex::new(HandleWrapper::new(runtime.handle().clone()))), false);
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker.clone()))));
+        let structure = |name: &str, fields: Vec<ParsingFuture<MemberField>>| UnfinalizedStruct {
+            generics: Default::default(),
+            generic_spans: Default::default(),
+            fields,
+            functions: vec![],
+            traits: vec![],
+            constants: vec![],
+            data: Arc::new(StructData::new(vec![], vec![], 0, Span::default(), name.to_string())),
+        };
+
+        let working = runtime.block_on(checker.try_verify_struct(structure("test::Working", vec![]), &syntax));
+        assert_eq!(working.unwrap().data.name, "test::Working");
+
+        let missing: ParsingFuture<MemberField> =
+            Box::pin(async { Err(Span::default().make_error(ParsingMessage::FailedToFind("Missing".to_string()))) });
+        let broken = runtime.block_on(checker.try_verify_struct(structure("test::Broken", vec![missing]), &syntax));
+        match broken {
+            Err(error) => {
+                assert_eq!(error.message.to_string(), ParsingMessage::FailedToFind("Missing".to_string()).to_string())
+            }
+            Ok(_) => panic!("The broken struct was verified!"),
+        }
+        assert!(syntax.lock().unwrap().errors.is_empty(), "The error was added to the program's errors!");
+    }
+
+    /// Checks a type satisfying the same bound at many call sites only searches every impl for it once
+    #[test]
+    pub fn test_bound_cache() {
+        let file = TESTS.get_file("repeated-bounds.rv").unwrap();
+        let arguments = test_arguments(file, "repeated-bounds.rv");
+        let (syntax, _checker) = check_sources(&arguments);
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        wait_for_function(&compiling, "repeated-bounds::test");
+
+        let locked = syntax.lock().unwrap();
+        assert!(locked.bound_hits > 0, "No bound checks were cached!");
+        let counter = locked
+            .bounds
+            .iter()
+            .filter(|((types, bound), _)| {
+                types.to_string() == "repeated-bounds::Counter" && bound.to_string() == "repeated-bounds::Total"
+            })
+            .collect::<Vec<_>>();
+        assert!(!counter.is_empty());
+        assert!(counter.iter().all(|(_, found)| matches!(found, BoundSatisfaction::Satisfied(_))));
+    }
+
+    /// Checks a generic function called with the same generics at many call sites is only degenericed once
+    #[test]
+    pub fn test_monomorphization_cache() {
+        let file = TESTS.get_file("generic-instances.rv").unwrap();
+        let arguments = test_arguments(file, "generic-instances.rv");
+        let (syntax, _checker) = check_sources(&arguments);
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        wait_for_function(&compiling, "generic-instances::test");
+
+        let locked = syntax.lock().unwrap();
+        let mut instances: Vec<_> = locked
+            .monomorphized
+            .iter()
+            .filter(|((base, _), _)| base == "generic-instances::pick")
+            .map(|(_, instance)| instance.name.clone())
+            .collect();
+        instances.sort();
+        assert_eq!(instances, vec!["generic-instances::pick$bool", "generic-instances::pick$i64"]);
+        assert!(locked.monomorphized_hits >= 4, "Only {} instances were reused!", locked.monomorphized_hits);
+    }
+
+    /// Checks every fuzzing seed returns errors instead of panicking, and that a valid source still checks
+    #[test]
+    pub fn test_fuzz_seeds() {
+        let mut libraries: Vec<Box<dyn SourceSet>> = Vec::default();
+        add_dependencies(&mut libraries);
+        for seed in FUZZ_SEEDS.files() {
+            let path = seed.path().to_str().unwrap();
+            let errors = match fuzz_check(seed.contents(), &libraries) {
+                Ok(()) => panic!("Fuzzing seed {} had no errors!", path),
+                Err(errors) => errors,
+            };
+            // Panics and hangs are turned into these errors, so they mean the checker broke on the seed
+            for error in errors {
+                if matches!(error.message, ParsingMessage::InternalError(_) | ParsingMessage::CheckTimedOut(_)) {
+                    panic!("Fuzzing seed {} broke the checker: {}", path, error.message);
+                }
+            }
+        }
+
+        let valid = "fn test() -> bool {\n    return true;\n}\n";
+        if let Err(errors) = fuzz_check(valid.as_bytes(), &libraries) {
+            panic!("Valid source failed to check: {}", errors[0].message);
+        }
+    }
+
+    /// Checks an extern function with the wrong #[ptr_len] count is reported at its name with both counts
+    #[test]
+    pub fn test_ptr_len_arguments() {
+        let file = FAILING.get_file("ptr-len-arguments.rv").unwrap();
+        let errors = match run::<bool>(&test_arguments(file, "ptr-len-arguments.rv")) {
+            Ok(_) => panic!("The wrong #[ptr_len] count compiled!"),
+            Err(errors) => errors,
+        };
+        let error = errors
+            .iter()
+            .find(|error| matches!(error.message, ParsingMessage::PtrLenArguments(2, 3)))
+            .unwrap_or_else(|| panic!("Expected the #[ptr_len] error, found {:?}", errors));
+
+        let tokens = FileWrapper { file }.read();
+        assert_eq!(error.span.line_columns(&tokens, file.contents_utf8().unwrap()), Some(((7, 10), (7, 16))));
+    }
+
+    /// Checks the return added to the end of a void function points at the function instead of nowhere
+    #[test]
+    pub fn test_implicit_return_span() {
+        let file = TESTS.get_file("implicit-return.rv").unwrap();
+        let arguments = test_arguments(file, "implicit-return.rv");
+        let compiling = check_sources(&arguments).0.lock().unwrap().compiling.clone();
+        let function = wait_for_function(&compiling, "implicit-return::nothing");
+
+        let location = |span: &Span| (span.file, span.start, span.end);
+        let returning = function.code.expressions.last().unwrap();
+        assert!(!function.data.span.is_default());
+        match &returning.expression_type {
+            ExpressionType::Return(found) => assert_eq!(location(found), location(&function.data.span)),
+            _ => assert!(false, "Last expression isn't a return!"),
+        }
+        assert_eq!(location(&returning.effect.span), location(&function.data.span));
+    }
+
+    /// Checks only the first line after a return is warned about, and not the code after an if that might not return
+    #[test]
+    pub fn test_unreachable_code() {
+        let file = TESTS.get_file("unreachable-code.rv").unwrap();
+        let arguments = test_arguments(file, "unreachable-code.rv");
+        let (syntax, _checker) = check_sources(&arguments);
+        let functions = ["unreachable-code::early", "unreachable-code::one_branch", "unreachable-code::both_branches"];
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        for function in functions {
+            wait_for_function(&compiling, function);
+        }
+
+        let tokens = FileWrapper { file }.read();
+        let source = file.contents_utf8().unwrap();
+        let locked = syntax.lock().unwrap();
+        let mut lines = locked
+            .warnings
+            .iter()
+            .filter(|warning| matches!(warning.message, ParsingMessage::UnreachableCode()))
+            .map(|warning| warning.span.line_columns(&tokens, source).unwrap().0 .0)
+            .collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines, vec![7, 24]);
+    }
+
+    /// Checks only the generics that nothing uses are warned about, skipping the ones starting with an underscore
+    #[test]
+    pub fn test_unused_generics() {
+        let file = TESTS.get_file("unused-generics.rv").unwrap();
+        let arguments = test_arguments(file, "unused-generics.rv");
+        let (syntax, _checker) = check_sources(&arguments);
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        wait_for_function(&compiling, "unused-generics::test");
+
+        let tokens = FileWrapper { file }.read();
+        let source = file.contents_utf8().unwrap();
+        let locked = syntax.lock().unwrap();
+        let mut warned = locked
+            .warnings
+            .iter()
+            .filter_map(|warning| match &warning.message {
+                ParsingMessage::UnusedGeneric(name) => {
+                    Some((name.clone(), warning.span.line_columns(&tokens, source).unwrap().0 .0))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        warned.sort();
+        assert_eq!(warned, vec![("T".to_string(), 17), ("U".to_string(), 5)]);
+    }
+
+    /// Checks an if returns only when every branch does, and that it stays in the function's code once it's checked
+    #[test]
+    pub fn test_branch_returns() {
+        let file = TESTS.get_file("branch-returns.rv").unwrap();
+        let arguments = test_arguments(file, "branch-returns.rv");
+        let (syntax, _checker) = check_sources(&arguments);
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        let function = |name: &str| {
+            return wait_for_function(&compiling, &format!("branch-returns::{}", name));
+        };
+        let if_returns = |name: &str| {
+            let found = function(name);
+            assert!(found.code.returns, "{} doesn't return", name);
+            return found
+                .code
+                .expressions
+                .iter()
+                .find_map(|line| match &line.effect.types {
+                    FinalizedEffectType::CodeBody(body) => Some(body.returns),
+                    _ => None,
+                })
+                .unwrap();
+        };
+
+        assert!(if_returns("both"));
+        assert!(if_returns("chained"));
+        assert!(if_returns("nested"));
+        assert!(!if_returns("one_arm"));
+    }
+
+    /// Checks a generic function's instance is named with its generics in the same order every time it's checked
+    #[test]
+    pub fn test_generic_order() {
+        let file = TESTS.get_file("generic-order.rv").unwrap();
+        for _ in 0..5 {
+            let arguments = test_arguments(file, "generic-order.rv");
+            let (syntax, _checker) = check_sources(&arguments);
+            let name = arguments.cpu_runtime.block_on(wait_for("the instance of pick", || {
+                let locked = syntax.lock().unwrap();
+                let found = locked.compiling.iter().find(|entry| entry.key().starts_with("generic-order::pick$"));
+                found.map(|entry| entry.key().clone())
+            }));
+            assert_eq!(name, "generic-order::pick$u64_bool_char");
+        }
+    }
+
+    /// Checks type aliases imported from another file expand to types that file imports
+    #[test]
+    pub fn test_imported_type_alias() {
+        let file = ALIASES.get_file("users.rv").unwrap();
+        let mut arguments = test_arguments(file, "users.rv");
+        arguments.runner_settings.sources.push(Box::new(InnerFileSourceSet { set: ALIASES.get_file("ids.rv").unwrap() }));
+        let (syntax, _checker) = check_sources(&arguments);
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        wait_for_function(&compiling, "users::test");
+
+        let errors = syntax.lock().unwrap().errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>();
+        assert!(errors.is_empty(), "Imported aliases failed with {:?}", errors);
+    }
+
+    /// Checks a native function added from Rust is called like any other function, and that running the program
+    /// calls the Rust function
+    #[test]
+    pub fn test_native_function() {
+        let add_print = |syntax: &Arc<Mutex<Syntax>>| {
+            let string = FinalizedTypes::Struct(STR.clone());
+            Syntax::add_native(syntax, "host::print".to_string(), vec![string], None, native_print as usize);
+        };
+        let file = NATIVES.get_file("print.rv").unwrap();
+        let arguments = test_arguments(file, "print.rv");
+        let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+        let checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+        add_print(&syntax);
+
+        parse_into(&arguments, &syntax, &handle);
+        syntax.lock().unwrap().finish();
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        let code = wait_for_function(&compiling, "print::test").code.clone();
+
+        match &code.expressions[0].effect.types {
+            FinalizedEffectType::MethodCall(_, function, _, _) => assert_eq!(function.data.name, "host::print"),
+            other => panic!("Expected a call to the native, found {:?}", other),
+        }
+
+        let result = run_with::<bool>(&test_arguments(file, "print.rv"), add_print);
+        assert!(matches!(result, Ok(Some(true))), "Running print.rv failed with {:?}", result);
+        assert_eq!(*PRINTED.lock().unwrap(), vec!["Hello from Rust!".to_string()]);
+    }
+
+    /// Checks diffing two parses of a program only reports the function whose body changed
+    #[test]
+    pub fn test_syntax_diff() {
+        let checked = |file: &'static File<'static>| {
+            let arguments = test_arguments(file, "changed.rv");
+            let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+            let checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+            let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+            parse_into(&arguments, &syntax, &handle);
+            syntax.lock().unwrap().finish();
+            // Every body has to be checked before it's hashed
+            arguments.cpu_runtime.block_on(wait_for_tasks(&handle));
+            return syntax;
+        };
+        let old = checked(&DIFF_OLD);
+        let new = checked(&DIFF_NEW);
+
+        let diff = SyntaxDiff::new(&old.lock().unwrap(), &new.lock().unwrap());
+        assert!(diff.structures.is_empty(), "Structures changed: {:?}", diff.structures);
+        assert!(diff.functions.added.is_empty() && diff.functions.removed.is_empty(), "Wrong diff {:?}", diff);
+        assert_eq!(diff.functions.modified, vec!["changed::doubled"]);
+        let locked = old.lock().unwrap();
+        assert!(SyntaxDiff::new(&locked, &locked).is_empty());
+    }
+
+    /// The strings passed to native_print
+    static PRINTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// The native print function added by test_native_function, which records what it prints
+    extern "C" fn native_print(value: *const c_char) {
+        let value = unsafe { CStr::from_ptr(value) };
+        PRINTED.lock().unwrap().push(value.to_string_lossy().to_string());
+    }
+
+    /// Checks a type implementing a trait with other generics is explained as that instead of a missing method
+    #[test]
+    pub fn test_explain_wrong_generic() {
+        let file = TESTS.get_file("trait-generic-impl.rv").unwrap();
+        let (syntax, _) = check_sources(&test_arguments(file, "trait-generic-impl.rv"));
+        let locked = syntax.lock().unwrap();
+        let data = |name: &str| locked.structures.data.get(locked.structures.types.get(name).unwrap()).unwrap().clone();
+        let cast = |types: &Arc<FinalizedStruct>| {
+            FinalizedTypes::GenericType(
+                Box::new(FinalizedTypes::Struct(data("numbers::Cast"))),
+                vec![FinalizedTypes::Struct(types.clone())],
+            )
+        };
+        let meters = FinalizedTypes::Struct(data("trait-generic-impl::Meters"));
+
+        assert_eq!(meters.explain_mismatch(&cast(&U64), &locked), None);
+        assert_eq!(
+            meters.explain_mismatch(&cast(&U8), &locked).map(|reason| reason.to_string()),
+            Some("it implements numbers::Cast<u64> instead".to_string())
+        );
+    }
+
+    /// Checks types are shown the way they're written in source
+    #[test]
+    pub fn test_display_with_generics() {
+        let structure =
+            |name: &str| FinalizedTypes::Struct(Arc::new(FinalizedStruct::empty_of(StructData::empty(name.to_string()))));
+        let generic = FinalizedTypes::Generic("T".to_string(), vec![structure("Named")]);
+
+        assert_eq!(generic.display_with_generics(), "T");
+        assert_eq!(FinalizedTypes::Reference(Box::new(generic.clone())).display_with_generics(), "&T");
+        assert_eq!(FinalizedTypes::Struct(VOID.clone()).display_with_generics(), "()");
+
+        // Solidified instances are named with their generics, which are only shown once
+        let inner =
+            FinalizedTypes::GenericType(Box::new(structure("Option$i64")), vec![FinalizedTypes::Struct(I64.clone())]);
+        let nested = FinalizedTypes::GenericType(
+            Box::new(structure("Vec")),
+            vec![inner, FinalizedTypes::Reference(Box::new(generic))],
+        );
+        assert_eq!(nested.display_with_generics(), "Vec<Option<i64>, &T>");
+    }
+
+    /// Checks a poisoned struct's error is reported where it's created and where an impl is called on it,
+    /// instead of only at the struct's declaration
+    #[test]
+    pub fn test_poisoned_struct_uses() {
+        let file = FAILING.get_file("poisoned-struct.rv").unwrap();
+        let errors = match run::<bool>(&test_arguments(file, "poisoned-struct.rv")) {
+            Ok(_) => panic!("Test poisoned-struct.rv compiled when it should've failed!"),
+            Err(errors) => errors,
+        };
+        let source = file.contents_utf8().unwrap();
+        let tokens = FileWrapper { file }.read();
+        let lines = errors
+            .iter()
+            .filter(|error| error.message.to_string().contains("Failed to find type Missing"))
+            .filter_map(|error| error.span.line_columns(&tokens, source))
+            .map(|((line, _), _)| line as usize)
+            .collect::<Vec<_>>();
+        for used in ["new Broken", "broken + 1"] {
+            let line = source.lines().position(|line| line.contains(used)).unwrap() + 1;
+            assert!(lines.contains(&line), "No error at {} on line {}, found errors on lines {:?}", used, line, lines);
+        }
+    }
+
+    /// Checks an operator's error covers its operands instead of one token
+    #[test]
+    pub fn test_operator_span() {
+        let file = FAILING.get_file("mismatched-operands.rv").unwrap();
+        let span = match run::<bool>(&test_arguments(file, "mismatched-operands.rv")) {
+            Ok(_) => panic!("Test mismatched-operands.rv compiled when it should've failed!"),
+            Err(errors) => errors[0].span.clone(),
+        };
+        let tokens = FileWrapper { file }.read();
+        let covered = &file.contents_utf8().unwrap()[tokens[span.start].start_offset..tokens[span.end].end_offset];
+        assert!(span.start < span.end, "Error only covers one token: {:?}", span);
+        assert!(covered.contains("+ 3u64"), "Error covers {:?} instead of the operation", covered);
+    }
+
+    /// Checks an error in the call an operator is checked as points back at the operator the call was expanded from
+    #[test]
+    pub fn test_operator_expansion() {
+        let file = FAILING.get_file("mismatched-operands.rv").unwrap();
+        let span = match run::<bool>(&test_arguments(file, "mismatched-operands.rv")) {
+            Ok(_) => panic!("Test mismatched-operands.rv compiled when it should've failed!"),
+            Err(errors) => errors[0].span.clone(),
+        };
+        let expansion = match &span.expansion {
+            Some(expansion) => expansion,
+            None => panic!("Error at {:?} isn't from the operator's expansion", span),
+        };
+        assert_eq!(expansion.name, "operator +");
+
+        let tokens = FileWrapper { file }.read();
+        let origin = &file.contents_utf8().unwrap()
+            [tokens[expansion.origin.start].start_offset..tokens[expansion.origin.end].end_offset];
+        assert_eq!(origin.trim(), "+", "Expansion points at {:?} instead of the operator", origin);
+    }
+
+    /// Checks an impl call that can't be resolved yet is listed as pending until the syntax is finished
+    #[test]
+    pub fn test_pending_impls() {
+        let file = FAILING.get_file("unsatisfiable-impl.rv").unwrap();
+        let arguments = test_arguments(file, "unsatisfiable-impl.rv");
+        let (syntax, _) = parse_sources(&arguments);
+        let expected = "impl of unsatisfiable-impl::Describe for u64";
+        arguments.cpu_runtime.block_on(wait_for(expected, || {
+            syntax.lock().unwrap().pending_impls().iter().any(|pending| pending.to_string() == expected).then_some(())
+        }));
+
+        syntax.lock().unwrap().finish();
+    }
+
+    /// Checks a method taking self by value loads its receiver when it's called on a reference,
+    /// which only happens without references since LLVM passes self by reference
+    #[test]
+    pub fn test_deref_receiver() {
+        let file = TESTS.get_file("reference-methods.rv").unwrap();
+        let arguments = test_arguments(file, "reference-methods.rv");
+        let loads = |include_refs: bool| {
+            let (syntax, _) = parse_sources_with_references(&arguments, include_refs);
+            syntax.lock().unwrap().finish();
+            let compiling = syntax.lock().unwrap().compiling.clone();
+            let function = wait_for_function(&compiling, "reference-methods::test");
+            return effects(&function.code).iter().any(|effect| matches!(effect, FinalizedEffectType::ReferenceLoad(_)));
+        };
+
+        assert!(loads(false));
+        assert!(!loads(true));
+    }
+
+    /// Checks effects made by the builder are verified like parsed ones, and that it rejects malformed ones
+    #[test]
+    pub fn test_effect_builder() {
+        let file = TESTS.get_file("method-calls.rv").unwrap();
+        let arguments = test_arguments(file, "method-calls.rv");
+        let (syntax, checker) = check_sources(&arguments);
+        let builder = EffectBuilder::default();
+        let verify = |effect: Effects| {
+            let mut verifier =
+                CodeVerifier::new(&checker, Box::new(ImportNameResolver::new("method-calls".to_string())), syntax.clone());
+            let mut variables = SimpleVariableManager::default();
+            arguments.cpu_runtime.block_on(verify_effect(&mut verifier, &mut variables, effect))
+        };
+
+        match verify(builder.call("calling", vec![]).unwrap()).unwrap().types {
+            FinalizedEffectType::MethodCall(_, function, _, _) => assert_eq!(function.data.name, "method-calls::calling"),
+            other => panic!("Expected a method call, found {:?}", other),
+        }
+        let error = verify(builder.call("calling", vec![builder.int(1)]).unwrap()).unwrap_err();
+        assert_eq!(error.message.to_string(), "Incorrect arguments length!");
+
+        assert!(builder.operation("{}+{}", vec![builder.int(1)]).is_err());
+        assert!(builder.typed_int(1, "u7").is_err());
+        assert!(builder.call("calling", vec![builder.named("a", builder.int(1)), builder.int(2)]).is_err());
+    }
+
+    /// Checks a method on a builtin type resolves from just its name and checked arguments, like a REPL would call it
+    #[test]
+    pub fn test_resolve_method_call() {
+        let file = TESTS.get_file("trait-calls.rv").unwrap();
+        let arguments = test_arguments(file, "trait-calls.rv");
+        let (syntax, checker) = check_sources(&arguments);
+        let mut resolver = ImportNameResolver::new("trait-calls".to_string());
+        resolver.imports.push("math::Add".to_string());
+        let verifier = CodeVerifier::new(&checker, Box::new(resolver), syntax.clone());
+        let variables = SimpleVariableManager::default();
+        let resolve = |method: &str, values: &[i64]| {
+            let values = values
+                .iter()
+                .map(|value| FinalizedEffects::new(Span::default(), FinalizedEffectType::Int(*value)))
+                .collect();
+            let receiver = FinalizedTypes::Struct(I64.clone());
+            arguments.cpu_runtime.block_on(resolve_method_call(
+                &verifier,
+                &variables,
+                receiver,
+                method.to_string(),
+                values,
+                &Span::default(),
+            ))
+        };
+
+        match resolve("add", &[2, 3]).unwrap().types {
+            FinalizedEffectType::MethodCall(_, function, arguments, _) => {
+                assert_eq!(function.data.name, "math::Add<T + T>_T::add");
+                assert_eq!(arguments.len(), 2);
+            }
+            other => panic!("Expected a method call, found {:?}", other),
+        }
+        assert_eq!(resolve("missing", &[2]).unwrap_err().message.to_string(), "No implementation of method missing for i64");
+        assert_eq!(resolve("add", &[]).unwrap_err().message.to_string(), "Incorrect arguments length!");
+    }
+
+    /// Checks each overflow trap in the compiled code resolves to the span of the math it checks
+    #[test]
+    pub fn test_source_map() {
+        let file = TESTS.get_file("checked-math.rv").unwrap();
+        let arguments = test_arguments(file, "checked-math.rv");
+        let (syntax, _) = check_sources(&arguments);
+        let checked = syntax.lock().unwrap().compiling.get("checked-math::checked").unwrap().data.span.file;
+        let compiler = {
+            let locked = syntax.lock().unwrap();
+            get_compiler::<bool>(
+                locked.compiling.clone(),
+                locked.strut_compiling.clone(),
+                arguments.runner_settings.compiler_arguments.clone(),
+            )
+        };
+        let (sender, receiver) = mpsc::channel(1);
+        let result = arguments.cpu_runtime.block_on(async {
+            sender.send(()).await.unwrap();
+            compiler.compile(receiver, &syntax).await
+        });
+        assert_eq!(result, Some(true));
+
+        let source_map = compiler.source_map();
+        let traps = source_map.traps("checked-math::checked");
+        assert_eq!(traps.len(), 4);
+        assert!(source_map.traps("checked-math::wrapping").is_empty());
+
+        let tokens = FileWrapper { file }.read();
+        let source = file.contents_utf8().unwrap();
+        for trap in traps {
+            let span = source_map.resolve("checked-math::checked", *trap).unwrap();
+            assert_eq!(span.file, checked, "Trap {} resolved to another file", trap);
+            let ((line, _), _) = span.line_columns(&tokens, source).unwrap();
+            assert_eq!(line, 7, "Trap {} resolved to line {}", trap, line);
+        }
+    }
+
+    /// Checks overflowing checked math actually traps
+    #[test]
+    pub fn test_overflow_trap() {
+        assert_traps("overflow.rv", "test_overflow_trap");
+    }
+
+    /// Checks unwrapping None traps instead of loading the value it doesn't have
+    #[test]
+    pub fn test_unwrap_none_trap() {
+        assert_traps("unwrap-none.rv", "test_unwrap_none_trap");
+    }
+
+    /// Checks compiling several entry points at once returns a handle to each of them, with the generic they share
+    /// compiled once instead of once for each separately compiled entry point
+    #[test]
+    pub fn test_compile_all() {
+        let file = TESTS.get_file("compile-all.rv").unwrap();
+        let arguments = test_arguments(file, "compile-all.rv");
+        let (syntax, _) = check_sources(&arguments);
+        let compiler = {
+            let locked = syntax.lock().unwrap();
+            get_compiler::<bool>(
+                locked.compiling.clone(),
+                locked.strut_compiling.clone(),
+                arguments.runner_settings.compiler_arguments.clone(),
+            )
+        };
+        let compile_all = |names: &[&str]| {
+            let (sender, receiver) = mpsc::channel(1);
+            return arguments.cpu_runtime.block_on(async {
+                sender.send(()).await.unwrap();
+                compiler.compile_all(receiver, names, &syntax).await
+            });
+        };
+        let helpers =
+            || compiler.source_map().functions().filter(|function| function.starts_with("compile-all::helper")).count();
+
+        let handles = compile_all(&["compile-all::first", "compile-all::second", "compile-all::add"]).unwrap();
+        let shared = helpers();
+        unsafe {
+            assert!(handles["compile-all::first"].cast::<unsafe extern "C" fn() -> bool>()());
+            assert!(handles["compile-all::second"].cast::<unsafe extern "C" fn() -> bool>()());
+            assert_eq!(handles["compile-all::add"].cast::<unsafe extern "C" fn(u64, u64) -> u64>()(2, 3), 5);
+        }
+
+        // Compiled separately, each entry point compiles its own instance of the generic
+        compile_all(&["compile-all::first"]).unwrap();
+        let separate = helpers();
+        compile_all(&["compile-all::second"]).unwrap();
+        assert_eq!(shared, 1);
+        assert_eq!(separate + helpers(), 2);
+        // The earlier handles are still callable after compiling again
+        assert!(unsafe { handles["compile-all::first"].cast::<unsafe extern "C" fn() -> bool>()() });
+
+        let errors = compile_all(&["compile-all::first", "compile-all::generic_entry"]).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>(),
+            vec!["Entry point compile-all::generic_entry can't have generics, nothing can pass them to it!"]
+        );
+    }
+
+    /// Checks a deterministic scheduler finds the same errors in the same order every time
+    #[test]
+    pub fn test_deterministic_order() {
+        let file = ORDERED.get_file("two-broken-calls.rv").unwrap();
+        let errors = || {
+            let mut arguments = test_arguments(file, "two-broken-calls.rv");
+            arguments.cpu_runtime = Builder::new_current_thread().enable_time().build().unwrap();
+            let scheduler = DeterministicScheduler::new(arguments.cpu_runtime.handle().clone()).unwrap();
+            let handle = Arc::new(Mutex::new(HandleWrapper::with_scheduler(Box::new(scheduler))));
+            let checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+            let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+            arguments.cpu_runtime.block_on(async {
+                for source_set in &arguments.runner_settings.sources {
+                    for file in source_set.get_files() {
+                        if file.path().ends_with("rv") {
+                            parse(syntax.clone(), handle.clone(), source_set.relative(&*file), file).await;
+                        }
+                    }
+                }
+                syntax.lock().unwrap().finish();
+                let mut errors = Vec::default();
+                loop {
+                    let task = handle.lock().unwrap().joining.pop();
+                    match task {
+                        Some(task) => {
+                            let finished = tokio::time::timeout(WAIT_TIMEOUT, task).await;
+                            errors.extend(finished.expect("A task didn't finish in time!").unwrap().err());
+                        }
+                        None => break,
+                    }
+                }
+                errors.append(&mut syntax.lock().unwrap().errors);
+                return errors.iter().map(|error| format!("{:?}", error)).collect::<Vec<_>>();
+            })
+        };
+
+        let first = errors();
+        assert_eq!(first.len(), 2, "Wrong errors {:?}", first);
+        for _ in 0..4 {
+            assert_eq!(errors(), first);
+        }
+    }
+
+    /// Checks a custom coercion rule changes arguments that the default rules reject
+    #[test]
+    pub fn test_coercion_rules() {
+        /// Passes bools where u64s are expected by casting them
+        struct BoolToInt;
+
+        impl CoercionRules for BoolToInt {
+            fn coerce(
+                &self,
+                argument: &FinalizedEffects,
+                found: &FinalizedTypes,
+                expected: &FinalizedTypes,
+            ) -> Option<FinalizedEffects> {
+                let expected = match expected {
+                    FinalizedTypes::Reference(inner) => inner,
+                    expected => expected,
+                };
+                if found.to_string() != "bool" || expected.to_string() != "u64" {
+                    return None;
+                }
+                let casting = FinalizedEffectType::Cast(Box::new(argument.clone()), found.clone(), expected.clone());
+                return Some(FinalizedEffects::new(argument.span.clone(), casting));
+            }
+        }
+
+        let file = FAILING.get_file("bool-argument.rv").unwrap();
+        let arguments = test_arguments(file, "bool-argument.rv");
+        let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+        let mut checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+        checker.coercions = Arc::new(BoolToInt);
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker))));
+        parse_into(&arguments, &syntax, &handle);
+        syntax.lock().unwrap().finish();
+
+        let compiling = syntax.lock().unwrap().compiling.clone();
+        let found = wait_for_function(&compiling, "bool-argument::test");
+        let errors = syntax.lock().unwrap().errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>();
+        assert!(errors.is_empty(), "Coerced call failed with {:?}", errors);
+        let cast = effects(&found.code).iter().any(|effect| matches!(effect, FinalizedEffectType::Cast(_, _, _)));
+        assert!(cast, "The bool wasn't cast");
+    }
+
+    /// Checks #[inline] and #[noinline] are recorded on the functions handed to the compiler, which still compiles them,
+    /// and that only the recursive #[inline] function is warned about
+    #[test]
+    pub fn test_inline_attributes() {
+        let file = TESTS.get_file("inline.rv").unwrap();
+        let arguments = test_arguments(file, "inline.rv");
+        let (syntax, _) = check_sources(&arguments);
+        {
+            let locked = syntax.lock().unwrap();
+            let inline = |name: &str| locked.compiling.get(name).unwrap().data.inline;
+            assert_eq!(inline("inline::double"), Inline::Always);
+            assert_eq!(inline("inline::triple"), Inline::Never);
+            assert_eq!(inline("inline::factorial"), Inline::Always);
+            assert_eq!(inline("inline::test"), Inline::Auto);
+
+            let warned = locked
+                .warnings
+                .iter()
+                .filter_map(|warning| match &warning.message {
+                    ParsingMessage::InlineRecursive(function) => Some(function.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(warned, vec!["inline::factorial".to_string()]);
+        }
+
+        let compiler = {
+            let locked = syntax.lock().unwrap();
+            get_compiler::<bool>(
+                locked.compiling.clone(),
+                locked.strut_compiling.clone(),
+                arguments.runner_settings.compiler_arguments.clone(),
+            )
+        };
+        let (sender, receiver) = mpsc::channel(1);
+        let result = arguments.cpu_runtime.block_on(async {
+            sender.send(()).await.unwrap();
+            compiler.compile(receiver, &syntax).await
+        });
+        assert_eq!(result, Some(true));
+    }
+
+    /// Checks explicit discriminants are kept and the variants without one count up from the variant before them
+    #[test]
+    pub fn test_enum_discriminants() {
+        let file = TESTS.get_file("enum-discriminants.rv").unwrap();
+        let arguments = test_arguments(file, "enum-discriminants.rv");
+        let (syntax, _) = check_sources(&arguments);
+        let discriminants = arguments.cpu_runtime.block_on(wait_for("Status to be checked", || {
+            let locked = syntax.lock().unwrap();
+            let status = locked.structures.types.get("enum-discriminants::Status").unwrap();
+            locked.structures.data.get(status).map(|found| found.discriminants.clone())
+        }));
+        assert_eq!(discriminants, vec![1, 2, 4, 5]);
+    }
+
+    /// Checks verifying a single impl finds missing methods, methods the trait doesn't have, and wrong signatures,
+    /// and nothing in an impl matching its trait
+    #[test]
+    pub fn test_verify_impl() {
+        let file = FAILING.get_file("verify-impl.rv").unwrap();
+        let arguments = test_arguments(file, "verify-impl.rv");
+        let (syntax, checker) = check_sources(&arguments);
+        let implementations = syntax.lock().unwrap().implementations.clone();
+        let errors = |base: &str| {
+            let implementor = implementations.iter().find(|implementor| implementor.base.to_string() == base).unwrap();
+            return arguments
+                .cpu_runtime
+                .block_on(verify_impl(&checker, implementor, &syntax))
+                .into_iter()
+                .map(|error| error.message)
+                .collect::<Vec<_>>();
+        };
+
+        assert!(errors("verify-impl::Square").is_empty());
+        let point = errors("verify-impl::Point");
+        assert_eq!(point.len(), 2, "Expected two errors, found {:?}", point);
+        assert!(matches!(&point[0], ParsingMessage::MissingTraitMethod(method, _) if method == "sides"));
+        assert!(matches!(&point[1], ParsingMessage::NotInTrait(method, _) if method == "corners"));
+        let circle = errors("verify-impl::Circle");
+        assert_eq!(circle.len(), 1, "Expected one error, found {:?}", circle);
+        assert!(matches!(&circle[0], ParsingMessage::TraitSignatureMismatch(method, _, _) if method == "area"));
+    }
+
+    /// Checks verifying an impl finds associated constants it forgot, and nothing in impls defining all of them
+    #[test]
+    pub fn test_verify_impl_constants() {
+        let verify = |file: &'static File<'static>, path: &str| {
+            let arguments = test_arguments(file, path);
+            let (syntax, checker) = check_sources(&arguments);
+            let implementations = syntax.lock().unwrap().implementations.clone();
+            let mut errors = Vec::default();
+            for implementor in &implementations {
+                errors.extend(arguments.cpu_runtime.block_on(verify_impl(&checker, implementor, &syntax)));
+            }
+            return errors.into_iter().map(|error| error.message).collect::<Vec<_>>();
+        };
+
+        let defined = verify(TESTS.get_file("associated-constants.rv").unwrap(), "associated-constants.rv");
+        assert!(defined.is_empty(), "Expected no errors, found {:?}", defined);
+        let missing = verify(FAILING.get_file("missing-associated-constant.rv").unwrap(), "missing-associated-constant.rv");
+        assert_eq!(missing.len(), 1, "Expected one error, found {:?}", missing);
+        assert!(matches!(&missing[0], ParsingMessage::MissingAssociatedConstant(constant, _) if constant == "SIDES"));
+    }
+
+    /// Checks functions streamed from the parser are checked before the program finishes parsing,
+    /// and every error is still found in order
+    #[test]
+    pub fn test_streaming_check() {
+        let file = ORDERED.get_file("streamed-functions.rv").unwrap();
+        let arguments = test_arguments(file, "streamed-functions.rv");
+        let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+        let checker = TypesChecker::new(handle.clone(), arguments.runner_settings.include_references());
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker.clone()))));
+        let (functions, receiver) = mpsc::unbounded_channel();
+        let name = "streaming".to_string();
+        handle.lock().unwrap().spawn(name.clone(), checker.check_stream(receiver, syntax.clone(), name));
+
+        let mut errors = arguments.cpu_runtime.block_on(async {
+            for source_set in &arguments.runner_settings.sources {
+                for file in source_set.get_files() {
+                    if file.path().ends_with("rv") {
+                        let name = source_set.relative(&*file);
+                        parse_streaming(syntax.clone(), handle.clone(), name, file, functions.clone()).await;
+                    }
+                }
+            }
+            drop(functions);
+
+            // The program isn't finished, but a function that doesn't need the rest of it is already checked
+            let first = || syntax.lock().unwrap().generics.contains_key("streamed-functions::first").then_some(());
+            wait_for("the streamed function before the program was finished", first).await;
+
+            syntax.lock().unwrap().finish();
+            wait_for_tasks(&handle).await;
+            return mem::take(&mut syntax.lock().unwrap().errors);
+        });
+
+        ParsingError::sort(&mut errors, &arguments.runner_settings.sources);
+        let messages = errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>();
+        let expected = expected_errors(file);
+        assert_eq!(messages.len(), expected.len(), "Expected {:?}, found {:?}", expected, messages);
+        for (message, part) in messages.iter().zip(&expected) {
+            assert!(message.contains(part), "Expected {:?}, found {:?}", expected, messages);
+        }
+    }
+
+    /// Parses the sources, which starts checking them, without finishing the syntax
+    fn parse_sources(arguments: &Arguments) -> (Arc<Mutex<Syntax>>, TypesChecker) {
+        return parse_sources_with_references(arguments, arguments.runner_settings.include_references());
+    }
+
+    /// Parses the sources like parse_sources, choosing if arguments and fields are wrapped in references
+    fn parse_sources_with_references(arguments: &Arguments, include_refs: bool) -> (Arc<Mutex<Syntax>>, TypesChecker) {
+        let handle = Arc::new(Mutex::new(HandleWrapper::new(arguments.cpu_runtime.handle().clone())));
+        let checker = TypesChecker::new(handle.clone(), include_refs);
+        let syntax = Arc::new(Mutex::new(Syntax::new(Box::new(checker.clone()))));
+        parse_into(arguments, &syntax, &handle);
+        return (syntax, checker);
+    }
+
+    /// Parses every source into the syntax without finishing it
+    fn parse_into(arguments: &Arguments, syntax: &Arc<Mutex<Syntax>>, handle: &Arc<Mutex<HandleWrapper>>) {
+        arguments.cpu_runtime.block_on(async {
+            for source_set in &arguments.runner_settings.sources {
+                for file in source_set.get_files() {
+                    if file.path().ends_with("rv") {
+                        parse(syntax.clone(), handle.clone(), source_set.relative(&*file), file).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Waits for the function to be checked, failing the test if it isn't checked in time
+    fn wait_for_function(compiling: &DashMap<String, Arc<FinalizedFunction>>, name: &str) -> Arc<FinalizedFunction> {
+        let start = Instant::now();
+        loop {
+            if let Some(found) = compiling.get(name) {
+                return found.clone();
+            }
+            assert!(start.elapsed() < WAIT_TIMEOUT, "{} wasn't checked within {:?}!", name, WAIT_TIMEOUT);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Every effect in the code, each before the effects inside of it
+    fn effects(code: &FinalizedCodeBody) -> Vec<&FinalizedEffectType> {
+        let mut effects = Vec::default();
+        walk_effects(code, &mut |effect, _| {
+            effects.push(&effect.types);
+            return true;
+        });
+        return effects;
+    }
+
+    /// Polls until found returns a value, failing the test if it doesn't return one in time
+    async fn wait_for<T>(waiting: &str, mut found: impl FnMut() -> Option<T>) -> T {
+        let start = Instant::now();
+        loop {
+            if let Some(found) = found() {
+                return found;
+            }
+            assert!(start.elapsed() < WAIT_TIMEOUT, "Waited for {} for over {:?}!", waiting, WAIT_TIMEOUT);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Waits for every task checking the program to finish, failing the test if they don't finish in time
+    async fn wait_for_tasks(handle: &Arc<Mutex<HandleWrapper>>) {
+        wait_for("every task to finish", || {
+            handle.lock().unwrap().joining.iter().all(|task| task.is_finished()).then_some(())
+        })
+        .await;
+    }
+
+    /// Parses and checks the sources without compiling them, returning the program and its checker once every impl is finished
+    fn check_sources(arguments: &Arguments) -> (Arc<Mutex<Syntax>>, TypesChecker) {
+        let (syntax, checker) = parse_sources(arguments);
+        arguments.cpu_runtime.block_on(async {
+            syntax.lock().unwrap().finish();
+            wait_for("every impl to finish", || syntax.lock().unwrap().finished_impls().then_some(())).await;
+        });
+        return (syntax, checker);
+    }
+
     /// Recursively searches for files in the test folder to run as a test
     fn test_recursive(dir: &'static Dir<'_>) {
         for entry in dir.entries() {
@@ -40,6 +1398,12 @@ mod test {
                                 compiler: "llvm".to_string(),
                                 target: path.clone(),
                                 temp_folder: env::current_dir().unwrap().join("target"),
+                                deny_warnings: false,
+                                features: Vec::default(),
+                                dump_ir: None,
+                                bounds_checks: true,
+                                reject_special_floats: false,
+                                position_independent_vtables: false,
                             },
                         },
                     );
@@ -62,6 +1426,101 @@ mod test {
             }
         }
     }
+
+    /// Recursively searches for files in the failing folder and checks they fail with the right error
+    fn failing_recursive(dir: &'static Dir<'_>) {
+        for entry in dir.entries() {
+            match entry {
+                DirEntry::File(file) => {
+                    let path = file.path().to_str().unwrap().replace(path::MAIN_SEPARATOR, "::");
+                    if !path.ends_with(".rv") {
+                        println!("File {} doesn't have the right file extension!", path);
+                        continue;
+                    }
+                    println!("Running {}", path);
+                    let expected = expected_errors(file);
+                    if expected.is_empty() {
+                        panic!("Failing test {} doesn't start with an expected error!", path);
+                    }
+
+                    match run::<bool>(&test_arguments(file, &path)) {
+                        Ok(_) => assert!(false, "Test {} compiled when it should've failed!", path),
+                        Err(errors) => {
+                            let messages = errors.iter().map(|error| error.message.to_string()).collect::<Vec<_>>();
+                            assert!(
+                                messages.iter().any(|message| expected.iter().all(|part| message.contains(part))),
+                                "Test {} failed with {:?} instead of {:?}!",
+                                path,
+                                messages,
+                                expected
+                            );
+                        }
+                    }
+                }
+                DirEntry::Dir(dir) => {
+                    failing_recursive(dir);
+                }
+            }
+        }
+    }
+
+    /// Gets the parts of the expected errors from the "// error: " comments at the top of the file
+    fn expected_errors(file: &File) -> Vec<String> {
+        return file
+            .contents_utf8()
+            .unwrap()
+            .lines()
+            .map_while(|line| line.strip_prefix("// error: "))
+            .map(|line| line.trim().to_string())
+            .collect::<Vec<_>>();
+    }
+
+    /// Runs the trap test in another process running only the named test, since the trap kills the process
+    fn assert_traps(name: &str, test: &str) {
+        if env::var(TRAP_VARIABLE).is_ok() {
+            let result = run::<bool>(&test_arguments(TRAPS.get_file(name).unwrap(), name));
+            panic!("Trap test {} returned {:?} instead of trapping!", name, result.ok());
+        }
+
+        let status = Command::new(env::current_exe().unwrap())
+            .args([format!("test::test::{}", test), "--exact".to_string()])
+            .env(TRAP_VARIABLE, "1")
+            .output()
+            .unwrap()
+            .status;
+        // llvm.trap is an illegal instruction on x86 and a breakpoint on ARM, a panic would exit with 101 instead
+        assert!(
+            matches!(status.signal(), Some(SIGILL) | Some(SIGTRAP)),
+            "Trap test {} exited with {} instead of trapping!",
+            name,
+            status
+        );
+    }
+
+    /// Gets the arguments to compile a test file with its dependencies
+    fn test_arguments(file: &'static File<'static>, path: &str) -> Arguments {
+        let mut source: Vec<Box<dyn SourceSet>> = vec![Box::new(InnerFileSourceSet { set: file })];
+        add_dependencies(&mut source);
+        let mut arguments = Arguments::build_args(
+            false,
+            RunnerSettings {
+                sources: vec![],
+                compiler_arguments: CompilerArguments {
+                    compiler: "llvm".to_string(),
+                    target: format!("{}::test", &path[0..path.len() - 3]),
+                    temp_folder: env::current_dir().unwrap().join("target"),
+                    deny_warnings: false,
+                    features: Vec::default(),
+                    dump_ir: None,
+                    bounds_checks: true,
+                    reject_special_floats: false,
+                    position_independent_vtables: false,
+                },
+            },
+        );
+        arguments.runner_settings.sources = source;
+        return arguments;
+    }
 }
 
 /// A source set of an internal file